@@ -0,0 +1,204 @@
+//! BIMI : enregistrements `<sélecteur>._bimi` (`v=BIMI1; l=…; a=…`) et
+//! prérequis DMARC (`quarantine` ou `reject`, `pct=100`).
+
+use super::dmarc::{DmarcPolicy, DmarcStatus};
+use super::tls_rpt::is_https_url;
+
+/// Sélecteur interrogé en l'absence d'en-tête `BIMI-Selector`.
+pub(crate) const DEFAULT_SELECTOR: &str = "default";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BimiStatus {
+    /// `default` puis les sélecteurs de [`super::AuthLookupOptions::with_bimi_selector`].
+    pub selectors: Vec<BimiSelectorStatus>,
+    /// Prérequis DMARC non rempli (`None` : politique compatible).
+    pub dmarc_issue: Option<BimiDmarcIssue>,
+}
+
+impl BimiStatus {
+    /// Logo publié sous `default` et DMARC compatible.
+    pub fn is_ready(&self) -> bool {
+        self.dmarc_issue.is_none()
+            && self.selectors.iter().any(|status| {
+                matches!(status, BimiSelectorStatus::Published { selector, .. }
+                    if selector == DEFAULT_SELECTOR)
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BimiSelectorStatus {
+    Missing {
+        selector: String,
+    },
+    MultipleRecords {
+        selector: String,
+        records: Vec<String>,
+    },
+    Invalid {
+        selector: String,
+        record: String,
+        issue: BimiIssue,
+    },
+    /// `l=` et `a=` vides : le domaine refuse explicitement BIMI.
+    Declined {
+        selector: String,
+        record: String,
+    },
+    Published {
+        selector: String,
+        record: String,
+        logo: String,
+        /// URL du certificat de marque (VMC), si publiée.
+        authority: Option<String>,
+    },
+    /// La requête `<sélecteur>._bimi` a échoué (SERVFAIL, délai, réponse
+    /// bogus…) ; les autres sélecteurs restent évalués.
+    LookupFailed {
+        selector: String,
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BimiIssue {
+    InvalidVersion,
+    DuplicateTag {
+        tag: String,
+    },
+    /// `a=` renseigné sans logo (`l=`).
+    MissingLogo,
+    InvalidLogoUrl {
+        url: String,
+    },
+    InvalidAuthorityUrl {
+        url: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BimiDmarcIssue {
+    /// Pas d'enregistrement DMARC valide.
+    MissingDmarc,
+    /// `p=none` (ou `sp=none`) : la politique n'est pas appliquée.
+    MonitoringPolicy,
+    /// `pct` inférieur à 100.
+    PartialPercentage { pct: String },
+}
+
+pub(crate) fn assemble_status(
+    selectors: Vec<BimiSelectorStatus>,
+    dmarc_status: &DmarcStatus,
+) -> BimiStatus {
+    BimiStatus {
+        selectors,
+        dmarc_issue: dmarc_issue(dmarc_status),
+    }
+}
+
+pub(crate) fn selector_status(selector: &str, records: &[String]) -> BimiSelectorStatus {
+    let mut bimi_records: Vec<String> = records
+        .iter()
+        .map(|record| record.trim())
+        .filter(|record| {
+            record
+                .get(..7)
+                .is_some_and(|head| head.eq_ignore_ascii_case("v=bimi1"))
+        })
+        .map(str::to_string)
+        .collect();
+    let selector = selector.to_string();
+
+    if bimi_records.is_empty() {
+        return BimiSelectorStatus::Missing { selector };
+    }
+    if bimi_records.len() > 1 {
+        bimi_records.sort();
+        bimi_records.dedup();
+        return BimiSelectorStatus::MultipleRecords {
+            selector,
+            records: bimi_records,
+        };
+    }
+
+    let record = bimi_records.remove(0);
+    match parse_record(&record) {
+        Ok((None, None)) => BimiSelectorStatus::Declined { selector, record },
+        Ok((Some(logo), authority)) => BimiSelectorStatus::Published {
+            selector,
+            record,
+            logo,
+            authority,
+        },
+        Ok((None, Some(_))) => BimiSelectorStatus::Invalid {
+            selector,
+            record,
+            issue: BimiIssue::MissingLogo,
+        },
+        Err(issue) => BimiSelectorStatus::Invalid {
+            selector,
+            record,
+            issue,
+        },
+    }
+}
+
+/// URL du logo et du VMC (`None` si vides).
+fn parse_record(record: &str) -> Result<(Option<String>, Option<String>), BimiIssue> {
+    let mut fields = record
+        .split(';')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .map(|field| field.split_once('=').unwrap_or((field, "")))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim()));
+
+    match fields.next() {
+        Some((name, value)) if name == "v" && value.eq_ignore_ascii_case("BIMI1") => {}
+        _ => return Err(BimiIssue::InvalidVersion),
+    }
+
+    let mut seen = Vec::new();
+    let mut logo = None;
+    let mut authority = None;
+    for (name, value) in fields {
+        if seen.contains(&name) {
+            return Err(BimiIssue::DuplicateTag { tag: name });
+        }
+        match name.as_str() {
+            "l" => logo = Some(value).filter(|value| !value.is_empty()),
+            "a" => authority = Some(value).filter(|value| !value.is_empty()),
+            _ => {}
+        }
+        seen.push(name);
+    }
+
+    if let Some(url) = logo.filter(|url| !is_https_url(url)) {
+        return Err(BimiIssue::InvalidLogoUrl {
+            url: url.to_string(),
+        });
+    }
+    if let Some(url) = authority.filter(|url| !is_https_url(url)) {
+        return Err(BimiIssue::InvalidAuthorityUrl {
+            url: url.to_string(),
+        });
+    }
+    Ok((logo.map(str::to_string), authority.map(str::to_string)))
+}
+
+fn dmarc_issue(status: &DmarcStatus) -> Option<BimiDmarcIssue> {
+    let parsed = match status {
+        DmarcStatus::Compliant { parsed, .. } | DmarcStatus::Weak { parsed, .. } => parsed,
+        _ => return Some(BimiDmarcIssue::MissingDmarc),
+    };
+    if parsed.policy == DmarcPolicy::None
+        || parsed.effective_subdomain_policy() == DmarcPolicy::None
+    {
+        return Some(BimiDmarcIssue::MonitoringPolicy);
+    }
+    if parsed.percentage < 100 {
+        return Some(BimiDmarcIssue::PartialPercentage {
+            pct: parsed.percentage.to_string(),
+        });
+    }
+    None
+}
@@ -0,0 +1,110 @@
+//! Décodage de `p=` : base64, puis SubjectPublicKeyInfo RSA (RFC 6376
+//! §3.6.1) ou clé Ed25519 brute (RFC 8463).
+
+const SEQUENCE: u8 = 0x30;
+const INTEGER: u8 = 0x02;
+const BIT_STRING: u8 = 0x03;
+const OBJECT_IDENTIFIER: u8 = 0x06;
+/// 1.2.840.113549.1.1.1
+const RSA_ENCRYPTION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+pub(super) const ED25519_KEY_LEN: usize = 32;
+
+/// Base64 standard ; les blancs sont ignorés et le remplissage `=` est
+/// facultatif.
+pub(super) fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0u32;
+    let mut padding = 0usize;
+    for byte in input.bytes().filter(|byte| !byte.is_ascii_whitespace()) {
+        if byte == b'=' {
+            padding += 1;
+            continue;
+        }
+        if padding > 0 {
+            return None;
+        }
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    // Au plus 4 bits de reste (6 bits seuls ne forment pas un octet).
+    (bits < 6 && padding <= 2).then_some(out)
+}
+
+/// Taille en bits du modulus d'une clé RSA : SubjectPublicKeyInfo, ou
+/// RSAPublicKey (PKCS#1) encore publiée par certains fournisseurs.
+pub(super) fn rsa_modulus_bits(der: &[u8]) -> Option<u32> {
+    let (tag, body, rest) = read_tlv(der)?;
+    if tag != SEQUENCE || !rest.is_empty() {
+        return None;
+    }
+    let (tag, algorithm, rest) = read_tlv(body)?;
+    let public_key = match tag {
+        SEQUENCE => {
+            let (tag, oid, _) = read_tlv(algorithm)?;
+            if tag != OBJECT_IDENTIFIER || oid != RSA_ENCRYPTION {
+                return None;
+            }
+            let (tag, bits, _) = read_tlv(rest)?;
+            // Premier octet du BIT STRING : bits inutilisés, toujours 0 ici.
+            match (tag, bits.split_first()) {
+                (BIT_STRING, Some((0, public_key))) => public_key,
+                _ => return None,
+            }
+        }
+        INTEGER => der,
+        _ => return None,
+    };
+
+    let (tag, body, _) = read_tlv(public_key)?;
+    if tag != SEQUENCE {
+        return None;
+    }
+    let (tag, modulus, _) = read_tlv(body)?;
+    if tag != INTEGER {
+        return None;
+    }
+    let modulus = match modulus.iter().position(|byte| *byte != 0) {
+        Some(start) => &modulus[start..],
+        None => return None,
+    };
+    let leading = modulus[0].leading_zeros();
+    u32::try_from(modulus.len() * 8)
+        .ok()
+        .map(|bits| bits - leading)
+}
+
+/// Élément DER en tête de `input` : (tag, contenu, reste).
+fn read_tlv(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let tag = *input.first()?;
+    let first = *input.get(1)?;
+    let (len, header) = if first & 0x80 == 0 {
+        (usize::from(first), 2)
+    } else {
+        let count = usize::from(first & 0x7f);
+        if count == 0 || count > 4 {
+            return None;
+        }
+        let bytes = input.get(2..2 + count)?;
+        let len = bytes
+            .iter()
+            .fold(0usize, |acc, byte| (acc << 8) | usize::from(*byte));
+        (len, 2 + count)
+    };
+    let end = header.checked_add(len)?;
+    let content = input.get(header..end)?;
+    Some((tag, content, &input[end..]))
+}
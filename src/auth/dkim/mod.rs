@@ -0,0 +1,379 @@
+mod key;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DkimStatus {
+    pub policy: DkimPolicyStatus,
+    pub selectors: Vec<DkimSelectorStatus>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DkimPolicyStatus {
+    NotRequested,
+    Missing,
+    Present { record: String, testing: bool },
+    Invalid { record: String, issue: DkimIssue },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DkimSelectorStatus {
+    Missing {
+        selector: String,
+    },
+    Invalid {
+        selector: String,
+        records: Vec<String>,
+        issue: DkimIssue,
+    },
+    Weak {
+        selector: String,
+        record: String,
+        key: DkimKey,
+        weaknesses: Vec<DkimWeakness>,
+    },
+    Compliant {
+        selector: String,
+        record: String,
+        key: DkimKey,
+    },
+}
+
+/// Clé publiée par un sélecteur (`k=`, `p=`, `h=`, `s=`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DkimKey {
+    pub key_type: DkimKeyType,
+    /// Taille du modulus RSA, en bits ; `None` pour Ed25519.
+    pub modulus_bits: Option<u32>,
+    /// `h=` ; vide si tous les algorithmes sont acceptés.
+    pub hash_algorithms: Vec<String>,
+    /// `s=` (`*` par défaut).
+    pub service_types: Vec<String>,
+}
+
+/// `k=` (`rsa` par défaut).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DkimKeyType {
+    Rsa,
+    Ed25519,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DkimWeakness {
+    TestingFlag,
+    /// Modulus RSA inférieur à 2048 bits.
+    WeakKey {
+        bits: u32,
+    },
+    /// `h=sha1` : signatures rsa-sha1 uniquement (RFC 8301).
+    Sha1Only,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DkimIssue {
+    InvalidVersion,
+    /// Balise `p=` absente.
+    MissingPublicKey,
+    /// `p=` vide : clé révoquée (RFC 6376 §3.6.1).
+    RevokedKey,
+    /// `p=` illisible : base64 ou structure de clé invalide.
+    MalformedPublicKey,
+    UnknownKeyType {
+        key_type: String,
+    },
+    /// Modulus RSA inférieur à 1024 bits, refusé par les vérificateurs
+    /// (RFC 8301 §3.2).
+    KeyTooShort {
+        bits: u32,
+    },
+    /// `h=` sans `sha1` ni `sha256`.
+    UnsupportedHashAlgorithms {
+        algorithms: Vec<String>,
+    },
+    /// `s=` sans `email` ni `*`.
+    NotForEmail {
+        service_types: Vec<String>,
+    },
+    MultipleRecords {
+        count: usize,
+    },
+}
+
+const MIN_RSA_BITS: u32 = 1024;
+const RECOMMENDED_RSA_BITS: u32 = 2048;
+
+pub(crate) fn assemble_status(
+    policy: DkimPolicyStatus,
+    selectors: Vec<DkimSelectorStatus>,
+) -> DkimStatus {
+    DkimStatus { policy, selectors }
+}
+
+pub(crate) fn policy_not_requested() -> DkimPolicyStatus {
+    DkimPolicyStatus::NotRequested
+}
+
+pub(crate) fn policy_status(records: &[String]) -> DkimPolicyStatus {
+    if records.is_empty() {
+        return DkimPolicyStatus::Missing;
+    }
+
+    let sanitized: Vec<String> = records
+        .iter()
+        .map(|record| record.trim().to_string())
+        .collect();
+    let mut relevant = Vec::new();
+    for record in sanitized.iter() {
+        let parsed = parse_tags(record);
+        if parsed
+            .version
+            .as_deref()
+            .map(|value| value.eq_ignore_ascii_case("dkim1"))
+            .unwrap_or(false)
+        {
+            relevant.push((record.clone(), parsed));
+        }
+    }
+
+    if relevant.is_empty() {
+        let fallback = sanitized.into_iter().next().unwrap_or_default();
+        return DkimPolicyStatus::Invalid {
+            record: fallback,
+            issue: DkimIssue::InvalidVersion,
+        };
+    }
+
+    if relevant.len() > 1 {
+        let (record, _) = &relevant[0];
+        return DkimPolicyStatus::Invalid {
+            record: record.clone(),
+            issue: DkimIssue::MultipleRecords {
+                count: relevant.len(),
+            },
+        };
+    }
+
+    let (record, parsed) = relevant
+        .into_iter()
+        .next()
+        .expect("one record after length check");
+    DkimPolicyStatus::Present {
+        record,
+        testing: parsed.testing,
+    }
+}
+
+pub(crate) fn selector_status(selector: &str, records: &[String]) -> DkimSelectorStatus {
+    if records.is_empty() {
+        return DkimSelectorStatus::Missing {
+            selector: selector.to_string(),
+        };
+    }
+
+    let sanitized: Vec<String> = records
+        .iter()
+        .map(|record| record.trim().to_string())
+        .collect();
+    let mut relevant = Vec::new();
+    for record in sanitized.iter() {
+        let parsed = parse_tags(record);
+        if parsed
+            .version
+            .as_deref()
+            .map(|value| value.eq_ignore_ascii_case("dkim1"))
+            .unwrap_or(false)
+        {
+            relevant.push((record.clone(), parsed));
+        }
+    }
+
+    if relevant.is_empty() {
+        return DkimSelectorStatus::Invalid {
+            selector: selector.to_string(),
+            records: sanitized,
+            issue: DkimIssue::InvalidVersion,
+        };
+    }
+
+    if relevant.len() > 1 {
+        return DkimSelectorStatus::Invalid {
+            selector: selector.to_string(),
+            records: sanitized,
+            issue: DkimIssue::MultipleRecords {
+                count: relevant.len(),
+            },
+        };
+    }
+
+    let (record, parsed) = relevant
+        .into_iter()
+        .next()
+        .expect("one record after length check");
+    let invalid = |issue| DkimSelectorStatus::Invalid {
+        selector: selector.to_string(),
+        records: sanitized.clone(),
+        issue,
+    };
+    let key = match parse_key(&parsed) {
+        Ok(key) => key,
+        Err(issue) => return invalid(issue),
+    };
+
+    let mut weaknesses = Vec::new();
+    if parsed.testing {
+        weaknesses.push(DkimWeakness::TestingFlag);
+    }
+    if let Some(bits) = key.modulus_bits.filter(|bits| *bits < RECOMMENDED_RSA_BITS) {
+        weaknesses.push(DkimWeakness::WeakKey { bits });
+    }
+    if !key.hash_algorithms.is_empty()
+        && key
+            .hash_algorithms
+            .iter()
+            .all(|algorithm| algorithm == "sha1")
+    {
+        weaknesses.push(DkimWeakness::Sha1Only);
+    }
+
+    if weaknesses.is_empty() {
+        DkimSelectorStatus::Compliant {
+            selector: selector.to_string(),
+            record,
+            key,
+        }
+    } else {
+        DkimSelectorStatus::Weak {
+            selector: selector.to_string(),
+            record,
+            key,
+            weaknesses,
+        }
+    }
+}
+
+fn parse_key(parsed: &ParsedTags) -> Result<DkimKey, DkimIssue> {
+    let public_key = parsed
+        .public_key
+        .as_deref()
+        .ok_or(DkimIssue::MissingPublicKey)?;
+    if public_key.trim().is_empty() {
+        return Err(DkimIssue::RevokedKey);
+    }
+
+    let key_type = match parsed.key_type.as_deref() {
+        None => DkimKeyType::Rsa,
+        Some(value) if value.eq_ignore_ascii_case("rsa") => DkimKeyType::Rsa,
+        Some(value) if value.eq_ignore_ascii_case("ed25519") => DkimKeyType::Ed25519,
+        Some(value) => {
+            return Err(DkimIssue::UnknownKeyType {
+                key_type: value.to_string(),
+            });
+        }
+    };
+    let der = key::decode_base64(public_key).ok_or(DkimIssue::MalformedPublicKey)?;
+    let modulus_bits = match key_type {
+        DkimKeyType::Rsa => {
+            let bits = key::rsa_modulus_bits(&der).ok_or(DkimIssue::MalformedPublicKey)?;
+            if bits < MIN_RSA_BITS {
+                return Err(DkimIssue::KeyTooShort { bits });
+            }
+            Some(bits)
+        }
+        DkimKeyType::Ed25519 if der.len() == key::ED25519_KEY_LEN => None,
+        DkimKeyType::Ed25519 => return Err(DkimIssue::MalformedPublicKey),
+    };
+
+    let service_types = parsed
+        .service_types
+        .as_deref()
+        .map_or_else(|| vec!["*".to_string()], split_list);
+    if !service_types
+        .iter()
+        .any(|service| service == "*" || service == "email")
+    {
+        return Err(DkimIssue::NotForEmail { service_types });
+    }
+    let hash_algorithms = parsed
+        .hash_algorithms
+        .as_deref()
+        .map(split_list)
+        .unwrap_or_default();
+    if !hash_algorithms.is_empty()
+        && !hash_algorithms
+            .iter()
+            .any(|algorithm| algorithm == "sha1" || algorithm == "sha256")
+    {
+        return Err(DkimIssue::UnsupportedHashAlgorithms {
+            algorithms: hash_algorithms,
+        });
+    }
+
+    Ok(DkimKey {
+        key_type,
+        modulus_bits,
+        hash_algorithms,
+        service_types,
+    })
+}
+
+/// Liste `a:b:c` de `h=` ou `s=`, en minuscules.
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(':')
+        .map(|item| item.trim().to_ascii_lowercase())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+#[derive(Debug)]
+struct ParsedTags {
+    version: Option<String>,
+    public_key: Option<String>,
+    key_type: Option<String>,
+    hash_algorithms: Option<String>,
+    service_types: Option<String>,
+    testing: bool,
+}
+
+fn parse_tags(record: &str) -> ParsedTags {
+    let mut version = None;
+    let mut public_key = None;
+    let mut key_type = None;
+    let mut hash_algorithms = None;
+    let mut service_types = None;
+    let mut testing = false;
+
+    for part in record.split(';') {
+        let trimmed = part.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let mut pieces = trimmed.splitn(2, '=');
+        let key = pieces.next().unwrap().trim().to_ascii_lowercase();
+        let value = pieces.next().map(str::trim).unwrap_or("").to_string();
+
+        if key == "v" {
+            version = Some(value.clone());
+        } else if key == "p" {
+            public_key = Some(value.clone());
+        } else if key == "k" {
+            key_type = Some(value.clone());
+        } else if key == "h" {
+            hash_algorithms = Some(value.clone());
+        } else if key == "s" {
+            service_types = Some(value.clone());
+        } else if key == "t" {
+            // Drapeaux séparés par `:` (RFC 6376 §3.6.1) ; `,` reste toléré.
+            testing = value
+                .split([':', ','])
+                .any(|flag| flag.trim().eq_ignore_ascii_case("y"));
+        }
+    }
+
+    ParsedTags {
+        version,
+        public_key,
+        key_type,
+        hash_algorithms,
+        service_types,
+        testing,
+    }
+}
@@ -1,4 +1,8 @@
-use std::collections::HashMap;
+use crate::dns::{DnsBackend, DnsRecordType};
+
+use super::psl::PublicSuffixList;
+use super::resolver::{fqdn, lookup_txt};
+use super::{AuthError, DnssecLookup};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DmarcStatus {
@@ -12,15 +16,38 @@ pub enum DmarcStatus {
     },
     Weak {
         record: String,
+        /// Politique appliquée au domaine (`p`, `sp` ou `np` selon `origin`).
         policy: DmarcPolicy,
-        weakness: DmarcWeakness,
+        parsed: DmarcRecord,
+        origin: DmarcOrigin,
+        /// Défauts dans l'ordre : politique, puis balises.
+        weaknesses: Vec<DmarcWeakness>,
     },
     Compliant {
         record: String,
         policy: DmarcPolicy,
+        parsed: DmarcRecord,
+        origin: DmarcOrigin,
     },
 }
 
+/// Domaine dont l'enregistrement s'applique, et balise retenue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DmarcOrigin {
+    pub domain: String,
+    pub scope: DmarcScope,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmarcScope {
+    /// Enregistrement du domaine lui-même : `p=`.
+    Domain,
+    /// Enregistrement d'un domaine parent : `sp=` (ou `p=`).
+    Subdomain,
+    /// Domaine inexistant (NXDOMAIN) sous un parent publiant `np=`.
+    NonexistentSubdomain,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DmarcIssue {
     InvalidVersion,
@@ -28,20 +55,369 @@ pub enum DmarcIssue {
     UnknownPolicy { policy: String },
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Du plus faible au plus strict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum DmarcPolicy {
     None,
     Quarantine,
     Reject,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DmarcWeakness {
     MonitoringPolicy,
     QuarantinePolicy,
+    /// `pct` inférieur à 100 : une partie des messages échappe à la politique.
+    PartialPercentage {
+        pct: u8,
+    },
+    /// `sp=` plus faible que `p=`.
+    WeakerSubdomainPolicy {
+        policy: DmarcPolicy,
+    },
+    /// `np=` plus faible que la politique des sous-domaines.
+    WeakerNonexistentPolicy {
+        policy: DmarcPolicy,
+    },
+    /// `t=y` (DMARCbis) : la politique n'est pas appliquée.
+    TestingMode,
+    /// Aucune adresse `rua=` exploitable.
+    MissingAggregateReports,
+    /// Destination externe sans autorisation (RFC 7489 §7.1) : elle
+    /// ignorera les rapports.
+    UnauthorizedReportDestination {
+        tag: String,
+        address: String,
+    },
+    /// URI de `rua=`/`ruf=` ignorée (seul `mailto:` est accepté).
+    InvalidUri {
+        tag: String,
+        uri: String,
+    },
+    /// Valeur invalide, remplacée par la valeur par défaut (RFC 7489 §6.3).
+    InvalidTag {
+        tag: String,
+        value: String,
+    },
+    DuplicateTag {
+        tag: String,
+    },
+}
+
+/// Enregistrement analysé ; les balises absentes ou invalides prennent leur
+/// valeur par défaut.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DmarcRecord {
+    /// `p=`.
+    pub policy: DmarcPolicy,
+    /// `sp=` ; à défaut, `p=` s'applique.
+    pub subdomain_policy: Option<DmarcPolicy>,
+    /// `np=` (DMARCbis), pour les sous-domaines inexistants.
+    pub nonexistent_policy: Option<DmarcPolicy>,
+    /// `pct=` (100 par défaut).
+    pub percentage: u8,
+    /// `rua=`.
+    pub aggregate_reports: Vec<DmarcUri>,
+    /// `ruf=`.
+    pub failure_reports: Vec<DmarcUri>,
+    /// `adkim=`.
+    pub dkim_alignment: DmarcAlignment,
+    /// `aspf=`.
+    pub spf_alignment: DmarcAlignment,
+    /// `fo=` (`0` par défaut).
+    pub failure_options: Vec<DmarcFailureOption>,
+    /// `ri=`, en secondes (86400 par défaut).
+    pub report_interval: u32,
+    /// `psd=y|n` (DMARCbis) ; `None` pour `u` ou en l'absence de la balise.
+    pub psd: Option<bool>,
+    /// `t=y` (DMARCbis).
+    pub testing: bool,
+}
+
+impl DmarcRecord {
+    /// Politique des sous-domaines (`sp=`, sinon `p=`).
+    pub fn effective_subdomain_policy(&self) -> DmarcPolicy {
+        self.subdomain_policy.unwrap_or(self.policy)
+    }
+
+    /// Politique des sous-domaines inexistants (`np=`, sinon `sp=`, sinon `p=`).
+    pub fn effective_nonexistent_policy(&self) -> DmarcPolicy {
+        self.nonexistent_policy
+            .unwrap_or_else(|| self.effective_subdomain_policy())
+    }
+}
+
+/// Destination de rapports (`mailto:adresse[!taille]`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DmarcUri {
+    pub address: String,
+    /// Taille maximale acceptée, en octets.
+    pub max_size: Option<u64>,
+    pub authorization: DmarcReportAuthorization,
+}
+
+impl DmarcUri {
+    /// Domaine de l'adresse.
+    pub fn domain(&self) -> &str {
+        self.address
+            .rsplit_once('@')
+            .map(|(_, domain)| domain)
+            .unwrap_or_default()
+    }
+}
+
+/// Autorisation d'une destination de rapports (RFC 7489 §7.1).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DmarcReportAuthorization {
+    /// Non vérifiée (enregistrement analysé sans résolution).
+    Unchecked,
+    /// Même domaine organisationnel que l'enregistrement.
+    NotRequired,
+    /// `<domaine>._report._dmarc.<destination>` publie `v=DMARC1`.
+    Authorized,
+    Unauthorized,
+    /// La requête d'autorisation a échoué (SERVFAIL, délai, réponse
+    /// bogus…) : la destination n'est ni acceptée ni signalée.
+    LookupFailed {
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmarcAlignment {
+    Relaxed,
+    Strict,
+}
+
+/// Options `fo=`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmarcFailureOption {
+    /// `0` : SPF et DKIM échouent tous deux.
+    All,
+    /// `1` : l'un des deux échoue.
+    Any,
+    /// `d` : échec DKIM.
+    Dkim,
+    /// `s` : échec SPF.
+    Spf,
+}
+
+/// Enregistrement de `domain` ; s'il n'en publie aucun (un enregistrement
+/// multiple ou invalide est renvoyé tel quel), celui du domaine organisationnel
+/// (`psl`, RFC 7489 §6.6.3) ou du premier parent qui en publie un
+/// (remontée DMARCbis, au plus 7 labels, sans le TLD). Les destinations
+/// de rapports externes sont ensuite vérifiées.
+pub(crate) fn discover<B>(
+    backend: &B,
+    domain: &str,
+    psl: Option<&PublicSuffixList>,
+    dnssec: &mut Vec<DnssecLookup>,
+) -> Result<DmarcStatus, AuthError>
+where
+    B: DnsBackend + ?Sized,
+{
+    let status = find(backend, domain, psl, dnssec)?;
+    Ok(authorize_reports(backend, status, psl, dnssec))
+}
+
+fn find<B>(
+    backend: &B,
+    domain: &str,
+    psl: Option<&PublicSuffixList>,
+    dnssec: &mut Vec<DnssecLookup>,
+) -> Result<DmarcStatus, AuthError>
+where
+    B: DnsBackend + ?Sized,
+{
+    let exact = evaluate(
+        domain,
+        &lookup_txt(backend, &fqdn("_dmarc", domain), dnssec)?,
+    );
+    if !matches!(exact, DmarcStatus::Missing) {
+        return Ok(exact);
+    }
+
+    let candidates: Vec<String> = match psl {
+        Some(psl) => psl
+            .organizational_domain(domain)
+            .filter(|organizational| organizational != domain)
+            .into_iter()
+            .collect(),
+        None => {
+            let labels: Vec<&str> = domain.split('.').collect();
+            let start = labels.len().saturating_sub(MAX_WALK_LABELS).max(1);
+            (start..labels.len().saturating_sub(1))
+                .map(|idx| labels[idx..].join("."))
+                .collect()
+        }
+    };
+    for candidate in candidates {
+        let records = lookup_txt(backend, &fqdn("_dmarc", &candidate), dnssec)?;
+        let status = evaluate_scoped(&candidate, &records, |parsed| {
+            if parsed.nonexistent_policy.is_some() && is_nonexistent(backend, domain) {
+                DmarcScope::NonexistentSubdomain
+            } else {
+                DmarcScope::Subdomain
+            }
+        });
+        if has_policy(&status) {
+            return Ok(status);
+        }
+    }
+    Ok(exact)
+}
+
+/// Renseigne `authorization` de chaque URI `rua=`/`ruf=` et ajoute
+/// [`DmarcWeakness::UnauthorizedReportDestination`] pour les refus.
+fn authorize_reports<B>(
+    backend: &B,
+    status: DmarcStatus,
+    psl: Option<&PublicSuffixList>,
+    dnssec: &mut Vec<DnssecLookup>,
+) -> DmarcStatus
+where
+    B: DnsBackend + ?Sized,
+{
+    let (record, policy, mut parsed, origin, mut weaknesses) = match status {
+        DmarcStatus::Weak {
+            record,
+            policy,
+            parsed,
+            origin,
+            weaknesses,
+        } => (record, policy, parsed, origin, weaknesses),
+        DmarcStatus::Compliant {
+            record,
+            policy,
+            parsed,
+            origin,
+        } => (record, policy, parsed, origin, Vec::new()),
+        other => return other,
+    };
+
+    let mut checked: Vec<(String, DmarcReportAuthorization)> = Vec::new();
+    for (tag, uris) in [
+        ("rua", &mut parsed.aggregate_reports),
+        ("ruf", &mut parsed.failure_reports),
+    ] {
+        for uri in uris.iter_mut() {
+            let destination = uri.domain().to_string();
+            let authorization = match checked.iter().find(|(name, _)| *name == destination) {
+                Some((_, authorization)) => authorization.clone(),
+                None => {
+                    let authorization =
+                        authorization(backend, &origin.domain, &destination, psl, dnssec);
+                    checked.push((destination, authorization.clone()));
+                    authorization
+                }
+            };
+            if authorization == DmarcReportAuthorization::Unauthorized {
+                weaknesses.push(DmarcWeakness::UnauthorizedReportDestination {
+                    tag: tag.to_string(),
+                    address: uri.address.clone(),
+                });
+            }
+            uri.authorization = authorization;
+        }
+    }
+
+    if weaknesses.is_empty() {
+        DmarcStatus::Compliant {
+            record,
+            policy,
+            parsed,
+            origin,
+        }
+    } else {
+        DmarcStatus::Weak {
+            record,
+            policy,
+            parsed,
+            origin,
+            weaknesses,
+        }
+    }
+}
+
+/// Sans liste de suffixes, deux domaines dont l'un contient l'autre sont
+/// considérés comme un même domaine organisationnel.
+fn authorization<B>(
+    backend: &B,
+    domain: &str,
+    destination: &str,
+    psl: Option<&PublicSuffixList>,
+    dnssec: &mut Vec<DnssecLookup>,
+) -> DmarcReportAuthorization
+where
+    B: DnsBackend + ?Sized,
+{
+    let same_organization = match psl {
+        Some(psl) => psl.organizational_domain(domain) == psl.organizational_domain(destination),
+        None => {
+            is_same_or_subdomain(domain, destination) || is_same_or_subdomain(destination, domain)
+        }
+    };
+    if same_organization {
+        return DmarcReportAuthorization::NotRequired;
+    }
+    let name = fqdn(&format!("{domain}._report._dmarc"), destination);
+    let records = match lookup_txt(backend, &name, dnssec) {
+        Ok(records) => records,
+        Err(err) => {
+            return DmarcReportAuthorization::LookupFailed {
+                reason: err.to_string(),
+            };
+        }
+    };
+    if records
+        .iter()
+        .any(|record| starts_with_ignore_ascii_case(record.trim(), "v=dmarc1"))
+    {
+        DmarcReportAuthorization::Authorized
+    } else {
+        DmarcReportAuthorization::Unauthorized
+    }
+}
+
+fn is_same_or_subdomain(name: &str, parent: &str) -> bool {
+    name.eq_ignore_ascii_case(parent)
+        || name
+            .len()
+            .checked_sub(parent.len() + 1)
+            .and_then(|split| name.get(split..))
+            .is_some_and(|tail| tail.starts_with('.') && tail[1..].eq_ignore_ascii_case(parent))
+}
+
+/// Nombre maximal de labels du premier parent interrogé (DMARCbis).
+const MAX_WALK_LABELS: usize = 7;
+
+fn has_policy(status: &DmarcStatus) -> bool {
+    matches!(
+        status,
+        DmarcStatus::Weak { .. } | DmarcStatus::Compliant { .. }
+    )
 }
 
-pub(crate) fn evaluate(records: &[String]) -> DmarcStatus {
+/// NXDOMAIN (RFC 9091 §2.1) ; toute autre réponse laisse `sp=` s'appliquer.
+fn is_nonexistent<B>(backend: &B, domain: &str) -> bool
+where
+    B: DnsBackend + ?Sized,
+{
+    backend
+        .lookup(domain, DnsRecordType::A)
+        .is_err_and(|err| err.is_nx_domain())
+}
+
+pub(crate) fn evaluate(domain: &str, records: &[String]) -> DmarcStatus {
+    evaluate_scoped(domain, records, |_| DmarcScope::Domain)
+}
+
+/// `scope` choisit, d'après l'enregistrement analysé, la politique appliquée.
+fn evaluate_scoped(
+    domain: &str,
+    records: &[String],
+    scope: impl FnOnce(&DmarcRecord) -> DmarcScope,
+) -> DmarcStatus {
     let mut dmarc_records: Vec<String> = records
         .iter()
         .map(|record| record.trim())
@@ -62,49 +438,64 @@ pub(crate) fn evaluate(records: &[String]) -> DmarcStatus {
     }
 
     let record = dmarc_records.remove(0);
-    let tags = parse_tags(&record);
-
-    let Some(version) = tags.get("v") else {
-        return DmarcStatus::Invalid {
-            record,
-            issue: DmarcIssue::InvalidVersion,
-        };
+    let (parsed, tag_weaknesses) = match parse(&record) {
+        Ok(parsed) => parsed,
+        Err(issue) => return DmarcStatus::Invalid { record, issue },
     };
-    if !version.eq_ignore_ascii_case("dmarc1") {
-        return DmarcStatus::Invalid {
-            record,
-            issue: DmarcIssue::InvalidVersion,
-        };
-    }
 
-    let Some(policy) = tags.get("p") else {
-        return DmarcStatus::Invalid {
-            record,
-            issue: DmarcIssue::MissingPolicy,
-        };
+    let scope = scope(&parsed);
+    let policy = match scope {
+        DmarcScope::Domain => parsed.policy,
+        DmarcScope::Subdomain => parsed.effective_subdomain_policy(),
+        DmarcScope::NonexistentSubdomain => parsed.effective_nonexistent_policy(),
     };
+    let origin = DmarcOrigin {
+        domain: domain.to_string(),
+        scope,
+    };
+    let mut weaknesses = Vec::new();
+    match policy {
+        DmarcPolicy::None => weaknesses.push(DmarcWeakness::MonitoringPolicy),
+        DmarcPolicy::Quarantine => weaknesses.push(DmarcWeakness::QuarantinePolicy),
+        DmarcPolicy::Reject => {}
+    }
+    if parsed.testing {
+        weaknesses.push(DmarcWeakness::TestingMode);
+    }
+    if parsed.percentage < 100 {
+        weaknesses.push(DmarcWeakness::PartialPercentage {
+            pct: parsed.percentage,
+        });
+    }
+    let subdomain = parsed.effective_subdomain_policy();
+    if subdomain < parsed.policy {
+        weaknesses.push(DmarcWeakness::WeakerSubdomainPolicy { policy: subdomain });
+    }
+    if let Some(nonexistent) = parsed.nonexistent_policy.filter(|np| *np < subdomain) {
+        weaknesses.push(DmarcWeakness::WeakerNonexistentPolicy {
+            policy: nonexistent,
+        });
+    }
+    if parsed.aggregate_reports.is_empty() {
+        weaknesses.push(DmarcWeakness::MissingAggregateReports);
+    }
+    weaknesses.extend(tag_weaknesses);
 
-    match policy.to_ascii_lowercase().as_str() {
-        "reject" => DmarcStatus::Compliant {
-            record,
-            policy: DmarcPolicy::Reject,
-        },
-        "quarantine" => DmarcStatus::Weak {
+    if weaknesses.is_empty() {
+        DmarcStatus::Compliant {
             record,
-            policy: DmarcPolicy::Quarantine,
-            weakness: DmarcWeakness::QuarantinePolicy,
-        },
-        "none" => DmarcStatus::Weak {
-            record,
-            policy: DmarcPolicy::None,
-            weakness: DmarcWeakness::MonitoringPolicy,
-        },
-        other => DmarcStatus::Invalid {
+            policy,
+            parsed,
+            origin,
+        }
+    } else {
+        DmarcStatus::Weak {
             record,
-            issue: DmarcIssue::UnknownPolicy {
-                policy: other.to_string(),
-            },
-        },
+            policy,
+            parsed,
+            origin,
+            weaknesses,
+        }
     }
 }
 
@@ -115,17 +506,213 @@ fn starts_with_ignore_ascii_case(input: &str, prefix: &str) -> bool {
         .unwrap_or(false)
 }
 
-fn parse_tags(record: &str) -> HashMap<String, String> {
-    let mut tags = HashMap::new();
-    for part in record.split(';') {
-        let trimmed = part.trim();
-        if trimmed.is_empty() {
+/// `v=DMARC1` doit être la première balise et `p=` est obligatoire ; les
+/// autres erreurs sont ignorées et renvoyées comme défauts.
+pub(crate) fn parse(record: &str) -> Result<(DmarcRecord, Vec<DmarcWeakness>), DmarcIssue> {
+    let mut tags = record
+        .split(';')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| part.split_once('=').unwrap_or((part, "")))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim()));
+
+    match tags.next() {
+        Some((name, value)) if name == "v" && value.eq_ignore_ascii_case("dmarc1") => {}
+        _ => return Err(DmarcIssue::InvalidVersion),
+    }
+
+    let mut weaknesses = Vec::new();
+    let mut seen: Vec<String> = Vec::new();
+    let mut policy = None;
+    let mut parsed = DmarcRecord {
+        policy: DmarcPolicy::None,
+        subdomain_policy: None,
+        nonexistent_policy: None,
+        percentage: 100,
+        aggregate_reports: Vec::new(),
+        failure_reports: Vec::new(),
+        dkim_alignment: DmarcAlignment::Relaxed,
+        spf_alignment: DmarcAlignment::Relaxed,
+        failure_options: vec![DmarcFailureOption::All],
+        report_interval: 86_400,
+        psd: None,
+        testing: false,
+    };
+
+    for (name, value) in tags {
+        if seen.contains(&name) {
+            weaknesses.push(DmarcWeakness::DuplicateTag { tag: name });
             continue;
         }
-        let mut kv = trimmed.splitn(2, '=');
-        let key = kv.next().unwrap().trim().to_ascii_lowercase();
-        let value = kv.next().map(str::trim).unwrap_or("").to_string();
-        tags.insert(key, value);
+        let invalid = || DmarcWeakness::InvalidTag {
+            tag: name.clone(),
+            value: value.to_string(),
+        };
+        let valid =
+            match name.as_str() {
+                "p" => {
+                    let parsed_policy =
+                        parse_policy(value).ok_or_else(|| DmarcIssue::UnknownPolicy {
+                            policy: value.to_ascii_lowercase(),
+                        })?;
+                    policy = Some(parsed_policy);
+                    true
+                }
+                "sp" => store(&mut parsed.subdomain_policy, parse_policy(value)),
+                "np" => store(&mut parsed.nonexistent_policy, parse_policy(value)),
+                "pct" => store_value(
+                    &mut parsed.percentage,
+                    value.parse().ok().filter(|pct| *pct <= 100),
+                ),
+                "adkim" => store_value(&mut parsed.dkim_alignment, parse_alignment(value)),
+                "aspf" => store_value(&mut parsed.spf_alignment, parse_alignment(value)),
+                "fo" => store_value(&mut parsed.failure_options, parse_failure_options(value)),
+                "ri" => store_value(&mut parsed.report_interval, value.parse().ok()),
+                "psd" => match value.to_ascii_lowercase().as_str() {
+                    "y" => store(&mut parsed.psd, Some(true)),
+                    "n" => store(&mut parsed.psd, Some(false)),
+                    "u" => true,
+                    _ => false,
+                },
+                "t" => match value.to_ascii_lowercase().as_str() {
+                    "y" => store_value(&mut parsed.testing, Some(true)),
+                    "n" => true,
+                    _ => false,
+                },
+                "rua" | "ruf" => {
+                    let (uris, invalid_uris) = parse_uris(value);
+                    weaknesses.extend(invalid_uris.into_iter().map(|uri| {
+                        DmarcWeakness::InvalidUri {
+                            tag: name.clone(),
+                            uri,
+                        }
+                    }));
+                    if name == "rua" {
+                        parsed.aggregate_reports = uris;
+                    } else {
+                        parsed.failure_reports = uris;
+                    }
+                    true
+                }
+                _ => true,
+            };
+        if !valid {
+            weaknesses.push(invalid());
+        }
+        seen.push(name);
+    }
+
+    parsed.policy = policy.ok_or(DmarcIssue::MissingPolicy)?;
+    Ok((parsed, weaknesses))
+}
+
+fn store<T>(slot: &mut Option<T>, value: Option<T>) -> bool {
+    let valid = value.is_some();
+    if valid {
+        *slot = value;
+    }
+    valid
+}
+
+fn store_value<T>(slot: &mut T, value: Option<T>) -> bool {
+    match value {
+        Some(value) => {
+            *slot = value;
+            true
+        }
+        None => false,
     }
-    tags
+}
+
+fn parse_policy(value: &str) -> Option<DmarcPolicy> {
+    match value.to_ascii_lowercase().as_str() {
+        "none" => Some(DmarcPolicy::None),
+        "quarantine" => Some(DmarcPolicy::Quarantine),
+        "reject" => Some(DmarcPolicy::Reject),
+        _ => None,
+    }
+}
+
+fn parse_alignment(value: &str) -> Option<DmarcAlignment> {
+    match value.to_ascii_lowercase().as_str() {
+        "r" => Some(DmarcAlignment::Relaxed),
+        "s" => Some(DmarcAlignment::Strict),
+        _ => None,
+    }
+}
+
+fn parse_failure_options(value: &str) -> Option<Vec<DmarcFailureOption>> {
+    value
+        .split(':')
+        .map(|option| match option.trim().to_ascii_lowercase().as_str() {
+            "0" => Some(DmarcFailureOption::All),
+            "1" => Some(DmarcFailureOption::Any),
+            "d" => Some(DmarcFailureOption::Dkim),
+            "s" => Some(DmarcFailureOption::Spf),
+            _ => None,
+        })
+        .collect()
+}
+
+/// URIs valides et URIs ignorées d'une liste `rua=`/`ruf=`.
+fn parse_uris(value: &str) -> (Vec<DmarcUri>, Vec<String>) {
+    let mut uris = Vec::new();
+    let mut invalid = Vec::new();
+    for raw in value
+        .split(',')
+        .map(str::trim)
+        .filter(|raw| !raw.is_empty())
+    {
+        match parse_uri(raw) {
+            Some(uri) => uris.push(uri),
+            None => invalid.push(raw.to_string()),
+        }
+    }
+    (uris, invalid)
+}
+
+/// `mailto:local@domaine[!taille[k|m|g|t]]` (RFC 7489 §6.2).
+fn parse_uri(raw: &str) -> Option<DmarcUri> {
+    let (uri, size) = match raw.split_once('!') {
+        Some((uri, size)) => (uri, Some(size)),
+        None => (raw, None),
+    };
+    let scheme_len = "mailto:".len();
+    if !uri
+        .get(..scheme_len)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("mailto:"))
+    {
+        return None;
+    }
+    let address = &uri[scheme_len..];
+    let (local, domain) = address.rsplit_once('@')?;
+    let valid_domain = domain.contains('.')
+        && domain.split('.').all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+    if local.is_empty() || !valid_domain || address.contains(char::is_whitespace) {
+        return None;
+    }
+    let max_size = match size {
+        Some(size) => Some(parse_size(size)?),
+        None => None,
+    };
+    Some(DmarcUri {
+        address: format!("{local}@{}", domain.to_ascii_lowercase()),
+        max_size,
+        authorization: DmarcReportAuthorization::Unchecked,
+    })
+}
+
+fn parse_size(size: &str) -> Option<u64> {
+    let digits = size.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let multiplier = match &size[digits.len()..].to_ascii_lowercase()[..] {
+        "" => 1,
+        "k" => 1 << 10,
+        "m" => 1 << 20,
+        "g" => 1 << 30,
+        "t" => 1 << 40,
+        _ => return None,
+    };
+    digits.parse::<u64>().ok()?.checked_mul(multiplier)
 }
@@ -19,13 +19,19 @@ pub enum AuthError {
     TxtLookup {
         name: String,
         #[source]
-        source: trust_dns_resolver::error::ResolveError,
+        source: crate::dns::DnsError,
     },
-    #[error("TXT record {name} contains invalid UTF-8 data: {source}")]
-    TxtDataUtf8 {
-        name: String,
+    #[error("SPF flattening failed: {source}")]
+    SpfFlatten {
+        #[source]
+        source: SpfFlattenError,
+    },
+    /// MX du domaine, comparés à la politique MTA-STS.
+    #[cfg(feature = "with-mta-sts")]
+    #[error("MX lookup failed: {source}")]
+    MxLookup {
         #[source]
-        source: std::str::Utf8Error,
+        source: crate::mx::Error,
     },
 }
 
@@ -38,20 +44,67 @@ impl AuthError {
         Self::ResolverInit { source }
     }
 
-    pub(crate) fn txt_lookup(
-        name: impl Into<String>,
-        source: trust_dns_resolver::error::ResolveError,
-    ) -> Self {
-        Self::TxtLookup {
-            name: name.into(),
-            source,
-        }
+    #[cfg(feature = "with-mta-sts")]
+    pub(crate) fn mx_lookup(source: crate::mx::Error) -> Self {
+        Self::MxLookup { source }
     }
 
-    pub(crate) fn txt_data_utf8(name: impl Into<String>, source: std::str::Utf8Error) -> Self {
-        Self::TxtDataUtf8 {
+    pub(crate) fn txt_lookup(name: impl Into<String>, source: crate::dns::DnsError) -> Self {
+        Self::TxtLookup {
             name: name.into(),
             source,
         }
     }
 }
+
+/// Politique SPF impossible à aplatir sans en changer le résultat.
+#[derive(Debug, Error)]
+pub enum SpfFlattenError {
+    #[error("lookup failed for {name}: {source}")]
+    Lookup {
+        name: String,
+        #[source]
+        source: crate::dns::DnsError,
+    },
+    #[error("no SPF record at {domain}")]
+    NoRecord { domain: String },
+    #[error("multiple SPF records at {domain}")]
+    MultipleRecords { domain: String },
+    #[error("invalid SPF record at {domain}: {reason}")]
+    Syntax { domain: String, reason: String },
+    #[error("include loop through {domain}")]
+    Loop { domain: String },
+    /// Terme non `pass`, `ptr`, `exists` ou macro dans un domaine inclus.
+    #[error("{domain}: '{term}' cannot be flattened")]
+    Unflattenable { domain: String, term: String },
+}
+
+/// Échec de la récupération d'une politique MTA-STS.
+#[cfg(feature = "with-mta-sts")]
+#[derive(Debug, Error)]
+pub enum PolicyFetchError {
+    #[error("connection to {host} failed: {source}")]
+    Connect {
+        host: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("TLS error: {source}")]
+    Tls {
+        #[source]
+        source: native_tls::Error,
+    },
+    #[error("I/O error: {source}")]
+    Io {
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("unexpected HTTP status {code}")]
+    Status { code: u16 },
+    #[error("unexpected content type '{content_type}'")]
+    ContentType { content_type: String },
+    #[error("policy larger than {limit} bytes")]
+    TooLarge { limit: usize },
+    #[error("malformed HTTP response: {0}")]
+    Malformed(String),
+}
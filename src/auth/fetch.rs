@@ -0,0 +1,267 @@
+//! Récupération de la politique MTA-STS (RFC 8461 §3.3) : GET HTTP/1.0
+//! minimal, sans redirection, corps `text/plain` limité à 64 Kio.
+
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use native_tls::{HandshakeError, TlsConnector};
+
+use super::PolicyFetchError;
+
+const HTTPS_PORT: u16 = 443;
+const WELL_KNOWN_PATH: &str = "/.well-known/mta-sts.txt";
+/// Taille maximale de la politique (64 Kio, RFC 8461 §3.3).
+const MAX_BODY: usize = 64 * 1024;
+/// Marge pour la ligne de statut et les en-têtes.
+const MAX_HEAD: usize = 16 * 1024;
+
+/// Récupère le corps de `https://<host>/.well-known/mta-sts.txt`.
+///
+/// [`HttpsPolicyFetcher`] est l'implémentation réelle ; les tests peuvent en
+/// fournir une qui interroge un serveur local.
+pub trait PolicyFetcher {
+    fn fetch(&self, host: &str) -> Result<String, PolicyFetchError>;
+}
+
+/// Pendant tokio de [`PolicyFetcher`] (feature `async`).
+#[cfg(feature = "async")]
+pub trait AsyncPolicyFetcher: Sync {
+    fn fetch(&self, host: &str) -> impl Future<Output = Result<String, PolicyFetchError>> + Send;
+}
+
+/// HTTPS sur le port 443, certificat vérifié pour `host` ; l'adresse est
+/// résolue par le système.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpsPolicyFetcher {
+    timeout: Option<Duration>,
+}
+
+impl Default for HttpsPolicyFetcher {
+    fn default() -> Self {
+        Self {
+            timeout: Some(Duration::from_secs(10)),
+        }
+    }
+}
+
+impl HttpsPolicyFetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Délai de connexion et de lecture ; `None` le désactive.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl PolicyFetcher for HttpsPolicyFetcher {
+    fn fetch(&self, host: &str) -> Result<String, PolicyFetchError> {
+        let stream = connect(host, self.timeout)?;
+        let connector = TlsConnector::new().map_err(|source| PolicyFetchError::Tls { source })?;
+        let stream = match connector.connect(host, stream) {
+            Ok(stream) => stream,
+            Err(HandshakeError::Failure(source)) => return Err(PolicyFetchError::Tls { source }),
+            Err(HandshakeError::WouldBlock(_)) => {
+                return Err(PolicyFetchError::Io {
+                    source: io::ErrorKind::TimedOut.into(),
+                });
+            }
+        };
+        get(stream, host)
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncPolicyFetcher for HttpsPolicyFetcher {
+    async fn fetch(&self, host: &str) -> Result<String, PolicyFetchError> {
+        let fetch = async {
+            let stream = tokio::net::TcpStream::connect((host, HTTPS_PORT))
+                .await
+                .map_err(|source| PolicyFetchError::Connect {
+                    host: host.to_string(),
+                    source,
+                })?;
+            let connector = TlsConnector::new()
+                .map(tokio_native_tls::TlsConnector::from)
+                .map_err(|source| PolicyFetchError::Tls { source })?;
+            let stream = connector
+                .connect(host, stream)
+                .await
+                .map_err(|source| PolicyFetchError::Tls { source })?;
+            get_async(stream, host).await
+        };
+        match self.timeout {
+            Some(timeout) => {
+                tokio::time::timeout(timeout, fetch)
+                    .await
+                    .map_err(|_| PolicyFetchError::Io {
+                        source: io::ErrorKind::TimedOut.into(),
+                    })?
+            }
+            None => fetch.await,
+        }
+    }
+}
+
+fn connect(host: &str, timeout: Option<Duration>) -> Result<TcpStream, PolicyFetchError> {
+    let connect_error = |source| PolicyFetchError::Connect {
+        host: host.to_string(),
+        source,
+    };
+    let mut last_err = None;
+    for addr in (host, HTTPS_PORT)
+        .to_socket_addrs()
+        .map_err(connect_error)?
+    {
+        let stream = match timeout {
+            Some(timeout) => TcpStream::connect_timeout(&addr, timeout),
+            None => TcpStream::connect(addr),
+        };
+        match stream {
+            Ok(stream) => {
+                stream
+                    .set_read_timeout(timeout)
+                    .and_then(|()| stream.set_write_timeout(timeout))
+                    .map_err(|source| PolicyFetchError::Io { source })?;
+                return Ok(stream);
+            }
+            Err(err) => last_err = Some(err),
+        }
+    }
+    Err(connect_error(last_err.unwrap_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, "no address")
+    })))
+}
+
+/// GET de la politique sur `stream`, déjà connecté (et chiffré) vers `host`.
+pub(crate) fn get<S: Read + Write>(mut stream: S, host: &str) -> Result<String, PolicyFetchError> {
+    let io_error = |source| PolicyFetchError::Io { source };
+    stream.write_all(&request(host)).map_err(io_error)?;
+    stream.flush().map_err(io_error)?;
+    let mut response = Vec::new();
+    let mut chunk = [0u8; 4096];
+    while !is_complete(&response) {
+        let read = match stream.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(io_error(err)),
+        };
+        push(&mut response, &chunk[..read])?;
+    }
+    parse_response(&response)
+}
+
+/// Variante tokio de [`get`].
+#[cfg(feature = "async")]
+pub(crate) async fn get_async<S>(mut stream: S, host: &str) -> Result<String, PolicyFetchError>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let io_error = |source| PolicyFetchError::Io { source };
+    stream.write_all(&request(host)).await.map_err(io_error)?;
+    stream.flush().await.map_err(io_error)?;
+    let mut response = Vec::new();
+    let mut chunk = [0u8; 4096];
+    while !is_complete(&response) {
+        let read = stream.read(&mut chunk).await.map_err(io_error)?;
+        if read == 0 {
+            break;
+        }
+        push(&mut response, &chunk[..read])?;
+    }
+    parse_response(&response)
+}
+
+fn request(host: &str) -> Vec<u8> {
+    format!(
+        "GET {WELL_KNOWN_PATH} HTTP/1.0\r\nHost: {host}\r\nAccept: text/plain\r\nConnection: close\r\n\r\n"
+    )
+    .into_bytes()
+}
+
+fn push(response: &mut Vec<u8>, data: &[u8]) -> Result<(), PolicyFetchError> {
+    response.extend_from_slice(data);
+    if response.len() > MAX_HEAD + MAX_BODY {
+        return Err(PolicyFetchError::TooLarge { limit: MAX_BODY });
+    }
+    Ok(())
+}
+
+/// En-têtes et corps d'une réponse, si les en-têtes sont complets.
+fn split_head(response: &[u8]) -> Option<(&[u8], &[u8])> {
+    let end = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")?;
+    Some((&response[..end], &response[end + 4..]))
+}
+
+fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    head.lines().skip(1).find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+/// Réponse reçue en entier d'après `Content-Length` (sinon, lire jusqu'à
+/// la fermeture).
+fn is_complete(response: &[u8]) -> bool {
+    let Some((head, body)) = split_head(response) else {
+        return false;
+    };
+    let length = std::str::from_utf8(head)
+        .ok()
+        .and_then(|head| header(head, "content-length"))
+        .and_then(|value| value.parse::<usize>().ok());
+    length.is_some_and(|length| body.len() >= length)
+}
+
+pub(crate) fn parse_response(response: &[u8]) -> Result<String, PolicyFetchError> {
+    let malformed = |reason: &str| PolicyFetchError::Malformed(reason.to_string());
+    let (head, body) = split_head(response).ok_or_else(|| malformed("incomplete headers"))?;
+    let head = std::str::from_utf8(head).map_err(|_| malformed("headers are not UTF-8"))?;
+
+    let status = head.lines().next().unwrap_or_default();
+    let code = match status.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [version, code, ..] if version.starts_with("HTTP/") => code.parse::<u16>().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| malformed("invalid status line"))?;
+    // Redirections comprises : elles ne doivent pas être suivies.
+    if code != 200 {
+        return Err(PolicyFetchError::Status { code });
+    }
+
+    let content_type = header(head, "content-type").unwrap_or_default();
+    let media_type = content_type.split(';').next().unwrap_or_default().trim();
+    if !media_type.eq_ignore_ascii_case("text/plain") {
+        return Err(PolicyFetchError::ContentType {
+            content_type: content_type.to_string(),
+        });
+    }
+    if header(head, "transfer-encoding")
+        .is_some_and(|value| !value.eq_ignore_ascii_case("identity"))
+    {
+        return Err(malformed("unsupported transfer encoding"));
+    }
+
+    let body = match header(head, "content-length") {
+        Some(value) => {
+            let length = value
+                .parse::<usize>()
+                .map_err(|_| malformed("invalid Content-Length"))?;
+            body.get(..length)
+                .ok_or_else(|| malformed("truncated body"))?
+        }
+        None => body,
+    };
+    if body.len() > MAX_BODY {
+        return Err(PolicyFetchError::TooLarge { limit: MAX_BODY });
+    }
+    String::from_utf8(body.to_vec()).map_err(|_| malformed("policy is not UTF-8"))
+}
@@ -1,18 +1,48 @@
+mod bimi;
 mod dkim;
 mod dmarc;
 mod error;
+#[cfg(feature = "with-mta-sts")]
+mod fetch;
+#[cfg(feature = "with-mta-sts")]
+mod mta_sts;
+mod psl;
 mod resolver;
 mod spf;
+mod tls_rpt;
 mod types;
 
-pub use dkim::{DkimIssue, DkimPolicyStatus, DkimSelectorStatus, DkimStatus, DkimWeakness};
-pub use dmarc::{DmarcIssue, DmarcPolicy, DmarcStatus, DmarcWeakness};
-pub use error::AuthError;
-pub use spf::{SpfIssue, SpfQualifier, SpfStatus};
-pub use types::{AuthLookupOptions, AuthStatus};
+pub use bimi::{BimiDmarcIssue, BimiIssue, BimiSelectorStatus, BimiStatus};
+pub use dkim::{
+    DkimIssue, DkimKey, DkimKeyType, DkimPolicyStatus, DkimSelectorStatus, DkimStatus, DkimWeakness,
+};
+pub use dmarc::{
+    DmarcAlignment, DmarcFailureOption, DmarcIssue, DmarcOrigin, DmarcPolicy, DmarcRecord,
+    DmarcReportAuthorization, DmarcScope, DmarcStatus, DmarcUri, DmarcWeakness,
+};
+#[cfg(feature = "with-mta-sts")]
+pub use error::PolicyFetchError;
+pub use error::{AuthError, SpfFlattenError};
+#[cfg(all(feature = "with-mta-sts", feature = "async"))]
+pub use fetch::AsyncPolicyFetcher;
+#[cfg(feature = "with-mta-sts")]
+pub use fetch::{HttpsPolicyFetcher, PolicyFetcher};
+#[cfg(feature = "with-mta-sts")]
+pub use mta_sts::{MtaStsIssue, MtaStsMode, MtaStsPolicy, MtaStsStatus};
+pub use psl::PublicSuffixList;
+pub use spf::{
+    SpfCheck, SpfFlatRecord, SpfFlattenOptions, SpfFlattened, SpfIssue, SpfLink, SpfLint,
+    SpfQualifier, SpfResult, SpfStatus, SpfTraceEvent, SpfTraceStep, SpfTree, SpfTreeNode,
+};
+pub use tls_rpt::{TlsRptIssue, TlsRptStatus, TlsRptUri};
+pub use types::{AuthLookupOptions, AuthStatus, DnssecLookup};
 
-use resolver::{LookupTxt, fqdn, normalize_domain};
-use trust_dns_resolver::Resolver;
+use std::net::IpAddr;
+
+use crate::dns::DnsBackend;
+#[cfg(feature = "async")]
+use crate::dns::{AsyncDnsBackend, replay};
+use resolver::{fqdn, lookup_txt, normalize_domain};
 
 pub fn check_auth_records(domain: &str) -> Result<AuthStatus, AuthError> {
     check_auth_records_with_options(domain, &AuthLookupOptions::default())
@@ -23,28 +53,314 @@ pub fn check_auth_records_with_options(
     options: &AuthLookupOptions,
 ) -> Result<AuthStatus, AuthError> {
     let ascii = normalize_domain(domain)?;
-    let resolver = Resolver::from_system_conf().map_err(AuthError::resolver_init)?;
-    check_with_resolver(&resolver, &ascii, options)
+    let backend = options
+        .dns_config()
+        .build_backend()
+        .map_err(AuthError::resolver_init)?;
+    check_auth_records_with_backend(&*backend, &ascii, options)
+}
+
+/// Like [`check_auth_records_with_options`], but queries `backend` instead of
+/// building a resolver.
+pub fn check_auth_records_with_backend<B>(
+    backend: &B,
+    domain: &str,
+    options: &AuthLookupOptions,
+) -> Result<AuthStatus, AuthError>
+where
+    B: DnsBackend + ?Sized,
+{
+    let ascii = normalize_domain(domain)?;
+    let status = check_with_backend(backend, &ascii, options)?;
+    #[cfg(feature = "with-mta-sts")]
+    let status = mta_sts::complete(status, backend, &HttpsPolicyFetcher::default(), options)?;
+    Ok(status)
+}
+
+/// Like [`check_auth_records_with_backend`], but fetches the MTA-STS policy
+/// through `fetcher` (`with-mta-sts` feature).
+#[cfg(feature = "with-mta-sts")]
+pub fn check_auth_records_with_fetcher<B, F>(
+    backend: &B,
+    fetcher: &F,
+    domain: &str,
+    options: &AuthLookupOptions,
+) -> Result<AuthStatus, AuthError>
+where
+    B: DnsBackend + ?Sized,
+    F: PolicyFetcher + ?Sized,
+{
+    let ascii = normalize_domain(domain)?;
+    let status = check_with_backend(backend, &ascii, options)?;
+    mta_sts::complete(status, backend, fetcher, options)
 }
 
-pub(crate) fn check_with_resolver<R>(
-    resolver: &R,
+/// Async variant of [`check_auth_records_with_options`] (`async` feature),
+/// querying the tokio resolver.
+/// Replays the sync logic per lookup, see [`AsyncDnsBackend`](crate::AsyncDnsBackend).
+#[cfg(feature = "async")]
+pub async fn check_auth_records_async(
+    domain: &str,
+    options: &AuthLookupOptions,
+) -> Result<AuthStatus, AuthError> {
+    let backend = options
+        .dns_config()
+        .build_async_backend()
+        .map_err(AuthError::resolver_init)?;
+    check_auth_records_async_with_backend(&backend, domain, options).await
+}
+
+/// Async variant of [`check_auth_records_with_backend`].
+/// Replays the sync logic per lookup, see [`AsyncDnsBackend`](crate::AsyncDnsBackend).
+#[cfg(feature = "async")]
+pub async fn check_auth_records_async_with_backend<B>(
+    backend: &B,
+    domain: &str,
+    options: &AuthLookupOptions,
+) -> Result<AuthStatus, AuthError>
+where
+    B: AsyncDnsBackend + ?Sized,
+{
+    let ascii = normalize_domain(domain)?;
+    let status = replay(backend, |prefetched| {
+        check_with_backend(prefetched, &ascii, options)
+    })
+    .await?;
+    #[cfg(feature = "with-mta-sts")]
+    let status =
+        mta_sts::complete_async(status, backend, &HttpsPolicyFetcher::default(), options).await?;
+    Ok(status)
+}
+
+/// Async variant of [`check_auth_records_with_fetcher`].
+/// Replays the sync logic per lookup, see [`AsyncDnsBackend`](crate::AsyncDnsBackend).
+#[cfg(all(feature = "with-mta-sts", feature = "async"))]
+pub async fn check_auth_records_async_with_fetcher<B, F>(
+    backend: &B,
+    fetcher: &F,
+    domain: &str,
+    options: &AuthLookupOptions,
+) -> Result<AuthStatus, AuthError>
+where
+    B: AsyncDnsBackend + ?Sized,
+    F: AsyncPolicyFetcher + ?Sized,
+{
+    let ascii = normalize_domain(domain)?;
+    let status = replay(backend, |prefetched| {
+        check_with_backend(prefetched, &ascii, options)
+    })
+    .await?;
+    mta_sts::complete_async(status, backend, fetcher, options).await
+}
+
+/// Evaluates the SPF policy of `domain` for a message from `sender` sent by
+/// `ip` (RFC 7208 `check_host()`). `sender` is the `MAIL FROM` address, or
+/// the HELO name when the reverse path is empty.
+pub fn check_spf_host(
+    ip: IpAddr,
+    domain: &str,
+    sender: &str,
+    options: &AuthLookupOptions,
+) -> Result<SpfCheck, AuthError> {
+    let ascii = normalize_domain(domain)?;
+    let backend = options
+        .dns_config()
+        .build_backend()
+        .map_err(AuthError::resolver_init)?;
+    check_spf_host_with_backend(&*backend, ip, &ascii, sender)
+}
+
+/// Like [`check_spf_host`], but queries `backend` instead of building a
+/// resolver. DNS failures are reported as `temperror`, not as errors.
+pub fn check_spf_host_with_backend<B>(
+    backend: &B,
+    ip: IpAddr,
+    domain: &str,
+    sender: &str,
+) -> Result<SpfCheck, AuthError>
+where
+    B: DnsBackend + ?Sized,
+{
+    let ascii = normalize_domain(domain)?;
+    Ok(spf::check_host(backend, ip, &ascii, sender))
+}
+
+/// Async variant of [`check_spf_host`] (`async` feature).
+/// Replays the sync logic per lookup, see [`AsyncDnsBackend`](crate::AsyncDnsBackend).
+#[cfg(feature = "async")]
+pub async fn check_spf_host_async(
+    ip: IpAddr,
+    domain: &str,
+    sender: &str,
+    options: &AuthLookupOptions,
+) -> Result<SpfCheck, AuthError> {
+    let backend = options
+        .dns_config()
+        .build_async_backend()
+        .map_err(AuthError::resolver_init)?;
+    check_spf_host_async_with_backend(&backend, ip, domain, sender).await
+}
+
+/// Async variant of [`check_spf_host_with_backend`].
+/// Replays the sync logic per lookup, see [`AsyncDnsBackend`](crate::AsyncDnsBackend).
+#[cfg(feature = "async")]
+pub async fn check_spf_host_async_with_backend<B>(
+    backend: &B,
+    ip: IpAddr,
+    domain: &str,
+    sender: &str,
+) -> Result<SpfCheck, AuthError>
+where
+    B: AsyncDnsBackend + ?Sized,
+{
+    let ascii = normalize_domain(domain)?;
+    Ok(replay(backend, |prefetched| {
+        spf::check_host(prefetched, ip, &ascii, sender)
+    })
+    .await)
+}
+
+/// Walks the SPF include/redirect tree of `domain` and reports lookup
+/// counts and common defects per branch.
+pub fn lint_spf(domain: &str, options: &AuthLookupOptions) -> Result<SpfTree, AuthError> {
+    let ascii = normalize_domain(domain)?;
+    let backend = options
+        .dns_config()
+        .build_backend()
+        .map_err(AuthError::resolver_init)?;
+    lint_spf_with_backend(&*backend, &ascii)
+}
+
+/// Like [`lint_spf`], but queries `backend` instead of building a resolver.
+/// Only a failed lookup of the root record is an error; deeper failures are
+/// reported as [`SpfLint::LookupFailed`].
+pub fn lint_spf_with_backend<B>(backend: &B, domain: &str) -> Result<SpfTree, AuthError>
+where
+    B: DnsBackend + ?Sized,
+{
+    let ascii = normalize_domain(domain)?;
+    spf::lint(backend, &ascii).map_err(|source| AuthError::TxtLookup {
+        name: ascii.clone(),
+        source,
+    })
+}
+
+/// Async variant of [`lint_spf`] (`async` feature).
+/// Replays the sync logic per lookup, see [`AsyncDnsBackend`](crate::AsyncDnsBackend).
+#[cfg(feature = "async")]
+pub async fn lint_spf_async(
+    domain: &str,
+    options: &AuthLookupOptions,
+) -> Result<SpfTree, AuthError> {
+    let backend = options
+        .dns_config()
+        .build_async_backend()
+        .map_err(AuthError::resolver_init)?;
+    lint_spf_async_with_backend(&backend, domain).await
+}
+
+/// Async variant of [`lint_spf_with_backend`].
+/// Replays the sync logic per lookup, see [`AsyncDnsBackend`](crate::AsyncDnsBackend).
+#[cfg(feature = "async")]
+pub async fn lint_spf_async_with_backend<B>(backend: &B, domain: &str) -> Result<SpfTree, AuthError>
+where
+    B: AsyncDnsBackend + ?Sized,
+{
+    let ascii = normalize_domain(domain)?;
+    replay(backend, |prefetched| spf::lint(prefetched, &ascii))
+        .await
+        .map_err(|source| AuthError::TxtLookup {
+            name: ascii.clone(),
+            source,
+        })
+}
+
+/// Resolves the SPF include tree of `domain` into `ip4:`/`ip6:` mechanisms
+/// and returns the records to publish, chained when they exceed
+/// [`SpfFlattenOptions::max_record_len`].
+pub fn flatten_spf(
+    domain: &str,
+    options: &AuthLookupOptions,
+    flatten: &SpfFlattenOptions,
+) -> Result<SpfFlattened, AuthError> {
+    let ascii = normalize_domain(domain)?;
+    let backend = options
+        .dns_config()
+        .build_backend()
+        .map_err(AuthError::resolver_init)?;
+    flatten_spf_with_backend(&*backend, &ascii, flatten)
+}
+
+/// Like [`flatten_spf`], but queries `backend` instead of building a
+/// resolver.
+pub fn flatten_spf_with_backend<B>(
+    backend: &B,
+    domain: &str,
+    flatten: &SpfFlattenOptions,
+) -> Result<SpfFlattened, AuthError>
+where
+    B: DnsBackend + ?Sized,
+{
+    let ascii = normalize_domain(domain)?;
+    spf::flatten(backend, &ascii, flatten).map_err(|source| AuthError::SpfFlatten { source })
+}
+
+/// Async variant of [`flatten_spf`] (`async` feature).
+/// Replays the sync logic per lookup, see [`AsyncDnsBackend`](crate::AsyncDnsBackend).
+#[cfg(feature = "async")]
+pub async fn flatten_spf_async(
+    domain: &str,
+    options: &AuthLookupOptions,
+    flatten: &SpfFlattenOptions,
+) -> Result<SpfFlattened, AuthError> {
+    let backend = options
+        .dns_config()
+        .build_async_backend()
+        .map_err(AuthError::resolver_init)?;
+    flatten_spf_async_with_backend(&backend, domain, flatten).await
+}
+
+/// Async variant of [`flatten_spf_with_backend`].
+/// Replays the sync logic per lookup, see [`AsyncDnsBackend`](crate::AsyncDnsBackend).
+#[cfg(feature = "async")]
+pub async fn flatten_spf_async_with_backend<B>(
+    backend: &B,
+    domain: &str,
+    flatten: &SpfFlattenOptions,
+) -> Result<SpfFlattened, AuthError>
+where
+    B: AsyncDnsBackend + ?Sized,
+{
+    let ascii = normalize_domain(domain)?;
+    replay(backend, |prefetched| {
+        spf::flatten(prefetched, &ascii, flatten)
+    })
+    .await
+    .map_err(|source| AuthError::SpfFlatten { source })
+}
+
+pub(crate) fn check_with_backend<B>(
+    backend: &B,
     ascii_domain: &str,
     options: &AuthLookupOptions,
 ) -> Result<AuthStatus, AuthError>
 where
-    R: LookupTxt,
+    B: DnsBackend + ?Sized,
 {
-    let spf_records = resolver.lookup_txt(ascii_domain)?;
+    let mut dnssec = Vec::new();
+    let spf_records = lookup_txt(backend, ascii_domain, &mut dnssec)?;
     let spf_status = spf::evaluate(&spf_records);
 
-    let dmarc_name = fqdn("_dmarc", ascii_domain);
-    let dmarc_records = resolver.lookup_txt(&dmarc_name)?;
-    let dmarc_status = dmarc::evaluate(&dmarc_records);
+    let dmarc_status = dmarc::discover(
+        backend,
+        ascii_domain,
+        options.public_suffix_list(),
+        &mut dnssec,
+    )?;
 
     let policy_status = if options.check_dkim_policy() {
         let policy_name = fqdn("_domainkey", ascii_domain);
-        let policy_records = resolver.lookup_txt(&policy_name)?;
+        let policy_records = lookup_txt(backend, &policy_name, &mut dnssec)?;
         dkim::policy_status(&policy_records)
     } else {
         dkim::policy_not_requested()
@@ -53,17 +369,42 @@ where
     let mut selector_statuses = Vec::new();
     for selector in options.dkim_selectors() {
         let selector_name = fqdn(&format!("{}._domainkey", selector), ascii_domain);
-        let selector_records = resolver.lookup_txt(&selector_name)?;
+        let selector_records = lookup_txt(backend, &selector_name, &mut dnssec)?;
         selector_statuses.push(dkim::selector_status(selector, &selector_records));
     }
 
     let dkim_status = dkim::assemble_status(policy_status, selector_statuses);
 
+    let tls_rpt_name = fqdn("_smtp._tls", ascii_domain);
+    let tls_rpt_status = match lookup_txt(backend, &tls_rpt_name, &mut dnssec) {
+        Ok(records) => tls_rpt::evaluate(&records),
+        Err(err) => TlsRptStatus::LookupFailed {
+            reason: err.to_string(),
+        },
+    };
+
+    let mut bimi_statuses = Vec::new();
+    let bimi_selectors = options.bimi_selectors().iter().map(String::as_str);
+    for selector in std::iter::once(bimi::DEFAULT_SELECTOR).chain(bimi_selectors) {
+        let bimi_name = fqdn(&format!("{}._bimi", selector), ascii_domain);
+        bimi_statuses.push(match lookup_txt(backend, &bimi_name, &mut dnssec) {
+            Ok(records) => bimi::selector_status(selector, &records),
+            Err(err) => BimiSelectorStatus::LookupFailed {
+                selector: selector.to_string(),
+                reason: err.to_string(),
+            },
+        });
+    }
+    let bimi_status = bimi::assemble_status(bimi_statuses, &dmarc_status);
+
     Ok(AuthStatus::new(
         ascii_domain.to_string(),
         spf_status,
         dmarc_status,
         dkim_status,
+        tls_rpt_status,
+        bimi_status,
+        dnssec,
     ))
 }
 
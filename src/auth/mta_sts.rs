@@ -0,0 +1,319 @@
+//! MTA-STS (RFC 8461) : enregistrement `_mta-sts`, politique publiée en
+//! HTTPS et couverture des MX du domaine par ses motifs `mx:`.
+
+use crate::dns::DnsBackend;
+#[cfg(feature = "async")]
+use crate::dns::{AsyncDnsBackend, replay};
+use crate::mx::{MxLookupOptions, MxStatus, check_mx_with_backend};
+
+#[cfg(feature = "async")]
+use super::fetch::AsyncPolicyFetcher;
+use super::fetch::PolicyFetcher;
+use super::resolver::{fqdn, lookup_txt};
+use super::{AuthError, AuthLookupOptions, AuthStatus, DnssecLookup};
+
+/// Durée de validité maximale d'une politique (RFC 8461 §3.2).
+const MAX_AGE_LIMIT: u64 = 31_557_600;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MtaStsStatus {
+    /// Vérification non demandée ([`AuthLookupOptions::check_mta_sts`]).
+    NotRequested,
+    /// Pas d'enregistrement `_mta-sts` : pas de politique.
+    Missing,
+    MultipleRecords {
+        records: Vec<String>,
+    },
+    InvalidRecord {
+        record: String,
+        issue: MtaStsIssue,
+    },
+    /// Enregistrement valide mais politique impossible à récupérer.
+    PolicyUnavailable {
+        id: String,
+        reason: String,
+    },
+    InvalidPolicy {
+        id: String,
+        issue: MtaStsIssue,
+    },
+    /// Politique valide ; `uncovered_mx` liste les MX qu'aucun motif `mx:`
+    /// n'autorise (ils seraient refusés en mode `enforce`).
+    Published {
+        id: String,
+        policy: MtaStsPolicy,
+        uncovered_mx: Vec<String>,
+    },
+}
+
+impl MtaStsStatus {
+    /// Politique `enforce` couvrant tous les MX.
+    pub fn is_enforced(&self) -> bool {
+        matches!(
+            self,
+            Self::Published { policy, uncovered_mx, .. }
+                if policy.mode == MtaStsMode::Enforce && uncovered_mx.is_empty()
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MtaStsPolicy {
+    pub mode: MtaStsMode,
+    /// Motifs `mx:`, en minuscules (`*.example.com` couvre un seul label).
+    pub mx: Vec<String>,
+    /// Durée de mise en cache, en secondes.
+    pub max_age: u64,
+}
+
+impl MtaStsPolicy {
+    /// `host` est-il autorisé par un des motifs `mx:` (RFC 8461 §4.1) ?
+    pub fn covers(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        self.mx
+            .iter()
+            .any(|pattern| match pattern.strip_prefix("*.") {
+                Some(suffix) => host
+                    .split_once('.')
+                    .is_some_and(|(label, rest)| !label.is_empty() && rest == suffix),
+                None => host == *pattern,
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MtaStsMode {
+    Enforce,
+    Testing,
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MtaStsIssue {
+    InvalidVersion,
+    MissingId,
+    InvalidId {
+        id: String,
+    },
+    MissingMode,
+    UnknownMode {
+        mode: String,
+    },
+    MissingMaxAge,
+    InvalidMaxAge {
+        value: String,
+    },
+    /// Aucun motif `mx:` alors que le mode n'est pas `none`.
+    MissingMx,
+}
+
+/// Issue de la partie DNS : conclusion immédiate, ou politique à récupérer.
+#[derive(Debug)]
+pub(crate) enum Discovery {
+    Done(MtaStsStatus),
+    Fetch { id: String, mx_hosts: Vec<String> },
+}
+
+/// Complète `status` avec MTA-STS si [`AuthLookupOptions::check_mta_sts`].
+pub(crate) fn complete<B, F>(
+    mut status: AuthStatus,
+    backend: &B,
+    fetcher: &F,
+    options: &AuthLookupOptions,
+) -> Result<AuthStatus, AuthError>
+where
+    B: DnsBackend + ?Sized,
+    F: PolicyFetcher + ?Sized,
+{
+    if !options.checks_mta_sts() {
+        return Ok(status);
+    }
+    let (discovery, dnssec) = discover(backend, &status.domain)?;
+    status.dnssec.extend(dnssec);
+    status.mta_sts = match discovery {
+        Discovery::Done(result) => result,
+        Discovery::Fetch { id, mx_hosts } => {
+            let fetched = fetcher.fetch(&policy_host(&status.domain));
+            evaluate(id, &mx_hosts, fetched.map_err(|err| err.to_string()))
+        }
+    };
+    Ok(status)
+}
+
+/// Variante async de [`complete`].
+#[cfg(feature = "async")]
+pub(crate) async fn complete_async<B, F>(
+    mut status: AuthStatus,
+    backend: &B,
+    fetcher: &F,
+    options: &AuthLookupOptions,
+) -> Result<AuthStatus, AuthError>
+where
+    B: AsyncDnsBackend + ?Sized,
+    F: AsyncPolicyFetcher + ?Sized,
+{
+    if !options.checks_mta_sts() {
+        return Ok(status);
+    }
+    let domain = status.domain.clone();
+    let (discovery, dnssec) = replay(backend, |prefetched| discover(prefetched, &domain)).await?;
+    status.dnssec.extend(dnssec);
+    status.mta_sts = match discovery {
+        Discovery::Done(result) => result,
+        Discovery::Fetch { id, mx_hosts } => {
+            let fetched = fetcher.fetch(&policy_host(&domain)).await;
+            evaluate(id, &mx_hosts, fetched.map_err(|err| err.to_string()))
+        }
+    };
+    Ok(status)
+}
+
+fn policy_host(domain: &str) -> String {
+    fqdn("mta-sts", domain)
+}
+
+/// TXT `_mta-sts` puis, s'il est valide, les MX à comparer à la politique.
+pub(crate) fn discover<B>(
+    backend: &B,
+    domain: &str,
+) -> Result<(Discovery, Vec<DnssecLookup>), AuthError>
+where
+    B: DnsBackend + ?Sized,
+{
+    let mut dnssec = Vec::new();
+    let records = lookup_txt(backend, &fqdn("_mta-sts", domain), &mut dnssec)?;
+    let id = match parse_record(&records) {
+        Ok(id) => id,
+        Err(status) => return Ok((Discovery::Done(status), dnssec)),
+    };
+    let mx_hosts = match check_mx_with_backend(backend, domain, &MxLookupOptions::default())
+        .map_err(AuthError::mx_lookup)?
+    {
+        MxStatus::Records { records, .. } => records
+            .into_iter()
+            .filter(|record| !record.is_null())
+            .map(|record| record.exchange)
+            .collect(),
+        MxStatus::NoMxButAddress { .. } => vec![domain.to_string()],
+        _ => Vec::new(),
+    };
+    Ok((Discovery::Fetch { id, mx_hosts }, dnssec))
+}
+
+/// `id` de l'unique enregistrement `v=STSv1` (RFC 8461 §3.1).
+pub(crate) fn parse_record(records: &[String]) -> Result<String, MtaStsStatus> {
+    let mut sts: Vec<String> = records
+        .iter()
+        .map(|record| record.trim())
+        .filter(|record| record.starts_with("v=STSv1"))
+        .map(str::to_string)
+        .collect();
+    if sts.is_empty() {
+        return Err(MtaStsStatus::Missing);
+    }
+    if sts.len() > 1 {
+        sts.sort();
+        sts.dedup();
+        return Err(MtaStsStatus::MultipleRecords { records: sts });
+    }
+    let record = sts.remove(0);
+    let invalid = |issue| MtaStsStatus::InvalidRecord {
+        record: record.clone(),
+        issue,
+    };
+
+    let mut fields = record
+        .split(';')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .map(|field| field.split_once('=').unwrap_or((field, "")));
+    if fields.next() != Some(("v", "STSv1")) {
+        return Err(invalid(MtaStsIssue::InvalidVersion));
+    }
+    let Some((_, id)) = fields.find(|(key, _)| *key == "id") else {
+        return Err(invalid(MtaStsIssue::MissingId));
+    };
+    if id.is_empty() || id.len() > 32 || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(invalid(MtaStsIssue::InvalidId { id: id.to_string() }));
+    }
+    Ok(id.to_string())
+}
+
+/// Politique récupérée (ou raison de l'échec) confrontée aux MX.
+pub(crate) fn evaluate(
+    id: String,
+    mx_hosts: &[String],
+    fetched: Result<String, String>,
+) -> MtaStsStatus {
+    let body = match fetched {
+        Ok(body) => body,
+        Err(reason) => return MtaStsStatus::PolicyUnavailable { id, reason },
+    };
+    match parse_policy(&body) {
+        Ok(policy) => {
+            let uncovered_mx = mx_hosts
+                .iter()
+                .filter(|host| !policy.covers(host))
+                .cloned()
+                .collect();
+            MtaStsStatus::Published {
+                id,
+                policy,
+                uncovered_mx,
+            }
+        }
+        Err(issue) => MtaStsStatus::InvalidPolicy { id, issue },
+    }
+}
+
+/// Lignes `clé: valeur` de la politique (RFC 8461 §3.2) ; les clés
+/// inconnues sont ignorées.
+pub(crate) fn parse_policy(body: &str) -> Result<MtaStsPolicy, MtaStsIssue> {
+    let mut version = None;
+    let mut mode = None;
+    let mut max_age = None;
+    let mut mx = Vec::new();
+    for line in body.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "version" => version = Some(value),
+            "mode" => mode = Some(value),
+            "max_age" => max_age = Some(value),
+            "mx" => mx.push(value.trim_end_matches('.').to_ascii_lowercase()),
+            _ => {}
+        }
+    }
+
+    if version != Some("STSv1") {
+        return Err(MtaStsIssue::InvalidVersion);
+    }
+    let mode = match mode {
+        Some("enforce") => MtaStsMode::Enforce,
+        Some("testing") => MtaStsMode::Testing,
+        Some("none") => MtaStsMode::None,
+        Some(other) => {
+            return Err(MtaStsIssue::UnknownMode {
+                mode: other.to_string(),
+            });
+        }
+        None => return Err(MtaStsIssue::MissingMode),
+    };
+    let max_age = match max_age {
+        Some(value) => Some(value)
+            .filter(|value| !value.is_empty() && value.len() <= 10)
+            .filter(|value| value.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|value| value.parse::<u64>().ok())
+            .filter(|age| *age <= MAX_AGE_LIMIT)
+            .ok_or_else(|| MtaStsIssue::InvalidMaxAge {
+                value: value.to_string(),
+            })?,
+        None => return Err(MtaStsIssue::MissingMaxAge),
+    };
+    if mx.is_empty() && mode != MtaStsMode::None {
+        return Err(MtaStsIssue::MissingMx);
+    }
+    Ok(MtaStsPolicy { mode, mx, max_age })
+}
@@ -0,0 +1,91 @@
+//! Public Suffix List (format publicsuffix.org), pour le domaine
+//! organisationnel DMARC (RFC 7489 §3.2).
+
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Règles d'une Public Suffix List.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PublicSuffixList {
+    rules: HashSet<String>,
+    /// Règles `*.suffixe`, stockées sans le `*.`.
+    wildcards: HashSet<String>,
+    /// Règles `!exception`.
+    exceptions: HashSet<String>,
+}
+
+impl PublicSuffixList {
+    /// Lit une liste au format `public_suffix_list.dat` ; les lignes
+    /// illisibles sont ignorées.
+    pub fn from_psl_str(input: &str) -> Self {
+        let mut list = Self::default();
+        for line in input.lines() {
+            let Some(rule) = line.split_whitespace().next() else {
+                continue;
+            };
+            if rule.starts_with("//") {
+                continue;
+            }
+            if let Some(exception) = rule.strip_prefix('!') {
+                if let Some(name) = to_ascii(exception) {
+                    list.exceptions.insert(name);
+                }
+            } else if let Some(suffix) = rule.strip_prefix("*.") {
+                if let Some(name) = to_ascii(suffix) {
+                    list.wildcards.insert(name);
+                }
+            } else if let Some(name) = to_ascii(rule) {
+                list.rules.insert(name);
+            }
+        }
+        list
+    }
+
+    /// Charge `public_suffix_list.dat` depuis le disque.
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        std::fs::read_to_string(path).map(|input| Self::from_psl_str(&input))
+    }
+
+    /// Suffixe public de `domain` (règle par défaut `*` : le dernier label).
+    pub fn public_suffix<'a>(&self, domain: &'a str) -> &'a str {
+        let domain = domain.trim_end_matches('.');
+        let starts: Vec<usize> = std::iter::once(0)
+            .chain(domain.match_indices('.').map(|(idx, _)| idx + 1))
+            .collect();
+        let mut longest = None;
+        for (position, start) in starts.iter().enumerate() {
+            let candidate = &domain[*start..];
+            if self.exceptions.contains(&candidate.to_ascii_lowercase()) {
+                return starts
+                    .get(position + 1)
+                    .map_or(candidate, |next| &domain[*next..]);
+            }
+            if longest.is_some() {
+                continue;
+            }
+            let parent = starts.get(position + 1).map(|next| &domain[*next..]);
+            let wildcard =
+                parent.is_some_and(|parent| self.wildcards.contains(&parent.to_ascii_lowercase()));
+            if wildcard || self.rules.contains(&candidate.to_ascii_lowercase()) {
+                longest = Some(candidate);
+            }
+        }
+        longest.unwrap_or_else(|| starts.last().map_or(domain, |start| &domain[*start..]))
+    }
+
+    /// Domaine organisationnel : le suffixe public et le label qui le
+    /// précède ; `None` si `domain` est lui-même un suffixe public.
+    pub fn organizational_domain(&self, domain: &str) -> Option<String> {
+        let domain = domain.trim_end_matches('.');
+        let suffix = self.public_suffix(domain);
+        let head = domain.strip_suffix(suffix)?.strip_suffix('.')?;
+        let label = head.rsplit('.').next()?;
+        Some(format!("{label}.{suffix}").to_ascii_lowercase())
+    }
+}
+
+fn to_ascii(name: &str) -> Option<String> {
+    idna::domain_to_ascii(name)
+        .ok()
+        .filter(|name| !name.is_empty())
+}
@@ -1,10 +1,6 @@
-use trust_dns_resolver::{
-    Resolver,
-    error::{ResolveError, ResolveErrorKind},
-    lookup::TxtLookup,
-};
+use crate::dns::{DnsBackend, DnsRecordType, nx_as_answer};
 
-use super::AuthError;
+use super::{AuthError, DnssecLookup};
 
 pub(crate) fn normalize_domain(domain: &str) -> Result<String, AuthError> {
     let trimmed = domain.trim();
@@ -23,39 +19,21 @@ pub(crate) fn fqdn(label: &str, domain: &str) -> String {
     }
 }
 
-pub(crate) trait LookupTxt {
-    fn lookup_txt(&self, name: &str) -> Result<Vec<String>, AuthError>;
-}
-
-impl LookupTxt for Resolver {
-    fn lookup_txt(&self, name: &str) -> Result<Vec<String>, AuthError> {
-        let lookup = match Resolver::txt_lookup(self, name) {
-            Ok(lookup) => lookup,
-            Err(err) => {
-                if should_treat_as_empty(&err) {
-                    return Ok(Vec::new());
-                }
-                return Err(AuthError::txt_lookup(name, err));
-            }
-        };
-        collect_txt_records(name, &lookup)
-    }
-}
-
-fn collect_txt_records(name: &str, lookup: &TxtLookup) -> Result<Vec<String>, AuthError> {
-    let mut records = Vec::new();
-    for txt in lookup.iter() {
-        let mut record = String::new();
-        for piece in txt.txt_data().iter() {
-            let segment = std::str::from_utf8(piece.as_ref())
-                .map_err(|err| AuthError::txt_data_utf8(name, err))?;
-            record.push_str(segment);
-        }
-        records.push(record);
-    }
-    Ok(records)
-}
-
-fn should_treat_as_empty(err: &ResolveError) -> bool {
-    matches!(err.kind(), ResolveErrorKind::NoRecordsFound { .. })
+/// Enregistrements TXT de `name` ; NXDOMAIN et NODATA donnent une liste vide.
+/// Le statut DNSSEC de la réponse est ajouté à `dnssec`.
+pub(crate) fn lookup_txt<B>(
+    backend: &B,
+    name: &str,
+    dnssec: &mut Vec<DnssecLookup>,
+) -> Result<Vec<String>, AuthError>
+where
+    B: DnsBackend + ?Sized,
+{
+    let answer = nx_as_answer(backend.lookup(name, DnsRecordType::Txt))
+        .map_err(|err| AuthError::txt_lookup(name, err))?;
+    dnssec.push(DnssecLookup {
+        name: name.to_string(),
+        status: answer.dnssec,
+    });
+    Ok(answer.txt())
 }
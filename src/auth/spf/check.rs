@@ -0,0 +1,501 @@
+//! Fonction `check_host()` de la RFC 7208 (§4) : évaluation de l'IP d'un
+//! client SMTP contre la politique SPF d'un domaine.
+
+use std::net::IpAddr;
+
+use crate::dns::{DnsAnswer, DnsBackend, DnsRecordType, nx_as_answer};
+
+use super::SpfQualifier;
+use super::macros::{self, MacroContext};
+use super::record::{self, DualCidr, Mechanism};
+
+/// Requêtes DNS autorisées (`include`, `a`, `mx`, `ptr`, `exists`,
+/// `redirect`), §4.6.4.
+pub(crate) const MAX_DNS_LOOKUPS: usize = 10;
+/// Requêtes sans réponse (NXDOMAIN ou NODATA) tolérées.
+pub(crate) const MAX_VOID_LOOKUPS: usize = 2;
+/// Noms examinés par requête `mx` ou `ptr`.
+const MAX_NAMES: usize = 10;
+
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "with-serde", serde(rename_all = "lowercase"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpfResult {
+    Pass,
+    Fail,
+    SoftFail,
+    Neutral,
+    /// Pas d'enregistrement SPF (ou domaine invalide).
+    None,
+    PermError,
+    TempError,
+}
+
+impl SpfResult {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pass => "pass",
+            Self::Fail => "fail",
+            Self::SoftFail => "softfail",
+            Self::Neutral => "neutral",
+            Self::None => "none",
+            Self::PermError => "permerror",
+            Self::TempError => "temperror",
+        }
+    }
+}
+
+impl From<SpfQualifier> for SpfResult {
+    fn from(qualifier: SpfQualifier) -> Self {
+        match qualifier {
+            SpfQualifier::Pass => Self::Pass,
+            SpfQualifier::Fail => Self::Fail,
+            SpfQualifier::SoftFail => Self::SoftFail,
+            SpfQualifier::Neutral => Self::Neutral,
+        }
+    }
+}
+
+/// Résultat de [`check_spf_host`](crate::auth::check_spf_host) et détail de
+/// l'évaluation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpfCheck {
+    pub result: SpfResult,
+    /// Texte `exp=` étendu, pour un `fail`.
+    pub explanation: Option<String>,
+    /// Termes ayant demandé une requête DNS (limite : 10).
+    pub dns_lookups: usize,
+    /// Requêtes restées sans réponse (limite : 2).
+    pub void_lookups: usize,
+    pub trace: Vec<SpfTraceStep>,
+}
+
+/// Étape de l'évaluation, dans l'ordre.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpfTraceStep {
+    /// 0 pour le domaine initial, +1 par `include` ou `redirect`.
+    pub depth: usize,
+    pub domain: String,
+    pub event: SpfTraceEvent,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpfTraceEvent {
+    /// Enregistrement SPF retenu pour le domaine.
+    Record {
+        record: String,
+    },
+    /// Terme évalué ; `matched` s'il s'applique à l'IP.
+    Term {
+        term: String,
+        matched: bool,
+    },
+    Redirect {
+        target: String,
+    },
+    /// Conclusion pour ce domaine ; `reason` explique les erreurs.
+    Result {
+        result: SpfResult,
+        reason: Option<String>,
+    },
+}
+
+/// Évaluation interrompue par un `permerror` ou un `temperror`.
+#[derive(Debug, Clone)]
+struct Abort {
+    result: SpfResult,
+    reason: String,
+}
+
+impl Abort {
+    fn perm(reason: impl Into<String>) -> Self {
+        Self {
+            result: SpfResult::PermError,
+            reason: reason.into(),
+        }
+    }
+
+    fn temp(reason: impl Into<String>) -> Self {
+        Self {
+            result: SpfResult::TempError,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Résultat d'un niveau et, pour un `fail`, son explication.
+type Outcome = Result<(SpfResult, Option<String>), Abort>;
+
+/// Évalue `ip` pour `domain` (déjà en ASCII) ; `sender` est l'adresse
+/// `MAIL FROM` (ou le nom HELO, traité comme `postmaster@<helo>`).
+pub(crate) fn check_host<B>(backend: &B, ip: IpAddr, domain: &str, sender: &str) -> SpfCheck
+where
+    B: DnsBackend + ?Sized,
+{
+    // Adresse IPv4 transportée en IPv6 : évaluée comme IPv4 (§5.6).
+    let ip = match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        ip => ip,
+    };
+    let sender = sender.trim();
+    let (local, sender_domain) = match sender.rsplit_once('@') {
+        Some(("", domain)) => ("postmaster", domain),
+        Some((local, domain)) => (local, domain),
+        None => ("postmaster", sender),
+    };
+    let sender = format!("{local}@{sender_domain}");
+    let mut evaluator = Evaluator {
+        backend,
+        ip,
+        sender: &sender,
+        local,
+        sender_domain,
+        dns_lookups: 0,
+        void_lookups: 0,
+        trace: Vec::new(),
+    };
+    let (result, explanation) = match evaluator.check_domain(domain, 0) {
+        Ok(outcome) => outcome,
+        Err(abort) => (abort.result, None),
+    };
+    SpfCheck {
+        result,
+        explanation,
+        dns_lookups: evaluator.dns_lookups,
+        void_lookups: evaluator.void_lookups,
+        trace: evaluator.trace,
+    }
+}
+
+struct Evaluator<'a, B: ?Sized> {
+    backend: &'a B,
+    ip: IpAddr,
+    sender: &'a str,
+    local: &'a str,
+    sender_domain: &'a str,
+    dns_lookups: usize,
+    void_lookups: usize,
+    trace: Vec<SpfTraceStep>,
+}
+
+impl<B> Evaluator<'_, B>
+where
+    B: DnsBackend + ?Sized,
+{
+    fn check_domain(&mut self, domain: &str, depth: usize) -> Outcome {
+        let outcome = self.evaluate(domain, depth);
+        let (result, reason) = match &outcome {
+            Ok((result, _)) => (*result, None),
+            Err(abort) => (abort.result, Some(abort.reason.clone())),
+        };
+        self.push(depth, domain, SpfTraceEvent::Result { result, reason });
+        outcome
+    }
+
+    fn evaluate(&mut self, domain: &str, depth: usize) -> Outcome {
+        if !is_valid_domain(domain) {
+            return Ok((SpfResult::None, None));
+        }
+        let records = self.query(domain, DnsRecordType::Txt)?.txt();
+        let selected = record::select(&records);
+        let text = match selected.as_slice() {
+            [] => return Ok((SpfResult::None, None)),
+            [text] => text.to_string(),
+            _ => return Err(Abort::perm("multiple SPF records")),
+        };
+        self.push(
+            depth,
+            domain,
+            SpfTraceEvent::Record {
+                record: text.clone(),
+            },
+        );
+        let spf = record::parse(&text).map_err(Abort::perm)?;
+
+        for directive in &spf.directives {
+            let matched = self.matches(&directive.mechanism, domain, depth)?;
+            self.push(
+                depth,
+                domain,
+                SpfTraceEvent::Term {
+                    term: directive.term.clone(),
+                    matched,
+                },
+            );
+            if matched {
+                let result = SpfResult::from(directive.qualifier);
+                let explanation = match (&spf.explanation, result) {
+                    (Some(exp), SpfResult::Fail) => self.explain(exp, domain),
+                    _ => None,
+                };
+                return Ok((result, explanation));
+            }
+        }
+
+        match &spf.redirect {
+            Some(target) => {
+                self.count_lookup()?;
+                let target = self.expand_domain(target, domain)?;
+                self.push(
+                    depth,
+                    domain,
+                    SpfTraceEvent::Redirect {
+                        target: target.clone(),
+                    },
+                );
+                match self.check_domain(&target, depth + 1)? {
+                    (SpfResult::None, _) => Err(Abort::perm(format!(
+                        "redirect target {target} has no SPF record"
+                    ))),
+                    outcome => Ok(outcome),
+                }
+            }
+            None => Ok((SpfResult::Neutral, None)),
+        }
+    }
+
+    fn matches(
+        &mut self,
+        mechanism: &Mechanism,
+        domain: &str,
+        depth: usize,
+    ) -> Result<bool, Abort> {
+        if mechanism.queries_dns() {
+            self.count_lookup()?;
+        }
+        match mechanism {
+            Mechanism::All => Ok(true),
+            Mechanism::Ip4(network, prefix) => {
+                Ok(in_network(self.ip, IpAddr::V4(*network), *prefix))
+            }
+            Mechanism::Ip6(network, prefix) => {
+                Ok(in_network(self.ip, IpAddr::V6(*network), *prefix))
+            }
+            Mechanism::Include(spec) => {
+                let target = self.expand_domain(spec, domain)?;
+                match self.check_domain(&target, depth + 1)? {
+                    (SpfResult::Pass, _) => Ok(true),
+                    (SpfResult::None, _) => Err(Abort::perm(format!(
+                        "included domain {target} has no SPF record"
+                    ))),
+                    _ => Ok(false),
+                }
+            }
+            Mechanism::A { domain: spec, cidr } => {
+                let target = self.target(spec.as_deref(), domain)?;
+                let addrs = self.addresses(&target, true)?;
+                Ok(self.any_in_network(&addrs, *cidr))
+            }
+            Mechanism::Mx { domain: spec, cidr } => {
+                let target = self.target(spec.as_deref(), domain)?;
+                let answer = self.query(&target, DnsRecordType::Mx)?;
+                self.count_void(&answer)?;
+                let exchanges = answer.mx();
+                if exchanges.len() > MAX_NAMES {
+                    return Err(Abort::perm(format!("{target}: more than 10 MX records")));
+                }
+                for exchange in exchanges.iter().filter(|mx| !mx.exchange.is_empty()) {
+                    let addrs = self.addresses(&exchange.exchange, false)?;
+                    if self.any_in_network(&addrs, *cidr) {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            Mechanism::Ptr(spec) => {
+                let target = self.target(spec.as_deref(), domain)?;
+                Ok(self
+                    .validated_names(true)?
+                    .iter()
+                    .any(|name| is_subdomain(name, &target)))
+            }
+            Mechanism::Exists(spec) => {
+                let target = self.expand_domain(spec, domain)?;
+                let answer = self.query(&target, DnsRecordType::A)?;
+                self.count_void(&answer)?;
+                Ok(!answer.addrs().is_empty())
+            }
+        }
+    }
+
+    /// Noms du PTR de l'IP dont une adresse revient à l'IP (§5.5) ;
+    /// `count_void` compte la requête PTR dans les requêtes vides.
+    fn validated_names(&mut self, count_void: bool) -> Result<Vec<String>, Abort> {
+        let answer = match nx_as_answer(
+            self.backend
+                .lookup(&reverse_name(self.ip), DnsRecordType::Ptr),
+        ) {
+            Ok(answer) => answer,
+            // Une erreur DNS sur le PTR fait simplement échouer la validation.
+            Err(_) => return Ok(Vec::new()),
+        };
+        if count_void {
+            self.count_void(&answer)?;
+        }
+        let mut validated = Vec::new();
+        for name in answer.ptr().into_iter().take(MAX_NAMES) {
+            let addrs = match self.addresses(&name, false) {
+                Ok(addrs) => addrs,
+                Err(_) => continue,
+            };
+            if addrs.contains(&self.ip) {
+                validated.push(name);
+            }
+        }
+        Ok(validated)
+    }
+
+    /// Adresses de la famille de l'IP évaluée.
+    fn addresses(&mut self, name: &str, count_void: bool) -> Result<Vec<IpAddr>, Abort> {
+        let rtype = match self.ip {
+            IpAddr::V4(_) => DnsRecordType::A,
+            IpAddr::V6(_) => DnsRecordType::Aaaa,
+        };
+        let answer = self.query(name, rtype)?;
+        if count_void {
+            self.count_void(&answer)?;
+        }
+        Ok(answer.addrs())
+    }
+
+    fn any_in_network(&self, addrs: &[IpAddr], cidr: DualCidr) -> bool {
+        addrs.iter().any(|addr| {
+            let prefix = match addr {
+                IpAddr::V4(_) => cidr.v4,
+                IpAddr::V6(_) => cidr.v6,
+            };
+            in_network(self.ip, *addr, prefix)
+        })
+    }
+
+    fn query(&self, name: &str, rtype: DnsRecordType) -> Result<DnsAnswer, Abort> {
+        nx_as_answer(self.backend.lookup(name, rtype)).map_err(|err| Abort::temp(err.to_string()))
+    }
+
+    fn count_lookup(&mut self) -> Result<(), Abort> {
+        self.dns_lookups += 1;
+        if self.dns_lookups > MAX_DNS_LOOKUPS {
+            return Err(Abort::perm("more than 10 DNS lookups"));
+        }
+        Ok(())
+    }
+
+    fn count_void(&mut self, answer: &DnsAnswer) -> Result<(), Abort> {
+        if answer.records.is_empty() {
+            self.void_lookups += 1;
+            if self.void_lookups > MAX_VOID_LOOKUPS {
+                return Err(Abort::perm("more than 2 void DNS lookups"));
+            }
+        }
+        Ok(())
+    }
+
+    fn target(&mut self, spec: Option<&str>, domain: &str) -> Result<String, Abort> {
+        match spec {
+            Some(spec) => self.expand_domain(spec, domain),
+            None => Ok(domain.to_string()),
+        }
+    }
+
+    fn expand_domain(&mut self, spec: &str, domain: &str) -> Result<String, Abort> {
+        let validated = self.validated_name(spec, domain);
+        let ctx = self.context(domain, validated.as_deref());
+        macros::expand_domain(spec, &ctx).map_err(Abort::perm)
+    }
+
+    /// Texte `exp=` : TXT du domaine désigné, macros étendues ; toute erreur
+    /// donne simplement un `fail` sans explication (§6.2).
+    fn explain(&mut self, spec: &str, domain: &str) -> Option<String> {
+        let target = self.expand_domain(spec, domain).ok()?;
+        let records = self.query(&target, DnsRecordType::Txt).ok()?.txt();
+        let [text] = records.as_slice() else {
+            return None;
+        };
+        let validated = self.validated_name(text, domain);
+        let ctx = self.context(domain, validated.as_deref());
+        macros::expand(text, &ctx, true).ok()
+    }
+
+    /// `%{p}` : premier nom validé, sous `domain` de préférence (§7.3).
+    fn validated_name(&mut self, input: &str, domain: &str) -> Option<String> {
+        if !macros::uses_validated_name(input) {
+            return None;
+        }
+        let names = self.validated_names(false).ok()?;
+        names
+            .iter()
+            .find(|name| is_subdomain(name, domain))
+            .or_else(|| names.first())
+            .cloned()
+    }
+
+    fn context<'c>(&'c self, domain: &'c str, validated: Option<&'c str>) -> MacroContext<'c> {
+        MacroContext {
+            sender: self.sender,
+            local: self.local,
+            sender_domain: self.sender_domain,
+            domain,
+            ip: self.ip,
+            helo: self.sender_domain,
+            validated,
+        }
+    }
+
+    fn push(&mut self, depth: usize, domain: &str, event: SpfTraceEvent) {
+        self.trace.push(SpfTraceStep {
+            depth,
+            domain: domain.to_string(),
+            event,
+        });
+    }
+}
+
+/// Nom de domaine syntaxiquement utilisable (§4.3).
+fn is_valid_domain(domain: &str) -> bool {
+    let domain = domain.strip_suffix('.').unwrap_or(domain);
+    !domain.is_empty()
+        && domain.len() <= 253
+        && domain.contains('.')
+        && domain
+            .split('.')
+            .all(|label| !label.is_empty() && label.len() <= 63)
+}
+
+fn is_subdomain(name: &str, domain: &str) -> bool {
+    let name = name.trim_end_matches('.');
+    let domain = domain.trim_end_matches('.');
+    name.eq_ignore_ascii_case(domain)
+        || name
+            .len()
+            .checked_sub(domain.len() + 1)
+            .is_some_and(|split| {
+                name.as_bytes()[split] == b'.' && name[split + 1..].eq_ignore_ascii_case(domain)
+            })
+}
+
+/// `ip` appartient-il à `network/prefix` (même famille uniquement) ?
+pub(crate) fn in_network(ip: IpAddr, network: IpAddr, prefix: u8) -> bool {
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => {
+            let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) => {
+            let mask = u128::MAX.checked_shl(128 - u32::from(prefix)).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+/// Nom `in-addr.arpa` / `ip6.arpa` de l'IP.
+fn reverse_name(ip: IpAddr) -> String {
+    let dotted = macros::dotted_ip(ip);
+    let mut labels: Vec<&str> = dotted.split('.').collect();
+    labels.reverse();
+    let suffix = match ip {
+        IpAddr::V4(_) => "in-addr.arpa",
+        IpAddr::V6(_) => "ip6.arpa",
+    };
+    format!("{}.{suffix}", labels.join("."))
+}
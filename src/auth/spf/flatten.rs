@@ -0,0 +1,438 @@
+//! Aplatissement SPF : `include:`, `redirect=`, `a` et `mx` remplacés par les
+//! réseaux `ip4:`/`ip6:` qu'ils désignent au moment de la résolution.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::auth::error::SpfFlattenError;
+use crate::dns::{DnsAnswer, DnsBackend, DnsRecordType, nx_as_answer};
+
+use super::SpfQualifier;
+use super::check::in_network;
+use super::record::{self, DualCidr, Mechanism, SpfRecord};
+
+/// Réglages de [`crate::auth::flatten_spf`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpfFlattenOptions {
+    /// Longueur maximale d'un enregistrement généré ; 255 tient dans une
+    /// seule chaîne TXT.
+    pub max_record_len: usize,
+    /// Préfixe des enregistrements chaînés (`_spf1.<domaine>`, `_spf2.…`).
+    pub label_prefix: String,
+}
+
+impl Default for SpfFlattenOptions {
+    fn default() -> Self {
+        Self {
+            max_record_len: 255,
+            label_prefix: "_spf".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpfFlattened {
+    /// Enregistrement du domaine, puis ceux qu'il inclut en chaîne.
+    pub records: Vec<SpfFlatRecord>,
+    /// Termes conservés tels quels (`ptr`, `exists`, cibles en macros).
+    pub kept: Vec<String>,
+    /// Requêtes DNS de la politique aplatie.
+    pub dns_lookups: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpfFlatRecord {
+    pub name: String,
+    pub text: String,
+}
+
+impl SpfFlatRecord {
+    /// Chaînes TXT de 255 octets au plus.
+    pub fn strings(&self) -> Vec<&str> {
+        let mut strings = Vec::new();
+        let mut rest = self.text.as_str();
+        while rest.len() > 255 {
+            let mut split = 255;
+            while !rest.is_char_boundary(split) {
+                split -= 1;
+            }
+            let (head, tail) = rest.split_at(split);
+            strings.push(head);
+            rest = tail;
+        }
+        strings.push(rest);
+        strings
+    }
+
+    /// Ligne de zone (`nom. TXT "…" "…"`).
+    pub fn to_zone_line(&self) -> String {
+        let strings: Vec<String> = self
+            .strings()
+            .into_iter()
+            .map(|part| format!("\"{part}\""))
+            .collect();
+        format!("{}. TXT {}", self.name, strings.join(" "))
+    }
+}
+
+pub(crate) fn flatten<B>(
+    backend: &B,
+    domain: &str,
+    options: &SpfFlattenOptions,
+) -> Result<SpfFlattened, SpfFlattenError>
+where
+    B: DnsBackend + ?Sized,
+{
+    let mut flattener = Flattener {
+        backend,
+        path: Vec::new(),
+    };
+    let (terms, explanation) = flattener.root(domain)?;
+    Ok(assemble(domain, terms, explanation, options))
+}
+
+/// Terme de la politique aplatie, dans l'ordre d'évaluation.
+enum Flat {
+    Network {
+        qualifier: SpfQualifier,
+        addr: IpAddr,
+        prefix: u8,
+    },
+    /// Terme recopié tel quel ; il demande toujours une requête DNS.
+    Kept(String),
+    All(SpfQualifier),
+}
+
+struct Flattener<'a, B: ?Sized> {
+    backend: &'a B,
+    /// Domaines en cours d'aplatissement, pour détecter les boucles.
+    path: Vec<String>,
+}
+
+impl<B> Flattener<'_, B>
+where
+    B: DnsBackend + ?Sized,
+{
+    /// Termes du domaine racine et son `exp=` éventuel.
+    fn root(&mut self, domain: &str) -> Result<(Vec<Flat>, Option<String>), SpfFlattenError> {
+        let record = self.record(domain)?;
+        let explanation = record.explanation.clone();
+        let terms = self.expand(domain, record, false)?;
+        Ok((terms, explanation))
+    }
+
+    /// Aplatit la politique de `domain`. Dans un `include:` (`nested`), seuls
+    /// les termes `pass` comptent : tout autre terme qu'un `all` final
+    /// changerait le résultat une fois aplati.
+    fn expand(
+        &mut self,
+        domain: &str,
+        record: SpfRecord,
+        nested: bool,
+    ) -> Result<Vec<Flat>, SpfFlattenError> {
+        self.path.push(domain.to_string());
+        let mut out = Vec::new();
+        let unflattenable = |term: &str| SpfFlattenError::Unflattenable {
+            domain: domain.to_string(),
+            term: term.to_string(),
+        };
+
+        for directive in &record.directives {
+            let qualifier = directive.qualifier;
+            let term = directive.term.as_str();
+            if nested && qualifier != SpfQualifier::Pass {
+                if directive.mechanism == Mechanism::All {
+                    self.path.pop();
+                    return Ok(out);
+                }
+                return Err(unflattenable(term));
+            }
+            let networks = match &directive.mechanism {
+                Mechanism::All if nested => return Err(unflattenable(term)),
+                Mechanism::All => {
+                    out.push(Flat::All(qualifier));
+                    self.path.pop();
+                    return Ok(out);
+                }
+                Mechanism::Ip4(addr, prefix) => vec![(IpAddr::V4(*addr), *prefix)],
+                Mechanism::Ip6(addr, prefix) => vec![(IpAddr::V6(*addr), *prefix)],
+                Mechanism::Include(spec) if !spec.contains('%') => {
+                    let target = normalize(spec);
+                    let included = self.follow(&target, true)?;
+                    included
+                        .into_iter()
+                        .filter_map(|flat| match flat {
+                            Flat::Network { addr, prefix, .. } => Some((addr, prefix)),
+                            _ => None,
+                        })
+                        .collect()
+                }
+                Mechanism::A { domain: spec, cidr } if !is_macro(spec) => {
+                    let target = spec
+                        .as_deref()
+                        .map_or_else(|| domain.to_string(), normalize);
+                    self.addresses(&target, *cidr)?
+                }
+                Mechanism::Mx { domain: spec, cidr } if !is_macro(spec) => {
+                    let target = spec
+                        .as_deref()
+                        .map_or_else(|| domain.to_string(), normalize);
+                    let exchanges = self.query(&target, DnsRecordType::Mx)?.mx();
+                    if exchanges.len() > 10 {
+                        return Err(unflattenable(term));
+                    }
+                    let mut networks = Vec::new();
+                    for exchange in exchanges.iter().filter(|mx| !mx.exchange.is_empty()) {
+                        networks.extend(self.addresses(&normalize(&exchange.exchange), *cidr)?);
+                    }
+                    networks
+                }
+                _ if nested => return Err(unflattenable(term)),
+                _ => {
+                    out.push(Flat::Kept(term.to_string()));
+                    continue;
+                }
+            };
+            out.extend(networks.into_iter().map(|(addr, prefix)| Flat::Network {
+                qualifier,
+                addr,
+                prefix,
+            }));
+        }
+
+        if let Some(spec) = &record.redirect {
+            if spec.contains('%') {
+                if nested {
+                    return Err(unflattenable(&format!("redirect={spec}")));
+                }
+                out.push(Flat::Kept(format!("redirect={spec}")));
+            } else {
+                out.extend(self.follow(&normalize(spec), nested)?);
+            }
+        }
+        self.path.pop();
+        Ok(out)
+    }
+
+    fn follow(&mut self, target: &str, nested: bool) -> Result<Vec<Flat>, SpfFlattenError> {
+        if self.path.iter().any(|domain| domain == target) {
+            return Err(SpfFlattenError::Loop {
+                domain: target.to_string(),
+            });
+        }
+        let record = self.record(target)?;
+        self.expand(target, record, nested)
+    }
+
+    fn record(&self, domain: &str) -> Result<SpfRecord, SpfFlattenError> {
+        let records = self.query(domain, DnsRecordType::Txt)?.txt();
+        let text = match record::select(&records).as_slice() {
+            [] => {
+                return Err(SpfFlattenError::NoRecord {
+                    domain: domain.to_string(),
+                });
+            }
+            [text] => text.to_string(),
+            _ => {
+                return Err(SpfFlattenError::MultipleRecords {
+                    domain: domain.to_string(),
+                });
+            }
+        };
+        record::parse(&text).map_err(|reason| SpfFlattenError::Syntax {
+            domain: domain.to_string(),
+            reason,
+        })
+    }
+
+    fn addresses(&self, name: &str, cidr: DualCidr) -> Result<Vec<(IpAddr, u8)>, SpfFlattenError> {
+        let mut networks = Vec::new();
+        for rtype in [DnsRecordType::A, DnsRecordType::Aaaa] {
+            for addr in self.query(name, rtype)?.addrs() {
+                let prefix = match addr {
+                    IpAddr::V4(_) => cidr.v4,
+                    IpAddr::V6(_) => cidr.v6,
+                };
+                networks.push((addr, prefix));
+            }
+        }
+        Ok(networks)
+    }
+
+    fn query(&self, name: &str, rtype: DnsRecordType) -> Result<DnsAnswer, SpfFlattenError> {
+        nx_as_answer(self.backend.lookup(name, rtype)).map_err(|source| SpfFlattenError::Lookup {
+            name: name.to_string(),
+            source,
+        })
+    }
+}
+
+fn is_macro(spec: &Option<String>) -> bool {
+    spec.as_deref().is_some_and(|spec| spec.contains('%'))
+}
+
+fn normalize(spec: &str) -> String {
+    spec.trim_end_matches('.').to_ascii_lowercase()
+}
+
+/// Répartit les termes : ceux qui dépendent de l'ordre restent en tête de
+/// l'enregistrement racine, les réseaux `pass` qui suivent sont dédoublonnés
+/// puis répartis entre la racine et la chaîne `_spfN`.
+fn assemble(
+    domain: &str,
+    terms: Vec<Flat>,
+    explanation: Option<String>,
+    options: &SpfFlattenOptions,
+) -> SpfFlattened {
+    let ordered = terms
+        .iter()
+        .rposition(|flat| {
+            !matches!(
+                flat,
+                Flat::Network {
+                    qualifier: SpfQualifier::Pass,
+                    ..
+                } | Flat::All(_)
+            )
+        })
+        .map_or(0, |idx| idx + 1);
+
+    let mut head = Vec::new();
+    let mut kept = Vec::new();
+    let mut networks = Vec::new();
+    let mut trailer = Vec::new();
+    for (idx, flat) in terms.into_iter().enumerate() {
+        match flat {
+            Flat::Network {
+                qualifier: SpfQualifier::Pass,
+                addr,
+                prefix,
+            } if idx >= ordered => networks.push((addr, prefix)),
+            Flat::Network {
+                qualifier,
+                addr,
+                prefix,
+            } => head.push(format!(
+                "{}{}",
+                qualifier_prefix(qualifier),
+                network_term(addr, prefix)
+            )),
+            Flat::Kept(term) => {
+                kept.push(term.clone());
+                head.push(term);
+            }
+            Flat::All(qualifier) => trailer.push(format!("{}all", qualifier_prefix(qualifier))),
+        }
+    }
+    if let Some(explanation) = explanation {
+        trailer.push(format!("exp={explanation}"));
+    }
+    let pending: Vec<String> = merge(networks)
+        .into_iter()
+        .map(|(addr, prefix)| network_term(addr, prefix))
+        .collect();
+
+    let name = |idx: usize| format!("{}{idx}.{domain}", options.label_prefix);
+    let max = options.max_record_len;
+    let mut records: Vec<SpfFlatRecord> = Vec::new();
+    let mut terms = head;
+    let mut next = 0;
+    loop {
+        let root = records.is_empty();
+        let closing: &[String] = if root { &trailer } else { &[] };
+        let used = "v=spf1".len() + words_len(&terms) + words_len(closing);
+        let rest = &pending[next..];
+        let take = if used + words_len(rest) <= max {
+            rest.len()
+        } else {
+            let mut len = used + 1 + "include:".len() + name(records.len() + 1).len();
+            let mut count = 0;
+            for term in rest {
+                if len + 1 + term.len() > max {
+                    break;
+                }
+                len += 1 + term.len();
+                count += 1;
+            }
+            if root { count } else { count.max(1) }
+        };
+        terms.extend_from_slice(&rest[..take]);
+        next += take;
+        if next < pending.len() {
+            terms.push(format!("include:{}", name(records.len() + 1)));
+        }
+        terms.extend_from_slice(closing);
+        records.push(SpfFlatRecord {
+            name: if root {
+                domain.to_string()
+            } else {
+                name(records.len())
+            },
+            text: format!("v=spf1 {}", terms.join(" ")).trim_end().to_string(),
+        });
+        if next >= pending.len() {
+            break;
+        }
+        terms = Vec::new();
+    }
+
+    SpfFlattened {
+        dns_lookups: kept.len() + records.len() - 1,
+        records,
+        kept,
+    }
+}
+
+fn words_len(words: &[String]) -> usize {
+    words.iter().map(|word| 1 + word.len()).sum()
+}
+
+/// Réseaux triés, sans doublon ni réseau contenu dans un autre.
+fn merge(networks: Vec<(IpAddr, u8)>) -> Vec<(IpAddr, u8)> {
+    let mut networks: Vec<(IpAddr, u8)> = networks
+        .into_iter()
+        .map(|(addr, prefix)| (mask(addr, prefix), prefix))
+        .collect();
+    networks.sort_by_key(|(addr, prefix)| (addr.is_ipv6(), *prefix, *addr));
+    let mut merged: Vec<(IpAddr, u8)> = Vec::new();
+    for (addr, prefix) in networks {
+        let covered = merged.iter().any(|(other, other_prefix)| {
+            other.is_ipv6() == addr.is_ipv6() && in_network(addr, *other, *other_prefix)
+        });
+        if !covered {
+            merged.push((addr, prefix));
+        }
+    }
+    merged.sort_by_key(|(addr, _)| (addr.is_ipv6(), *addr));
+    merged
+}
+
+fn mask(addr: IpAddr, prefix: u8) -> IpAddr {
+    match addr {
+        IpAddr::V4(v4) => {
+            let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+            IpAddr::V4(Ipv4Addr::from(u32::from(v4) & mask))
+        }
+        IpAddr::V6(v6) => {
+            let mask = u128::MAX.checked_shl(128 - u32::from(prefix)).unwrap_or(0);
+            IpAddr::V6(Ipv6Addr::from(u128::from(v6) & mask))
+        }
+    }
+}
+
+fn network_term(addr: IpAddr, prefix: u8) -> String {
+    match addr {
+        IpAddr::V4(v4) if prefix == 32 => format!("ip4:{v4}"),
+        IpAddr::V4(v4) => format!("ip4:{v4}/{prefix}"),
+        IpAddr::V6(v6) if prefix == 128 => format!("ip6:{v6}"),
+        IpAddr::V6(v6) => format!("ip6:{v6}/{prefix}"),
+    }
+}
+
+fn qualifier_prefix(qualifier: SpfQualifier) -> &'static str {
+    match qualifier {
+        SpfQualifier::Pass => "",
+        SpfQualifier::Fail => "-",
+        SpfQualifier::SoftFail => "~",
+        SpfQualifier::Neutral => "?",
+    }
+}
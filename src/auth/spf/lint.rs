@@ -0,0 +1,432 @@
+//! Analyse statique d'une politique SPF : arbre des `include:` et
+//! `redirect=`, requêtes DNS par branche et défauts courants.
+
+use std::net::IpAddr;
+
+use crate::dns::{DnsAnswer, DnsBackend, DnsError, DnsRecordType, nx_as_answer};
+
+use super::check::{MAX_DNS_LOOKUPS, MAX_VOID_LOOKUPS, in_network};
+use super::record::{self, Mechanism};
+
+/// Taille maximale d'une chaîne TXT (RFC 7208 §3.3).
+const TXT_STRING_LIMIT: usize = 255;
+/// Taille au-delà de laquelle la réponse risque de ne plus tenir en UDP
+/// (RFC 7208 §3.4).
+const UDP_RECORD_LIMIT: usize = 512;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpfTree {
+    pub root: SpfTreeNode,
+}
+
+impl SpfTree {
+    /// Requêtes DNS de tout l'arbre, comme les compterait `check_host()`
+    /// s'il parcourait chaque branche.
+    pub fn dns_lookups(&self) -> usize {
+        self.root.total_lookups()
+    }
+
+    pub fn void_lookups(&self) -> usize {
+        self.root.total_void_lookups()
+    }
+
+    /// Défauts de tous les nœuds, avec le domaine concerné.
+    pub fn issues(&self) -> Vec<(&str, &SpfLint)> {
+        let mut issues = Vec::new();
+        self.root.collect_issues(&mut issues);
+        issues
+    }
+
+    /// Au moins un défaut qui rendrait l'évaluation `permerror` ou `temperror`.
+    pub fn has_errors(&self) -> bool {
+        self.issues().iter().any(|(_, issue)| issue.is_error())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpfTreeNode {
+    pub domain: String,
+    pub link: SpfLink,
+    /// Enregistrement `v=spf1` du domaine, s'il y en a un seul.
+    pub record: Option<String>,
+    /// Termes de cet enregistrement demandant une requête DNS.
+    pub lookups: usize,
+    /// Requêtes de cet enregistrement restées sans réponse.
+    pub void_lookups: usize,
+    pub issues: Vec<SpfLint>,
+    pub children: Vec<SpfTreeNode>,
+}
+
+impl SpfTreeNode {
+    /// Requêtes de ce nœud et de ses descendants.
+    pub fn total_lookups(&self) -> usize {
+        self.lookups + self.children.iter().map(Self::total_lookups).sum::<usize>()
+    }
+
+    pub fn total_void_lookups(&self) -> usize {
+        self.void_lookups
+            + self
+                .children
+                .iter()
+                .map(Self::total_void_lookups)
+                .sum::<usize>()
+    }
+
+    fn collect_issues<'a>(&'a self, issues: &mut Vec<(&'a str, &'a SpfLint)>) {
+        issues.extend(
+            self.issues
+                .iter()
+                .map(|issue| (self.domain.as_str(), issue)),
+        );
+        for child in &self.children {
+            child.collect_issues(issues);
+        }
+    }
+}
+
+/// Comment un nœud est atteint depuis son parent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpfLink {
+    Root,
+    Include,
+    Redirect,
+}
+
+impl SpfLink {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Root => "root",
+            Self::Include => "include",
+            Self::Redirect => "redirect",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpfLint {
+    /// Pas d'enregistrement SPF (cible d'`include`/`redirect` : `permerror`).
+    NoRecord,
+    MultipleRecords {
+        count: usize,
+    },
+    Syntax {
+        reason: String,
+    },
+    LookupFailed {
+        reason: String,
+    },
+    /// Plus de 10 requêtes DNS dans l'arbre (porté par la racine).
+    TooManyLookups {
+        count: usize,
+    },
+    /// Plus de 2 requêtes vides dans l'arbre (porté par la racine).
+    TooManyVoidLookups {
+        count: usize,
+    },
+    /// `mx` renvoyant plus de 10 enregistrements.
+    TooManyMx {
+        term: String,
+        count: usize,
+    },
+    /// `include`/`redirect` vers un domaine déjà en cours d'évaluation.
+    Loop {
+        target: String,
+    },
+    /// Domaine inclus plusieurs fois dans l'arbre.
+    DuplicateInclude {
+        target: String,
+    },
+    /// `ptr` est déconseillé (RFC 7208 §5.5).
+    DeprecatedPtr {
+        term: String,
+    },
+    /// Réseau contenu dans (ou contenant) un réseau déjà autorisé.
+    OverlappingNetwork {
+        term: String,
+        other_domain: String,
+        other_term: String,
+    },
+    /// Cible construite par macros, non évaluable hors d'une transaction.
+    MacroTarget {
+        term: String,
+    },
+    /// Enregistrement de plus de 255 ou 512 octets (`limit`).
+    RecordLength {
+        length: usize,
+        limit: usize,
+    },
+}
+
+impl SpfLint {
+    /// Défaut rendant l'évaluation `permerror` ou `temperror`.
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            Self::NoRecord
+                | Self::MultipleRecords { .. }
+                | Self::Syntax { .. }
+                | Self::LookupFailed { .. }
+                | Self::TooManyLookups { .. }
+                | Self::TooManyVoidLookups { .. }
+                | Self::TooManyMx { .. }
+                | Self::Loop { .. }
+        )
+    }
+}
+
+/// Parcourt la politique de `domain` ; seule l'absence de réponse pour la
+/// racine est une erreur, les échecs plus profonds deviennent des défauts.
+pub(crate) fn lint<B>(backend: &B, domain: &str) -> Result<SpfTree, DnsError>
+where
+    B: DnsBackend + ?Sized,
+{
+    let root_records = nx_as_answer(backend.lookup(domain, DnsRecordType::Txt))?;
+    let mut walker = Walker {
+        backend,
+        path: Vec::new(),
+        included: Vec::new(),
+        networks: Vec::new(),
+    };
+    let mut root = walker.node(domain, SpfLink::Root, Ok(root_records));
+    let lookups = root.total_lookups();
+    if lookups > MAX_DNS_LOOKUPS {
+        root.issues.push(SpfLint::TooManyLookups { count: lookups });
+    }
+    let void_lookups = root.total_void_lookups();
+    if void_lookups > MAX_VOID_LOOKUPS {
+        root.issues.push(SpfLint::TooManyVoidLookups {
+            count: void_lookups,
+        });
+    }
+    Ok(SpfTree { root })
+}
+
+/// Réseau `ip4`/`ip6` rencontré, pour détecter les chevauchements.
+struct Network {
+    addr: IpAddr,
+    prefix: u8,
+    domain: String,
+    term: String,
+}
+
+struct Walker<'a, B: ?Sized> {
+    backend: &'a B,
+    /// Domaines en cours d'évaluation, de la racine au nœud courant.
+    path: Vec<String>,
+    included: Vec<String>,
+    networks: Vec<Network>,
+}
+
+impl<B> Walker<'_, B>
+where
+    B: DnsBackend + ?Sized,
+{
+    fn node(
+        &mut self,
+        domain: &str,
+        link: SpfLink,
+        answer: Result<DnsAnswer, DnsError>,
+    ) -> SpfTreeNode {
+        let mut node = SpfTreeNode {
+            domain: domain.to_string(),
+            link,
+            record: None,
+            lookups: 0,
+            void_lookups: 0,
+            issues: Vec::new(),
+            children: Vec::new(),
+        };
+        let records = match answer {
+            Ok(answer) => answer.txt(),
+            Err(err) => {
+                node.issues.push(SpfLint::LookupFailed {
+                    reason: err.to_string(),
+                });
+                return node;
+            }
+        };
+        let text = match record::select(&records).as_slice() {
+            [] => {
+                node.issues.push(SpfLint::NoRecord);
+                return node;
+            }
+            [text] => text.to_string(),
+            selected => {
+                node.issues.push(SpfLint::MultipleRecords {
+                    count: selected.len(),
+                });
+                return node;
+            }
+        };
+        for limit in [UDP_RECORD_LIMIT, TXT_STRING_LIMIT] {
+            if text.len() > limit {
+                node.issues.push(SpfLint::RecordLength {
+                    length: text.len(),
+                    limit,
+                });
+                break;
+            }
+        }
+        node.record = Some(text.clone());
+        let spf = match record::parse(&text) {
+            Ok(spf) => spf,
+            Err(reason) => {
+                node.issues.push(SpfLint::Syntax { reason });
+                return node;
+            }
+        };
+
+        // Garde-fou : au-delà, l'évaluation serait de toute façon en erreur.
+        if self.path.len() > MAX_DNS_LOOKUPS {
+            return node;
+        }
+        self.path.push(domain.to_string());
+        for directive in &spf.directives {
+            if directive.mechanism.queries_dns() {
+                node.lookups += 1;
+            }
+            self.directive(&mut node, &directive.mechanism, &directive.term);
+        }
+        if let Some(target) = &spf.redirect {
+            node.lookups += 1;
+            self.follow(
+                &mut node,
+                SpfLink::Redirect,
+                target,
+                &format!("redirect={target}"),
+            );
+        }
+        self.path.pop();
+        node
+    }
+
+    fn directive(&mut self, node: &mut SpfTreeNode, mechanism: &Mechanism, term: &str) {
+        match mechanism {
+            Mechanism::All => {}
+            Mechanism::Include(target) => self.follow(node, SpfLink::Include, target, term),
+            Mechanism::Ip4(addr, prefix) => self.network(node, IpAddr::V4(*addr), *prefix, term),
+            Mechanism::Ip6(addr, prefix) => self.network(node, IpAddr::V6(*addr), *prefix, term),
+            Mechanism::Ptr(_) => node.issues.push(SpfLint::DeprecatedPtr {
+                term: term.to_string(),
+            }),
+            Mechanism::A { domain, .. } => {
+                let Some(target) = self.target(node, domain.as_deref(), term) else {
+                    return;
+                };
+                let v4 = self.lookup(node, &target, DnsRecordType::A);
+                let v6 = self.lookup(node, &target, DnsRecordType::Aaaa);
+                if let (Some(v4), Some(v6)) = (v4, v6) {
+                    if v4.records.is_empty() && v6.records.is_empty() {
+                        node.void_lookups += 1;
+                    }
+                }
+            }
+            Mechanism::Mx { domain, .. } => {
+                let Some(target) = self.target(node, domain.as_deref(), term) else {
+                    return;
+                };
+                if let Some(answer) = self.lookup(node, &target, DnsRecordType::Mx) {
+                    let count = answer.mx().len();
+                    if count == 0 {
+                        node.void_lookups += 1;
+                    } else if count > 10 {
+                        node.issues.push(SpfLint::TooManyMx {
+                            term: term.to_string(),
+                            count,
+                        });
+                    }
+                }
+            }
+            Mechanism::Exists(spec) => {
+                let Some(target) = self.target(node, Some(spec), term) else {
+                    return;
+                };
+                if let Some(answer) = self.lookup(node, &target, DnsRecordType::A) {
+                    if answer.records.is_empty() {
+                        node.void_lookups += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    fn follow(&mut self, node: &mut SpfTreeNode, link: SpfLink, spec: &str, term: &str) {
+        let Some(target) = self.target(node, Some(spec), term) else {
+            return;
+        };
+        if self.path.contains(&target) {
+            node.issues.push(SpfLint::Loop { target });
+            return;
+        }
+        if link == SpfLink::Include {
+            if self.included.contains(&target) {
+                node.issues.push(SpfLint::DuplicateInclude {
+                    target: target.clone(),
+                });
+            }
+            self.included.push(target.clone());
+        }
+        let answer = nx_as_answer(self.backend.lookup(&target, DnsRecordType::Txt));
+        if answer
+            .as_ref()
+            .is_ok_and(|answer| answer.records.is_empty())
+        {
+            node.void_lookups += 1;
+        }
+        let child = self.node(&target, link, answer);
+        node.children.push(child);
+    }
+
+    fn network(&mut self, node: &mut SpfTreeNode, addr: IpAddr, prefix: u8, term: &str) {
+        // Un domaine inclus deux fois relit ses propres réseaux : déjà
+        // signalé par `DuplicateInclude`.
+        let overlapping = self.networks.iter().find(|other| {
+            let repeated = other.domain == node.domain && other.term == term;
+            !repeated && in_network(addr, other.addr, prefix.min(other.prefix))
+        });
+        if let Some(other) = overlapping {
+            node.issues.push(SpfLint::OverlappingNetwork {
+                term: term.to_string(),
+                other_domain: other.domain.clone(),
+                other_term: other.term.clone(),
+            });
+        }
+        self.networks.push(Network {
+            addr,
+            prefix,
+            domain: node.domain.clone(),
+            term: term.to_string(),
+        });
+    }
+
+    /// Domaine visé par le terme ; `None` s'il dépend de macros.
+    fn target(&self, node: &mut SpfTreeNode, spec: Option<&str>, term: &str) -> Option<String> {
+        match spec {
+            None => Some(node.domain.clone()),
+            Some(spec) if spec.contains('%') => {
+                node.issues.push(SpfLint::MacroTarget {
+                    term: term.to_string(),
+                });
+                None
+            }
+            Some(spec) => Some(spec.trim_end_matches('.').to_ascii_lowercase()),
+        }
+    }
+
+    fn lookup(
+        &self,
+        node: &mut SpfTreeNode,
+        name: &str,
+        rtype: DnsRecordType,
+    ) -> Option<DnsAnswer> {
+        match nx_as_answer(self.backend.lookup(name, rtype)) {
+            Ok(answer) => Some(answer),
+            Err(err) => {
+                node.issues.push(SpfLint::LookupFailed {
+                    reason: err.to_string(),
+                });
+                None
+            }
+        }
+    }
+}
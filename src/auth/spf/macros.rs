@@ -0,0 +1,223 @@
+//! Expansion des macros SPF (RFC 7208 §7).
+
+use std::net::IpAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Valeurs disponibles pour l'expansion.
+#[derive(Debug, Clone)]
+pub(crate) struct MacroContext<'a> {
+    /// `<local>@<domaine>` de l'expéditeur.
+    pub sender: &'a str,
+    pub local: &'a str,
+    pub sender_domain: &'a str,
+    /// Domaine en cours d'évaluation (`%{d}`).
+    pub domain: &'a str,
+    pub ip: IpAddr,
+    pub helo: &'a str,
+    /// Nom validé de l'IP (`%{p}`), calculé à la demande.
+    pub validated: Option<&'a str>,
+}
+
+/// Vérifie la syntaxe de `input` sans l'étendre.
+pub(crate) fn validate(input: &str) -> Result<(), String> {
+    tokens(input, true).map(|_| ())
+}
+
+/// Le texte utilise-t-il `%{p}` (qui demande des requêtes PTR) ?
+pub(crate) fn uses_validated_name(input: &str) -> bool {
+    tokens(input, true).is_ok_and(|tokens| {
+        tokens.iter().any(
+            |token| matches!(token, Token::Macro { letter, .. } if letter.eq_ignore_ascii_case(&'p')),
+        )
+    })
+}
+
+/// Étend un `domain-spec` et le tronque à 253 caractères en retirant des
+/// labels à gauche (§7.3).
+pub(crate) fn expand_domain(input: &str, ctx: &MacroContext<'_>) -> Result<String, String> {
+    let expanded = expand(input, ctx, false)?;
+    let mut domain = expanded.strip_suffix('.').unwrap_or(&expanded);
+    while domain.len() > 253 {
+        domain = domain
+            .split_once('.')
+            .map(|(_, rest)| rest)
+            .unwrap_or_default();
+    }
+    if domain.is_empty() || domain.split('.').any(str::is_empty) {
+        return Err(format!("invalid domain after expansion: {expanded}"));
+    }
+    Ok(domain.to_ascii_lowercase())
+}
+
+/// Étend `input` ; `c`, `r` et `t` ne sont admis que dans le texte `exp`.
+pub(crate) fn expand(
+    input: &str,
+    ctx: &MacroContext<'_>,
+    explanation: bool,
+) -> Result<String, String> {
+    let mut out = String::new();
+    for token in tokens(input, explanation)? {
+        match token {
+            Token::Literal(text) => out.push_str(text),
+            Token::Macro {
+                letter,
+                keep,
+                reverse,
+                delimiters,
+            } => {
+                let value = value(letter.to_ascii_lowercase(), ctx);
+                let mut parts: Vec<&str> = value.split(|c| delimiters.contains(c)).collect();
+                if reverse {
+                    parts.reverse();
+                }
+                if let Some(keep) = keep {
+                    parts.drain(..parts.len().saturating_sub(keep));
+                }
+                let joined = parts.join(".");
+                if letter.is_ascii_uppercase() {
+                    out.push_str(&url_escape(&joined));
+                } else {
+                    out.push_str(&joined);
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[derive(Debug)]
+enum Token<'a> {
+    Literal(&'a str),
+    Macro {
+        letter: char,
+        keep: Option<usize>,
+        reverse: bool,
+        delimiters: &'a str,
+    },
+}
+
+fn tokens(input: &str, explanation: bool) -> Result<Vec<Token<'_>>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = input;
+    while let Some(idx) = rest.find('%') {
+        if idx > 0 {
+            tokens.push(Token::Literal(&rest[..idx]));
+        }
+        let after = &rest[idx + 1..];
+        match after.chars().next() {
+            Some('%') => tokens.push(Token::Literal("%")),
+            Some('_') => tokens.push(Token::Literal(" ")),
+            Some('-') => tokens.push(Token::Literal("%20")),
+            Some('{') => {
+                let end = after.find('}').ok_or("unterminated macro")?;
+                tokens.push(macro_token(&after[1..end], explanation)?);
+                rest = &after[end + 1..];
+                continue;
+            }
+            _ => return Err("invalid '%' in macro string".to_string()),
+        }
+        rest = &after[1..];
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Literal(rest));
+    }
+    if input.chars().any(|c| !(' '..='~').contains(&c)) {
+        return Err("non-printable character in macro string".to_string());
+    }
+    Ok(tokens)
+}
+
+/// `lettre [chiffres] ["r"] *délimiteur`.
+fn macro_token(body: &str, explanation: bool) -> Result<Token<'_>, String> {
+    let mut chars = body.char_indices();
+    let letter = chars.next().map(|(_, c)| c).ok_or("empty macro")?;
+    let allowed = match letter.to_ascii_lowercase() {
+        's' | 'l' | 'o' | 'd' | 'i' | 'p' | 'h' | 'v' => true,
+        'c' | 'r' | 't' => explanation,
+        _ => false,
+    };
+    if !allowed {
+        return Err(format!("invalid macro letter '{letter}'"));
+    }
+
+    let rest = &body[letter.len_utf8()..];
+    let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let keep = match &rest[..digits] {
+        "" => None,
+        number => match number.parse::<usize>() {
+            Ok(0) | Err(_) => return Err(format!("invalid macro transformer in %{{{body}}}")),
+            Ok(keep) => Some(keep),
+        },
+    };
+    let rest = &rest[digits..];
+    let (reverse, delimiters) = match rest.strip_prefix(['r', 'R']) {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+    if !delimiters
+        .chars()
+        .all(|c| matches!(c, '.' | '-' | '+' | ',' | '/' | '_' | '='))
+    {
+        return Err(format!("invalid macro delimiter in %{{{body}}}"));
+    }
+    Ok(Token::Macro {
+        letter,
+        keep,
+        reverse,
+        delimiters: if delimiters.is_empty() {
+            "."
+        } else {
+            delimiters
+        },
+    })
+}
+
+fn value(letter: char, ctx: &MacroContext<'_>) -> String {
+    match letter {
+        's' => ctx.sender.to_string(),
+        'l' => ctx.local.to_string(),
+        'o' => ctx.sender_domain.to_string(),
+        'd' => ctx.domain.to_string(),
+        'i' => dotted_ip(ctx.ip),
+        'p' => ctx.validated.unwrap_or("unknown").to_string(),
+        'v' => match ctx.ip {
+            IpAddr::V4(_) => "in-addr".to_string(),
+            IpAddr::V6(_) => "ip6".to_string(),
+        },
+        'h' => ctx.helo.to_string(),
+        'c' => ctx.ip.to_string(),
+        'r' => "unknown".to_string(),
+        't' => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default()
+            .to_string(),
+        _ => String::new(),
+    }
+}
+
+/// `%{i}` : IPv4 pointée, IPv6 en 32 quartets séparés par des points.
+pub(crate) fn dotted_ip(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(v4) => v4.to_string(),
+        IpAddr::V6(v6) => v6
+            .octets()
+            .iter()
+            .flat_map(|byte| [byte >> 4, byte & 0x0f])
+            .map(|nibble| format!("{nibble:x}"))
+            .collect::<Vec<_>>()
+            .join("."),
+    }
+}
+
+fn url_escape(input: &str) -> String {
+    let mut out = String::new();
+    for byte in input.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
@@ -1,3 +1,19 @@
+mod check;
+mod flatten;
+mod lint;
+mod macros;
+mod record;
+
+pub(crate) use check::check_host;
+pub use check::{SpfCheck, SpfResult, SpfTraceEvent, SpfTraceStep};
+pub(crate) use flatten::flatten;
+pub use flatten::{SpfFlatRecord, SpfFlattenOptions, SpfFlattened};
+pub(crate) use lint::lint;
+pub use lint::{SpfLink, SpfLint, SpfTree, SpfTreeNode};
+
+#[cfg(test)]
+mod tests;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SpfStatus {
     Missing,
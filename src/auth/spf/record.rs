@@ -0,0 +1,252 @@
+//! Syntaxe d'un enregistrement SPF (RFC 7208 §4.6 et §12).
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use super::SpfQualifier;
+use super::macros;
+
+/// Enregistrement analysé : directives dans l'ordre, puis modificateurs
+/// `redirect=` et `exp=` (les modificateurs inconnus sont ignorés).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SpfRecord {
+    pub directives: Vec<Directive>,
+    pub redirect: Option<String>,
+    pub explanation: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Directive {
+    pub qualifier: SpfQualifier,
+    pub mechanism: Mechanism,
+    /// Terme tel qu'écrit dans l'enregistrement.
+    pub term: String,
+}
+
+/// Mécanismes ; les `domain-spec` sont gardés avant expansion des macros.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Mechanism {
+    All,
+    Include(String),
+    A {
+        domain: Option<String>,
+        cidr: DualCidr,
+    },
+    Mx {
+        domain: Option<String>,
+        cidr: DualCidr,
+    },
+    Ptr(Option<String>),
+    Ip4(Ipv4Addr, u8),
+    Ip6(Ipv6Addr, u8),
+    Exists(String),
+}
+
+impl Mechanism {
+    /// `include`, `a`, `mx`, `ptr` et `exists` comptent dans la limite de
+    /// 10 requêtes DNS (§4.6.4).
+    pub fn queries_dns(&self) -> bool {
+        !matches!(self, Self::All | Self::Ip4(..) | Self::Ip6(..))
+    }
+}
+
+/// Préfixes `/n` et `//n` de `a` et `mx`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DualCidr {
+    pub v4: u8,
+    pub v6: u8,
+}
+
+impl Default for DualCidr {
+    fn default() -> Self {
+        Self { v4: 32, v6: 128 }
+    }
+}
+
+/// Enregistrements `v=spf1` parmi les TXT d'un domaine (§4.5).
+pub(crate) fn select(records: &[String]) -> Vec<&str> {
+    records
+        .iter()
+        .map(|record| record.trim())
+        .filter(|record| {
+            let bytes = record.as_bytes();
+            bytes
+                .get(..6)
+                .is_some_and(|version| version.eq_ignore_ascii_case(b"v=spf1"))
+                && bytes.get(6).is_none_or(|byte| *byte == b' ')
+        })
+        .collect()
+}
+
+/// Analyse `record` ; toute erreur de syntaxe est un `permerror` (§4.6).
+pub(crate) fn parse(record: &str) -> Result<SpfRecord, String> {
+    let mut terms = record.split(' ').filter(|term| !term.is_empty());
+    if !terms
+        .next()
+        .is_some_and(|version| version.eq_ignore_ascii_case("v=spf1"))
+    {
+        return Err("missing v=spf1 version".to_string());
+    }
+
+    let mut parsed = SpfRecord {
+        directives: Vec::new(),
+        redirect: None,
+        explanation: None,
+    };
+    for term in terms {
+        if let Some((name, value)) = modifier(term) {
+            macros::validate(value).map_err(|reason| format!("{term}: {reason}"))?;
+            let slot = match name.to_ascii_lowercase().as_str() {
+                "redirect" => &mut parsed.redirect,
+                "exp" => &mut parsed.explanation,
+                _ => continue,
+            };
+            if slot.is_some() {
+                return Err(format!("duplicate {name}= modifier"));
+            }
+            check_domain_spec(value).map_err(|reason| format!("{term}: {reason}"))?;
+            *slot = Some(value.to_string());
+        } else {
+            let directive = directive(term).map_err(|reason| format!("{term}: {reason}"))?;
+            parsed.directives.push(directive);
+        }
+    }
+    Ok(parsed)
+}
+
+/// `nom=valeur` si `term` est un modificateur (nom : ALPHA *(ALPHA / DIGIT
+/// / "-" / "_" / ".")).
+fn modifier(term: &str) -> Option<(&str, &str)> {
+    let (name, value) = term.split_once('=')?;
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    valid.then_some((name, value))
+}
+
+fn directive(term: &str) -> Result<Directive, String> {
+    let (qualifier, rest) = match term.chars().next() {
+        Some('+') => (SpfQualifier::Pass, &term[1..]),
+        Some('-') => (SpfQualifier::Fail, &term[1..]),
+        Some('~') => (SpfQualifier::SoftFail, &term[1..]),
+        Some('?') => (SpfQualifier::Neutral, &term[1..]),
+        _ => (SpfQualifier::Pass, term),
+    };
+    let split = rest.find([':', '/']).unwrap_or(rest.len());
+    let (name, arg) = rest.split_at(split);
+    let value = arg.strip_prefix(':');
+
+    let mechanism = match name.to_ascii_lowercase().as_str() {
+        "all" if arg.is_empty() => Mechanism::All,
+        "include" => Mechanism::Include(domain_spec(value)?),
+        "exists" => Mechanism::Exists(domain_spec(value)?),
+        "ptr" => Mechanism::Ptr(value.map(|value| domain_spec(Some(value))).transpose()?),
+        "a" | "mx" => {
+            let (domain, cidr) = match value {
+                Some(value) => {
+                    let (spec, cidr) = split_cidr(value);
+                    (Some(domain_spec(Some(spec))?), dual_cidr(cidr)?)
+                }
+                None => (None, dual_cidr(arg)?),
+            };
+            if name.eq_ignore_ascii_case("a") {
+                Mechanism::A { domain, cidr }
+            } else {
+                Mechanism::Mx { domain, cidr }
+            }
+        }
+        "ip4" => {
+            let (addr, prefix) = network(value, 32)?;
+            Mechanism::Ip4(addr.parse().map_err(|_| "invalid IPv4 network")?, prefix)
+        }
+        "ip6" => {
+            let (addr, prefix) = network(value, 128)?;
+            Mechanism::Ip6(addr.parse().map_err(|_| "invalid IPv6 network")?, prefix)
+        }
+        _ => return Err("unknown mechanism".to_string()),
+    };
+    Ok(Directive {
+        qualifier,
+        mechanism,
+        term: term.to_string(),
+    })
+}
+
+fn domain_spec(value: Option<&str>) -> Result<String, String> {
+    let spec = value
+        .filter(|spec| !spec.is_empty())
+        .ok_or("missing domain")?;
+    macros::validate(spec)?;
+    check_domain_spec(spec)?;
+    Ok(spec.to_string())
+}
+
+/// Sans macro finale, le domaine doit se terminer par un label de premier
+/// niveau valide (`domain-end`, §7.1).
+fn check_domain_spec(spec: &str) -> Result<(), String> {
+    let trimmed = spec.strip_suffix('.').unwrap_or(spec);
+    if trimmed.ends_with('}') {
+        return Ok(());
+    }
+    let toplabel = trimmed.rsplit('.').next().unwrap_or_default();
+    let valid = trimmed.contains('.')
+        && !toplabel.is_empty()
+        && toplabel
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        && !toplabel.starts_with('-')
+        && !toplabel.ends_with('-')
+        && !toplabel.chars().all(|c| c.is_ascii_digit());
+    if valid {
+        Ok(())
+    } else {
+        Err("invalid domain".to_string())
+    }
+}
+
+/// Sépare `domaine/24//64` en domaine et suffixe CIDR.
+fn split_cidr(value: &str) -> (&str, &str) {
+    match value.find('/') {
+        Some(idx) => value.split_at(idx),
+        None => (value, ""),
+    }
+}
+
+fn dual_cidr(input: &str) -> Result<DualCidr, String> {
+    let mut cidr = DualCidr::default();
+    if input.is_empty() {
+        return Ok(cidr);
+    }
+    let (v4, v6) = match input.split_once("//") {
+        Some((v4, v6)) => (v4, Some(v6)),
+        None => (input, None),
+    };
+    if !v4.is_empty() {
+        let v4 = v4.strip_prefix('/').ok_or("invalid CIDR length")?;
+        cidr.v4 = prefix_length(v4, 32)?;
+    }
+    if let Some(v6) = v6 {
+        cidr.v6 = prefix_length(v6, 128)?;
+    }
+    Ok(cidr)
+}
+
+fn network(value: Option<&str>, max: u8) -> Result<(&str, u8), String> {
+    let value = value.ok_or("missing network")?;
+    match value.split_once('/') {
+        Some((addr, prefix)) => Ok((addr, prefix_length(prefix, max)?)),
+        None => Ok((value, max)),
+    }
+}
+
+/// Longueur de préfixe décimale, sans zéro initial (§5.6).
+fn prefix_length(input: &str, max: u8) -> Result<u8, String> {
+    let valid = !input.is_empty()
+        && input.len() <= 3
+        && input.bytes().all(|b| b.is_ascii_digit())
+        && (input == "0" || !input.starts_with('0'));
+    input
+        .parse::<u8>()
+        .ok()
+        .filter(|len| valid && *len <= max)
+        .ok_or_else(|| format!("invalid CIDR length /{input}"))
+}
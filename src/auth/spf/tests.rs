@@ -0,0 +1,538 @@
+use std::net::IpAddr;
+
+use super::macros::{self, MacroContext};
+use super::{
+    SpfFlattenOptions, SpfLink, SpfLint, SpfResult, SpfTraceEvent, check_host, flatten, lint,
+    record,
+};
+use crate::auth::error::SpfFlattenError;
+use crate::dns::{DnsRecord, DnssecStatus, StaticZone};
+
+fn zone(input: &str) -> StaticZone {
+    StaticZone::from_zone_str(input).expect("valid zone")
+}
+
+fn ip(input: &str) -> IpAddr {
+    input.parse().expect("valid IP")
+}
+
+#[test]
+fn expands_rfc_macro_examples() {
+    let ctx = |ip| MacroContext {
+        sender: "strong-bad@email.example.com",
+        local: "strong-bad",
+        sender_domain: "email.example.com",
+        domain: "email.example.com",
+        ip,
+        helo: "email.example.com",
+        validated: None,
+    };
+    let v4 = ctx(ip("192.0.2.3"));
+    let expand = |input: &str| macros::expand(input, &v4, false).expect("valid macro");
+    assert_eq!(expand("%{s}"), "strong-bad@email.example.com");
+    assert_eq!(expand("%{d2}"), "example.com");
+    assert_eq!(expand("%{d1}"), "com");
+    assert_eq!(expand("%{dr}"), "com.example.email");
+    assert_eq!(expand("%{d2r}"), "example.email");
+    assert_eq!(expand("%{l-}"), "strong.bad");
+    assert_eq!(expand("%{lr-}"), "bad.strong");
+    assert_eq!(expand("%{l1r-}"), "strong");
+    assert_eq!(
+        expand("%{ir}.%{v}._spf.%{d2}"),
+        "3.2.0.192.in-addr._spf.example.com"
+    );
+    assert_eq!(expand("%{S}%_%%"), "strong-bad%40email.example.com %");
+
+    let v6 = ctx(ip("2001:db8::cb01"));
+    assert_eq!(
+        macros::expand("%{ir}.%{v}._spf.%{d2}", &v6, false).expect("valid macro"),
+        "1.0.b.c.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6._spf.example.com"
+    );
+
+    assert!(macros::validate("%{x}").is_err());
+    assert!(macros::validate("%{d0}").is_err());
+    assert!(macros::validate("50%").is_err());
+    assert!(macros::expand("%{t}", &v4, false).is_err());
+}
+
+#[test]
+fn parses_mechanisms_and_rejects_bad_syntax() {
+    let parsed = record::parse(
+        "v=spf1 a mx/24//64 a:mail.example.com/28 ip6:2001:db8::/32 ~ptr exists:%{i}.bl.example.org \
+         redirect=_spf.example.com unknown=ignored",
+    )
+    .expect("valid record");
+    assert_eq!(parsed.directives.len(), 6);
+    assert_eq!(parsed.redirect.as_deref(), Some("_spf.example.com"));
+
+    for record in [
+        "v=spf1 foo -all",
+        "v=spf1 ip4:192.0.2.0/33 -all",
+        "v=spf1 a/024 -all",
+        "v=spf1 include: -all",
+        "v=spf1 redirect=a.example.com redirect=b.example.com",
+        "v=spf1 include:example.123 -all",
+    ] {
+        assert!(
+            record::parse(record).is_err(),
+            "{record} should be rejected"
+        );
+    }
+    assert_eq!(
+        record::select(&["v=spf10 -all".to_string(), "v=spf1".to_string()]),
+        vec!["v=spf1"]
+    );
+}
+
+#[test]
+fn follows_includes_and_redirects() {
+    let zone = zone(
+        "example.com. TXT \"v=spf1 ip4:192.0.2.0/24 include:_spf.example.net redirect=_spf.example.org\"
+_spf.example.net. TXT \"v=spf1 a:relay.example.net/30 -all\"
+relay.example.net. A 198.51.100.8
+_spf.example.org. TXT \"v=spf1 mx:example.org//64 -all exp=why.example.org\"
+example.org. MX 10 mx.example.org.
+mx.example.org. AAAA 2001:db8:1::25
+why.example.org. TXT \"%{i} is not allowed to send for %{d}\"",
+    );
+    let check = |addr: &str| check_host(&zone, ip(addr), "example.com", "user@example.com");
+
+    assert_eq!(check("192.0.2.77").result, SpfResult::Pass);
+
+    let included = check("198.51.100.10");
+    assert_eq!(included.result, SpfResult::Pass);
+    assert_eq!(included.dns_lookups, 2);
+    assert!(included.trace.iter().any(|step| step.depth == 1
+        && step.domain == "_spf.example.net"
+        && step.event
+            == SpfTraceEvent::Term {
+                term: "a:relay.example.net/30".to_string(),
+                matched: true
+            }));
+
+    assert_eq!(check("2001:db8:1::beef").result, SpfResult::Pass);
+
+    let failed = check("203.0.113.5");
+    assert_eq!(failed.result, SpfResult::Fail);
+    assert_eq!(failed.dns_lookups, 4);
+    assert_eq!(
+        failed.explanation.as_deref(),
+        Some("203.0.113.5 is not allowed to send for _spf.example.org")
+    );
+}
+
+#[test]
+fn enforces_lookup_limits() {
+    let mut input = String::new();
+    for n in 0..11 {
+        input.push_str(&format!(
+            "s{n}.example.com. TXT \"v=spf1 include:s{}.example.com -all\"\n",
+            n + 1
+        ));
+    }
+    input.push_str("s11.example.com. TXT \"v=spf1 +all\"\n");
+    let deep = zone(&input);
+    let check = check_host(&deep, ip("192.0.2.1"), "s0.example.com", "a@s0.example.com");
+    assert_eq!(check.result, SpfResult::PermError);
+    assert_eq!(check.dns_lookups, 11);
+
+    let voids = zone(
+        "example.com. TXT \"v=spf1 a:a.example.com a:b.example.com exists:c.example.com +all\"",
+    );
+    let check = check_host(&voids, ip("192.0.2.1"), "example.com", "a@example.com");
+    assert_eq!(check.result, SpfResult::PermError);
+    assert_eq!(check.void_lookups, 3);
+    assert!(matches!(
+        check.trace.last().map(|step| &step.event),
+        Some(SpfTraceEvent::Result { reason: Some(reason), .. }) if reason.contains("void")
+    ));
+}
+
+#[test]
+fn reports_none_permerror_and_temperror() {
+    let zone = zone(
+        "multi.example.com. TXT \"v=spf1 -all\"
+multi.example.com. TXT \"v=spf1 +all\"
+broken.example.com. TXT \"v=spf1 include:missing.example.com -all\"
+other.example.com. TXT \"google-site-verification=abc\"",
+    );
+    let result =
+        |domain: &str| check_host(&zone, ip("192.0.2.1"), domain, "postmaster@example.com").result;
+    assert_eq!(result("other.example.com"), SpfResult::None);
+    assert_eq!(result("absent.example.com"), SpfResult::None);
+    assert_eq!(result("multi.example.com"), SpfResult::PermError);
+    assert_eq!(result("broken.example.com"), SpfResult::PermError);
+
+    let bogus = zone.with_dnssec("multi.example.com", DnssecStatus::Bogus);
+    assert_eq!(
+        check_host(
+            &bogus,
+            ip("192.0.2.1"),
+            "multi.example.com",
+            "x@example.com"
+        )
+        .result,
+        SpfResult::TempError
+    );
+}
+
+#[test]
+fn ignores_non_ascii_version_tags() {
+    let zone = zone(
+        "accent.example.com. TXT \"v=spfé -all\"
+suffix.example.com. TXT \"v=spf1é -all\"
+short.example.com. TXT \"vé\"",
+    );
+    for domain in [
+        "accent.example.com",
+        "suffix.example.com",
+        "short.example.com",
+    ] {
+        let check = check_host(&zone, ip("192.0.2.1"), domain, "postmaster@example.com");
+        assert_eq!(check.result, SpfResult::None, "{domain}");
+    }
+}
+
+#[test]
+fn validates_ptr_names() {
+    let zone = zone(
+        "example.com. TXT \"v=spf1 ptr -all\"
+1.2.0.192.in-addr.arpa. PTR mail.example.com.
+mail.example.com. A 192.0.2.1
+2.2.0.192.in-addr.arpa. PTR spoof.example.com.",
+    );
+    let check = |addr: &str| check_host(&zone, ip(addr), "example.com", "example.com").result;
+    assert_eq!(check("192.0.2.1"), SpfResult::Pass);
+    assert_eq!(check("::ffff:192.0.2.1"), SpfResult::Pass);
+    assert_eq!(check("192.0.2.2"), SpfResult::Fail);
+}
+
+#[test]
+fn lint_counts_lookups_per_branch() {
+    let zone = zone(
+        "example.com. TXT \"v=spf1 mx include:vendor.example.net include:crm.example.org ~all\"
+example.com. MX 10 mx.example.com.
+vendor.example.net. TXT \"v=spf1 include:a.vendor.example.net include:b.vendor.example.net -all\"
+a.vendor.example.net. TXT \"v=spf1 a:gone.example.net exists:gone2.example.net -all\"
+b.vendor.example.net. TXT \"v=spf1 include:c.vendor.example.net a mx -all\"
+c.vendor.example.net. TXT \"v=spf1 ip4:198.51.100.0/24 -all\"
+crm.example.org. TXT \"v=spf1 include:missing.example.org redirect=_spf.example.org\"
+_spf.example.org. TXT \"v=spf1 a:mail.example.org -all\"
+mail.example.org. A 203.0.113.9",
+    );
+    let tree = lint(&zone, "example.com").expect("root answered");
+
+    let vendor = &tree.root.children[0];
+    assert_eq!(vendor.link, SpfLink::Include);
+    assert_eq!(vendor.lookups, 2);
+    assert_eq!(vendor.total_lookups(), 7);
+    assert_eq!(vendor.total_void_lookups(), 4);
+
+    let crm = &tree.root.children[1];
+    assert_eq!(crm.children[0].issues, vec![SpfLint::NoRecord]);
+    assert_eq!(crm.children[1].link, SpfLink::Redirect);
+    assert_eq!(crm.total_lookups(), 3);
+
+    assert_eq!(tree.dns_lookups(), 13);
+    assert_eq!(tree.void_lookups(), 5);
+    assert!(
+        tree.root
+            .issues
+            .contains(&SpfLint::TooManyLookups { count: 13 })
+    );
+    assert!(
+        tree.root
+            .issues
+            .contains(&SpfLint::TooManyVoidLookups { count: 5 })
+    );
+    assert!(tree.has_errors());
+
+    assert!(
+        lint(
+            &zone.with_dnssec("example.com", DnssecStatus::Bogus),
+            "example.com"
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn lint_treats_non_ascii_include_targets_as_missing() {
+    let zone = zone(
+        "example.com. TXT \"v=spf1 include:accent.example.net -all\"
+accent.example.net. TXT \"v=spfé -all\"",
+    );
+    let tree = lint(&zone, "example.com").expect("root answered");
+    assert_eq!(tree.root.children[0].issues, vec![SpfLint::NoRecord]);
+    assert!(tree.has_errors());
+}
+
+#[test]
+fn lint_reports_loops_duplicates_and_overlaps() {
+    let long = (0..40)
+        .map(|n| format!("ip4:192.0.{n}.1"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let zone = zone(&format!(
+        "example.com. TXT \"v=spf1 ip4:192.0.2.0/24 ptr include:a.example.net include:b.example.net -all\"
+a.example.net. TXT \"v=spf1 ip4:192.0.2.128/25 include:shared.example.net include:example.com -all\"
+b.example.net. TXT \"v=spf1 include:shared.example.net exists:%{{i}}.rbl.example.net -all\"
+shared.example.net. TXT \"v=spf1 ip6:2001:db8::/32 -all\"
+long.example.com. TXT \"v=spf1 {long} -all\""
+    ));
+    let tree = lint(&zone, "example.com").expect("root answered");
+    let issues: Vec<_> = tree
+        .issues()
+        .into_iter()
+        .map(|(domain, issue)| (domain.to_string(), issue.clone()))
+        .collect();
+
+    let expect = |domain: &str, issue: SpfLint| {
+        assert!(
+            issues.contains(&(domain.to_string(), issue.clone())),
+            "{domain}: {issue:?} not in {issues:?}"
+        );
+    };
+    expect(
+        "example.com",
+        SpfLint::DeprecatedPtr {
+            term: "ptr".to_string(),
+        },
+    );
+    expect(
+        "a.example.net",
+        SpfLint::OverlappingNetwork {
+            term: "ip4:192.0.2.128/25".to_string(),
+            other_domain: "example.com".to_string(),
+            other_term: "ip4:192.0.2.0/24".to_string(),
+        },
+    );
+    expect(
+        "a.example.net",
+        SpfLint::Loop {
+            target: "example.com".to_string(),
+        },
+    );
+    expect(
+        "b.example.net",
+        SpfLint::DuplicateInclude {
+            target: "shared.example.net".to_string(),
+        },
+    );
+    expect(
+        "b.example.net",
+        SpfLint::MacroTarget {
+            term: "exists:%{i}.rbl.example.net".to_string(),
+        },
+    );
+    assert!(
+        !issues
+            .iter()
+            .any(|(_, issue)| matches!(issue, SpfLint::OverlappingNetwork { term, .. } if term.starts_with("ip6")))
+    );
+    assert_eq!(tree.dns_lookups(), 7);
+
+    let long = lint(&zone, "long.example.com").expect("root answered");
+    assert!(matches!(
+        long.root.issues.as_slice(),
+        [SpfLint::RecordLength { limit: 512, .. }]
+    ));
+    assert!(!long.has_errors());
+}
+
+#[test]
+fn flattens_include_tree_into_networks() {
+    let zone = zone(
+        "example.com. TXT \"v=spf1 mx include:vendor.example.net include:crm.example.org ~all\"
+example.com. MX 10 mx.example.com.
+mx.example.com. A 192.0.2.25
+vendor.example.net. TXT \"v=spf1 ip4:198.51.100.0/24 ip4:198.51.100.7 include:sub.example.net -all\"
+sub.example.net. TXT \"v=spf1 ip6:2001:db8::/48 a:relay.example.net -all\"
+relay.example.net. A 203.0.113.5
+crm.example.org. TXT \"v=spf1 redirect=vendor.example.net\"",
+    );
+    let flat = flatten(&zone, "example.com", &SpfFlattenOptions::default()).expect("flattened");
+    assert_eq!(flat.records.len(), 1);
+    assert_eq!(flat.records[0].name, "example.com");
+    assert_eq!(
+        flat.records[0].text,
+        "v=spf1 ip4:192.0.2.25 ip4:198.51.100.0/24 ip4:203.0.113.5 ip6:2001:db8::/48 ~all"
+    );
+    assert_eq!(flat.dns_lookups, 0);
+    assert!(flat.kept.is_empty());
+
+    let ordered = zone.with_record(
+        "ordered.example.com",
+        DnsRecord::Txt(
+            "v=spf1 -ip4:198.51.100.7 include:vendor.example.net exists:%{i}.bl.example.org ?all"
+                .to_string(),
+        ),
+    );
+    let flat = flatten(
+        &ordered,
+        "ordered.example.com",
+        &SpfFlattenOptions::default(),
+    )
+    .expect("flattened");
+    assert_eq!(
+        flat.records[0].text,
+        "v=spf1 -ip4:198.51.100.7 ip4:198.51.100.0/24 ip4:198.51.100.7 ip6:2001:db8::/48 \
+         ip4:203.0.113.5 exists:%{i}.bl.example.org ?all"
+    );
+    assert_eq!(flat.kept, vec!["exists:%{i}.bl.example.org".to_string()]);
+    assert_eq!(flat.dns_lookups, 1);
+}
+
+#[test]
+fn chains_flattened_records_over_size_limit() {
+    let networks = (0..30)
+        .map(|n| format!("ip4:198.51.{n}.0/24"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let source = zone(&format!(
+        "example.com. TXT \"v=spf1 include:_netblocks.example.net -all\"
+_netblocks.example.net. TXT \"v=spf1 {networks} ip6:2001:db8::/32 ~all\""
+    ));
+    let options = SpfFlattenOptions {
+        max_record_len: 120,
+        ..SpfFlattenOptions::default()
+    };
+    let flat = flatten(&source, "example.com", &options).expect("flattened");
+    assert!(flat.records.len() > 2);
+    assert_eq!(flat.dns_lookups, flat.records.len() - 1);
+    assert!(flat.records.iter().all(|record| record.text.len() <= 120));
+    assert!(
+        flat.records[0]
+            .text
+            .ends_with("include:_spf1.example.com -all")
+    );
+    assert_eq!(flat.records[1].name, "_spf1.example.com");
+    assert!(
+        !flat
+            .records
+            .last()
+            .expect("records")
+            .text
+            .contains("include:")
+    );
+
+    let published = zone(
+        &flat
+            .records
+            .iter()
+            .map(|record| record.to_zone_line())
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    let check = |addr: &str| check_host(&published, ip(addr), "example.com", "a@example.com");
+    assert_eq!(check("198.51.0.1").result, SpfResult::Pass);
+    assert_eq!(check("198.51.29.200").result, SpfResult::Pass);
+    assert_eq!(check("2001:db8:ffff::1").result, SpfResult::Pass);
+    assert_eq!(check("198.51.30.1").result, SpfResult::Fail);
+    assert!(check("198.51.29.200").dns_lookups <= 10);
+}
+
+#[test]
+fn refuses_to_flatten_non_pass_includes_and_loops() {
+    let zone = zone(
+        "shadow.example.com. TXT \"v=spf1 include:shadow.example.net -all\"
+shadow.example.net. TXT \"v=spf1 -ip4:192.0.2.1 ip4:192.0.2.0/24 -all\"
+loop.example.com. TXT \"v=spf1 include:loop.example.net -all\"
+loop.example.net. TXT \"v=spf1 redirect=loop.example.com\"
+broken.example.com. TXT \"v=spf1 include:missing.example.net -all\"
+accent.example.com. TXT \"v=spf1 include:accent.example.net -all\"
+accent.example.net. TXT \"v=spfé ip4:192.0.2.1 -all\"",
+    );
+    let options = SpfFlattenOptions::default();
+    assert!(matches!(
+        flatten(&zone, "shadow.example.com", &options),
+        Err(SpfFlattenError::Unflattenable { domain, term })
+            if domain == "shadow.example.net" && term == "-ip4:192.0.2.1"
+    ));
+    assert!(matches!(
+        flatten(&zone, "loop.example.com", &options),
+        Err(SpfFlattenError::Loop { domain }) if domain == "loop.example.com"
+    ));
+    assert!(matches!(
+        flatten(&zone, "broken.example.com", &options),
+        Err(SpfFlattenError::NoRecord { domain }) if domain == "missing.example.net"
+    ));
+    assert!(matches!(
+        flatten(&zone, "accent.example.com", &options),
+        Err(SpfFlattenError::NoRecord { domain }) if domain == "accent.example.net"
+    ));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_check_matches_sync() {
+    use crate::auth::{check_spf_host_async_with_backend, check_spf_host_with_backend};
+    use crate::dns::block_on;
+
+    let zone = zone(
+        "example.com. TXT \"v=spf1 include:_spf.example.net -all\"
+_spf.example.net. TXT \"v=spf1 mx -all\"
+_spf.example.net. MX 10 mx.example.net.
+mx.example.net. A 192.0.2.25",
+    );
+    let sync = check_spf_host_with_backend(&zone, ip("192.0.2.25"), "Example.COM", "a@example.com")
+        .expect("valid domain");
+    let async_check = block_on(check_spf_host_async_with_backend(
+        &zone,
+        ip("192.0.2.25"),
+        "example.com",
+        "a@example.com",
+    ))
+    .expect("valid domain");
+    assert_eq!(sync.result, SpfResult::Pass);
+    assert_eq!(async_check, sync);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_lint_issues_only_the_lookups_of_the_sync_run() {
+    use std::sync::Mutex;
+
+    use crate::auth::{lint_spf_async_with_backend, lint_spf_with_backend};
+    use crate::dns::{AsyncDnsBackend, DnsAnswer, DnsBackend, DnsError, DnsRecordType, block_on};
+
+    struct Recording {
+        zone: StaticZone,
+        queries: Mutex<Vec<(String, DnsRecordType)>>,
+    }
+
+    impl DnsBackend for Recording {
+        fn lookup(&self, name: &str, rtype: DnsRecordType) -> Result<DnsAnswer, DnsError> {
+            self.queries.lock().unwrap().push((name.to_string(), rtype));
+            DnsBackend::lookup(&self.zone, name, rtype)
+        }
+    }
+
+    impl AsyncDnsBackend for Recording {
+        async fn lookup(&self, name: &str, rtype: DnsRecordType) -> Result<DnsAnswer, DnsError> {
+            DnsBackend::lookup(self, name, rtype)
+        }
+    }
+
+    let recording = || Recording {
+        zone: zone(
+            "example.com. TXT \"v=spf1 include:a.example.net include:b.example.net mx -all\"
+example.com. MX 10 mx.example.com.
+mx.example.com. A 192.0.2.25
+a.example.net. TXT \"v=spf1 ip4:192.0.2.1 -all\"
+b.example.net. TXT \"v=spf1 a:mail.example.net -all\"
+mail.example.net. A 192.0.2.2",
+        ),
+        queries: Mutex::new(Vec::new()),
+    };
+    let sync_backend = recording();
+    let sync = lint_spf_with_backend(&sync_backend, "example.com").expect("valid domain");
+    let async_backend = recording();
+    let async_lint =
+        block_on(lint_spf_async_with_backend(&async_backend, "example.com")).expect("valid domain");
+    assert_eq!(async_lint, sync);
+    assert_eq!(
+        async_backend.queries.into_inner().unwrap(),
+        sync_backend.queries.into_inner().unwrap()
+    );
+}
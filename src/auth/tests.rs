@@ -1,12 +1,32 @@
 use std::collections::HashMap;
 
 use super::{
-    AuthError, AuthLookupOptions, DkimPolicyStatus, DkimSelectorStatus, DmarcStatus,
-    check_with_resolver,
-    dkim::DkimWeakness,
-    resolver::LookupTxt,
+    AuthLookupOptions, DkimPolicyStatus, DkimSelectorStatus, DmarcStatus, PublicSuffixList,
+    bimi::{BimiDmarcIssue, BimiIssue, BimiSelectorStatus},
+    check_with_backend,
+    dkim::{DkimIssue, DkimKey, DkimKeyType, DkimWeakness},
+    dmarc::{
+        DmarcAlignment, DmarcFailureOption, DmarcIssue, DmarcPolicy, DmarcReportAuthorization,
+        DmarcUri, DmarcWeakness,
+    },
     spf::{SpfQualifier, SpfStatus},
+    tls_rpt::{TlsRptIssue, TlsRptStatus, TlsRptUri},
 };
+use crate::dns::{DnsAnswer, DnsBackend, DnsError, DnsRecord, DnsRecordType};
+
+/// SubjectPublicKeyInfo RSA 2048 bits, en base64.
+const RSA_2048_KEY: &str = "MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEAs8YuA/f446EIl8RcPVo+Z0g6/DyOV9LJd2B8gOjCw9mS\
+    pBPtrovTG1FjlMUJKoSI4VRdtpOg9R7bJOslQD+VMZEkUKt8aBrwA2njH4VZ2c504Tt9AkogIzPWMyH0LA8SaDjv\
+    /E8xJGqro6t2t5MqDY+yaPaseUbKjYKyRoXmw4DebUIev6tfE8APuO/BrN0n6m+r6rJVkRN+kv+cKGqK/lAcMkxq\
+    nROUSAcPFFpfhhcGx0D6t8Uyvi2FfgIOhsycb+eK6eD5WhwFXsm452cxDjrkcTYtkHM2v/qXzYO+urvdiR1DOCIp\
+    i8akAAMPj6O+bADsqzAwqXlZxUWA2wLr1QIDAQAB";
+const RSA_1024_KEY: &str = "MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQDO70uPZDAhBvBTgbNQoOCoyRHKruiQKMPY6CuFwiZtA5Lz7boB\
+    Q1P9lvj5d/64vwk/z25i+p40zTQ5Um9rwde1urYYmzXZMzfRjnvbmARoWwOjTBrGrYuFl84tgRYrrgPayYXnuBXA\
+    92eXnfgi7gJRplZ4f8mxu1hWO0jVQc+QDwIDAQAB";
+const RSA_512_KEY: &str = "MFwwDQYJKoZIhvcNAQEBBQADSwAwSAJBALZl1858MJt0KaJlTzX0g2QLYOI1MudiE/CeZs9sIQaf7lNe0rCZONNQ\
+    bRVVe5Y0t5Jl9f8J9Y+k4n4E5qNfWU0CAwEAAQ==";
+/// Clé Ed25519 brute (RFC 8463).
+const ED25519_KEY: &str = "yG3wamPtVVfYqHy6WuNY4kZwnbodyKxra3OprVpoXSY=";
 
 struct StubResolver {
     records: HashMap<String, Vec<String>>,
@@ -30,10 +50,20 @@ impl StubResolver {
     }
 }
 
-impl LookupTxt for StubResolver {
-    fn lookup_txt(&self, name: &str) -> Result<Vec<String>, AuthError> {
-        let key = normalize_name(name);
-        Ok(self.records.get(&key).cloned().unwrap_or_default())
+impl DnsBackend for StubResolver {
+    fn lookup(&self, name: &str, rtype: DnsRecordType) -> Result<DnsAnswer, DnsError> {
+        let records = match rtype {
+            DnsRecordType::Txt => self
+                .records
+                .get(&normalize_name(name))
+                .into_iter()
+                .flatten()
+                .cloned()
+                .map(DnsRecord::Txt)
+                .collect(),
+            _ => Vec::new(),
+        };
+        Ok(DnsAnswer::new(records, None))
     }
 }
 
@@ -73,21 +103,287 @@ fn spf_redirect_marked_delegated() {
 #[test]
 fn dmarc_none_policy_flagged_weak() {
     let input = vec!["v=DMARC1; p=none; rua=mailto:d@example.com".to_string()];
-    let status = super::dmarc::evaluate(&input);
+    let status = super::dmarc::evaluate("example.com", &input);
     assert!(matches!(status, DmarcStatus::Weak { .. }));
 }
 
+#[test]
+fn dmarc_tags_parsed_with_defaults() {
+    let input = vec![
+        "v=DMARC1; p=reject; rua=mailto:agg@example.com!10m, mailto:dmarc@reports.example.net; \
+         ruf=mailto:forensic@example.com; adkim=s; fo=1:d; ri=3600; psd=n"
+            .to_string(),
+    ];
+    let DmarcStatus::Compliant { parsed, .. } = super::dmarc::evaluate("example.com", &input)
+    else {
+        panic!("expected compliant status");
+    };
+    assert_eq!(parsed.policy, DmarcPolicy::Reject);
+    assert_eq!(parsed.effective_subdomain_policy(), DmarcPolicy::Reject);
+    assert_eq!(parsed.effective_nonexistent_policy(), DmarcPolicy::Reject);
+    assert_eq!(parsed.percentage, 100);
+    assert_eq!(
+        parsed.aggregate_reports,
+        vec![
+            DmarcUri {
+                address: "agg@example.com".to_string(),
+                max_size: Some(10 << 20),
+                authorization: DmarcReportAuthorization::Unchecked,
+            },
+            DmarcUri {
+                address: "dmarc@reports.example.net".to_string(),
+                max_size: None,
+                authorization: DmarcReportAuthorization::Unchecked,
+            },
+        ]
+    );
+    assert_eq!(parsed.aggregate_reports[1].domain(), "reports.example.net");
+    assert_eq!(parsed.failure_reports.len(), 1);
+    assert_eq!(parsed.dkim_alignment, DmarcAlignment::Strict);
+    assert_eq!(parsed.spf_alignment, DmarcAlignment::Relaxed);
+    assert_eq!(
+        parsed.failure_options,
+        vec![DmarcFailureOption::Any, DmarcFailureOption::Dkim]
+    );
+    assert_eq!(parsed.report_interval, 3600);
+    assert_eq!(parsed.psd, Some(false));
+    assert!(!parsed.testing);
+
+    let reordered = vec!["p=reject; v=DMARC1".to_string()];
+    assert_eq!(
+        super::dmarc::evaluate("example.com", &reordered),
+        DmarcStatus::Missing
+    );
+    let version = vec!["v=DMARC10; p=reject".to_string()];
+    assert!(matches!(
+        super::dmarc::evaluate("example.com", &version),
+        DmarcStatus::Invalid {
+            issue: DmarcIssue::InvalidVersion,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn dmarc_weaknesses_listed_per_tag() {
+    let input = vec![
+        "v=DMARC1; p=reject; sp=none; np=quarantine; pct=20; t=y; \
+         rua=https://reports.example.com/dmarc, mailto:broken; adkim=x; pct=100"
+            .to_string(),
+    ];
+    let DmarcStatus::Weak {
+        parsed, weaknesses, ..
+    } = super::dmarc::evaluate("example.com", &input)
+    else {
+        panic!("expected weak status");
+    };
+    assert_eq!(parsed.percentage, 20);
+    assert_eq!(parsed.dkim_alignment, DmarcAlignment::Relaxed);
+    assert!(parsed.testing);
+    assert_eq!(
+        weaknesses,
+        vec![
+            DmarcWeakness::TestingMode,
+            DmarcWeakness::PartialPercentage { pct: 20 },
+            DmarcWeakness::WeakerSubdomainPolicy {
+                policy: DmarcPolicy::None
+            },
+            DmarcWeakness::MissingAggregateReports,
+            DmarcWeakness::InvalidUri {
+                tag: "rua".to_string(),
+                uri: "https://reports.example.com/dmarc".to_string(),
+            },
+            DmarcWeakness::InvalidUri {
+                tag: "rua".to_string(),
+                uri: "mailto:broken".to_string(),
+            },
+            DmarcWeakness::InvalidTag {
+                tag: "adkim".to_string(),
+                value: "x".to_string(),
+            },
+            DmarcWeakness::DuplicateTag {
+                tag: "pct".to_string(),
+            },
+        ]
+    );
+
+    let nonexistent =
+        vec!["v=DMARC1; p=reject; np=none; rua=mailto:d@example.com; pct=abc".to_string()];
+    let DmarcStatus::Weak { weaknesses, .. } = super::dmarc::evaluate("example.com", &nonexistent)
+    else {
+        panic!("expected weak status");
+    };
+    assert_eq!(
+        weaknesses,
+        vec![
+            DmarcWeakness::WeakerNonexistentPolicy {
+                policy: DmarcPolicy::None
+            },
+            DmarcWeakness::InvalidTag {
+                tag: "pct".to_string(),
+                value: "abc".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn tls_rpt_lists_report_destinations() {
+    let input = vec![
+        "v=spf1 -all".to_string(),
+        "v=TLSRPTv1; rua=mailto:tls@example.com,https://reports.example.net/tlsrpt".to_string(),
+    ];
+    match super::tls_rpt::evaluate(&input) {
+        TlsRptStatus::Valid { rua, .. } => assert_eq!(
+            rua,
+            vec![
+                TlsRptUri::Mailto("tls@example.com".to_string()),
+                TlsRptUri::Https("https://reports.example.net/tlsrpt".to_string()),
+            ]
+        ),
+        other => panic!("expected valid status, got {:?}", other),
+    }
+    assert_eq!(super::tls_rpt::evaluate(&[]), TlsRptStatus::Missing);
+}
+
+#[test]
+fn tls_rpt_rejects_malformed_records() {
+    let issue = |record: &str| match super::tls_rpt::evaluate(&[record.to_string()]) {
+        TlsRptStatus::Invalid { issue, .. } => issue,
+        other => panic!("expected invalid status, got {:?}", other),
+    };
+    assert_eq!(issue("v=TLSRPTv1"), TlsRptIssue::MissingRua);
+    assert_eq!(
+        issue("v=TLSRPTv10; rua=mailto:a@example.com"),
+        TlsRptIssue::InvalidVersion
+    );
+    assert_eq!(
+        issue("v=TLSRPTv1; rua=mailto:a@example.com; rua=mailto:b@example.com"),
+        TlsRptIssue::DuplicateField {
+            field: "rua".to_string()
+        }
+    );
+    assert_eq!(
+        issue("v=TLSRPTv1; rua=http://example.com/report"),
+        TlsRptIssue::UnsupportedUri {
+            uri: "http://example.com/report".to_string()
+        }
+    );
+    assert_eq!(
+        issue("v=TLSRPTv1; rua=mailto:reports"),
+        TlsRptIssue::MalformedUri {
+            uri: "mailto:reports".to_string()
+        }
+    );
+
+    let duplicated = vec![
+        "v=TLSRPTv1; rua=mailto:a@example.com".to_string(),
+        "v=TLSRPTv1; rua=mailto:b@example.com".to_string(),
+    ];
+    assert!(matches!(
+        super::tls_rpt::evaluate(&duplicated),
+        TlsRptStatus::MultipleRecords { ref records } if records.len() == 2
+    ));
+}
+
 #[test]
 fn dkim_testing_selector_reported_weak() {
-    let records = vec!["v=DKIM1; p=MIIB...; t=y".to_string()];
+    let records = vec![format!("v=DKIM1; p={RSA_2048_KEY}; t=y")];
     let status = super::dkim::selector_status("default", &records);
     assert!(matches!(
         status,
-        DkimSelectorStatus::Weak {
-            weakness: DkimWeakness::TestingFlag,
-            ..
+        DkimSelectorStatus::Weak { ref weaknesses, .. }
+            if weaknesses == &[DkimWeakness::TestingFlag]
+    ));
+}
+
+#[test]
+fn dkim_keys_decoded_and_rated() {
+    let status = |record: String| super::dkim::selector_status("s1", &[record]);
+    let key = |status: DkimSelectorStatus| match status {
+        DkimSelectorStatus::Compliant { key, .. } | DkimSelectorStatus::Weak { key, .. } => key,
+        other => panic!("expected usable key, got {other:?}"),
+    };
+    let issue = |status: DkimSelectorStatus| match status {
+        DkimSelectorStatus::Invalid { issue, .. } => issue,
+        other => panic!("expected invalid key, got {other:?}"),
+    };
+
+    let strong = status(format!("v=DKIM1; k=rsa; p={RSA_2048_KEY}"));
+    assert!(matches!(strong, DkimSelectorStatus::Compliant { .. }));
+    assert_eq!(
+        key(strong),
+        DkimKey {
+            key_type: DkimKeyType::Rsa,
+            modulus_bits: Some(2048),
+            hash_algorithms: Vec::new(),
+            service_types: vec!["*".to_string()],
         }
+    );
+
+    let ed25519 = key(status(format!(
+        "v=DKIM1; k=ed25519; s=email; p={ED25519_KEY}"
+    )));
+    assert_eq!(ed25519.key_type, DkimKeyType::Ed25519);
+    assert_eq!(ed25519.modulus_bits, None);
+
+    assert!(matches!(
+        status(format!("v=DKIM1; h=sha1; t=y:s; p={RSA_1024_KEY}")),
+        DkimSelectorStatus::Weak { ref weaknesses, .. } if weaknesses == &[
+            DkimWeakness::TestingFlag,
+            DkimWeakness::WeakKey { bits: 1024 },
+            DkimWeakness::Sha1Only,
+        ]
     ));
+    assert!(matches!(
+        status(format!("v=DKIM1; h=sha1:sha256; p={RSA_2048_KEY}")),
+        DkimSelectorStatus::Compliant { .. }
+    ));
+
+    assert_eq!(
+        issue(status(format!("v=DKIM1; p={RSA_512_KEY}"))),
+        DkimIssue::KeyTooShort { bits: 512 }
+    );
+    assert_eq!(
+        issue(status("v=DKIM1; p=".to_string())),
+        DkimIssue::RevokedKey
+    );
+    assert_eq!(
+        issue(status("v=DKIM1; k=rsa".to_string())),
+        DkimIssue::MissingPublicKey
+    );
+    assert_eq!(
+        issue(status(
+            "v=DKIM1; p=MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8A".to_string()
+        )),
+        DkimIssue::MalformedPublicKey
+    );
+    assert_eq!(
+        issue(status("v=DKIM1; p=not*base64".to_string())),
+        DkimIssue::MalformedPublicKey
+    );
+    assert_eq!(
+        issue(status(format!("v=DKIM1; k=ed25519; p={RSA_2048_KEY}"))),
+        DkimIssue::MalformedPublicKey
+    );
+    assert_eq!(
+        issue(status(format!("v=DKIM1; k=dsa; p={RSA_2048_KEY}"))),
+        DkimIssue::UnknownKeyType {
+            key_type: "dsa".to_string()
+        }
+    );
+    assert_eq!(
+        issue(status(format!("v=DKIM1; h=md5; p={RSA_2048_KEY}"))),
+        DkimIssue::UnsupportedHashAlgorithms {
+            algorithms: vec!["md5".to_string()]
+        }
+    );
+    assert_eq!(
+        issue(status(format!("v=DKIM1; s=tlsrpt; p={RSA_2048_KEY}"))),
+        DkimIssue::NotForEmail {
+            service_types: vec!["tlsrpt".to_string()]
+        }
+    );
 }
 
 #[test]
@@ -101,11 +397,11 @@ fn check_with_resolver_combines_findings() {
     stub.insert_records("_domainkey.example.com", vec!["v=DKIM1; o=-"]);
     stub.insert_records(
         "default._domainkey.example.com",
-        vec!["v=DKIM1; p=MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8A; t=y"],
+        vec![format!("v=DKIM1; p={RSA_2048_KEY}; t=y")],
     );
 
     let options = AuthLookupOptions::new().with_dkim_selector("default");
-    let status = check_with_resolver(&stub, "example.com", &options).expect("resolution succeeds");
+    let status = check_with_backend(&stub, "example.com", &options).expect("resolution succeeds");
 
     match status.spf {
         SpfStatus::Compliant {
@@ -132,9 +428,610 @@ fn check_with_resolver_combines_findings() {
         .find(|entry| matches!(entry, DkimSelectorStatus::Weak { selector, .. } if selector == "default"))
         .unwrap_or_else(|| panic!("expected selector status"));
 
-    if let DkimSelectorStatus::Weak { weakness, .. } = selector {
-        assert_eq!(*weakness, DkimWeakness::TestingFlag);
+    if let DkimSelectorStatus::Weak { weaknesses, .. } = selector {
+        assert_eq!(weaknesses, &[DkimWeakness::TestingFlag]);
     } else {
         panic!("expected weak selector, got {:?}", selector);
     }
 }
+
+#[test]
+fn bimi_record_parsed_per_selector() {
+    let published =
+        vec!["v=BIMI1; l=https://example.com/logo.svg; a=https://example.com/vmc.pem".to_string()];
+    assert_eq!(
+        super::bimi::selector_status("default", &published),
+        BimiSelectorStatus::Published {
+            selector: "default".to_string(),
+            record: published[0].clone(),
+            logo: "https://example.com/logo.svg".to_string(),
+            authority: Some("https://example.com/vmc.pem".to_string()),
+        }
+    );
+    assert!(matches!(
+        super::bimi::selector_status("default", &["v=BIMI1; l=; a=".to_string()]),
+        BimiSelectorStatus::Declined { .. }
+    ));
+    assert!(matches!(
+        super::bimi::selector_status(
+            "default",
+            &["v=bimi1; l=https://example.com/logo.svg".to_string()]
+        ),
+        BimiSelectorStatus::Published { .. }
+    ));
+
+    let issue = |record: &str| match super::bimi::selector_status("brand", &[record.to_string()]) {
+        BimiSelectorStatus::Invalid { issue, .. } => issue,
+        other => panic!("expected invalid status, got {:?}", other),
+    };
+    assert_eq!(
+        issue("v=BIMI1; l=http://example.com/logo.svg"),
+        BimiIssue::InvalidLogoUrl {
+            url: "http://example.com/logo.svg".to_string()
+        }
+    );
+    assert_eq!(
+        issue("v=BIMI1; l=https://example.com/logo.svg; a=vmc.pem"),
+        BimiIssue::InvalidAuthorityUrl {
+            url: "vmc.pem".to_string()
+        }
+    );
+    assert_eq!(
+        issue("v=BIMI1; l=; a=https://example.com/vmc.pem"),
+        BimiIssue::MissingLogo
+    );
+    assert_eq!(
+        issue("v=BIMI1; l=https://a.example/1.svg; l=https://a.example/2.svg"),
+        BimiIssue::DuplicateTag {
+            tag: "l".to_string()
+        }
+    );
+}
+
+#[test]
+fn bimi_requires_enforced_dmarc() {
+    let mut stub = StubResolver::new();
+    stub.insert_records(
+        "default._bimi.example.com",
+        vec!["v=BIMI1; l=https://example.com/logo.svg"],
+    );
+    stub.insert_records(
+        "brand._bimi.example.com",
+        vec!["v=BIMI1; l=https://example.com/brand.svg"],
+    );
+    let options = AuthLookupOptions::new().with_bimi_selectors(["Brand", "default"]);
+    let check = |dmarc: &str| {
+        let mut stub = StubResolver {
+            records: stub.records.clone(),
+        };
+        stub.insert_records("_dmarc.example.com", vec![dmarc]);
+        check_with_backend(&stub, "example.com", &options).expect("resolution succeeds")
+    };
+
+    let status = check("v=DMARC1; p=reject");
+    assert!(status.bimi.is_ready());
+    let selectors: Vec<_> = status
+        .bimi
+        .selectors
+        .iter()
+        .map(|status| match status {
+            BimiSelectorStatus::Published { selector, .. } => selector.as_str(),
+            other => panic!("expected published selector, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(selectors, vec!["default", "brand"]);
+
+    let status = check("v=DMARC1; p=quarantine; pct=50");
+    assert_eq!(
+        status.bimi.dmarc_issue,
+        Some(BimiDmarcIssue::PartialPercentage {
+            pct: "50".to_string()
+        })
+    );
+    assert!(!status.bimi.is_ready());
+    let status = check("v=DMARC1; p=reject; sp=none");
+    assert_eq!(
+        status.bimi.dmarc_issue,
+        Some(BimiDmarcIssue::MonitoringPolicy)
+    );
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_check_matches_sync() {
+    use super::check_auth_records_async_with_backend;
+    use crate::dns::{StaticZone, block_on};
+
+    let zone = StaticZone::from_zone_str(
+        "example.com. TXT \"v=spf1 -all\"
+_dmarc.example.com. TXT \"v=DMARC1; p=reject; rua=mailto:dmarc@example.com\"
+s1._domainkey.example.com. TXT \"v=DKIM1; p=MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8A\"",
+    )
+    .expect("valid zone");
+    let options = AuthLookupOptions::new().with_dkim_selector("s1");
+
+    let status = block_on(check_auth_records_async_with_backend(
+        &zone,
+        "Example.COM",
+        &options,
+    ))
+    .expect("resolution succeeds");
+    let sync = check_with_backend(&zone, "example.com", &options).expect("resolution succeeds");
+    assert_eq!(status, sync);
+    assert!(matches!(status.dmarc, DmarcStatus::Compliant { .. }));
+}
+
+#[test]
+fn records_dnssec_status_per_lookup() {
+    use super::{AuthError, DnssecLookup};
+    use crate::dns::{DnssecStatus, StaticZone};
+
+    let zone = StaticZone::from_zone_str(
+        "example.com. TXT \"v=spf1 -all\"
+_dmarc.example.com. TXT \"v=DMARC1; p=reject\"",
+    )
+    .expect("valid zone")
+    .with_dnssec("example.com", DnssecStatus::Secure)
+    .with_dnssec("_domainkey.example.com", DnssecStatus::Insecure);
+
+    let status = check_with_backend(&zone, "example.com", &AuthLookupOptions::new())
+        .expect("resolution succeeds");
+    let lookup = |name: &str, status| DnssecLookup {
+        name: name.to_string(),
+        status,
+    };
+    assert_eq!(
+        status.dnssec,
+        vec![
+            lookup("example.com", DnssecStatus::Secure),
+            lookup("_dmarc.example.com", DnssecStatus::Secure),
+            lookup("_domainkey.example.com", DnssecStatus::Insecure),
+            lookup("_smtp._tls.example.com", DnssecStatus::Secure),
+            lookup("default._bimi.example.com", DnssecStatus::Secure),
+        ]
+    );
+    assert_eq!(status.dnssec_status(), DnssecStatus::Insecure);
+
+    let bogus = zone.with_dnssec("_dmarc.example.com", DnssecStatus::Bogus);
+    let err = check_with_backend(&bogus, "example.com", &AuthLookupOptions::new())
+        .expect_err("bogus DMARC record must not be used");
+    assert!(matches!(
+        err,
+        AuthError::TxtLookup { ref name, ref source }
+            if name == "_dmarc.example.com" && source.is_bogus()
+    ));
+}
+
+#[test]
+fn optional_record_lookup_failures_do_not_abort() {
+    use crate::dns::{DnssecStatus, StaticZone};
+
+    let zone = StaticZone::from_zone_str(
+        "example.com. TXT \"v=spf1 -all\"
+_dmarc.example.com. TXT \"v=DMARC1; p=reject\"
+_smtp._tls.example.com. TXT \"v=TLSRPTv1; rua=mailto:tls@example.com\"
+default._bimi.example.com. TXT \"v=BIMI1; l=https://example.com/logo.svg\"
+brand._bimi.example.com. TXT \"v=BIMI1; l=https://example.com/brand.svg\"",
+    )
+    .expect("valid zone")
+    .with_dnssec("_smtp._tls.example.com", DnssecStatus::Bogus)
+    .with_dnssec("brand._bimi.example.com", DnssecStatus::Bogus);
+
+    let options = AuthLookupOptions::new().with_bimi_selector("brand");
+    let status = check_with_backend(&zone, "example.com", &options)
+        .expect("TLS-RPT and BIMI failures must not abort the check");
+    assert!(matches!(
+        status.tls_rpt,
+        TlsRptStatus::LookupFailed { ref reason } if reason.contains("_smtp._tls.example.com")
+    ));
+    assert!(matches!(status.dmarc, DmarcStatus::Weak { .. }));
+    assert!(matches!(
+        status.bimi.selectors.as_slice(),
+        [
+            BimiSelectorStatus::Published { .. },
+            BimiSelectorStatus::LookupFailed { selector, .. },
+        ] if selector == "brand"
+    ));
+}
+
+#[test]
+fn dmarc_falls_back_to_parent_domains() {
+    use super::{DmarcOrigin, DmarcScope};
+    use crate::dns::StaticZone;
+
+    let zone = StaticZone::from_zone_str(
+        "mail.example.co.uk. A 192.0.2.1
+_dmarc.example.co.uk. TXT \"v=DMARC1; p=reject; sp=quarantine; np=reject; rua=mailto:d@example.co.uk\"",
+    )
+    .expect("valid zone");
+    let options = AuthLookupOptions::new();
+
+    let status = check_with_backend(&zone, "mail.example.co.uk", &options).expect("resolves");
+    let DmarcStatus::Weak {
+        policy,
+        origin,
+        weaknesses,
+        ..
+    } = status.dmarc
+    else {
+        panic!("expected inherited policy, got {:?}", status.dmarc);
+    };
+    assert_eq!(policy, DmarcPolicy::Quarantine);
+    assert_eq!(
+        origin,
+        DmarcOrigin {
+            domain: "example.co.uk".to_string(),
+            scope: DmarcScope::Subdomain,
+        }
+    );
+    assert_eq!(
+        weaknesses,
+        vec![
+            DmarcWeakness::QuarantinePolicy,
+            DmarcWeakness::WeakerSubdomainPolicy {
+                policy: DmarcPolicy::Quarantine
+            },
+        ]
+    );
+
+    let status = check_with_backend(&zone, "ghost.example.co.uk", &options).expect("resolves");
+    let (DmarcStatus::Weak { policy, origin, .. } | DmarcStatus::Compliant { policy, origin, .. }) =
+        status.dmarc
+    else {
+        panic!("expected np policy, got {:?}", status.dmarc);
+    };
+    assert_eq!(policy, DmarcPolicy::Reject);
+    assert_eq!(origin.scope, DmarcScope::NonexistentSubdomain);
+
+    let status = check_with_backend(&zone, "example.co.uk", &options).expect("resolves");
+    let (DmarcStatus::Weak { origin, .. } | DmarcStatus::Compliant { origin, .. }) = status.dmarc
+    else {
+        panic!("expected own policy, got {:?}", status.dmarc);
+    };
+    assert_eq!(origin.scope, DmarcScope::Domain);
+
+    let psl = PublicSuffixList::from_psl_str("uk\nco.uk\n");
+    let with_psl = AuthLookupOptions::new().with_public_suffix_list(psl);
+    let status = check_with_backend(&zone, "a.b.mail.example.co.uk", &with_psl).expect("resolves");
+    assert!(matches!(
+        status.dmarc,
+        DmarcStatus::Weak { ref origin, .. } if origin.domain == "example.co.uk"
+    ));
+
+    let status = check_with_backend(&zone, "mail.example.org", &options).expect("resolves");
+    assert_eq!(status.dmarc, DmarcStatus::Missing);
+
+    let broken = zone.with_record(
+        "_dmarc.mail.example.co.uk",
+        DnsRecord::Txt("v=DMARC1; rua=mailto:d@example.co.uk".to_string()),
+    );
+    let status = check_with_backend(&broken, "mail.example.co.uk", &options).expect("resolves");
+    assert!(matches!(status.dmarc, DmarcStatus::Invalid { .. }));
+}
+
+#[test]
+fn dmarc_external_report_destinations_need_authorization() {
+    use crate::dns::{DnssecStatus, StaticZone};
+
+    let zone = StaticZone::from_zone_str(
+        "_dmarc.example.com. TXT \"v=DMARC1; p=reject; rua=mailto:agg@reports.example.com,mailto:agg@vendor.example.net; ruf=mailto:f@vendor.example.net,mailto:f@other.example.org\"
+example.com._report._dmarc.vendor.example.net. TXT \"v=DMARC1\"",
+    )
+    .expect("valid zone");
+
+    let status =
+        check_with_backend(&zone, "example.com", &AuthLookupOptions::new()).expect("resolves");
+    let DmarcStatus::Weak {
+        parsed, weaknesses, ..
+    } = status.dmarc
+    else {
+        panic!("expected unauthorized destination, got {:?}", status.dmarc);
+    };
+    let authorizations = |uris: &[DmarcUri]| -> Vec<DmarcReportAuthorization> {
+        uris.iter().map(|uri| uri.authorization.clone()).collect()
+    };
+    assert_eq!(
+        authorizations(&parsed.aggregate_reports),
+        vec![
+            DmarcReportAuthorization::NotRequired,
+            DmarcReportAuthorization::Authorized,
+        ]
+    );
+    assert_eq!(
+        authorizations(&parsed.failure_reports),
+        vec![
+            DmarcReportAuthorization::Authorized,
+            DmarcReportAuthorization::Unauthorized,
+        ]
+    );
+    assert_eq!(
+        weaknesses,
+        vec![DmarcWeakness::UnauthorizedReportDestination {
+            tag: "ruf".to_string(),
+            address: "f@other.example.org".to_string(),
+        }]
+    );
+    let report_lookups = status
+        .dnssec
+        .iter()
+        .filter(|lookup| lookup.name.contains("._report._dmarc."))
+        .count();
+    assert_eq!(report_lookups, 2);
+
+    let failing = zone.with_dnssec("_report._dmarc.other.example.org", DnssecStatus::Bogus);
+    let status =
+        check_with_backend(&failing, "example.com", &AuthLookupOptions::new()).expect("resolves");
+    let DmarcStatus::Compliant { policy, parsed, .. } = status.dmarc else {
+        panic!(
+            "expected policy despite failed lookup, got {:?}",
+            status.dmarc
+        );
+    };
+    assert_eq!(policy, DmarcPolicy::Reject);
+    assert!(matches!(
+        authorizations(&parsed.failure_reports).as_slice(),
+        [
+            DmarcReportAuthorization::Authorized,
+            DmarcReportAuthorization::LookupFailed { reason },
+        ] if reason.contains("bogus")
+    ));
+}
+
+#[test]
+fn public_suffix_rules_with_wildcards_and_exceptions() {
+    let psl = PublicSuffixList::from_psl_str(
+        "// commentaire
+com
+co.uk
+*.ck
+!www.ck
+",
+    );
+    assert_eq!(psl.public_suffix("mail.example.com"), "com");
+    assert_eq!(psl.public_suffix("a.example.co.uk"), "co.uk");
+    assert_eq!(psl.public_suffix("shop.example.ck"), "example.ck");
+    assert_eq!(psl.public_suffix("www.ck"), "ck");
+    assert_eq!(psl.public_suffix("example.test"), "test");
+    assert_eq!(
+        psl.organizational_domain("a.b.Example.CO.UK").as_deref(),
+        Some("example.co.uk")
+    );
+    assert_eq!(
+        psl.organizational_domain("shop.example.ck").as_deref(),
+        Some("shop.example.ck")
+    );
+    assert_eq!(
+        psl.organizational_domain("www.ck").as_deref(),
+        Some("www.ck")
+    );
+    assert_eq!(psl.organizational_domain("co.uk"), None);
+}
+
+/// Serveur HTTP local : renvoie `response` à la première requête, qu'il
+/// restitue à la fin du fil.
+#[cfg(feature = "with-mta-sts")]
+fn policy_server(response: &'static str) -> (u16, std::thread::JoinHandle<String>) {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind listener");
+    let port = listener.local_addr().expect("local addr").port();
+    let handle = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("accept");
+        let mut request = Vec::new();
+        let mut chunk = [0u8; 512];
+        while !request.ends_with(b"\r\n\r\n") {
+            let read = stream.read(&mut chunk).expect("read request");
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&chunk[..read]);
+        }
+        stream
+            .write_all(response.as_bytes())
+            .expect("write response");
+        String::from_utf8(request).expect("ASCII request")
+    });
+    (port, handle)
+}
+
+/// Fetcher des tests : HTTP en clair vers [`policy_server`].
+#[cfg(feature = "with-mta-sts")]
+struct StandInFetcher {
+    port: u16,
+}
+
+#[cfg(feature = "with-mta-sts")]
+impl super::PolicyFetcher for StandInFetcher {
+    fn fetch(&self, host: &str) -> Result<String, super::PolicyFetchError> {
+        let stream = std::net::TcpStream::connect(("127.0.0.1", self.port))
+            .map_err(|source| super::PolicyFetchError::Io { source })?;
+        super::fetch::get(stream, host)
+    }
+}
+
+#[cfg(feature = "with-mta-sts")]
+#[test]
+fn mta_sts_record_requires_single_valid_id() {
+    use super::mta_sts::parse_record;
+    use super::{MtaStsIssue, MtaStsStatus};
+
+    let records = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+        parse_record(&records(&["v=spf1 -all"])),
+        Err(MtaStsStatus::Missing)
+    );
+    assert_eq!(
+        parse_record(&records(&["v=STSv1; id=20260101T000000;", "other"])),
+        Ok("20260101T000000".to_string())
+    );
+    assert!(matches!(
+        parse_record(&records(&["v=STSv1; id=1", "v=STSv1; id=2"])),
+        Err(MtaStsStatus::MultipleRecords { .. })
+    ));
+    assert!(matches!(
+        parse_record(&records(&["v=STSv1; id=not-alnum"])),
+        Err(MtaStsStatus::InvalidRecord {
+            issue: MtaStsIssue::InvalidId { .. },
+            ..
+        })
+    ));
+    assert!(matches!(
+        parse_record(&records(&["v=STSv1;"])),
+        Err(MtaStsStatus::InvalidRecord {
+            issue: MtaStsIssue::MissingId,
+            ..
+        })
+    ));
+}
+
+#[cfg(feature = "with-mta-sts")]
+#[test]
+fn mta_sts_policy_parsing_and_mx_patterns() {
+    use super::mta_sts::parse_policy;
+    use super::{MtaStsIssue, MtaStsMode};
+
+    let policy = parse_policy(
+        "version: STSv1\r\nmode: enforce\r\nmx: mail.example.com\r\nmx: *.Example.NET.\r\nmax_age: 604800\r\n",
+    )
+    .expect("valid policy");
+    assert_eq!(policy.mode, MtaStsMode::Enforce);
+    assert_eq!(policy.max_age, 604_800);
+    assert!(policy.covers("MAIL.example.com."));
+    assert!(policy.covers("mx1.example.net"));
+    assert!(!policy.covers("example.net"));
+    assert!(!policy.covers("a.mx1.example.net"));
+    assert!(!policy.covers("backup.example.com"));
+
+    let cases = [
+        (
+            "mode: enforce\nmx: a\nmax_age: 1",
+            MtaStsIssue::InvalidVersion,
+        ),
+        (
+            "version: STSv1\nmx: a\nmax_age: 1",
+            MtaStsIssue::MissingMode,
+        ),
+        (
+            "version: STSv1\nmode: strict\nmax_age: 1",
+            MtaStsIssue::UnknownMode {
+                mode: "strict".to_string(),
+            },
+        ),
+        (
+            "version: STSv1\nmode: testing\nmx: a",
+            MtaStsIssue::MissingMaxAge,
+        ),
+        (
+            "version: STSv1\nmode: testing\nmx: a\nmax_age: 99999999999",
+            MtaStsIssue::InvalidMaxAge {
+                value: "99999999999".to_string(),
+            },
+        ),
+        (
+            "version: STSv1\nmode: enforce\nmax_age: 1",
+            MtaStsIssue::MissingMx,
+        ),
+    ];
+    for (body, issue) in cases {
+        assert_eq!(parse_policy(body), Err(issue), "{body}");
+    }
+    assert!(parse_policy("version: STSv1\nmode: none\nmax_age: 86400").is_ok());
+}
+
+#[cfg(feature = "with-mta-sts")]
+#[test]
+fn mta_sts_compares_policy_with_mx_hosts() {
+    use super::{MtaStsMode, MtaStsStatus, check_auth_records_with_fetcher};
+    use crate::dns::StaticZone;
+
+    let zone = StaticZone::from_zone_str(
+        "example.com. MX 10 mx1.example.com.
+example.com. MX 20 backup.example.org.
+_mta-sts.example.com. TXT \"v=STSv1; id=2026a\"",
+    )
+    .expect("valid zone");
+    let (port, server) = policy_server(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: 62\r\n\r\n\
+         version: STSv1\nmode: enforce\nmx: *.example.com\nmax_age: 86400\n",
+    );
+
+    let options = AuthLookupOptions::new().check_mta_sts(true);
+    let status =
+        check_auth_records_with_fetcher(&zone, &StandInFetcher { port }, "example.com", &options)
+            .expect("resolution succeeds");
+    let request = server.join().expect("server thread");
+    assert!(request.starts_with("GET /.well-known/mta-sts.txt HTTP/1.0\r\n"));
+    assert!(request.contains("Host: mta-sts.example.com\r\n"));
+
+    match &status.mta_sts {
+        MtaStsStatus::Published {
+            id,
+            policy,
+            uncovered_mx,
+        } => {
+            assert_eq!(id, "2026a");
+            assert_eq!(policy.mode, MtaStsMode::Enforce);
+            assert_eq!(uncovered_mx, &vec!["backup.example.org".to_string()]);
+        }
+        other => panic!("expected a published policy, got {other:?}"),
+    }
+    assert!(!status.mta_sts.is_enforced());
+    assert!(
+        status
+            .dnssec
+            .iter()
+            .any(|lookup| lookup.name == "_mta-sts.example.com")
+    );
+
+    let unchecked = check_auth_records_with_fetcher(
+        &zone,
+        &StandInFetcher { port },
+        "example.com",
+        &AuthLookupOptions::new(),
+    )
+    .expect("resolution succeeds");
+    assert_eq!(unchecked.mta_sts, MtaStsStatus::NotRequested);
+}
+
+#[cfg(feature = "with-mta-sts")]
+#[test]
+fn mta_sts_rejects_redirects_and_wrong_media_type() {
+    use super::{MtaStsStatus, check_auth_records_with_fetcher};
+    use crate::dns::StaticZone;
+
+    let zone = StaticZone::from_zone_str(
+        "example.com. MX 10 mx1.example.com.
+_mta-sts.example.com. TXT \"v=STSv1; id=1\"",
+    )
+    .expect("valid zone");
+    let options = AuthLookupOptions::new().check_mta_sts(true);
+    let responses = [
+        (
+            "HTTP/1.1 301 Moved Permanently\r\nLocation: https://example.com/\r\n\r\n",
+            "unexpected HTTP status 301",
+        ),
+        (
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\n\r\n<html></html>",
+            "unexpected content type 'text/html'",
+        ),
+    ];
+    for (response, expected) in responses {
+        let (port, server) = policy_server(response);
+        let status = check_auth_records_with_fetcher(
+            &zone,
+            &StandInFetcher { port },
+            "example.com",
+            &options,
+        )
+        .expect("resolution succeeds");
+        server.join().expect("server thread");
+        assert_eq!(
+            status.mta_sts,
+            MtaStsStatus::PolicyUnavailable {
+                id: "1".to_string(),
+                reason: expected.to_string(),
+            }
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! SMTP TLS Reporting (RFC 8460) : enregistrement `_smtp._tls`.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TlsRptStatus {
+    Missing,
+    MultipleRecords {
+        records: Vec<String>,
+    },
+    Invalid {
+        record: String,
+        issue: TlsRptIssue,
+    },
+    Valid {
+        record: String,
+        rua: Vec<TlsRptUri>,
+    },
+    /// La requête `_smtp._tls` a échoué (SERVFAIL, délai, réponse bogus…) ;
+    /// les autres vérifications ne sont pas interrompues.
+    LookupFailed {
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TlsRptIssue {
+    InvalidVersion,
+    MissingRua,
+    /// Champ présent plusieurs fois dans l'enregistrement.
+    DuplicateField {
+        field: String,
+    },
+    /// URI `rua` dont le schéma n'est ni `mailto` ni `https`.
+    UnsupportedUri {
+        uri: String,
+    },
+    /// URI `mailto`/`https` mal formée.
+    MalformedUri {
+        uri: String,
+    },
+}
+
+/// Destination des rapports agrégés.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TlsRptUri {
+    /// Adresse e-mail, sans le préfixe `mailto:`.
+    Mailto(String),
+    /// URL complète.
+    Https(String),
+}
+
+pub(crate) fn evaluate(records: &[String]) -> TlsRptStatus {
+    let mut tlsrpt_records: Vec<String> = records
+        .iter()
+        .map(|record| record.trim())
+        .filter(|record| record.starts_with("v=TLSRPTv1"))
+        .map(str::to_string)
+        .collect();
+
+    if tlsrpt_records.is_empty() {
+        return TlsRptStatus::Missing;
+    }
+
+    if tlsrpt_records.len() > 1 {
+        tlsrpt_records.sort();
+        tlsrpt_records.dedup();
+        return TlsRptStatus::MultipleRecords {
+            records: tlsrpt_records,
+        };
+    }
+
+    let record = tlsrpt_records.remove(0);
+    match parse_record(&record) {
+        Ok(rua) => TlsRptStatus::Valid { record, rua },
+        Err(issue) => TlsRptStatus::Invalid { record, issue },
+    }
+}
+
+fn parse_record(record: &str) -> Result<Vec<TlsRptUri>, TlsRptIssue> {
+    let mut fields = record
+        .split(';')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .map(|field| field.split_once('=').unwrap_or((field, "")));
+
+    if fields.next() != Some(("v", "TLSRPTv1")) {
+        return Err(TlsRptIssue::InvalidVersion);
+    }
+
+    let mut seen = Vec::new();
+    let mut rua = None;
+    for (name, value) in fields {
+        let name = name.trim();
+        if seen.contains(&name) {
+            return Err(TlsRptIssue::DuplicateField {
+                field: name.to_string(),
+            });
+        }
+        seen.push(name);
+        // Les extensions (`ext=…`) sont ignorées (RFC 8460 §3).
+        if name == "rua" {
+            rua = Some(value.trim());
+        }
+    }
+
+    let Some(rua) = rua.filter(|value| !value.is_empty()) else {
+        return Err(TlsRptIssue::MissingRua);
+    };
+    rua.split(',').map(|uri| parse_uri(uri.trim())).collect()
+}
+
+fn parse_uri(uri: &str) -> Result<TlsRptUri, TlsRptIssue> {
+    let malformed = || TlsRptIssue::MalformedUri {
+        uri: uri.to_string(),
+    };
+    let Some((scheme, rest)) = uri.split_once(':') else {
+        return Err(malformed());
+    };
+    if scheme.eq_ignore_ascii_case("mailto") {
+        let address = rest.split('?').next().unwrap_or_default();
+        match address.rsplit_once('@') {
+            Some((local, domain))
+                if !local.is_empty() && domain.contains('.') && !domain.ends_with('.') =>
+            {
+                Ok(TlsRptUri::Mailto(address.to_string()))
+            }
+            _ => Err(malformed()),
+        }
+    } else if scheme.eq_ignore_ascii_case("https") {
+        if is_https_url(uri) {
+            Ok(TlsRptUri::Https(uri.to_string()))
+        } else {
+            Err(malformed())
+        }
+    } else {
+        Err(TlsRptIssue::UnsupportedUri {
+            uri: uri.to_string(),
+        })
+    }
+}
+
+/// `https://hôte[/…]`, avec un hôte non vide et sans espace.
+pub(crate) fn is_https_url(uri: &str) -> bool {
+    let Some((scheme, rest)) = uri.split_once(':') else {
+        return false;
+    };
+    let host = rest
+        .strip_prefix("//")
+        .map(|rest| rest.split(['/', '?', '#']).next().unwrap_or_default());
+    scheme.eq_ignore_ascii_case("https")
+        && host.is_some_and(|host| !host.is_empty())
+        && !uri.contains(char::is_whitespace)
+}
@@ -1,4 +1,17 @@
-use super::{dkim::DkimStatus, dmarc::DmarcStatus, spf::SpfStatus};
+use std::sync::Arc;
+
+use crate::dns::{DnsConfig, DnssecStatus};
+
+#[cfg(feature = "with-mta-sts")]
+use super::mta_sts::MtaStsStatus;
+use super::{
+    bimi::{self, BimiStatus},
+    dkim::DkimStatus,
+    dmarc::DmarcStatus,
+    psl::PublicSuffixList,
+    spf::SpfStatus,
+    tls_rpt::TlsRptStatus,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AuthStatus {
@@ -6,12 +19,30 @@ pub struct AuthStatus {
     pub spf: SpfStatus,
     pub dmarc: DmarcStatus,
     pub dkim: DkimStatus,
+    pub tls_rpt: TlsRptStatus,
+    pub bimi: BimiStatus,
+    #[cfg(feature = "with-mta-sts")]
+    pub mta_sts: MtaStsStatus,
+    /// Statut DNSSEC de chaque requête TXT, dans l'ordre où elles ont été faites.
+    pub dnssec: Vec<DnssecLookup>,
+}
+
+/// Statut DNSSEC de la réponse TXT pour `name`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnssecLookup {
+    pub name: String,
+    pub status: DnssecStatus,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AuthLookupOptions {
     dkim_selectors: Vec<String>,
     check_dkim_policy: bool,
+    bimi_selectors: Vec<String>,
+    public_suffix_list: Option<Arc<PublicSuffixList>>,
+    #[cfg(feature = "with-mta-sts")]
+    check_mta_sts: bool,
+    dns: DnsConfig,
 }
 
 impl AuthLookupOptions {
@@ -39,11 +70,51 @@ impl AuthLookupOptions {
         self
     }
 
+    /// Ajoute un sélecteur BIMI ; `default` est toujours interrogé.
+    pub fn with_bimi_selector(mut self, selector: impl Into<String>) -> Self {
+        if let Some(normalized) = normalize_selector(selector.into()) {
+            if normalized != bimi::DEFAULT_SELECTOR && !self.bimi_selectors.contains(&normalized) {
+                self.bimi_selectors.push(normalized);
+            }
+        }
+        self
+    }
+
+    pub fn with_bimi_selectors<I, S>(mut self, selectors: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for selector in selectors {
+            self = self.with_bimi_selector(selector);
+        }
+        self
+    }
+
+    /// Cherche DMARC au domaine organisationnel de la liste (RFC 7489) au
+    /// lieu de remonter l'arbre DNS (DMARCbis).
+    pub fn with_public_suffix_list(mut self, list: impl Into<Arc<PublicSuffixList>>) -> Self {
+        self.public_suffix_list = Some(list.into());
+        self
+    }
+
     pub fn check_policy_record(mut self, value: bool) -> Self {
         self.check_dkim_policy = value;
         self
     }
 
+    /// Vérifie la politique MTA-STS (requête HTTPS vers `mta-sts.<domaine>`).
+    #[cfg(feature = "with-mta-sts")]
+    pub fn check_mta_sts(mut self, value: bool) -> Self {
+        self.check_mta_sts = value;
+        self
+    }
+
+    #[cfg(feature = "with-mta-sts")]
+    pub fn checks_mta_sts(&self) -> bool {
+        self.check_mta_sts
+    }
+
     pub fn dkim_selectors(&self) -> &[String] {
         &self.dkim_selectors
     }
@@ -51,6 +122,24 @@ impl AuthLookupOptions {
     pub fn check_dkim_policy(&self) -> bool {
         self.check_dkim_policy
     }
+
+    /// Sélecteurs BIMI ajoutés à `default`.
+    pub fn bimi_selectors(&self) -> &[String] {
+        &self.bimi_selectors
+    }
+
+    pub fn public_suffix_list(&self) -> Option<&PublicSuffixList> {
+        self.public_suffix_list.as_deref()
+    }
+
+    pub fn with_dns_config(mut self, dns: DnsConfig) -> Self {
+        self.dns = dns;
+        self
+    }
+
+    pub fn dns_config(&self) -> &DnsConfig {
+        &self.dns
+    }
 }
 
 impl Default for AuthLookupOptions {
@@ -58,6 +147,11 @@ impl Default for AuthLookupOptions {
         Self {
             dkim_selectors: Vec::new(),
             check_dkim_policy: true,
+            bimi_selectors: Vec::new(),
+            public_suffix_list: None,
+            #[cfg(feature = "with-mta-sts")]
+            check_mta_sts: false,
+            dns: DnsConfig::default(),
         }
     }
 }
@@ -76,12 +170,28 @@ impl AuthStatus {
         spf: SpfStatus,
         dmarc: DmarcStatus,
         dkim: DkimStatus,
+        tls_rpt: TlsRptStatus,
+        bimi: BimiStatus,
+        dnssec: Vec<DnssecLookup>,
     ) -> Self {
         Self {
             domain,
             spf,
             dmarc,
             dkim,
+            tls_rpt,
+            bimi,
+            #[cfg(feature = "with-mta-sts")]
+            mta_sts: MtaStsStatus::NotRequested,
+            dnssec,
         }
     }
+
+    /// Statut le plus faible des requêtes effectuées (`Indeterminate` si
+    /// aucune).
+    pub fn dnssec_status(&self) -> DnssecStatus {
+        let mut statuses = self.dnssec.iter().map(|lookup| lookup.status);
+        let first = statuses.next().unwrap_or_default();
+        statuses.fold(first, DnssecStatus::weakest)
+    }
 }
@@ -6,30 +6,41 @@ mod auth;
 #[cfg(feature = "with-smtp-verify")]
 #[path = "mailcheck-cli/deliverability.rs"]
 mod deliverability;
+#[cfg(feature = "with-dmarc-report")]
+#[path = "mailcheck-cli/dmarc_report.rs"]
+mod dmarc_report;
 #[cfg(feature = "with-mx")]
 #[path = "mailcheck-cli/mx.rs"]
 mod mx;
 #[path = "mailcheck-cli/output.rs"]
 mod output;
+#[path = "mailcheck-cli/profile.rs"]
+mod profile;
+#[cfg(feature = "with-auth-records")]
+#[path = "mailcheck-cli/spf_tree.rs"]
+mod spf_tree;
 #[cfg(feature = "with-smtp-verify")]
 #[path = "mailcheck-cli/verify.rs"]
 mod verify;
 
 use anyhow::{Context, Result};
-use args::{Cli, Commands, mode_from_str, spec_options_from_profile};
+use args::{Cli, Commands, mode_from_str};
 use mailcheck_lib::{SpecOptions, ValidationMode, normalize_email, normalize_email_with_spec};
 use output::{OutputRow, any_invalid, make_row, write_reports, write_spec_json};
 
 use std::io::{self, BufRead};
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+    cli.load_dns_cache()?;
+    #[cfg(feature = "with-auth-records")]
+    cli.load_public_suffix_list()?;
     let mut mode = cli.parsed_mode();
     let mut rows = Vec::new();
 
     let spec_requested = cli.spec_requested();
     let mut spec_options = if spec_requested {
-        Some(spec_options_from_profile(&cli.spec_profile)?)
+        Some(cli.spec_options()?)
     } else {
         None
     };
@@ -65,6 +76,8 @@ fn main() -> Result<()> {
                 max_mx,
                 timeout_ms,
                 ipv6,
+                #[cfg(feature = "with-dane")]
+                dane,
             } => {
                 let config = verify::VerifyConfig {
                     email,
@@ -76,8 +89,30 @@ fn main() -> Result<()> {
                     max_mx: *max_mx,
                     timeout_ms: *timeout_ms,
                     ipv6: *ipv6,
+                    dns: cli.dns_config(),
+                    #[cfg(feature = "with-dane")]
+                    dane: *dane,
                 };
                 verify::run_verify_exists(config)?;
+                cli.save_dns_cache()?;
+                return Ok(());
+            }
+            #[cfg(feature = "with-auth-records")]
+            Commands::SpfTree { domain, format } => {
+                let has_errors = spf_tree::run_spf_tree(domain, format, &cli.auth_options())?;
+                cli.save_dns_cache()?;
+                if has_errors {
+                    std::process::exit(2);
+                }
+                return Ok(());
+            }
+            #[cfg(feature = "with-dmarc-report")]
+            Commands::DmarcReport { files } => {
+                let unauthenticated =
+                    dmarc_report::run_dmarc_report(files, &cli.format, cli.out.as_deref())?;
+                if unauthenticated {
+                    std::process::exit(2);
+                }
                 return Ok(());
             }
         }
@@ -88,6 +123,7 @@ fn main() -> Result<()> {
     }
 
     write_reports(&rows, &cli)?;
+    cli.save_dns_cache()?;
 
     if cli.spec_json {
         write_spec_json(&rows)?;
@@ -1,6 +1,28 @@
+#[cfg(any(
+    feature = "with-auth-records",
+    all(
+        feature = "with-serde",
+        any(feature = "with-mx", feature = "with-smtp-verify")
+    )
+))]
+use anyhow::Context;
 use anyhow::{Result, bail};
 use clap::{Parser, Subcommand};
+#[cfg(feature = "with-mx")]
+use mailcheck_lib::MxLookupOptions;
+#[cfg(feature = "with-auth-records")]
+use mailcheck_lib::{AuthLookupOptions, PublicSuffixList};
+#[cfg(any(
+    feature = "with-mx",
+    feature = "with-smtp-verify",
+    feature = "with-auth-records"
+))]
+use mailcheck_lib::{DnsCache, DnsConfig, DnsServer};
 use mailcheck_lib::{SpecOptions, ValidationMode};
+#[cfg(feature = "with-auth-records")]
+use std::sync::Arc;
+
+use crate::profile::load_profile_file;
 
 #[derive(Parser)]
 #[command(name = "mailcheck-cli")]
@@ -28,10 +50,14 @@ pub struct Cli {
     #[arg(long)]
     pub spec_chars: bool,
 
-    /// profil: standard|strict|fr-fraud
+    /// profil: standard|strict|fr-fraud|de-fraud|es-fraud|it-fraud|pt-br-fraud|generic-bank
     #[arg(long, default_value = "standard")]
     pub spec_profile: String,
 
+    /// profil personnalisé (TOML/JSON) dérivé de --spec-profile ou de sa clé `extends`
+    #[arg(long)]
+    pub spec_profile_file: Option<String>,
+
     /// dump SpecCharacters (JSON par ligne)
     #[arg(long)]
     pub spec_json: bool,
@@ -45,6 +71,16 @@ pub struct Cli {
     #[arg(long)]
     pub mx: bool,
 
+    /// avec --mx : résout chaque exchange (A/AAAA, CNAME) et signale les anomalies
+    #[cfg(feature = "with-mx")]
+    #[arg(long, requires = "mx")]
+    pub mx_resolve: bool,
+
+    /// avec --mx : vérifie les TLSA DANE de chaque exchange (avec --dnssec)
+    #[cfg(all(feature = "with-mx", feature = "with-dane"))]
+    #[arg(long, requires = "mx")]
+    pub dane: bool,
+
     /// teste la délivrabilité SMTP (feature `with-smtp-verify`)
     #[cfg(feature = "with-smtp-verify")]
     #[arg(long)]
@@ -60,10 +96,87 @@ pub struct Cli {
     #[arg(long = "dkim-selector")]
     pub dkim_selectors: Vec<String>,
 
+    /// ajoute un sélecteur BIMI à interroger en plus de `default` (répétable)
+    #[cfg(feature = "with-auth-records")]
+    #[arg(long = "bimi-selector")]
+    pub bimi_selectors: Vec<String>,
+
     /// ignore l'enregistrement de politique DKIM (_domainkey)
     #[cfg(feature = "with-auth-records")]
     #[arg(long)]
     pub skip_dkim_policy: bool,
+
+    /// Public Suffix List : DMARC au domaine organisationnel (RFC 7489) au lieu de la remontée DMARCbis
+    #[cfg(feature = "with-auth-records")]
+    #[arg(long = "psl-file")]
+    pub psl_file: Option<String>,
+
+    #[cfg(feature = "with-auth-records")]
+    #[arg(skip)]
+    pub public_suffix_list: Option<Arc<PublicSuffixList>>,
+
+    /// avec --auth : récupère la politique MTA-STS et la compare aux MX
+    #[cfg(feature = "with-mta-sts")]
+    #[arg(long, requires = "auth")]
+    pub mta_sts: bool,
+
+    /// serveur DNS à interroger (répétable) : ip[:port], tcp://, tls://ip#nom, https://ip#nom
+    #[cfg(any(
+        feature = "with-mx",
+        feature = "with-smtp-verify",
+        feature = "with-auth-records"
+    ))]
+    #[arg(long = "dns-server", global = true)]
+    pub dns_servers: Vec<DnsServer>,
+
+    /// timeout d'une requête DNS (ms)
+    #[cfg(any(
+        feature = "with-mx",
+        feature = "with-smtp-verify",
+        feature = "with-auth-records"
+    ))]
+    #[arg(long = "dns-timeout", global = true)]
+    pub dns_timeout_ms: Option<u64>,
+
+    /// valide DNSSEC (feature with-dnssec) ; une réponse bogus devient une erreur
+    #[cfg(any(
+        feature = "with-mx",
+        feature = "with-smtp-verify",
+        feature = "with-auth-records"
+    ))]
+    #[arg(long, global = true)]
+    pub dnssec: bool,
+
+    /// cache DNS persistant (JSON), relu au démarrage et réécrit en fin d'exécution
+    #[cfg(all(
+        feature = "with-serde",
+        any(
+            feature = "with-mx",
+            feature = "with-smtp-verify",
+            feature = "with-auth-records"
+        )
+    ))]
+    #[arg(long = "dns-cache", global = true)]
+    pub dns_cache_file: Option<String>,
+
+    /// nombre maximal d'entrées du cache DNS (10 000 par défaut) ; au-delà,
+    /// celles qui expirent le plus tôt sont évincées
+    #[cfg(any(
+        feature = "with-mx",
+        feature = "with-smtp-verify",
+        feature = "with-auth-records"
+    ))]
+    #[arg(long = "dns-cache-max-entries", global = true)]
+    pub dns_cache_max_entries: Option<usize>,
+
+    /// cache partagé par toutes les lignes traitées
+    #[cfg(any(
+        feature = "with-mx",
+        feature = "with-smtp-verify",
+        feature = "with-auth-records"
+    ))]
+    #[arg(skip)]
+    pub dns_cache: DnsCache,
 }
 
 #[derive(Subcommand)]
@@ -103,6 +216,27 @@ pub enum Commands {
         /// autorise IPv6
         #[arg(long = "ipv6")]
         ipv6: bool,
+        /// vérifie les TLSA DANE des MX et le certificat après STARTTLS
+        #[cfg(feature = "with-dane")]
+        #[arg(long)]
+        dane: bool,
+    },
+    #[cfg(feature = "with-auth-records")]
+    #[command(name = "spf-tree")]
+    SpfTree {
+        /// domaine dont l'arbre SPF est analysé
+        domain: String,
+        /// format de sortie (human|json)
+        #[arg(long, default_value = "human")]
+        format: String,
+    },
+    /// résume des rapports agrégés DMARC (sortie selon --format et --out)
+    #[cfg(feature = "with-dmarc-report")]
+    #[command(name = "dmarc-report")]
+    DmarcReport {
+        /// rapports agrégés (XML, .gz ou .zip)
+        #[arg(required = true)]
+        files: Vec<String>,
     },
 }
 
@@ -120,7 +254,109 @@ impl Cli {
     }
 
     pub fn spec_requested(&self) -> bool {
-        self.spec_chars || self.spec_json || self.ascii_hint
+        self.spec_chars || self.spec_json || self.ascii_hint || self.spec_profile_file.is_some()
+    }
+
+    #[cfg(any(
+        feature = "with-mx",
+        feature = "with-smtp-verify",
+        feature = "with-auth-records"
+    ))]
+    pub fn dns_config(&self) -> DnsConfig {
+        DnsConfig {
+            nameservers: self.dns_servers.clone(),
+            timeout_ms: self.dns_timeout_ms,
+            dnssec: self.dnssec,
+            cache: Some(self.dns_cache.clone()),
+            ..DnsConfig::default()
+        }
+    }
+
+    #[cfg(feature = "with-mx")]
+    pub fn mx_options(&self) -> MxLookupOptions {
+        let options = MxLookupOptions::new()
+            .resolve_exchanges(self.mx_resolve)
+            .with_dns_config(self.dns_config());
+        #[cfg(feature = "with-dane")]
+        let options = options.check_dane(self.dane);
+        options
+    }
+
+    #[cfg(feature = "with-auth-records")]
+    pub fn auth_options(&self) -> AuthLookupOptions {
+        let options = AuthLookupOptions::new()
+            .check_policy_record(!self.skip_dkim_policy)
+            .with_dkim_selectors(self.dkim_selectors.iter().cloned())
+            .with_bimi_selectors(self.bimi_selectors.iter().cloned())
+            .with_dns_config(self.dns_config());
+        let options = match &self.public_suffix_list {
+            Some(list) => options.with_public_suffix_list(Arc::clone(list)),
+            None => options,
+        };
+        #[cfg(feature = "with-mta-sts")]
+        let options = options.check_mta_sts(self.mta_sts);
+        options
+    }
+
+    /// Charge la liste de `--psl-file`, si elle est fournie.
+    #[cfg(feature = "with-auth-records")]
+    pub fn load_public_suffix_list(&mut self) -> Result<()> {
+        if let Some(path) = &self.psl_file {
+            let list = PublicSuffixList::load(path).with_context(|| format!("read {path}"))?;
+            self.public_suffix_list = Some(Arc::new(list));
+        }
+        Ok(())
+    }
+
+    /// Recharge le cache DNS depuis `--dns-cache`, s'il est fourni, et lui
+    /// applique `--dns-cache-max-entries`.
+    pub fn load_dns_cache(&mut self) -> Result<()> {
+        #[cfg(any(
+            feature = "with-mx",
+            feature = "with-smtp-verify",
+            feature = "with-auth-records"
+        ))]
+        if let Some(max_entries) = self.dns_cache_max_entries {
+            self.dns_cache = self.dns_cache.clone().with_max_entries(max_entries);
+        }
+        #[cfg(all(
+            feature = "with-serde",
+            any(
+                feature = "with-mx",
+                feature = "with-smtp-verify",
+                feature = "with-auth-records"
+            )
+        ))]
+        if let Some(path) = &self.dns_cache_file {
+            self.dns_cache = DnsCache::load_with_max_entries(path, self.dns_cache.max_entries())
+                .with_context(|| format!("read {path}"))?;
+        }
+        Ok(())
+    }
+
+    /// Enregistre le cache DNS dans `--dns-cache`, s'il est fourni.
+    pub fn save_dns_cache(&self) -> Result<()> {
+        #[cfg(all(
+            feature = "with-serde",
+            any(
+                feature = "with-mx",
+                feature = "with-smtp-verify",
+                feature = "with-auth-records"
+            )
+        ))]
+        if let Some(path) = &self.dns_cache_file {
+            self.dns_cache
+                .save(path)
+                .with_context(|| format!("write {path}"))?;
+        }
+        Ok(())
+    }
+
+    pub fn spec_options(&self) -> Result<SpecOptions> {
+        match &self.spec_profile_file {
+            Some(path) => load_profile_file(path, &self.spec_profile),
+            None => spec_options_from_profile(&self.spec_profile),
+        }
     }
 }
 
@@ -132,10 +368,11 @@ pub fn mode_from_str(s: &str) -> ValidationMode {
 }
 
 pub fn spec_options_from_profile(profile: &str) -> Result<SpecOptions> {
-    match profile {
-        "standard" => Ok(SpecOptions::standard()),
-        "strict" => Ok(SpecOptions::strict()),
-        "fr-fraud" => Ok(SpecOptions::fr_fraud()),
-        other => bail!("unknown --spec-profile '{other}'"),
+    match SpecOptions::profile(profile) {
+        Some(options) => Ok(options),
+        None => bail!(
+            "unknown --spec-profile '{profile}', use: {}",
+            SpecOptions::PROFILE_NAMES.join("|")
+        ),
     }
 }
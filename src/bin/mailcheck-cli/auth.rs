@@ -1,8 +1,12 @@
 use mailcheck_lib::{
-    AuthError, AuthLookupOptions, AuthStatus, DkimIssue, DkimPolicyStatus, DkimSelectorStatus,
-    DkimWeakness, DmarcIssue, DmarcPolicy, DmarcStatus, DmarcWeakness, NormalizedEmail, SpfIssue,
-    SpfQualifier, SpfStatus, check_auth_records_with_options,
+    AuthError, AuthLookupOptions, AuthStatus, BimiDmarcIssue, BimiIssue, BimiSelectorStatus,
+    BimiStatus, DkimIssue, DkimKey, DkimKeyType, DkimPolicyStatus, DkimSelectorStatus,
+    DkimWeakness, DmarcIssue, DmarcOrigin, DmarcPolicy, DmarcScope, DmarcStatus, DmarcWeakness,
+    DnssecStatus, NormalizedEmail, SpfIssue, SpfQualifier, SpfStatus, TlsRptIssue, TlsRptStatus,
+    TlsRptUri, check_auth_records_with_options,
 };
+#[cfg(feature = "with-mta-sts")]
+use mailcheck_lib::{MtaStsIssue, MtaStsMode, MtaStsStatus};
 
 #[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
 #[derive(Debug, Clone)]
@@ -55,7 +59,18 @@ impl AuthSummary {
         lines.push(format!("domain={}", status.domain));
         lines.push(format!("spf={}", status.spf.summary()));
         lines.push(format!("dmarc={}", status.dmarc.summary()));
+        lines.push(format!("tls_rpt={}", status.tls_rpt.summary()));
         lines.push(format!("dkim_policy={}", status.dkim_policy.summary()));
+        for selector in &status.bimi {
+            lines.push(format!("bimi {} {}", selector.selector, selector.summary()));
+        }
+        #[cfg(feature = "with-mta-sts")]
+        if let Some(mta_sts) = &status.mta_sts {
+            lines.push(format!("mta_sts={}", mta_sts.summary()));
+        }
+        if let Some(dnssec) = status.dnssec {
+            lines.push(format!("dnssec={}", dnssec.as_str()));
+        }
 
         if status.selectors.is_empty() {
             lines.push("dkim_selectors=none".to_string());
@@ -89,9 +104,17 @@ impl AuthSummary {
                     .collect::<Vec<_>>()
                     .join(" | ")
             };
+            let bimi = status
+                .bimi
+                .iter()
+                .map(|selector| format!("{} {}", selector.selector, selector.summary()))
+                .collect::<Vec<_>>()
+                .join(" | ");
             AuthCsvFields {
                 spf: status.spf.summary(),
                 dmarc: status.dmarc.summary(),
+                tls_rpt: status.tls_rpt.summary(),
+                bimi,
                 dkim_policy: status.dkim_policy.summary(),
                 selectors,
                 error: String::new(),
@@ -101,6 +124,8 @@ impl AuthSummary {
             AuthCsvFields {
                 spf: String::new(),
                 dmarc: String::new(),
+                tls_rpt: String::new(),
+                bimi: String::new(),
                 dkim_policy: String::new(),
                 selectors: String::new(),
                 error: self.error.clone().unwrap_or_default(),
@@ -115,6 +140,8 @@ impl AuthSummary {
 pub struct AuthCsvFields {
     pub spf: String,
     pub dmarc: String,
+    pub tls_rpt: String,
+    pub bimi: String,
     pub dkim_policy: String,
     pub selectors: String,
     pub error: String,
@@ -127,6 +154,8 @@ impl AuthCsvFields {
         Self {
             spf: String::new(),
             dmarc: String::new(),
+            tls_rpt: String::new(),
+            bimi: String::new(),
             dkim_policy: String::new(),
             selectors: String::new(),
             error: String::new(),
@@ -141,17 +170,33 @@ pub struct AuthStatusSnapshot {
     pub domain: String,
     pub spf: AuthSectionSnapshot,
     pub dmarc: AuthSectionSnapshot,
+    pub tls_rpt: AuthSectionSnapshot,
+    /// Un élément par sélecteur BIMI, `default` en premier.
+    pub bimi: Vec<AuthSelectorSnapshot>,
     pub dkim_policy: AuthSectionSnapshot,
     #[cfg_attr(feature = "with-serde", serde(skip_serializing_if = "Vec::is_empty"))]
     pub selectors: Vec<AuthSelectorSnapshot>,
+    /// Statut DNSSEC le plus faible des requêtes TXT (absent sans `--dnssec`).
+    #[cfg_attr(feature = "with-serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub dnssec: Option<DnssecStatus>,
+    /// Politique MTA-STS (absente sans `--mta-sts`).
+    #[cfg(feature = "with-mta-sts")]
+    #[cfg_attr(feature = "with-serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub mta_sts: Option<AuthSectionSnapshot>,
 }
 
 impl AuthStatusSnapshot {
     fn from_status(status: AuthStatus) -> Self {
+        let dnssec = Some(status.dnssec_status()).filter(|s| *s != DnssecStatus::Indeterminate);
         Self {
+            dnssec,
+            #[cfg(feature = "with-mta-sts")]
+            mta_sts: summarize_mta_sts(&status.mta_sts),
             domain: status.domain,
             spf: summarize_spf(&status.spf),
             dmarc: summarize_dmarc(&status.dmarc),
+            tls_rpt: summarize_tls_rpt(&status.tls_rpt),
+            bimi: summarize_bimi(status.bimi),
             dkim_policy: summarize_dkim_policy(&status.dkim.policy),
             selectors: status
                 .dkim
@@ -223,7 +268,7 @@ impl AuthSelectorSnapshot {
     }
 }
 
-pub fn resolve(row: &NormalizedEmail, skip_dkim_policy: bool, selectors: &[String]) -> AuthSummary {
+pub fn resolve(row: &NormalizedEmail, options: &AuthLookupOptions) -> AuthSummary {
     let target = if !row.ascii_domain.is_empty() {
         row.ascii_domain.as_str()
     } else {
@@ -233,15 +278,7 @@ pub fn resolve(row: &NormalizedEmail, skip_dkim_policy: bool, selectors: &[Strin
         return AuthSummary::skipped("domain missing");
     }
 
-    let mut options = AuthLookupOptions::new();
-    if skip_dkim_policy {
-        options = options.check_policy_record(false);
-    }
-    if !selectors.is_empty() {
-        options = options.with_dkim_selectors(selectors.iter().cloned());
-    }
-
-    match check_auth_records_with_options(target, &options) {
+    match check_auth_records_with_options(target, options) {
         Ok(status) => AuthSummary::from_status(status),
         Err(AuthError::EmptyDomain) => AuthSummary::skipped("domain missing"),
         Err(err) => AuthSummary::from_error(&err),
@@ -302,22 +339,113 @@ fn summarize_dmarc(status: &DmarcStatus) -> AuthSectionSnapshot {
         DmarcStatus::Weak {
             record,
             policy,
-            weakness,
+            origin,
+            weaknesses,
+            ..
         } => {
+            let weaknesses: Vec<String> = weaknesses.iter().map(describe_dmarc_weakness).collect();
             let detail = format!(
-                "policy={}; weakness={}; record={record}",
+                "policy={}; {}weakness={}; record={record}",
                 describe_dmarc_policy(*policy),
-                describe_dmarc_weakness(*weakness)
+                describe_dmarc_origin(origin),
+                weaknesses.join(",")
             );
             AuthSectionSnapshot::new("weak_policy", Some(detail))
         }
-        DmarcStatus::Compliant { record, policy } => {
-            let detail = format!("policy={}; record={record}", describe_dmarc_policy(*policy));
+        DmarcStatus::Compliant {
+            record,
+            policy,
+            origin,
+            ..
+        } => {
+            let detail = format!(
+                "policy={}; {}record={record}",
+                describe_dmarc_policy(*policy),
+                describe_dmarc_origin(origin)
+            );
             AuthSectionSnapshot::new("compliant", Some(detail))
         }
     }
 }
 
+fn summarize_tls_rpt(status: &TlsRptStatus) -> AuthSectionSnapshot {
+    match status {
+        TlsRptStatus::Missing => AuthSectionSnapshot::new("missing", None),
+        TlsRptStatus::MultipleRecords { records } => {
+            let detail = format!("records={}", records.join(" | "));
+            AuthSectionSnapshot::new("multiple_records", Some(detail))
+        }
+        TlsRptStatus::Invalid { record, issue } => {
+            let detail = format!("issue={}; record={record}", describe_tls_rpt_issue(issue));
+            AuthSectionSnapshot::new("invalid", Some(detail))
+        }
+        TlsRptStatus::Valid { record, rua } => {
+            let rua = rua
+                .iter()
+                .map(|uri| match uri {
+                    TlsRptUri::Mailto(address) => format!("mailto:{address}"),
+                    TlsRptUri::Https(url) => url.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            let detail = format!("rua={rua}; record={record}");
+            AuthSectionSnapshot::new("valid", Some(detail))
+        }
+        TlsRptStatus::LookupFailed { reason } => {
+            AuthSectionSnapshot::new("lookup_failed", Some(reason.clone()))
+        }
+    }
+}
+
+fn summarize_bimi(status: BimiStatus) -> Vec<AuthSelectorSnapshot> {
+    let dmarc_issue = status.dmarc_issue.as_ref().map(describe_bimi_dmarc_issue);
+    status
+        .selectors
+        .into_iter()
+        .map(|selector| match selector {
+            BimiSelectorStatus::Missing { selector } => {
+                AuthSelectorSnapshot::new(selector, "missing", None)
+            }
+            BimiSelectorStatus::MultipleRecords { selector, records } => {
+                let detail = format!("records={}", records.join(" | "));
+                AuthSelectorSnapshot::new(selector, "multiple_records", Some(detail))
+            }
+            BimiSelectorStatus::Invalid {
+                selector,
+                record,
+                issue,
+            } => {
+                let detail = format!("issue={}; record={record}", describe_bimi_issue(&issue));
+                AuthSelectorSnapshot::new(selector, "invalid", Some(detail))
+            }
+            BimiSelectorStatus::Declined { selector, record } => {
+                AuthSelectorSnapshot::new(selector, "declined", Some(format!("record={record}")))
+            }
+            BimiSelectorStatus::Published {
+                selector,
+                logo,
+                authority,
+                ..
+            } => {
+                let mut detail = format!("logo={logo}");
+                if let Some(authority) = authority {
+                    detail.push_str(&format!("; authority={authority}"));
+                }
+                match &dmarc_issue {
+                    Some(issue) => {
+                        let detail = format!("dmarc={issue}; {detail}");
+                        AuthSelectorSnapshot::new(selector, "dmarc_not_ready", Some(detail))
+                    }
+                    None => AuthSelectorSnapshot::new(selector, "ready", Some(detail)),
+                }
+            }
+            BimiSelectorStatus::LookupFailed { selector, reason } => {
+                AuthSelectorSnapshot::new(selector, "lookup_failed", Some(reason))
+            }
+        })
+        .collect()
+}
+
 fn summarize_dkim_policy(status: &DkimPolicyStatus) -> AuthSectionSnapshot {
     match status {
         DkimPolicyStatus::NotRequested => AuthSectionSnapshot::new("not_requested", None),
@@ -333,6 +461,70 @@ fn summarize_dkim_policy(status: &DkimPolicyStatus) -> AuthSectionSnapshot {
     }
 }
 
+#[cfg(feature = "with-mta-sts")]
+fn summarize_mta_sts(status: &MtaStsStatus) -> Option<AuthSectionSnapshot> {
+    let snapshot = match status {
+        MtaStsStatus::NotRequested => return None,
+        MtaStsStatus::Missing => AuthSectionSnapshot::new("missing", None),
+        MtaStsStatus::MultipleRecords { records } => {
+            let detail = format!("records={}", records.join(" | "));
+            AuthSectionSnapshot::new("multiple_records", Some(detail))
+        }
+        MtaStsStatus::InvalidRecord { record, issue } => {
+            let detail = format!("issue={}; record={record}", describe_mta_sts_issue(issue));
+            AuthSectionSnapshot::new("invalid_record", Some(detail))
+        }
+        MtaStsStatus::PolicyUnavailable { id, reason } => {
+            let detail = format!("id={id}; reason={reason}");
+            AuthSectionSnapshot::new("policy_unavailable", Some(detail))
+        }
+        MtaStsStatus::InvalidPolicy { id, issue } => {
+            let detail = format!("id={id}; issue={}", describe_mta_sts_issue(issue));
+            AuthSectionSnapshot::new("invalid_policy", Some(detail))
+        }
+        MtaStsStatus::Published {
+            id,
+            policy,
+            uncovered_mx,
+        } => {
+            let mode = match policy.mode {
+                MtaStsMode::Enforce => "enforce",
+                MtaStsMode::Testing => "testing",
+                MtaStsMode::None => "none",
+            };
+            let mut detail = format!(
+                "id={id}; mode={mode}; max_age={}; mx={}",
+                policy.max_age,
+                policy.mx.join(",")
+            );
+            if !uncovered_mx.is_empty() {
+                detail.push_str(&format!("; uncovered_mx={}", uncovered_mx.join(",")));
+            }
+            let label = if status.is_enforced() {
+                "enforced"
+            } else {
+                "published"
+            };
+            AuthSectionSnapshot::new(label, Some(detail))
+        }
+    };
+    Some(snapshot)
+}
+
+#[cfg(feature = "with-mta-sts")]
+fn describe_mta_sts_issue(issue: &MtaStsIssue) -> String {
+    match issue {
+        MtaStsIssue::InvalidVersion => "invalid_version".to_string(),
+        MtaStsIssue::MissingId => "missing_id".to_string(),
+        MtaStsIssue::InvalidId { id } => format!("invalid_id({id})"),
+        MtaStsIssue::MissingMode => "missing_mode".to_string(),
+        MtaStsIssue::UnknownMode { mode } => format!("unknown_mode({mode})"),
+        MtaStsIssue::MissingMaxAge => "missing_max_age".to_string(),
+        MtaStsIssue::InvalidMaxAge { value } => format!("invalid_max_age({value})"),
+        MtaStsIssue::MissingMx => "missing_mx".to_string(),
+    }
+}
+
 fn summarize_selector(status: DkimSelectorStatus) -> AuthSelectorSnapshot {
     match status {
         DkimSelectorStatus::Missing { selector } => {
@@ -357,16 +549,23 @@ fn summarize_selector(status: DkimSelectorStatus) -> AuthSelectorSnapshot {
         DkimSelectorStatus::Weak {
             selector,
             record,
-            weakness,
+            key,
+            weaknesses,
         } => {
+            let weaknesses: Vec<String> = weaknesses.iter().map(describe_dkim_weakness).collect();
             let detail = format!(
-                "weakness={}; record={record}",
-                describe_dkim_weakness(weakness)
+                "key={}; weakness={}; record={record}",
+                describe_dkim_key(&key),
+                weaknesses.join(",")
             );
             AuthSelectorSnapshot::new(selector, "weak", Some(detail))
         }
-        DkimSelectorStatus::Compliant { selector, record } => {
-            let detail = format!("record={record}");
+        DkimSelectorStatus::Compliant {
+            selector,
+            record,
+            key,
+        } => {
+            let detail = format!("key={}; record={record}", describe_dkim_key(&key));
             AuthSelectorSnapshot::new(selector, "compliant", Some(detail))
         }
     }
@@ -404,10 +603,63 @@ fn describe_dmarc_policy(policy: DmarcPolicy) -> &'static str {
     }
 }
 
-fn describe_dmarc_weakness(weakness: DmarcWeakness) -> &'static str {
+/// `from=<domaine>; scope=sp|np; ` quand la politique vient d'un parent.
+fn describe_dmarc_origin(origin: &DmarcOrigin) -> String {
+    let scope = match origin.scope {
+        DmarcScope::Domain => return String::new(),
+        DmarcScope::Subdomain => "sp",
+        DmarcScope::NonexistentSubdomain => "np",
+    };
+    format!("from={}; scope={scope}; ", origin.domain)
+}
+
+fn describe_dmarc_weakness(weakness: &DmarcWeakness) -> String {
     match weakness {
-        DmarcWeakness::MonitoringPolicy => "monitoring_policy",
-        DmarcWeakness::QuarantinePolicy => "quarantine_policy",
+        DmarcWeakness::MonitoringPolicy => "monitoring_policy".to_string(),
+        DmarcWeakness::QuarantinePolicy => "quarantine_policy".to_string(),
+        DmarcWeakness::PartialPercentage { pct } => format!("partial_pct({pct})"),
+        DmarcWeakness::WeakerSubdomainPolicy { policy } => {
+            format!("weaker_sp({})", describe_dmarc_policy(*policy))
+        }
+        DmarcWeakness::WeakerNonexistentPolicy { policy } => {
+            format!("weaker_np({})", describe_dmarc_policy(*policy))
+        }
+        DmarcWeakness::TestingMode => "testing".to_string(),
+        DmarcWeakness::MissingAggregateReports => "missing_rua".to_string(),
+        DmarcWeakness::UnauthorizedReportDestination { tag, address } => {
+            format!("unauthorized_destination({tag}={address})")
+        }
+        DmarcWeakness::InvalidUri { tag, uri } => format!("invalid_uri({tag}={uri})"),
+        DmarcWeakness::InvalidTag { tag, value } => format!("invalid_tag({tag}={value})"),
+        DmarcWeakness::DuplicateTag { tag } => format!("duplicate_tag({tag})"),
+    }
+}
+
+fn describe_tls_rpt_issue(issue: &TlsRptIssue) -> String {
+    match issue {
+        TlsRptIssue::InvalidVersion => "invalid_version".to_string(),
+        TlsRptIssue::MissingRua => "missing_rua".to_string(),
+        TlsRptIssue::DuplicateField { field } => format!("duplicate_field({field})"),
+        TlsRptIssue::UnsupportedUri { uri } => format!("unsupported_uri({uri})"),
+        TlsRptIssue::MalformedUri { uri } => format!("malformed_uri({uri})"),
+    }
+}
+
+fn describe_bimi_issue(issue: &BimiIssue) -> String {
+    match issue {
+        BimiIssue::InvalidVersion => "invalid_version".to_string(),
+        BimiIssue::DuplicateTag { tag } => format!("duplicate_tag({tag})"),
+        BimiIssue::MissingLogo => "missing_logo".to_string(),
+        BimiIssue::InvalidLogoUrl { url } => format!("invalid_logo_url({url})"),
+        BimiIssue::InvalidAuthorityUrl { url } => format!("invalid_authority_url({url})"),
+    }
+}
+
+fn describe_bimi_dmarc_issue(issue: &BimiDmarcIssue) -> String {
+    match issue {
+        BimiDmarcIssue::MissingDmarc => "missing".to_string(),
+        BimiDmarcIssue::MonitoringPolicy => "monitoring_policy".to_string(),
+        BimiDmarcIssue::PartialPercentage { pct } => format!("partial_pct({pct})"),
     }
 }
 
@@ -415,12 +667,33 @@ fn describe_dkim_issue(issue: &DkimIssue) -> String {
     match issue {
         DkimIssue::InvalidVersion => "invalid_version".to_string(),
         DkimIssue::MissingPublicKey => "missing_public_key".to_string(),
+        DkimIssue::RevokedKey => "revoked_key".to_string(),
+        DkimIssue::MalformedPublicKey => "malformed_public_key".to_string(),
+        DkimIssue::UnknownKeyType { key_type } => format!("unknown_key_type({key_type})"),
+        DkimIssue::KeyTooShort { bits } => format!("key_too_short({bits})"),
+        DkimIssue::UnsupportedHashAlgorithms { algorithms } => {
+            format!("unsupported_hash({})", algorithms.join(":"))
+        }
+        DkimIssue::NotForEmail { service_types } => {
+            format!("not_for_email({})", service_types.join(":"))
+        }
         DkimIssue::MultipleRecords { count } => format!("multiple_records({count})"),
     }
 }
 
-fn describe_dkim_weakness(weakness: DkimWeakness) -> &'static str {
+fn describe_dkim_weakness(weakness: &DkimWeakness) -> String {
     match weakness {
-        DkimWeakness::TestingFlag => "testing_flag",
+        DkimWeakness::TestingFlag => "testing_flag".to_string(),
+        DkimWeakness::WeakKey { bits } => format!("weak_key({bits})"),
+        DkimWeakness::Sha1Only => "sha1_only".to_string(),
+    }
+}
+
+/// `rsa-2048`, `ed25519`.
+fn describe_dkim_key(key: &DkimKey) -> String {
+    match (key.key_type, key.modulus_bits) {
+        (DkimKeyType::Rsa, Some(bits)) => format!("rsa-{bits}"),
+        (DkimKeyType::Rsa, None) => "rsa".to_string(),
+        (DkimKeyType::Ed25519, _) => "ed25519".to_string(),
     }
 }
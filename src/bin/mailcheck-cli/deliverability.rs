@@ -1,5 +1,5 @@
 use mailcheck_lib::{
-    Existence, NormalizedEmail, SmtpProbeOptions, SmtpProbeReport, SmtpVerifyError,
+    DnsConfig, Existence, NormalizedEmail, SmtpProbeOptions, SmtpProbeReport, SmtpVerifyError,
     check_mailaddress_exists,
 };
 
@@ -83,11 +83,11 @@ impl DeliverabilitySummary {
     }
 }
 
-pub fn probe(row: &NormalizedEmail) -> DeliverabilitySummary {
-    probe_with(row, check_mailaddress_exists)
+pub fn probe(row: &NormalizedEmail, dns: &DnsConfig) -> DeliverabilitySummary {
+    probe_with(row, dns, check_mailaddress_exists)
 }
 
-fn probe_with<F>(row: &NormalizedEmail, check: F) -> DeliverabilitySummary
+fn probe_with<F>(row: &NormalizedEmail, dns: &DnsConfig, check: F) -> DeliverabilitySummary
 where
     F: Fn(&str, &SmtpProbeOptions) -> Result<SmtpProbeReport, SmtpVerifyError>,
 {
@@ -116,6 +116,7 @@ where
         mail_from: format!("postmaster@{domain}"),
         catchall_probes: 1,
         max_mx: 3,
+        dns: dns.clone(),
         ..SmtpProbeOptions::default()
     };
 
@@ -139,12 +140,12 @@ mod tests {
     use super::*;
 
     fn fake_report(existence: Existence) -> SmtpProbeReport {
-        SmtpProbeReport {
-            result: existence,
-            mx_tried: vec!["mx.example".to_string()],
-            transcript: vec!["[mx.example] S: 550 5.1.1 user unknown".to_string()],
-            confidence: 0.95,
-        }
+        SmtpProbeReport::new(
+            existence,
+            vec!["mx.example".to_string()],
+            vec!["[mx.example] S: 550 5.1.1 user unknown".to_string()],
+            0.95,
+        )
     }
 
     #[test]
@@ -156,6 +157,8 @@ mod tests {
             ascii_domain: String::new(),
             mode: mailcheck_lib::ValidationMode::Strict,
             valid: false,
+            nfc: "bad".to_string(),
+            nfkc: "bad".to_string(),
             reasons: vec!["invalid".to_string()],
             spec_chars: None,
             has_confusables: None,
@@ -164,7 +167,9 @@ mod tests {
             spec_notes: None,
             ascii_hint: None,
         };
-        let summary = probe_with(&normalized, |_, _| Ok(fake_report(Existence::Exists)));
+        let summary = probe_with(&normalized, &DnsConfig::default(), |_, _| {
+            Ok(fake_report(Existence::Exists))
+        });
         assert_eq!(summary.human_summary(), "skipped: email invalid");
     }
 
@@ -177,6 +182,8 @@ mod tests {
             ascii_domain: "example.com".to_string(),
             mode: mailcheck_lib::ValidationMode::Strict,
             valid: true,
+            nfc: "user@example.com".to_string(),
+            nfkc: "user@example.com".to_string(),
             reasons: Vec::new(),
             spec_chars: None,
             has_confusables: None,
@@ -185,7 +192,9 @@ mod tests {
             spec_notes: None,
             ascii_hint: None,
         };
-        let summary = probe_with(&normalized, |_, _| Err(SmtpVerifyError::NoSmtpServers));
+        let summary = probe_with(&normalized, &DnsConfig::default(), |_, _| {
+            Err(SmtpVerifyError::NoSmtpServers)
+        });
         assert!(summary.human_summary().starts_with("error:"));
     }
 
@@ -198,6 +207,8 @@ mod tests {
             ascii_domain: "example.com".to_string(),
             mode: mailcheck_lib::ValidationMode::Strict,
             valid: true,
+            nfc: "user@example.com".to_string(),
+            nfkc: "user@example.com".to_string(),
             reasons: Vec::new(),
             spec_chars: None,
             has_confusables: None,
@@ -206,7 +217,9 @@ mod tests {
             spec_notes: None,
             ascii_hint: None,
         };
-        let summary = probe_with(&normalized, |_, _| Ok(fake_report(Existence::Exists)));
+        let summary = probe_with(&normalized, &DnsConfig::default(), |_, _| {
+            Ok(fake_report(Existence::Exists))
+        });
         assert!(summary.human_summary().starts_with("Exists"));
     }
 }
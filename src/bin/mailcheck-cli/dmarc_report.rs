@@ -0,0 +1,140 @@
+use anyhow::{Context, Result, bail};
+
+use mailcheck_lib::{
+    AggregateReport, ReportSourceSummary, load_aggregate_reports, summarize_aggregate_reports,
+};
+
+#[cfg(feature = "with-csv")]
+use crate::output::write_csv_to;
+#[cfg(feature = "with-serde")]
+use crate::output::{write_json_to, write_ndjson_to};
+
+/// Résume par source les rapports agrégés de `files` ; renvoie `true` si
+/// une source n'a aucun message authentifié.
+pub fn run_dmarc_report(files: &[String], format: &str, out: Option<&str>) -> Result<bool> {
+    let mut reports = Vec::new();
+    for path in files {
+        reports.extend(load_aggregate_reports(path).with_context(|| format!("read {path}"))?);
+    }
+    let summaries = summarize_aggregate_reports(&reports);
+
+    match format {
+        "human" => print_human(&reports, &summaries),
+        "json" => write_json(&summaries, out)?,
+        "ndjson" => write_ndjson(&summaries, out)?,
+        "csv" => write_csv(&summaries, out)?,
+        other => bail!("unknown --format '{other}', use: human|json|ndjson|csv"),
+    }
+
+    Ok(summaries
+        .iter()
+        .any(ReportSourceSummary::is_unauthenticated))
+}
+
+fn print_human(reports: &[AggregateReport], summaries: &[ReportSourceSummary]) {
+    for report in reports {
+        println!(
+            "report {} #{} domain={} p={} period={}-{} records={}",
+            report.metadata.org_name,
+            report.metadata.report_id,
+            report.policy.domain,
+            report.policy.p,
+            report.metadata.begin,
+            report.metadata.end,
+            report.records.len()
+        );
+    }
+    for summary in summaries {
+        let label = if summary.is_unauthenticated() {
+            "[UNAUTH]"
+        } else {
+            "[OK]    "
+        };
+        println!(
+            "{label} {} messages={} pass={:.1}% dkim={} spf={} quarantined={} rejected={} from={}",
+            summary.source_ip,
+            summary.messages,
+            summary.pass_rate() * 100.0,
+            summary.dkim_pass,
+            summary.spf_pass,
+            summary.quarantined,
+            summary.rejected,
+            summary.header_from.join(",")
+        );
+    }
+    let unauthenticated = summaries
+        .iter()
+        .filter(|summary| summary.is_unauthenticated())
+        .count();
+    println!(
+        "total: {} sources, {unauthenticated} unauthenticated",
+        summaries.len()
+    );
+}
+
+#[cfg(feature = "with-serde")]
+#[derive(serde::Serialize)]
+struct SourceRow<'a> {
+    #[serde(flatten)]
+    summary: &'a ReportSourceSummary,
+    pass_rate: f64,
+    unauthenticated: bool,
+}
+
+#[cfg(feature = "with-serde")]
+fn source_rows(summaries: &[ReportSourceSummary]) -> Vec<SourceRow<'_>> {
+    summaries
+        .iter()
+        .map(|summary| SourceRow {
+            summary,
+            pass_rate: summary.pass_rate(),
+            unauthenticated: summary.is_unauthenticated(),
+        })
+        .collect()
+}
+
+#[cfg(feature = "with-serde")]
+fn write_json(summaries: &[ReportSourceSummary], out: Option<&str>) -> Result<()> {
+    write_json_to(&source_rows(summaries), out)
+}
+
+#[cfg(not(feature = "with-serde"))]
+fn write_json(_: &[ReportSourceSummary], _: Option<&str>) -> Result<()> {
+    bail!("format=json nécessite la feature 'with-serde'")
+}
+
+#[cfg(feature = "with-serde")]
+fn write_ndjson(summaries: &[ReportSourceSummary], out: Option<&str>) -> Result<()> {
+    write_ndjson_to(&source_rows(summaries), out)
+}
+
+#[cfg(not(feature = "with-serde"))]
+fn write_ndjson(_: &[ReportSourceSummary], _: Option<&str>) -> Result<()> {
+    bail!("format=ndjson nécessite la feature 'with-serde'")
+}
+
+/// Colonnes : source_ip, header_from, messages, dmarc_pass, dkim_pass,
+/// spf_pass, quarantined, rejected, pass_rate, unauthenticated.
+#[cfg(feature = "with-csv")]
+fn write_csv(summaries: &[ReportSourceSummary], out: Option<&str>) -> Result<()> {
+    let records = summaries.iter().map(|summary| {
+        vec![
+            summary.source_ip.to_string(),
+            summary.header_from.join("|"),
+            summary.messages.to_string(),
+            summary.dmarc_pass.to_string(),
+            summary.dkim_pass.to_string(),
+            summary.spf_pass.to_string(),
+            summary.quarantined.to_string(),
+            summary.rejected.to_string(),
+            format!("{:.4}", summary.pass_rate()),
+            summary.is_unauthenticated().to_string(),
+        ]
+    });
+    write_csv_to(records, out)
+}
+
+#[cfg(not(feature = "with-csv"))]
+fn write_csv(_: &[ReportSourceSummary], _: Option<&str>) -> Result<()> {
+    bail!("format=csv nécessite la feature 'with-csv'")
+}
@@ -1,4 +1,11 @@
-use mailcheck_lib::{MxError, MxStatus, NormalizedEmail, check_mx};
+use std::net::IpAddr;
+
+#[cfg(feature = "with-dane")]
+use mailcheck_lib::MxDaneStatus;
+use mailcheck_lib::{
+    DnssecStatus, MxError, MxLint, MxLookupOptions, MxRecord, MxStatus, NormalizedEmail,
+    check_mx_with_options,
+};
 
 #[cfg_attr(feature = "with-serde", derive(serde::Serialize))]
 #[derive(Debug, Clone)]
@@ -38,20 +45,32 @@ impl MxSummary {
 
     pub fn human_summary(&self) -> String {
         if let Some(status) = &self.status {
-            match status {
-                MxStatus::Records(records) => {
+            let summary = match status {
+                MxStatus::Records { records, .. } => {
                     if records.is_empty() {
                         "records: <empty>".to_string()
                     } else {
                         let summary = records
                             .iter()
-                            .map(|r| format!("{}:{}", r.preference, r.exchange))
+                            .map(format_record)
                             .collect::<Vec<_>>()
                             .join(", ");
                         format!("records: {summary}")
                     }
                 }
-                MxStatus::NoRecords => "no MX records".to_string(),
+                MxStatus::NullMx { .. } => "null MX (domain accepts no mail)".to_string(),
+                MxStatus::DomainNotFound { .. } => "domain not found (NXDOMAIN)".to_string(),
+                MxStatus::NoMxButAddress { addrs, .. } => {
+                    format!(
+                        "no MX, implicit MX via A/AAAA: {}",
+                        format_addrs(addrs, ", ")
+                    )
+                }
+                MxStatus::NoMailHost { .. } => "no mail host (no MX, A or AAAA)".to_string(),
+            };
+            match status.dnssec() {
+                DnssecStatus::Indeterminate => summary,
+                dnssec => format!("{summary} [dnssec={}]", dnssec.as_str()),
             }
         } else if let Some(error) = &self.error {
             format!("error: {error}")
@@ -66,15 +85,20 @@ impl MxSummary {
     pub fn csv_fields(&self) -> (String, String) {
         if let Some(status) = &self.status {
             match status {
-                MxStatus::Records(records) => {
+                MxStatus::Records { records, .. } => {
                     let detail = records
                         .iter()
-                        .map(|r| format!("{}:{}", r.preference, r.exchange))
+                        .map(format_record)
                         .collect::<Vec<_>>()
                         .join(";");
                     ("records".to_string(), detail)
                 }
-                MxStatus::NoRecords => ("no_records".to_string(), String::new()),
+                MxStatus::NullMx { .. } => ("null_mx".to_string(), String::new()),
+                MxStatus::DomainNotFound { .. } => ("domain_not_found".to_string(), String::new()),
+                MxStatus::NoMxButAddress { addrs, .. } => {
+                    ("implicit_mx".to_string(), format_addrs(addrs, ";"))
+                }
+                MxStatus::NoMailHost { .. } => ("no_mail_host".to_string(), String::new()),
             }
         } else if let Some(error) = &self.error {
             ("error".to_string(), error.clone())
@@ -86,7 +110,56 @@ impl MxSummary {
     }
 }
 
-pub fn resolve(row: &NormalizedEmail) -> MxSummary {
+fn format_record(record: &MxRecord) -> String {
+    let exchange = if record.is_null() {
+        "."
+    } else {
+        record.exchange.as_str()
+    };
+    let mut out = format!("{}:{exchange}", record.preference);
+    if !record.addresses.is_empty() {
+        out.push_str(&format!(" ({})", format_addrs(&record.addresses, " ")));
+    }
+    if record.dnssec != DnssecStatus::Indeterminate {
+        out.push_str(&format!(" [dnssec={}]", record.dnssec.as_str()));
+    }
+    #[cfg(feature = "with-dane")]
+    match &record.dane {
+        Some(MxDaneStatus::Checked(dane)) => {
+            out.push_str(&format!(" [dane={}]", dane.state().as_str()))
+        }
+        Some(MxDaneStatus::LookupFailed { reason }) => {
+            out.push_str(&format!(" [dane=lookup_failed={reason}]"))
+        }
+        None => {}
+    }
+    for lint in &record.lints {
+        out.push_str(&format!(" [{}]", describe_lint(lint)));
+    }
+    out
+}
+
+fn format_addrs(addrs: &[IpAddr], sep: &str) -> String {
+    addrs
+        .iter()
+        .map(IpAddr::to_string)
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+fn describe_lint(lint: &MxLint) -> String {
+    match lint {
+        MxLint::NullMxWithOtherRecords => "null_mx_with_other_records".to_string(),
+        MxLint::Unresolvable => "unresolvable".to_string(),
+        MxLint::CnameTarget { target } => format!("cname={target}"),
+        MxLint::IpLiteral => "ip_literal".to_string(),
+        MxLint::PrivateAddress { addr } => format!("private_address={addr}"),
+        MxLint::LoopbackAddress { addr } => format!("loopback_address={addr}"),
+        MxLint::LookupFailed { reason } => format!("lookup_failed={reason}"),
+    }
+}
+
+pub fn resolve(row: &NormalizedEmail, options: &MxLookupOptions) -> MxSummary {
     let target = if !row.ascii_domain.is_empty() {
         row.ascii_domain.as_str()
     } else {
@@ -97,7 +170,7 @@ pub fn resolve(row: &NormalizedEmail) -> MxSummary {
         return MxSummary::skipped("domain missing");
     }
 
-    match check_mx(target) {
+    match check_mx_with_options(target, options) {
         Ok(status) => MxSummary::from_status(status),
         Err(MxError::EmptyDomain) => MxSummary::skipped("domain missing"),
         Err(err) => MxSummary::from_error(&err),
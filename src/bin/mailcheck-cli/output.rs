@@ -61,21 +61,17 @@ pub fn make_row(normalized: NormalizedEmail, cli: &Cli) -> OutputRow {
 
     #[cfg(feature = "with-mx")]
     if cli.mx {
-        row.mx = Some(mx::resolve(&row.normalized));
+        row.mx = Some(mx::resolve(&row.normalized, &cli.mx_options()));
     }
 
     #[cfg(feature = "with-smtp-verify")]
     if cli.deliverability {
-        row.deliverability = Some(deliverability::probe(&row.normalized));
+        row.deliverability = Some(deliverability::probe(&row.normalized, &cli.dns_config()));
     }
 
     #[cfg(feature = "with-auth-records")]
     if cli.auth {
-        row.auth = Some(auth::resolve(
-            &row.normalized,
-            cli.skip_dkim_policy,
-            &cli.dkim_selectors,
-        ));
+        row.auth = Some(auth::resolve(&row.normalized, &cli.auth_options()));
     }
 
     row
@@ -159,8 +155,14 @@ fn write_human(rows: &[OutputRow], cli: &Cli) -> Result<()> {
 
 #[cfg(feature = "with-serde")]
 fn write_json(rows: &[OutputRow], cli: &Cli) -> Result<()> {
-    let s = serde_json::to_string_pretty(rows)?;
-    if let Some(path) = &cli.out {
+    write_json_to(rows, cli.out.as_deref())
+}
+
+/// JSON indenté vers `out`, ou sur la sortie standard.
+#[cfg(feature = "with-serde")]
+pub fn write_json_to<T: serde::Serialize + ?Sized>(value: &T, out: Option<&str>) -> Result<()> {
+    let s = serde_json::to_string_pretty(value)?;
+    if let Some(path) = out {
         write_all_atomically(path, s.as_bytes())?;
     } else {
         println!("{s}");
@@ -175,17 +177,23 @@ fn write_json(_: &[OutputRow], _: &Cli) -> Result<()> {
 
 #[cfg(feature = "with-serde")]
 fn write_ndjson(rows: &[OutputRow], cli: &Cli) -> Result<()> {
-    if let Some(path) = &cli.out {
+    write_ndjson_to(rows, cli.out.as_deref())
+}
+
+/// Un objet JSON par ligne vers `out`, ou sur la sortie standard.
+#[cfg(feature = "with-serde")]
+pub fn write_ndjson_to<T: serde::Serialize>(items: &[T], out: Option<&str>) -> Result<()> {
+    if let Some(path) = out {
         let mut buf = Vec::new();
-        for row in rows {
-            let line = serde_json::to_string(row)?;
+        for item in items {
+            let line = serde_json::to_string(item)?;
             buf.extend_from_slice(line.as_bytes());
             buf.push(b'\n');
         }
         write_all_atomically(path, &buf)?;
     } else {
-        for row in rows {
-            println!("{}", serde_json::to_string(row)?);
+        for item in items {
+            println!("{}", serde_json::to_string(item)?);
         }
     }
     Ok(())
@@ -198,18 +206,28 @@ fn write_ndjson(_: &[OutputRow], _: &Cli) -> Result<()> {
 
 #[cfg(feature = "with-csv")]
 fn write_csv(rows: &[OutputRow], cli: &Cli) -> Result<()> {
-    if let Some(path) = &cli.out {
+    write_csv_to(
+        rows.iter().map(|row| csv_record(row, cli)),
+        cli.out.as_deref(),
+    )
+}
+
+/// Enregistrements CSV vers `out`, ou sur la sortie standard.
+#[cfg(feature = "with-csv")]
+pub fn write_csv_to<I>(records: I, out: Option<&str>) -> Result<()>
+where
+    I: IntoIterator<Item = Vec<String>>,
+{
+    if let Some(path) = out {
         let mut wtr = csv::Writer::from_writer(Vec::new());
-        for row in rows {
-            let record = csv_record(row, cli);
+        for record in records {
             wtr.write_record(&record)?;
         }
         let data = wtr.into_inner()?;
         write_all_atomically(path, &data)?;
     } else {
         let mut wtr = csv::Writer::from_writer(std::io::stdout());
-        for row in rows {
-            let record = csv_record(row, cli);
+        for record in records {
             wtr.write_record(&record)?;
         }
         wtr.flush()?;
@@ -283,6 +301,8 @@ fn csv_record(row: &OutputRow, cli: &Cli) -> Vec<String> {
             .unwrap_or_else(AuthCsvFields::empty);
         record.push(fields.spf);
         record.push(fields.dmarc);
+        record.push(fields.tls_rpt);
+        record.push(fields.bimi);
         record.push(fields.dkim_policy);
         record.push(fields.selectors);
         record.push(fields.error);
@@ -307,6 +327,7 @@ fn format_spec_summary(row: &NormalizedEmail) -> Option<String> {
         format!("confusables={}", spec.has_confusables),
         format!("diacritics={}", spec.has_diacritics),
         format!("mixed_scripts={}", spec.has_mixed_scripts),
+        format!("risk={:.2}", f32::from(spec.risk_score) / 1000.0),
     ];
     if let Some(notes) = row.spec_notes.as_ref() {
         if !notes.is_empty() {
@@ -318,6 +339,15 @@ fn format_spec_summary(row: &NormalizedEmail) -> Option<String> {
             parts.push(format!("ascii_hint={hint}"));
         }
     }
+    if !spec.hint_substitutions.is_empty() {
+        let rules = spec
+            .hint_substitutions
+            .iter()
+            .map(|s| format!("{}→{}({:?})", s.codepoint, s.replacement, s.rule))
+            .collect::<Vec<_>>()
+            .join(" ");
+        parts.push(format!("hint_rules={rules}"));
+    }
     Some(parts.join(", "))
 }
 
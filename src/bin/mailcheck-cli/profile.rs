@@ -0,0 +1,166 @@
+#[cfg(feature = "with-serde")]
+use anyhow::Context;
+use anyhow::{Result, bail};
+use mailcheck_lib::SpecOptions;
+
+#[cfg(feature = "with-serde")]
+use serde_json::{Map, Value};
+
+#[cfg(feature = "with-serde")]
+use crate::args::spec_options_from_profile;
+
+/// Listes complétées (et non remplacées) par le fichier de profil.
+#[cfg(feature = "with-serde")]
+const APPENDED_KEYS: [&str; 3] = [
+    "allowlist_labels",
+    "confusable_tld_warnings",
+    "lookalike_targets",
+];
+
+/// Charge un profil personnalisé (TOML si l'extension est `.toml`, JSON
+/// sinon). Le fichier part du profil nommé par `extends` (à défaut
+/// `default_base`, i.e. `--spec-profile`) et le surcharge ; les tables
+/// (`risk_weights`, `extra_confusables`…) sont fusionnées clé par clé.
+#[cfg(feature = "with-serde")]
+pub fn load_profile_file(path: &str, default_base: &str) -> Result<SpecOptions> {
+    let raw = std::fs::read_to_string(path).with_context(|| format!("read {path}"))?;
+    let is_toml = std::path::Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+    let document: Value = if is_toml {
+        toml::from_str(&raw).with_context(|| format!("parse TOML profile {path}"))?
+    } else {
+        serde_json::from_str(&raw).with_context(|| format!("parse JSON profile {path}"))?
+    };
+    profile_from_value(document, default_base)
+        .with_context(|| format!("invalid spec profile {path}"))
+}
+
+#[cfg(not(feature = "with-serde"))]
+pub fn load_profile_file(_path: &str, _default_base: &str) -> Result<SpecOptions> {
+    bail!("--spec-profile-file nécessite la feature 'with-serde'")
+}
+
+#[cfg(feature = "with-serde")]
+fn profile_from_value(document: Value, default_base: &str) -> Result<SpecOptions> {
+    let Value::Object(mut overlay) = document else {
+        bail!("profile must be a table/object");
+    };
+    let base_name = match overlay.remove("extends") {
+        Some(Value::String(name)) => name,
+        Some(other) => bail!("'extends' must be a profile name, got {other}"),
+        None => default_base.to_string(),
+    };
+
+    let base = spec_options_from_profile(&base_name)?;
+    let Value::Object(mut merged) = serde_json::to_value(base)? else {
+        bail!("base profile '{base_name}' did not serialize to an object");
+    };
+
+    for (key, value) in overlay {
+        let appended = APPENDED_KEYS.contains(&key.as_str());
+        let slot = merged.entry(key).or_insert(Value::Null);
+        match (slot, value) {
+            (Value::Array(existing), Value::Array(extra)) if appended => existing.extend(extra),
+            (slot, value) => merge_value(slot, value),
+        }
+    }
+
+    Ok(serde_json::from_value(Value::Object(merged))?)
+}
+
+/// Surcharge `slot` par `value`, récursivement pour les tables.
+#[cfg(feature = "with-serde")]
+fn merge_value(slot: &mut Value, value: Value) {
+    match (slot, value) {
+        (Value::Object(existing), Value::Object(extra)) => merge_tables(existing, extra),
+        (slot, value) => *slot = value,
+    }
+}
+
+#[cfg(feature = "with-serde")]
+fn merge_tables(existing: &mut Map<String, Value>, extra: Map<String, Value>) {
+    for (key, value) in extra {
+        merge_value(existing.entry(key).or_insert(Value::Null), value);
+    }
+}
+
+#[cfg(all(test, feature = "with-serde"))]
+mod tests {
+    use super::*;
+    use mailcheck_lib::SpecLocale;
+
+    #[test]
+    fn json_profile_extends_default_base() {
+        let document = serde_json::json!({
+            "allowlist_labels": ["bаnk"],
+            "extra_confusables": { "ı": "i" },
+            "ascii_hint": false
+        });
+        let opts = profile_from_value(document, "standard").expect("profile");
+        assert_eq!(opts.allowlist_labels, vec!["bаnk".to_string()]);
+        assert_eq!(
+            opts.extra_confusables.get(&'ı').map(String::as_str),
+            Some("i")
+        );
+        assert!(!opts.ascii_hint);
+        assert!(opts.detect_confusables);
+    }
+
+    #[test]
+    fn toml_profile_appends_to_named_base() {
+        let raw = r#"
+            extends = "fr-fraud"
+            confusable_tld_warnings = [["banque", "banque: confusable label"]]
+
+            [extra_diacritics]
+            "ș" = "s"
+        "#;
+        let document: Value = toml::from_str(raw).expect("toml");
+        let opts = profile_from_value(document, "standard").expect("profile");
+        assert_eq!(opts.hint_locale, Some(SpecLocale::Fr));
+        assert_eq!(opts.confusable_tld_warnings.len(), 3);
+        assert_eq!(opts.confusable_tld_warnings[2].0, "banque");
+        assert_eq!(
+            opts.extra_diacritics.get(&'ș').map(String::as_str),
+            Some("s")
+        );
+    }
+
+    #[test]
+    fn nested_tables_override_single_keys() {
+        let raw = r#"
+            extends = "fr-fraud"
+
+            [risk_weights]
+            lookalike = 0.9
+        "#;
+        let document: Value = toml::from_str(raw).expect("toml");
+        let opts = profile_from_value(document, "standard").expect("profile");
+        let base = SpecOptions::fr_fraud().risk_weights;
+        assert_eq!(opts.risk_weights.lookalike, 0.9);
+        assert_eq!(opts.risk_weights.domain_confusable, base.domain_confusable);
+        assert_eq!(opts.risk_weights.invisible, base.invisible);
+
+        let mut base = serde_json::json!({ "weights": { "a": 1, "b": { "c": 2, "d": 3 } } });
+        merge_value(
+            &mut base,
+            serde_json::json!({ "weights": { "b": { "c": 4 } } }),
+        );
+        assert_eq!(
+            base,
+            serde_json::json!({ "weights": { "a": 1, "b": { "c": 4, "d": 3 } } })
+        );
+
+        let typo = serde_json::json!({ "risk_weights": { "lookalikes": 0.9 } });
+        assert!(profile_from_value(typo, "standard").is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_profiles() {
+        let typo = serde_json::json!({ "allowlist": ["x"] });
+        assert!(profile_from_value(typo, "standard").is_err());
+        let unknown = serde_json::json!({ "extends": "nope" });
+        assert!(profile_from_value(unknown, "standard").is_err());
+    }
+}
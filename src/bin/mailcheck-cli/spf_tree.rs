@@ -0,0 +1,145 @@
+use anyhow::{Result, bail};
+
+use mailcheck_lib::{AuthLookupOptions, SpfLint, SpfTree, SpfTreeNode, lint_spf};
+
+#[cfg(feature = "with-serde")]
+use serde::Serialize;
+
+/// Affiche l'arbre SPF de `domain` ; renvoie `true` si un défaut rend
+/// l'évaluation `permerror`/`temperror`.
+pub fn run_spf_tree(domain: &str, format: &str, options: &AuthLookupOptions) -> Result<bool> {
+    let tree = lint_spf(domain, options)?;
+
+    match format {
+        "human" => print_human(&tree),
+        "json" => {
+            #[cfg(feature = "with-serde")]
+            {
+                let payload = TreePayload::from(&tree);
+                println!("{}", serde_json::to_string_pretty(&payload)?);
+            }
+            #[cfg(not(feature = "with-serde"))]
+            {
+                bail!("format=json nécessite la feature 'with-serde'");
+            }
+        }
+        other => bail!("format inconnu '{other}', utilisez human|json"),
+    }
+
+    Ok(tree.has_errors())
+}
+
+fn print_human(tree: &SpfTree) {
+    print_node(&tree.root, "", "");
+    println!(
+        "total: {}/10 DNS lookups, {}/2 void lookups",
+        tree.dns_lookups(),
+        tree.void_lookups()
+    );
+}
+
+/// `lead` préfixe la ligne du nœud, `indent` celles de ses enfants.
+fn print_node(node: &SpfTreeNode, lead: &str, indent: &str) {
+    println!(
+        "{lead}{} {} [lookups={} void={}]",
+        node.link.as_str(),
+        node.domain,
+        node.total_lookups(),
+        node.total_void_lookups()
+    );
+    let detail = if node.children.is_empty() {
+        format!("{indent}    ")
+    } else {
+        format!("{indent}│   ")
+    };
+    if let Some(record) = &node.record {
+        println!("{detail}{record}");
+    }
+    for issue in &node.issues {
+        let level = if issue.is_error() { "error" } else { "warning" };
+        println!("{detail}{level}: {}", describe_lint(issue));
+    }
+    for (idx, child) in node.children.iter().enumerate() {
+        if idx + 1 == node.children.len() {
+            print_node(child, &format!("{indent}└── "), &format!("{indent}    "));
+        } else {
+            print_node(child, &format!("{indent}├── "), &format!("{indent}│   "));
+        }
+    }
+}
+
+fn describe_lint(issue: &SpfLint) -> String {
+    match issue {
+        SpfLint::NoRecord => "no_record".to_string(),
+        SpfLint::MultipleRecords { count } => format!("multiple_records({count})"),
+        SpfLint::Syntax { reason } => format!("syntax({reason})"),
+        SpfLint::LookupFailed { reason } => format!("lookup_failed({reason})"),
+        SpfLint::TooManyLookups { count } => format!("too_many_lookups({count})"),
+        SpfLint::TooManyVoidLookups { count } => format!("too_many_void_lookups({count})"),
+        SpfLint::TooManyMx { term, count } => format!("too_many_mx({term}: {count})"),
+        SpfLint::Loop { target } => format!("loop({target})"),
+        SpfLint::DuplicateInclude { target } => format!("duplicate_include({target})"),
+        SpfLint::DeprecatedPtr { term } => format!("deprecated_ptr({term})"),
+        SpfLint::OverlappingNetwork {
+            term,
+            other_domain,
+            other_term,
+        } => format!("overlapping_network({term} ~ {other_domain} {other_term})"),
+        SpfLint::MacroTarget { term } => format!("macro_target({term})"),
+        SpfLint::RecordLength { length, limit } => {
+            format!("record_length({length} > {limit})")
+        }
+    }
+}
+
+#[cfg(feature = "with-serde")]
+#[derive(Serialize)]
+struct TreePayload<'a> {
+    dns_lookups: usize,
+    void_lookups: usize,
+    root: NodePayload<'a>,
+}
+
+#[cfg(feature = "with-serde")]
+#[derive(Serialize)]
+struct NodePayload<'a> {
+    domain: &'a str,
+    link: &'static str,
+    record: Option<&'a str>,
+    lookups: usize,
+    void_lookups: usize,
+    total_lookups: usize,
+    errors: Vec<String>,
+    warnings: Vec<String>,
+    children: Vec<NodePayload<'a>>,
+}
+
+#[cfg(feature = "with-serde")]
+impl<'a> From<&'a SpfTree> for TreePayload<'a> {
+    fn from(tree: &'a SpfTree) -> Self {
+        Self {
+            dns_lookups: tree.dns_lookups(),
+            void_lookups: tree.void_lookups(),
+            root: NodePayload::from(&tree.root),
+        }
+    }
+}
+
+#[cfg(feature = "with-serde")]
+impl<'a> From<&'a SpfTreeNode> for NodePayload<'a> {
+    fn from(node: &'a SpfTreeNode) -> Self {
+        let (errors, warnings): (Vec<_>, Vec<_>) =
+            node.issues.iter().partition(|issue| issue.is_error());
+        Self {
+            domain: &node.domain,
+            link: node.link.as_str(),
+            record: node.record.as_deref(),
+            lookups: node.lookups,
+            void_lookups: node.void_lookups,
+            total_lookups: node.total_lookups(),
+            errors: errors.into_iter().map(describe_lint).collect(),
+            warnings: warnings.into_iter().map(describe_lint).collect(),
+            children: node.children.iter().map(NodePayload::from).collect(),
+        }
+    }
+}
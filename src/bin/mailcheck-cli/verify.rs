@@ -1,6 +1,8 @@
 use anyhow::{Result, bail};
 
-use mailcheck_lib::{Existence, SmtpProbeOptions, check_mailaddress_exists};
+#[cfg(feature = "with-dane")]
+use mailcheck_lib::CertificateMatch;
+use mailcheck_lib::{DnsConfig, Existence, SmtpProbeOptions, check_mailaddress_exists};
 
 #[cfg(feature = "with-serde")]
 use serde::Serialize;
@@ -15,6 +17,9 @@ pub struct VerifyConfig<'a> {
     pub max_mx: usize,
     pub timeout_ms: u64,
     pub ipv6: bool,
+    pub dns: DnsConfig,
+    #[cfg(feature = "with-dane")]
+    pub dane: bool,
 }
 
 pub fn run_verify_exists(cfg: VerifyConfig<'_>) -> Result<()> {
@@ -30,6 +35,11 @@ pub fn run_verify_exists(cfg: VerifyConfig<'_>) -> Result<()> {
     options.max_mx = cfg.max_mx;
     options.timeout_ms = cfg.timeout_ms;
     options.ipv6 = cfg.ipv6;
+    options.dns = cfg.dns;
+    #[cfg(feature = "with-dane")]
+    {
+        options.dane = cfg.dane;
+    }
 
     let report = check_mailaddress_exists(cfg.email, &options)?;
 
@@ -69,12 +79,42 @@ fn print_human(report: &mailcheck_lib::SmtpProbeReport) {
     if !report.mx_tried.is_empty() {
         println!("MX tried: {}", report.mx_tried.join(", "));
     }
+    #[cfg(feature = "with-dane")]
+    for dane in &report.dane {
+        println!("DANE {}: {}", dane.host, describe_dane(dane));
+    }
     println!("Evidence:");
     for line in &report.transcript {
         println!("  {line}");
     }
 }
 
+#[cfg(feature = "with-dane")]
+fn describe_dane(dane: &mailcheck_lib::DaneStatus) -> String {
+    let mut out = format!(
+        "{} [dnssec={}]",
+        dane.state().as_str(),
+        dane.dnssec.as_str()
+    );
+    match &dane.certificate {
+        Some(CertificateMatch::Matched {
+            usage,
+            selector,
+            matching_type,
+        }) => out.push_str(&format!(
+            ", certificate matches {usage} {selector} {matching_type}"
+        )),
+        Some(CertificateMatch::Mismatch) => out.push_str(", certificate mismatch"),
+        Some(CertificateMatch::ChainUnavailable) => {
+            out.push_str(", DANE-TA only, chain unavailable")
+        }
+        Some(CertificateMatch::NoStartTls) => out.push_str(", STARTTLS not offered"),
+        Some(CertificateMatch::InvalidCertificate) => out.push_str(", unreadable certificate"),
+        None => {}
+    }
+    out
+}
+
 #[cfg(feature = "with-serde")]
 #[derive(Serialize)]
 struct ProbePayload<'a> {
@@ -82,6 +122,9 @@ struct ProbePayload<'a> {
     confidence: f32,
     mx_tried: &'a [String],
     transcript: &'a [String],
+    #[cfg(feature = "with-dane")]
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    dane: &'a [mailcheck_lib::DaneStatus],
 }
 
 #[cfg(feature = "with-serde")]
@@ -92,6 +135,8 @@ impl<'a> From<&'a mailcheck_lib::SmtpProbeReport> for ProbePayload<'a> {
             confidence: report.confidence,
             mx_tried: &report.mx_tried,
             transcript: &report.transcript,
+            #[cfg(feature = "with-dane")]
+            dane: &report.dane,
         }
     }
 }
@@ -0,0 +1,89 @@
+//! Lecture minimale d'un certificat X.509 (DER) et empreintes TLSA.
+
+use sha2::{Digest, Sha256, Sha512};
+
+pub(crate) const DANE_TA: u8 = 2;
+pub(crate) const DANE_EE: u8 = 3;
+pub(crate) const SELECTOR_FULL: u8 = 0;
+pub(crate) const SELECTOR_SPKI: u8 = 1;
+pub(crate) const MATCH_FULL: u8 = 0;
+pub(crate) const MATCH_SHA256: u8 = 1;
+pub(crate) const MATCH_SHA512: u8 = 2;
+
+const SEQUENCE: u8 = 0x30;
+/// `[0] EXPLICIT Version` du TBSCertificate.
+const VERSION_TAG: u8 = 0xa0;
+
+/// Longueur attendue des données pour un type de correspondance.
+pub(crate) fn digest_len(matching_type: u8) -> Option<usize> {
+    match matching_type {
+        MATCH_SHA256 => Some(32),
+        MATCH_SHA512 => Some(64),
+        _ => None,
+    }
+}
+
+pub(crate) fn matches(matching_type: u8, selected: &[u8], expected: &[u8]) -> bool {
+    match matching_type {
+        MATCH_FULL => selected == expected,
+        MATCH_SHA256 => Sha256::digest(selected).as_slice() == expected,
+        MATCH_SHA512 => Sha512::digest(selected).as_slice() == expected,
+        _ => false,
+    }
+}
+
+/// SubjectPublicKeyInfo (DER complet) du certificat : septième élément du
+/// TBSCertificate, ou sixième sans version explicite (RFC 5280 §4.1).
+pub(crate) fn subject_public_key_info(der: &[u8]) -> Option<&[u8]> {
+    let (tag, certificate, _) = read_tlv(der)?;
+    if tag != SEQUENCE {
+        return None;
+    }
+    let (tag, tbs, _) = read_tlv(content(certificate)?)?;
+    if tag != SEQUENCE {
+        return None;
+    }
+    let mut rest = content(tbs)?;
+    if rest.first() == Some(&VERSION_TAG) {
+        rest = read_tlv(rest)?.2;
+    }
+    // serialNumber, signature, issuer, validity, subject
+    for _ in 0..5 {
+        rest = read_tlv(rest)?.2;
+    }
+    let (tag, spki, _) = read_tlv(rest)?;
+    (tag == SEQUENCE).then_some(spki)
+}
+
+/// Élément DER en tête de `input` : (tag, élément complet, reste).
+fn read_tlv(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let tag = *input.first()?;
+    let first = *input.get(1)?;
+    let (len, header) = if first & 0x80 == 0 {
+        (usize::from(first), 2)
+    } else {
+        let count = usize::from(first & 0x7f);
+        if count == 0 || count > 4 {
+            return None;
+        }
+        let bytes = input.get(2..2 + count)?;
+        let len = bytes
+            .iter()
+            .fold(0usize, |acc, byte| (acc << 8) | usize::from(*byte));
+        (len, 2 + count)
+    };
+    let end = header.checked_add(len)?;
+    let element = input.get(..end)?;
+    Some((tag, element, &input[end..]))
+}
+
+/// Contenu d'un élément lu par [`read_tlv`].
+fn content(element: &[u8]) -> Option<&[u8]> {
+    let first = *element.get(1)?;
+    let header = if first & 0x80 == 0 {
+        2
+    } else {
+        2 + usize::from(first & 0x7f)
+    };
+    element.get(header..)
+}
@@ -0,0 +1,22 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DaneError {
+    #[error("host name is empty")]
+    EmptyHost,
+    #[error("host IDNA conversion failed")]
+    IdnaConversion {
+        #[source]
+        source: idna::Errors,
+    },
+    #[error("resolver initialization failed: {source}")]
+    ResolverInit {
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("TLSA lookup failed: {source}")]
+    Lookup {
+        #[source]
+        source: crate::dns::DnsError,
+    },
+}
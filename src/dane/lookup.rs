@@ -0,0 +1,91 @@
+use crate::dns::{DnsBackend, DnsConfig, DnsError, DnsRecordType, TlsaRdata, nx_as_answer};
+
+use super::cert::{self, DANE_EE, DANE_TA, SELECTOR_SPKI};
+use super::{DaneError, DaneStatus, TlsaIssue, TlsaRecord};
+
+const SMTP_PORT: u16 = 25;
+
+/// Fetch and check the TLSA records of the MX host `host` with the system
+/// resolver. The records are only usable when the answer is DNSSEC-secure,
+/// so pair this with [`DnsConfig::dnssec`] (see [`check_dane_with_options`]).
+pub fn check_dane(host: &str) -> Result<DaneStatus, DaneError> {
+    check_dane_with_options(host, &DnsConfig::default())
+}
+
+pub fn check_dane_with_options(host: &str, dns: &DnsConfig) -> Result<DaneStatus, DaneError> {
+    let backend = dns
+        .build_backend()
+        .map_err(|source| DaneError::ResolverInit { source })?;
+    check_dane_with_backend(&*backend, host)
+}
+
+/// Like [`check_dane_with_options`], but queries `backend`.
+pub fn check_dane_with_backend<B>(backend: &B, host: &str) -> Result<DaneStatus, DaneError>
+where
+    B: DnsBackend + ?Sized,
+{
+    let trimmed = host.trim().trim_end_matches('.');
+    if trimmed.is_empty() {
+        return Err(DaneError::EmptyHost);
+    }
+    let ascii =
+        idna::domain_to_ascii(trimmed).map_err(|source| DaneError::IdnaConversion { source })?;
+    lookup_dane(backend, &ascii, SMTP_PORT).map_err(|source| DaneError::Lookup { source })
+}
+
+/// TLSA de `_<port>._tcp.<host>` ; NXDOMAIN et NODATA donnent un statut
+/// sans enregistrement.
+pub(crate) fn lookup_dane<B>(backend: &B, host: &str, port: u16) -> Result<DaneStatus, DnsError>
+where
+    B: DnsBackend + ?Sized,
+{
+    let tlsa_name = format!("_{port}._tcp.{host}");
+    let answer = nx_as_answer(backend.lookup(&tlsa_name, DnsRecordType::Tlsa))?;
+    let mut records: Vec<TlsaRecord> = answer
+        .tlsa()
+        .into_iter()
+        .map(|rdata| TlsaRecord {
+            issues: lint(&rdata),
+            rdata,
+        })
+        .collect();
+    records.sort();
+    records.dedup();
+    Ok(DaneStatus {
+        host: host.to_string(),
+        tlsa_name,
+        records,
+        dnssec: answer.dnssec,
+        certificate: None,
+    })
+}
+
+fn lint(tlsa: &TlsaRdata) -> Vec<TlsaIssue> {
+    let mut issues = Vec::new();
+    match tlsa.usage {
+        DANE_TA | DANE_EE => {}
+        usage @ 0..=1 => issues.push(TlsaIssue::PkixUsage { usage }),
+        usage => issues.push(TlsaIssue::UnknownUsage { usage }),
+    }
+    if tlsa.selector > SELECTOR_SPKI {
+        issues.push(TlsaIssue::UnknownSelector {
+            selector: tlsa.selector,
+        });
+    }
+    if tlsa.matching_type > cert::MATCH_SHA512 {
+        issues.push(TlsaIssue::UnknownMatchingType {
+            matching_type: tlsa.matching_type,
+        });
+    }
+    if tlsa.data.is_empty() {
+        issues.push(TlsaIssue::EmptyData);
+    } else if let Some(expected) = cert::digest_len(tlsa.matching_type) {
+        if tlsa.data.len() != expected {
+            issues.push(TlsaIssue::DigestLength {
+                expected,
+                actual: tlsa.data.len(),
+            });
+        }
+    }
+    issues
+}
@@ -0,0 +1,23 @@
+//! DANE for SMTP (RFC 7672, optional `with-dane` feature).
+//!
+//! [`check_dane`] fetches the `_25._tcp.<mx>` TLSA records of an MX host,
+//! checks that they are well-formed and usable for SMTP, and returns a
+//! [`DaneStatus`]. [`DaneStatus::match_certificate`] compares them with the
+//! certificate presented after STARTTLS. The same status is attached to each
+//! exchange by `check_mx` (`MxLookupOptions::check_dane`) and to each host
+//! tried by the SMTP probe (`SmtpProbeOptions::dane`).
+
+mod cert;
+mod error;
+mod lookup;
+mod types;
+
+pub use error::DaneError;
+pub use lookup::{check_dane, check_dane_with_backend, check_dane_with_options};
+pub use types::{CertificateMatch, DaneState, DaneStatus, TlsaIssue, TlsaRecord};
+
+#[cfg(any(feature = "with-mx", feature = "with-smtp-verify"))]
+pub(crate) use lookup::lookup_dane;
+
+#[cfg(test)]
+mod tests;
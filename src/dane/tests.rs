@@ -0,0 +1,173 @@
+use sha2::{Digest, Sha256, Sha512};
+
+use super::{CertificateMatch, DaneError, DaneState, TlsaIssue, cert, check_dane_with_backend};
+use crate::dns::{DnsRecord, DnssecStatus, StaticZone, TlsaRdata};
+
+fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    match content.len() {
+        len @ 0..=0x7f => out.push(len as u8),
+        len @ 0x80..=0xff => out.extend([0x81, len as u8]),
+        len => out.extend([0x82, (len >> 8) as u8, len as u8]),
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+fn sequence(parts: &[Vec<u8>]) -> Vec<u8> {
+    tlv(0x30, &parts.concat())
+}
+
+/// SubjectPublicKeyInfo factice, assez long pour une longueur DER sur
+/// plusieurs octets.
+fn spki() -> Vec<u8> {
+    sequence(&[
+        sequence(&[tlv(0x06, &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01])]),
+        tlv(0x03, &[0x42; 200]),
+    ])
+}
+
+/// Certificat X.509 minimal : seule sa structure compte.
+fn certificate(with_version: bool) -> Vec<u8> {
+    let mut tbs = Vec::new();
+    if with_version {
+        tbs.push(tlv(0xa0, &tlv(0x02, &[2])));
+    }
+    tbs.extend([
+        tlv(0x02, &[0x01, 0x23]),
+        sequence(&[tlv(0x06, &[0x2a, 0x86, 0x48])]),
+        sequence(&[tlv(0x0c, b"issuer")]),
+        sequence(&[tlv(0x17, b"260101000000Z"), tlv(0x17, b"270101000000Z")]),
+        sequence(&[tlv(0x0c, b"mx.example.com")]),
+        spki(),
+    ]);
+    sequence(&[
+        sequence(&tbs),
+        sequence(&[tlv(0x06, &[0x2a, 0x86, 0x48])]),
+        tlv(0x03, &[0x00, 0x01]),
+    ])
+}
+
+fn tlsa(usage: u8, selector: u8, matching_type: u8, data: Vec<u8>) -> DnsRecord {
+    DnsRecord::Tlsa(TlsaRdata {
+        usage,
+        selector,
+        matching_type,
+        data,
+    })
+}
+
+fn signed_zone(records: Vec<DnsRecord>) -> StaticZone {
+    let mut zone = StaticZone::new().with_dnssec("example.com", DnssecStatus::Secure);
+    for record in records {
+        zone.insert("_25._tcp.mx.example.com", 300, record);
+    }
+    zone
+}
+
+#[test]
+fn extracts_subject_public_key_info() {
+    assert_eq!(
+        cert::subject_public_key_info(&certificate(true)),
+        Some(spki().as_slice())
+    );
+    assert_eq!(
+        cert::subject_public_key_info(&certificate(false)),
+        Some(spki().as_slice())
+    );
+    assert_eq!(cert::subject_public_key_info(b"not a certificate"), None);
+    let truncated = certificate(true);
+    assert_eq!(
+        cert::subject_public_key_info(&truncated[..truncated.len() / 2]),
+        None
+    );
+}
+
+#[test]
+fn reports_missing_records() {
+    let zone = StaticZone::new().with_dnssec("example.com", DnssecStatus::Secure);
+    let status = check_dane_with_backend(&zone, "MX.example.com.").expect("lookup succeeds");
+    assert_eq!(status.tlsa_name, "_25._tcp.mx.example.com");
+    assert_eq!(status.state(), DaneState::NotPublished);
+    assert!(matches!(
+        check_dane_with_backend(&zone, " "),
+        Err(DaneError::EmptyHost)
+    ));
+}
+
+#[test]
+fn ignores_unsigned_records() {
+    let digest = Sha256::digest(spki()).to_vec();
+    let zone = StaticZone::new().with_record("_25._tcp.mx.example.com", tlsa(3, 1, 1, digest));
+    let status = check_dane_with_backend(&zone, "mx.example.com").expect("lookup succeeds");
+    assert_eq!(status.records.len(), 1);
+    assert_eq!(status.state(), DaneState::Unauthenticated);
+}
+
+#[test]
+fn lints_malformed_records() {
+    let zone = signed_zone(vec![
+        tlsa(1, 1, 1, vec![0; 32]),
+        tlsa(7, 2, 9, vec![0; 4]),
+        tlsa(3, 1, 2, vec![0; 32]),
+    ]);
+    let status = check_dane_with_backend(&zone, "mx.example.com").expect("lookup succeeds");
+    let issues: Vec<_> = status.records.iter().map(|r| r.issues.clone()).collect();
+    assert_eq!(
+        issues,
+        vec![
+            vec![TlsaIssue::PkixUsage { usage: 1 }],
+            vec![TlsaIssue::DigestLength {
+                expected: 64,
+                actual: 32
+            }],
+            vec![
+                TlsaIssue::UnknownUsage { usage: 7 },
+                TlsaIssue::UnknownSelector { selector: 2 },
+                TlsaIssue::UnknownMatchingType { matching_type: 9 },
+            ],
+        ]
+    );
+    assert_eq!(status.state(), DaneState::Unusable);
+}
+
+#[test]
+fn matches_dane_ee_certificate() {
+    let certificate = certificate(true);
+    let zone = signed_zone(vec![
+        tlsa(3, 1, 1, Sha256::digest(spki()).to_vec()),
+        tlsa(3, 0, 2, Sha512::digest(&certificate).to_vec()),
+    ]);
+    let status = check_dane_with_backend(&zone, "mx.example.com").expect("lookup succeeds");
+    assert_eq!(status.state(), DaneState::Usable);
+    assert_eq!(
+        status.match_certificate(&certificate),
+        CertificateMatch::Matched {
+            usage: 3,
+            selector: 0,
+            matching_type: 2
+        }
+    );
+
+    let other = signed_zone(vec![tlsa(3, 1, 0, b"another key".to_vec())]);
+    let status = check_dane_with_backend(&other, "mx.example.com").expect("lookup succeeds");
+    assert_eq!(
+        status.match_certificate(&certificate),
+        CertificateMatch::Mismatch
+    );
+    assert_eq!(
+        status.match_certificate(b"garbage"),
+        CertificateMatch::InvalidCertificate
+    );
+}
+
+#[test]
+fn dane_ta_needs_the_chain() {
+    let zone = signed_zone(vec![tlsa(2, 1, 1, vec![0x11; 32])]);
+    let status = check_dane_with_backend(&zone, "mx.example.com").expect("lookup succeeds");
+    assert_eq!(status.state(), DaneState::Usable);
+    assert_eq!(
+        status.match_certificate(&certificate(true)),
+        CertificateMatch::ChainUnavailable
+    );
+}
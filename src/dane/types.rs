@@ -0,0 +1,172 @@
+use crate::dns::{DnssecStatus, TlsaRdata};
+
+use super::cert;
+
+/// Enregistrement TLSA publié pour un MX, avec ses anomalies ; il n'est
+/// utilisable que s'il n'en a aucune.
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TlsaRecord {
+    pub rdata: TlsaRdata,
+    #[cfg_attr(
+        feature = "with-serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub issues: Vec<TlsaIssue>,
+}
+
+impl TlsaRecord {
+    pub fn is_usable(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Anomalies d'un enregistrement TLSA (RFC 6698 §2.1, RFC 7672 §3.1).
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TlsaIssue {
+    /// PKIX-TA (0) ou PKIX-EE (1) : inutilisables pour SMTP (RFC 7672 §3.1.3).
+    PkixUsage {
+        usage: u8,
+    },
+    UnknownUsage {
+        usage: u8,
+    },
+    UnknownSelector {
+        selector: u8,
+    },
+    UnknownMatchingType {
+        matching_type: u8,
+    },
+    /// Empreinte dont la longueur ne correspond pas au type de correspondance.
+    DigestLength {
+        expected: usize,
+        actual: usize,
+    },
+    /// Données d'association vides.
+    EmptyData,
+}
+
+/// Synthèse du déploiement DANE d'un MX.
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "with-serde", serde(rename_all = "snake_case"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DaneState {
+    /// Aucun TLSA publié.
+    NotPublished,
+    /// TLSA publiés mais non validés par DNSSEC : ils doivent être ignorés
+    /// (RFC 7672 §2.2).
+    Unauthenticated,
+    /// TLSA validés, mais aucun n'est utilisable.
+    Unusable,
+    /// Au moins un TLSA validé et utilisable : TLS authentifié obligatoire.
+    Usable,
+}
+
+impl DaneState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NotPublished => "not_published",
+            Self::Unauthenticated => "unauthenticated",
+            Self::Unusable => "unusable",
+            Self::Usable => "usable",
+        }
+    }
+}
+
+/// Comparaison entre les TLSA et le certificat présenté après STARTTLS.
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CertificateMatch {
+    /// Le certificat correspond à cet enregistrement.
+    Matched {
+        usage: u8,
+        selector: u8,
+        matching_type: u8,
+    },
+    /// Aucun enregistrement utilisable ne correspond.
+    Mismatch,
+    /// Seuls des DANE-TA (2) restent à vérifier, or la chaîne présentée n'est
+    /// pas disponible (seul le certificat du serveur l'est).
+    ChainUnavailable,
+    /// Le serveur n'a pas proposé STARTTLS.
+    NoStartTls,
+    /// Certificat illisible (DER invalide).
+    InvalidCertificate,
+}
+
+impl CertificateMatch {
+    pub fn is_match(&self) -> bool {
+        matches!(self, Self::Matched { .. })
+    }
+}
+
+/// Statut DANE d'un MX : ses TLSA (`_25._tcp.<hôte>`), le statut DNSSEC de la
+/// réponse et, après une sonde SMTP, la comparaison avec le certificat.
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DaneStatus {
+    pub host: String,
+    pub tlsa_name: String,
+    #[cfg_attr(
+        feature = "with-serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub records: Vec<TlsaRecord>,
+    pub dnssec: DnssecStatus,
+    #[cfg_attr(
+        feature = "with-serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub certificate: Option<CertificateMatch>,
+}
+
+impl DaneStatus {
+    pub fn state(&self) -> DaneState {
+        if self.records.is_empty() {
+            DaneState::NotPublished
+        } else if self.dnssec != DnssecStatus::Secure {
+            DaneState::Unauthenticated
+        } else if self.records.iter().any(TlsaRecord::is_usable) {
+            DaneState::Usable
+        } else {
+            DaneState::Unusable
+        }
+    }
+
+    pub fn usable_records(&self) -> impl Iterator<Item = &TlsaRecord> {
+        self.records.iter().filter(|record| record.is_usable())
+    }
+
+    /// Compare `leaf_der`, le certificat du serveur, aux TLSA utilisables.
+    /// N'a de sens que si [`state`](Self::state) vaut [`DaneState::Usable`].
+    pub fn match_certificate(&self, leaf_der: &[u8]) -> CertificateMatch {
+        let spki = cert::subject_public_key_info(leaf_der);
+        let mut trust_anchors = false;
+        for record in self.usable_records() {
+            let tlsa = &record.rdata;
+            if tlsa.usage == cert::DANE_TA {
+                trust_anchors = true;
+                continue;
+            }
+            let selected = match tlsa.selector {
+                cert::SELECTOR_FULL => Some(leaf_der),
+                _ => spki,
+            };
+            if selected.is_some_and(|data| cert::matches(tlsa.matching_type, data, &tlsa.data)) {
+                return CertificateMatch::Matched {
+                    usage: tlsa.usage,
+                    selector: tlsa.selector,
+                    matching_type: tlsa.matching_type,
+                };
+            }
+        }
+        if spki.is_none() {
+            CertificateMatch::InvalidCertificate
+        } else if trust_anchors {
+            CertificateMatch::ChainUnavailable
+        } else {
+            CertificateMatch::Mismatch
+        }
+    }
+}
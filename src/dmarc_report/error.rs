@@ -0,0 +1,30 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ReportError {
+    #[error("report read failed: {source}")]
+    Io {
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("zip archive unreadable: {source}")]
+    Zip {
+        #[source]
+        source: zip::result::ZipError,
+    },
+    #[error("report is not valid XML: {source}")]
+    Xml {
+        #[source]
+        source: roxmltree::Error,
+    },
+    #[error("report is not UTF-8")]
+    Encoding,
+    #[error("report exceeds {limit} bytes once decompressed")]
+    TooLarge { limit: u64 },
+    #[error("archive contains no XML report")]
+    EmptyArchive,
+    #[error("missing element <{element}>")]
+    MissingElement { element: String },
+    #[error("invalid <{element}> value '{value}'")]
+    InvalidValue { element: String, value: String },
+}
@@ -0,0 +1,24 @@
+//! DMARC aggregate reports (RFC 7489 Appendix C, optional `with-dmarc-report`
+//! feature).
+//!
+//! [`load_aggregate_reports`] reads a report file (raw XML, gzip or zip, as
+//! sent to `rua=` addresses) into typed [`AggregateReport`]s, and
+//! [`summarize_aggregate_reports`] aggregates their records per source IP to
+//! spot senders that never pass DMARC.
+
+mod error;
+mod parse;
+mod summary;
+mod types;
+
+pub use error::ReportError;
+pub use parse::{load_aggregate_reports, parse_aggregate_report, read_aggregate_reports};
+pub use summary::summarize_aggregate_reports;
+pub use types::{
+    AggregateReport, ReportAuthResult, ReportDisposition, ReportDkimResult, ReportMetadata,
+    ReportPolicyOverride, ReportPublishedPolicy, ReportRecord, ReportSourceSummary,
+    ReportSpfResult, ReportVerdict,
+};
+
+#[cfg(test)]
+mod tests;
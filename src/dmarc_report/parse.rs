@@ -0,0 +1,260 @@
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use roxmltree::{Document, Node};
+
+use super::error::ReportError;
+use super::types::{
+    AggregateReport, ReportAuthResult, ReportDisposition, ReportDkimResult, ReportMetadata,
+    ReportPolicyOverride, ReportPublishedPolicy, ReportRecord, ReportSpfResult, ReportVerdict,
+};
+
+/// Taille maximale d'un rapport décompressé.
+const MAX_REPORT_SIZE: u64 = 64 << 20;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Analyse le XML d'un rapport agrégé (RFC 7489 annexe C, ou DMARCbis :
+/// l'espace de noms est ignoré).
+pub fn parse_aggregate_report(xml: &str) -> Result<AggregateReport, ReportError> {
+    let document = Document::parse(xml).map_err(|source| ReportError::Xml { source })?;
+    let feedback = document.root_element();
+    if feedback.tag_name().name() != "feedback" {
+        return Err(ReportError::MissingElement {
+            element: "feedback".to_string(),
+        });
+    }
+
+    let metadata = required_child(feedback, "report_metadata")?;
+    let date_range = required_child(metadata, "date_range")?;
+    let metadata = ReportMetadata {
+        org_name: required_text(metadata, "org_name")?,
+        email: text(metadata, "email"),
+        extra_contact_info: text(metadata, "extra_contact_info"),
+        report_id: required_text(metadata, "report_id")?,
+        begin: number(date_range, "begin")?,
+        end: number(date_range, "end")?,
+        errors: children(metadata, "error")
+            .filter_map(|node| node_text(&node))
+            .collect(),
+    };
+
+    let published = required_child(feedback, "policy_published")?;
+    let policy = ReportPublishedPolicy {
+        domain: required_text(published, "domain")?,
+        adkim: text(published, "adkim"),
+        aspf: text(published, "aspf"),
+        p: required_text(published, "p")?,
+        sp: text(published, "sp"),
+        np: text(published, "np"),
+        pct: match text(published, "pct") {
+            Some(value) => Some(parse_value("pct", &value)?),
+            None => None,
+        },
+        fo: text(published, "fo"),
+    };
+
+    let records = children(feedback, "record")
+        .map(parse_record)
+        .collect::<Result<_, _>>()?;
+
+    Ok(AggregateReport {
+        metadata,
+        policy,
+        records,
+    })
+}
+
+/// Rapports contenus dans `bytes` : XML brut, gzip, ou archive zip (un
+/// rapport par fichier `.xml`).
+pub fn read_aggregate_reports(bytes: &[u8]) -> Result<Vec<AggregateReport>, ReportError> {
+    if bytes.starts_with(GZIP_MAGIC) {
+        let decoder = flate2::read::MultiGzDecoder::new(bytes);
+        let xml = read_limited(decoder)?;
+        return parse_aggregate_report(&xml).map(|report| vec![report]);
+    }
+    if bytes.starts_with(ZIP_MAGIC) {
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes))
+            .map_err(|source| ReportError::Zip { source })?;
+        let mut reports = Vec::new();
+        for idx in 0..archive.len() {
+            let file = archive
+                .by_index(idx)
+                .map_err(|source| ReportError::Zip { source })?;
+            if !file.is_file() || !file.name().to_ascii_lowercase().ends_with(".xml") {
+                continue;
+            }
+            let xml = read_limited(file)?;
+            reports.push(parse_aggregate_report(&xml)?);
+        }
+        if reports.is_empty() {
+            return Err(ReportError::EmptyArchive);
+        }
+        return Ok(reports);
+    }
+    let xml = std::str::from_utf8(bytes).map_err(|_| ReportError::Encoding)?;
+    parse_aggregate_report(xml).map(|report| vec![report])
+}
+
+/// Lit un fichier de rapport ; le conteneur est reconnu à son contenu.
+pub fn load_aggregate_reports(path: impl AsRef<Path>) -> Result<Vec<AggregateReport>, ReportError> {
+    let bytes = std::fs::read(path).map_err(|source| ReportError::Io { source })?;
+    read_aggregate_reports(&bytes)
+}
+
+fn read_limited(reader: impl Read) -> Result<String, ReportError> {
+    let mut bytes = Vec::new();
+    reader
+        .take(MAX_REPORT_SIZE + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|source| ReportError::Io { source })?;
+    if bytes.len() as u64 > MAX_REPORT_SIZE {
+        return Err(ReportError::TooLarge {
+            limit: MAX_REPORT_SIZE,
+        });
+    }
+    String::from_utf8(bytes).map_err(|_| ReportError::Encoding)
+}
+
+fn parse_record(record: Node<'_, '_>) -> Result<ReportRecord, ReportError> {
+    let row = required_child(record, "row")?;
+    let evaluated = required_child(row, "policy_evaluated")?;
+    let identifiers = required_child(record, "identifiers")?;
+
+    let source_ip = required_text(row, "source_ip")?;
+    let source_ip = parse_value("source_ip", &source_ip)?;
+    let disposition = required_text(evaluated, "disposition")?;
+    let disposition = match disposition.to_ascii_lowercase().as_str() {
+        "none" => ReportDisposition::None,
+        "quarantine" => ReportDisposition::Quarantine,
+        "reject" => ReportDisposition::Reject,
+        _ => return Err(invalid("disposition", &disposition)),
+    };
+
+    let auth_results = child(record, "auth_results");
+    let dkim_results = auth_results
+        .into_iter()
+        .flat_map(|results| children(results, "dkim"))
+        .map(|dkim| {
+            Ok(ReportDkimResult {
+                domain: required_text(dkim, "domain")?,
+                selector: text(dkim, "selector"),
+                result: auth_result(&required_text(dkim, "result")?),
+            })
+        })
+        .collect::<Result<_, ReportError>>()?;
+    let spf_results = auth_results
+        .into_iter()
+        .flat_map(|results| children(results, "spf"))
+        .map(|spf| {
+            Ok(ReportSpfResult {
+                domain: required_text(spf, "domain")?,
+                scope: text(spf, "scope"),
+                result: auth_result(&required_text(spf, "result")?),
+            })
+        })
+        .collect::<Result<_, ReportError>>()?;
+
+    Ok(ReportRecord {
+        source_ip,
+        count: number(row, "count")?,
+        disposition,
+        dkim: verdict(evaluated, "dkim")?,
+        spf: verdict(evaluated, "spf")?,
+        reasons: children(evaluated, "reason")
+            .map(|reason| {
+                Ok(ReportPolicyOverride {
+                    kind: required_text(reason, "type")?,
+                    comment: text(reason, "comment"),
+                })
+            })
+            .collect::<Result<_, ReportError>>()?,
+        header_from: required_text(identifiers, "header_from")?,
+        envelope_from: text(identifiers, "envelope_from"),
+        envelope_to: text(identifiers, "envelope_to"),
+        dkim_results,
+        spf_results,
+    })
+}
+
+fn verdict(node: Node<'_, '_>, name: &str) -> Result<ReportVerdict, ReportError> {
+    let value = required_text(node, name)?;
+    match value.to_ascii_lowercase().as_str() {
+        "pass" => Ok(ReportVerdict::Pass),
+        "fail" => Ok(ReportVerdict::Fail),
+        _ => Err(invalid(name, &value)),
+    }
+}
+
+fn auth_result(value: &str) -> ReportAuthResult {
+    match value.to_ascii_lowercase().as_str() {
+        "none" => ReportAuthResult::None,
+        "pass" => ReportAuthResult::Pass,
+        "fail" | "hardfail" => ReportAuthResult::Fail,
+        "softfail" => ReportAuthResult::SoftFail,
+        "neutral" => ReportAuthResult::Neutral,
+        "policy" => ReportAuthResult::Policy,
+        "temperror" => ReportAuthResult::TempError,
+        "permerror" => ReportAuthResult::PermError,
+        _ => ReportAuthResult::Other(value.to_string()),
+    }
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    children(node, name).next()
+}
+
+fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    let name = name.to_string();
+    node.children()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+fn required_child<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &str,
+) -> Result<Node<'a, 'input>, ReportError> {
+    child(node, name).ok_or_else(|| ReportError::MissingElement {
+        element: name.to_string(),
+    })
+}
+
+/// Texte de l'élément, sans espaces autour ; `None` s'il est absent ou vide.
+fn text(node: Node<'_, '_>, name: &str) -> Option<String> {
+    child(node, name).and_then(|child| node_text(&child))
+}
+
+fn node_text(node: &Node<'_, '_>) -> Option<String> {
+    let value: String = node
+        .descendants()
+        .filter(|descendant| descendant.is_text())
+        .filter_map(|descendant| descendant.text())
+        .collect();
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn required_text(node: Node<'_, '_>, name: &str) -> Result<String, ReportError> {
+    text(node, name).ok_or_else(|| ReportError::MissingElement {
+        element: name.to_string(),
+    })
+}
+
+fn number(node: Node<'_, '_>, name: &str) -> Result<u64, ReportError> {
+    parse_value(name, &required_text(node, name)?)
+}
+
+fn parse_value<T: std::str::FromStr>(element: &str, value: &str) -> Result<T, ReportError> {
+    value.parse().map_err(|_| invalid(element, value))
+}
+
+fn invalid(element: &str, value: &str) -> ReportError {
+    ReportError::InvalidValue {
+        element: element.to_string(),
+        value: value.to_string(),
+    }
+}
@@ -0,0 +1,47 @@
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+use super::types::{AggregateReport, ReportDisposition, ReportSourceSummary, ReportVerdict};
+
+/// Regroupe les enregistrements de `reports` par adresse IP source, de la
+/// source la plus volumineuse à la plus petite.
+pub fn summarize_aggregate_reports(reports: &[AggregateReport]) -> Vec<ReportSourceSummary> {
+    let mut sources: BTreeMap<IpAddr, ReportSourceSummary> = BTreeMap::new();
+    for record in reports.iter().flat_map(|report| &report.records) {
+        let summary = sources
+            .entry(record.source_ip)
+            .or_insert_with(|| ReportSourceSummary {
+                source_ip: record.source_ip,
+                header_from: Vec::new(),
+                messages: 0,
+                dmarc_pass: 0,
+                dkim_pass: 0,
+                spf_pass: 0,
+                quarantined: 0,
+                rejected: 0,
+            });
+        let header_from = record.header_from.to_ascii_lowercase();
+        if let Err(position) = summary.header_from.binary_search(&header_from) {
+            summary.header_from.insert(position, header_from);
+        }
+        summary.messages += record.count;
+        if record.dmarc_pass() {
+            summary.dmarc_pass += record.count;
+        }
+        if record.dkim == ReportVerdict::Pass {
+            summary.dkim_pass += record.count;
+        }
+        if record.spf == ReportVerdict::Pass {
+            summary.spf_pass += record.count;
+        }
+        match record.disposition {
+            ReportDisposition::None => {}
+            ReportDisposition::Quarantine => summary.quarantined += record.count,
+            ReportDisposition::Reject => summary.rejected += record.count,
+        }
+    }
+
+    let mut summaries: Vec<ReportSourceSummary> = sources.into_values().collect();
+    summaries.sort_by_key(|summary| std::cmp::Reverse(summary.messages));
+    summaries
+}
@@ -0,0 +1,178 @@
+use std::io::Write;
+use std::net::IpAddr;
+
+use super::{
+    ReportAuthResult, ReportDisposition, ReportDkimResult, ReportError, ReportPolicyOverride,
+    ReportVerdict, parse_aggregate_report, read_aggregate_reports, summarize_aggregate_reports,
+};
+
+const REPORT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feedback xmlns="urn:ietf:params:xml:ns:dmarc-2.0">
+  <report_metadata>
+    <org_name>receiver.example</org_name>
+    <email>noreply-dmarc@receiver.example</email>
+    <report_id>4242</report_id>
+    <date_range><begin>1760745600</begin><end>1760831999</end></date_range>
+  </report_metadata>
+  <policy_published>
+    <domain>example.com</domain>
+    <adkim>r</adkim>
+    <aspf>r</aspf>
+    <p>reject</p>
+    <sp>quarantine</sp>
+    <pct>100</pct>
+  </policy_published>
+  <record>
+    <row>
+      <source_ip>192.0.2.10</source_ip>
+      <count>40</count>
+      <policy_evaluated><disposition>none</disposition><dkim>pass</dkim><spf>pass</spf></policy_evaluated>
+    </row>
+    <identifiers><header_from>example.com</header_from><envelope_from>example.com</envelope_from></identifiers>
+    <auth_results>
+      <dkim><domain>example.com</domain><selector>s1</selector><result>pass</result></dkim>
+      <spf><domain>example.com</domain><scope>mfrom</scope><result>pass</result></spf>
+    </auth_results>
+  </record>
+  <record>
+    <row>
+      <source_ip>192.0.2.10</source_ip>
+      <count>2</count>
+      <policy_evaluated>
+        <disposition>none</disposition><dkim>fail</dkim><spf>fail</spf>
+        <reason><type>mailing_list</type><comment>list.example</comment></reason>
+      </policy_evaluated>
+    </row>
+    <identifiers><header_from>Example.com</header_from></identifiers>
+    <auth_results><spf><domain>list.example</domain><result>softfail</result></spf></auth_results>
+  </record>
+  <record>
+    <row>
+      <source_ip>2001:db8::25</source_ip>
+      <count>7</count>
+      <policy_evaluated><disposition>reject</disposition><dkim>fail</dkim><spf>fail</spf></policy_evaluated>
+    </row>
+    <identifiers><header_from>example.com</header_from></identifiers>
+    <auth_results>
+      <dkim><domain>spoof.example</domain><result>fail</result></dkim>
+      <spf><domain>spoof.example</domain><result>hardfail</result></spf>
+    </auth_results>
+  </record>
+</feedback>
+"#;
+
+#[test]
+fn parses_aggregate_report_records() {
+    let report = parse_aggregate_report(REPORT).expect("valid report");
+    assert_eq!(report.metadata.org_name, "receiver.example");
+    assert_eq!(report.metadata.report_id, "4242");
+    assert_eq!(report.metadata.begin, 1_760_745_600);
+    assert_eq!(report.policy.domain, "example.com");
+    assert_eq!(report.policy.p, "reject");
+    assert_eq!(report.policy.sp.as_deref(), Some("quarantine"));
+    assert_eq!(report.policy.pct, Some(100));
+    assert_eq!(report.records.len(), 3);
+
+    let first = &report.records[0];
+    assert_eq!(first.source_ip, "192.0.2.10".parse::<IpAddr>().unwrap());
+    assert_eq!(first.count, 40);
+    assert!(first.dmarc_pass());
+    assert_eq!(
+        first.dkim_results,
+        vec![ReportDkimResult {
+            domain: "example.com".to_string(),
+            selector: Some("s1".to_string()),
+            result: ReportAuthResult::Pass,
+        }]
+    );
+
+    let forwarded = &report.records[1];
+    assert!(!forwarded.dmarc_pass());
+    assert_eq!(
+        forwarded.reasons,
+        vec![ReportPolicyOverride {
+            kind: "mailing_list".to_string(),
+            comment: Some("list.example".to_string()),
+        }]
+    );
+    assert_eq!(forwarded.spf_results[0].result, ReportAuthResult::SoftFail);
+
+    let spoofed = &report.records[2];
+    assert_eq!(spoofed.disposition, ReportDisposition::Reject);
+    assert_eq!(spoofed.dkim, ReportVerdict::Fail);
+    assert_eq!(spoofed.spf_results[0].result, ReportAuthResult::Fail);
+}
+
+#[test]
+fn reads_gzip_and_zip_containers() {
+    let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    gzip.write_all(REPORT.as_bytes()).unwrap();
+    let gzip = gzip.finish().unwrap();
+    let reports = read_aggregate_reports(&gzip).expect("gzip report");
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].records.len(), 3);
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default();
+    zip.start_file(
+        "receiver.example!example.com!1760745600!1760831999.xml",
+        options,
+    )
+    .unwrap();
+    zip.write_all(REPORT.as_bytes()).unwrap();
+    zip.start_file("README.txt", options).unwrap();
+    zip.write_all(b"ignored").unwrap();
+    let zip = zip.finish().unwrap().into_inner();
+    let reports = read_aggregate_reports(&zip).expect("zip report");
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].metadata.report_id, "4242");
+
+    let reports = read_aggregate_reports(REPORT.as_bytes()).expect("raw XML report");
+    assert_eq!(reports.len(), 1);
+}
+
+#[test]
+fn rejects_incomplete_reports() {
+    let err = parse_aggregate_report(&REPORT.replace("<p>reject</p>", "")).unwrap_err();
+    assert!(matches!(err, ReportError::MissingElement { ref element } if element == "p"));
+
+    let err = parse_aggregate_report(&REPORT.replace("192.0.2.10", "mx.example")).unwrap_err();
+    assert!(matches!(
+        err,
+        ReportError::InvalidValue { ref element, .. } if element == "source_ip"
+    ));
+
+    let err = parse_aggregate_report("<feedback>").unwrap_err();
+    assert!(matches!(err, ReportError::Xml { .. }));
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file("README.txt", zip::write::SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(b"no report").unwrap();
+    let zip = zip.finish().unwrap().into_inner();
+    let err = read_aggregate_reports(&zip).unwrap_err();
+    assert!(matches!(err, ReportError::EmptyArchive));
+}
+
+#[test]
+fn summarizes_pass_rates_per_source() {
+    let report = parse_aggregate_report(REPORT).expect("valid report");
+    let summaries = summarize_aggregate_reports(&[report.clone(), report]);
+    assert_eq!(summaries.len(), 2);
+
+    let legitimate = &summaries[0];
+    assert_eq!(
+        legitimate.source_ip,
+        "192.0.2.10".parse::<IpAddr>().unwrap()
+    );
+    assert_eq!(legitimate.header_from, vec!["example.com".to_string()]);
+    assert_eq!(legitimate.messages, 84);
+    assert_eq!(legitimate.dmarc_pass, 80);
+    assert!((legitimate.pass_rate() - 80.0 / 84.0).abs() < f64::EPSILON);
+    assert!(!legitimate.is_unauthenticated());
+
+    let spoofed = &summaries[1];
+    assert_eq!(spoofed.messages, 14);
+    assert_eq!(spoofed.rejected, 14);
+    assert!(spoofed.is_unauthenticated());
+}
@@ -0,0 +1,166 @@
+use std::net::IpAddr;
+
+/// Rapport agrégé DMARC (RFC 7489 annexe C).
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregateReport {
+    pub metadata: ReportMetadata,
+    pub policy: ReportPublishedPolicy,
+    pub records: Vec<ReportRecord>,
+}
+
+/// `<report_metadata>` : émetteur et période couverte.
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportMetadata {
+    pub org_name: String,
+    pub email: Option<String>,
+    pub extra_contact_info: Option<String>,
+    pub report_id: String,
+    /// Début de la période, en secondes depuis l'époque Unix.
+    pub begin: u64,
+    pub end: u64,
+    #[cfg_attr(
+        feature = "with-serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub errors: Vec<String>,
+}
+
+/// `<policy_published>` : enregistrement DMARC vu par l'émetteur. Les
+/// valeurs sont recopiées telles quelles.
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportPublishedPolicy {
+    pub domain: String,
+    pub adkim: Option<String>,
+    pub aspf: Option<String>,
+    pub p: String,
+    pub sp: Option<String>,
+    /// `np` (DMARCbis).
+    pub np: Option<String>,
+    pub pct: Option<u8>,
+    pub fo: Option<String>,
+}
+
+/// `<record>` : messages d'une même source aux mêmes résultats.
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportRecord {
+    pub source_ip: IpAddr,
+    pub count: u64,
+    pub disposition: ReportDisposition,
+    /// DKIM aligné selon l'émetteur du rapport.
+    pub dkim: ReportVerdict,
+    /// SPF aligné selon l'émetteur du rapport.
+    pub spf: ReportVerdict,
+    #[cfg_attr(
+        feature = "with-serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub reasons: Vec<ReportPolicyOverride>,
+    pub header_from: String,
+    pub envelope_from: Option<String>,
+    pub envelope_to: Option<String>,
+    pub dkim_results: Vec<ReportDkimResult>,
+    pub spf_results: Vec<ReportSpfResult>,
+}
+
+impl ReportRecord {
+    /// DMARC passe si DKIM ou SPF passe aligné.
+    pub fn dmarc_pass(&self) -> bool {
+        self.dkim == ReportVerdict::Pass || self.spf == ReportVerdict::Pass
+    }
+}
+
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "with-serde", serde(rename_all = "snake_case"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportDisposition {
+    None,
+    Quarantine,
+    Reject,
+}
+
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "with-serde", serde(rename_all = "snake_case"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportVerdict {
+    Pass,
+    Fail,
+}
+
+/// `<reason>` : politique non appliquée (liste de diffusion, transfert…).
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportPolicyOverride {
+    pub kind: String,
+    pub comment: Option<String>,
+}
+
+/// Signature DKIM évaluée, alignée ou non.
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportDkimResult {
+    pub domain: String,
+    pub selector: Option<String>,
+    pub result: ReportAuthResult,
+}
+
+/// Évaluation SPF, alignée ou non.
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportSpfResult {
+    pub domain: String,
+    /// `mfrom` ou `helo`.
+    pub scope: Option<String>,
+    pub result: ReportAuthResult,
+}
+
+/// Résultats DKIM et SPF de `<auth_results>` ; les valeurs hors schéma
+/// sont conservées dans `Other`.
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "with-serde", serde(rename_all = "snake_case"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReportAuthResult {
+    None,
+    Pass,
+    Fail,
+    SoftFail,
+    Neutral,
+    Policy,
+    TempError,
+    PermError,
+    Other(String),
+}
+
+/// Synthèse des messages d'une adresse IP source, tous rapports confondus.
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportSourceSummary {
+    pub source_ip: IpAddr,
+    /// Domaines `header_from` vus pour cette source, triés.
+    pub header_from: Vec<String>,
+    pub messages: u64,
+    pub dmarc_pass: u64,
+    pub dkim_pass: u64,
+    pub spf_pass: u64,
+    pub quarantined: u64,
+    pub rejected: u64,
+}
+
+impl ReportSourceSummary {
+    /// Part des messages qui passent DMARC, entre 0 et 1.
+    pub fn pass_rate(&self) -> f64 {
+        if self.messages == 0 {
+            0.0
+        } else {
+            self.dmarc_pass as f64 / self.messages as f64
+        }
+    }
+
+    /// Aucun message de la source ne passe DMARC.
+    pub fn is_unauthenticated(&self) -> bool {
+        self.messages > 0 && self.dmarc_pass == 0
+    }
+}
@@ -0,0 +1,402 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use thiserror::Error;
+use trust_dns_resolver::{
+    Resolver,
+    error::{ResolveError, ResolveErrorKind},
+    lookup::Lookup,
+    proto::{
+        op::ResponseCode,
+        rr::{RData, RecordType},
+    },
+};
+
+use super::DnssecStatus;
+
+/// Types d'enregistrements interrogés par la crate.
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DnsRecordType {
+    Mx,
+    Txt,
+    A,
+    Aaaa,
+    Cname,
+    Tlsa,
+    Ptr,
+}
+
+impl DnsRecordType {
+    /// Mnémonique de la zone (`MX`, `TXT`…).
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Mx => "MX",
+            Self::Txt => "TXT",
+            Self::A => "A",
+            Self::Aaaa => "AAAA",
+            Self::Cname => "CNAME",
+            Self::Tlsa => "TLSA",
+            Self::Ptr => "PTR",
+        }
+    }
+
+    pub(crate) fn from_mnemonic(input: &str) -> Option<Self> {
+        match input.to_ascii_uppercase().as_str() {
+            "MX" => Some(Self::Mx),
+            "TXT" => Some(Self::Txt),
+            "A" => Some(Self::A),
+            "AAAA" => Some(Self::Aaaa),
+            "CNAME" => Some(Self::Cname),
+            "TLSA" => Some(Self::Tlsa),
+            "PTR" => Some(Self::Ptr),
+            _ => None,
+        }
+    }
+}
+
+impl From<DnsRecordType> for RecordType {
+    fn from(rtype: DnsRecordType) -> Self {
+        match rtype {
+            DnsRecordType::Mx => RecordType::MX,
+            DnsRecordType::Txt => RecordType::TXT,
+            DnsRecordType::A => RecordType::A,
+            DnsRecordType::Aaaa => RecordType::AAAA,
+            DnsRecordType::Cname => RecordType::CNAME,
+            DnsRecordType::Tlsa => RecordType::TLSA,
+            DnsRecordType::Ptr => RecordType::PTR,
+        }
+    }
+}
+
+/// Données d'un MX. L'exchange est normalisé (minuscules, sans point final) ;
+/// il est vide pour un null MX (`MX 0 .`).
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MxRdata {
+    pub preference: u16,
+    pub exchange: String,
+}
+
+/// Données d'un TLSA (RFC 6698).
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TlsaRdata {
+    pub usage: u8,
+    pub selector: u8,
+    pub matching_type: u8,
+    pub data: Vec<u8>,
+}
+
+/// Un enregistrement de réponse. Les chaînes TXT sont concaténées ; les noms
+/// (exchange, CNAME, PTR) sont normalisés comme [`MxRdata::exchange`].
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DnsRecord {
+    Mx(MxRdata),
+    Txt(String),
+    A(Ipv4Addr),
+    Aaaa(Ipv6Addr),
+    Cname(String),
+    Tlsa(TlsaRdata),
+    Ptr(String),
+}
+
+impl DnsRecord {
+    pub fn record_type(&self) -> DnsRecordType {
+        match self {
+            Self::Mx(_) => DnsRecordType::Mx,
+            Self::Txt(_) => DnsRecordType::Txt,
+            Self::A(_) => DnsRecordType::A,
+            Self::Aaaa(_) => DnsRecordType::Aaaa,
+            Self::Cname(_) => DnsRecordType::Cname,
+            Self::Tlsa(_) => DnsRecordType::Tlsa,
+            Self::Ptr(_) => DnsRecordType::Ptr,
+        }
+    }
+}
+
+/// Réponse à une requête. `records` est vide pour une réponse NODATA ; la
+/// chaîne de CNAME suivie pour y arriver peut y figurer.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DnsAnswer {
+    pub records: Vec<DnsRecord>,
+    /// TTL minimal de la réponse (TTL négatif pour NODATA), si connu.
+    pub ttl: Option<u32>,
+    /// `Indeterminate` tant que la validation DNSSEC n'est pas activée.
+    pub dnssec: DnssecStatus,
+}
+
+impl DnsAnswer {
+    pub fn new(records: Vec<DnsRecord>, ttl: Option<u32>) -> Self {
+        Self {
+            records,
+            ttl,
+            dnssec: DnssecStatus::Indeterminate,
+        }
+    }
+
+    pub fn with_dnssec(mut self, dnssec: DnssecStatus) -> Self {
+        self.dnssec = dnssec;
+        self
+    }
+
+    pub fn mx(&self) -> Vec<MxRdata> {
+        self.pick(|record| match record {
+            DnsRecord::Mx(mx) => Some(mx.clone()),
+            _ => None,
+        })
+    }
+
+    pub fn txt(&self) -> Vec<String> {
+        self.pick(|record| match record {
+            DnsRecord::Txt(txt) => Some(txt.clone()),
+            _ => None,
+        })
+    }
+
+    /// Adresses A et AAAA de la réponse.
+    pub fn addrs(&self) -> Vec<IpAddr> {
+        self.pick(|record| match record {
+            DnsRecord::A(addr) => Some(IpAddr::V4(*addr)),
+            DnsRecord::Aaaa(addr) => Some(IpAddr::V6(*addr)),
+            _ => None,
+        })
+    }
+
+    pub fn tlsa(&self) -> Vec<TlsaRdata> {
+        self.pick(|record| match record {
+            DnsRecord::Tlsa(tlsa) => Some(tlsa.clone()),
+            _ => None,
+        })
+    }
+
+    /// Première cible CNAME de la réponse.
+    pub fn cname(&self) -> Option<String> {
+        self.records.iter().find_map(|record| match record {
+            DnsRecord::Cname(target) => Some(target.clone()),
+            _ => None,
+        })
+    }
+
+    /// Noms des enregistrements PTR de la réponse.
+    pub fn ptr(&self) -> Vec<String> {
+        self.pick(|record| match record {
+            DnsRecord::Ptr(name) => Some(name.clone()),
+            _ => None,
+        })
+    }
+
+    fn pick<T>(&self, select: impl Fn(&DnsRecord) -> Option<T>) -> Vec<T> {
+        self.records.iter().filter_map(select).collect()
+    }
+}
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum DnsError {
+    /// `negative_ttl` : durée de cache négatif dérivée du SOA de la réponse
+    /// (RFC 2308 §5), si le serveur l'a fourni.
+    #[error("{name}: domain does not exist (NXDOMAIN)")]
+    NxDomain {
+        name: String,
+        dnssec: DnssecStatus,
+        negative_ttl: Option<u32>,
+    },
+    /// Réponse dont la validation DNSSEC a échoué : elle n'est pas utilisée.
+    #[error("{name}: DNSSEC validation failed (bogus): {reason}")]
+    Bogus { name: String, reason: String },
+    #[error("{name}: DNS query timed out")]
+    Timeout { name: String },
+    #[error("{name}: server answered {rcode}")]
+    ServerFailure { name: String, rcode: String },
+    #[error("{name}: malformed {rtype} data: {reason}")]
+    InvalidData {
+        name: String,
+        rtype: &'static str,
+        reason: String,
+    },
+    #[error("{name}: {message}")]
+    Resolver { name: String, message: String },
+}
+
+impl DnsError {
+    pub fn is_nx_domain(&self) -> bool {
+        matches!(self, Self::NxDomain { .. })
+    }
+
+    pub fn is_bogus(&self) -> bool {
+        matches!(self, Self::Bogus { .. })
+    }
+
+    fn from_resolve(name: &str, err: &ResolveError) -> Self {
+        let name = name.to_string();
+        match err.kind() {
+            ResolveErrorKind::NoRecordsFound {
+                response_code: ResponseCode::NXDomain,
+                negative_ttl,
+                ..
+            } => Self::NxDomain {
+                name,
+                dnssec: DnssecStatus::Indeterminate,
+                negative_ttl: *negative_ttl,
+            },
+            ResolveErrorKind::NoRecordsFound { response_code, .. } => Self::ServerFailure {
+                name,
+                rcode: response_code.to_string(),
+            },
+            ResolveErrorKind::Timeout => Self::Timeout { name },
+            _ => Self::Resolver {
+                name,
+                message: err.to_string(),
+            },
+        }
+    }
+}
+
+/// Source d'enregistrements DNS utilisée par `mx`, `auth` et `smtp_verify`.
+///
+/// Seule [`lookup`](Self::lookup) est requise : NODATA donne une réponse vide,
+/// NXDOMAIN l'erreur [`DnsError::NxDomain`]. Les méthodes typées filtrent la
+/// réponse. Implémentée pour le [`Resolver`] trust-dns et pour
+/// [`StaticZone`](super::StaticZone).
+pub trait DnsBackend {
+    fn lookup(&self, name: &str, rtype: DnsRecordType) -> Result<DnsAnswer, DnsError>;
+
+    fn mx(&self, name: &str) -> Result<Vec<MxRdata>, DnsError> {
+        Ok(self.lookup(name, DnsRecordType::Mx)?.mx())
+    }
+
+    fn txt(&self, name: &str) -> Result<Vec<String>, DnsError> {
+        Ok(self.lookup(name, DnsRecordType::Txt)?.txt())
+    }
+
+    fn a(&self, name: &str) -> Result<Vec<Ipv4Addr>, DnsError> {
+        let answer = self.lookup(name, DnsRecordType::A)?;
+        Ok(answer
+            .records
+            .into_iter()
+            .filter_map(|record| match record {
+                DnsRecord::A(addr) => Some(addr),
+                _ => None,
+            })
+            .collect())
+    }
+
+    fn aaaa(&self, name: &str) -> Result<Vec<Ipv6Addr>, DnsError> {
+        let answer = self.lookup(name, DnsRecordType::Aaaa)?;
+        Ok(answer
+            .records
+            .into_iter()
+            .filter_map(|record| match record {
+                DnsRecord::Aaaa(addr) => Some(addr),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Cible du CNAME porté par `name`, s'il existe.
+    fn cname(&self, name: &str) -> Result<Option<String>, DnsError> {
+        Ok(self.lookup(name, DnsRecordType::Cname)?.cname())
+    }
+
+    fn tlsa(&self, name: &str) -> Result<Vec<TlsaRdata>, DnsError> {
+        Ok(self.lookup(name, DnsRecordType::Tlsa)?.tlsa())
+    }
+
+    fn ptr(&self, name: &str) -> Result<Vec<String>, DnsError> {
+        Ok(self.lookup(name, DnsRecordType::Ptr)?.ptr())
+    }
+
+    /// Adresses A puis AAAA de `name`.
+    fn addrs(&self, name: &str) -> Result<Vec<IpAddr>, DnsError> {
+        let mut addrs: Vec<IpAddr> = self.a(name)?.into_iter().map(IpAddr::V4).collect();
+        addrs.extend(self.aaaa(name)?.into_iter().map(IpAddr::V6));
+        Ok(addrs)
+    }
+}
+
+impl<B: DnsBackend + ?Sized> DnsBackend for Box<B> {
+    fn lookup(&self, name: &str, rtype: DnsRecordType) -> Result<DnsAnswer, DnsError> {
+        (**self).lookup(name, rtype)
+    }
+}
+
+/// Traite NXDOMAIN comme une réponse vide, en gardant son statut DNSSEC.
+pub(crate) fn nx_as_answer(result: Result<DnsAnswer, DnsError>) -> Result<DnsAnswer, DnsError> {
+    match result {
+        Err(DnsError::NxDomain { dnssec, .. }) => Ok(DnsAnswer::default().with_dnssec(dnssec)),
+        other => other,
+    }
+}
+
+pub(crate) fn normalize_name(name: &str) -> String {
+    name.trim().trim_end_matches('.').to_ascii_lowercase()
+}
+
+impl DnsBackend for Resolver {
+    fn lookup(&self, name: &str, rtype: DnsRecordType) -> Result<DnsAnswer, DnsError> {
+        answer_from_lookup(name, Resolver::lookup(self, name, rtype.into()))
+    }
+}
+
+/// Convertit une réponse trust-dns (synchrone ou asynchrone).
+pub(crate) fn answer_from_lookup(
+    name: &str,
+    result: Result<Lookup, ResolveError>,
+) -> Result<DnsAnswer, DnsError> {
+    let lookup = match result {
+        Ok(lookup) => lookup,
+        Err(err) => {
+            return match err.kind() {
+                ResolveErrorKind::NoRecordsFound {
+                    response_code: ResponseCode::NoError,
+                    negative_ttl,
+                    ..
+                } => Ok(DnsAnswer::new(Vec::new(), *negative_ttl)),
+                _ => Err(DnsError::from_resolve(name, &err)),
+            };
+        }
+    };
+
+    let mut records = Vec::new();
+    let mut ttl: Option<u32> = None;
+    for record in lookup.record_iter() {
+        ttl = Some(ttl.map_or(record.ttl(), |min| min.min(record.ttl())));
+        if let Some(data) = record.data() {
+            records.extend(convert_rdata(name, data)?);
+        }
+    }
+    Ok(DnsAnswer::new(records, ttl))
+}
+
+fn convert_rdata(name: &str, data: &RData) -> Result<Option<DnsRecord>, DnsError> {
+    let record = match data {
+        RData::MX(mx) => DnsRecord::Mx(MxRdata {
+            preference: mx.preference(),
+            exchange: normalize_name(&mx.exchange().to_utf8()),
+        }),
+        RData::TXT(txt) => {
+            let mut joined = String::new();
+            for piece in txt.txt_data() {
+                let segment = std::str::from_utf8(piece).map_err(|err| DnsError::InvalidData {
+                    name: name.to_string(),
+                    rtype: "TXT",
+                    reason: err.to_string(),
+                })?;
+                joined.push_str(segment);
+            }
+            DnsRecord::Txt(joined)
+        }
+        RData::A(a) => DnsRecord::A(a.0),
+        RData::AAAA(aaaa) => DnsRecord::Aaaa(aaaa.0),
+        RData::CNAME(cname) => DnsRecord::Cname(normalize_name(&cname.0.to_utf8())),
+        RData::PTR(ptr) => DnsRecord::Ptr(normalize_name(&ptr.0.to_utf8())),
+        RData::TLSA(tlsa) => DnsRecord::Tlsa(TlsaRdata {
+            usage: tlsa.cert_usage().into(),
+            selector: tlsa.selector().into(),
+            matching_type: tlsa.matching().into(),
+            data: tlsa.cert_data().to_vec(),
+        }),
+        _ => return Ok(None),
+    };
+    Ok(Some(record))
+}
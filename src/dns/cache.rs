@@ -0,0 +1,408 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::DnssecStatus;
+use super::backend::{DnsAnswer, DnsBackend, DnsError, DnsRecordType, normalize_name};
+
+const DEFAULT_NEGATIVE_TTL: u32 = 300;
+const DEFAULT_MAX_TTL: u32 = 86_400;
+const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+/// Nom, type et résolveur validant ou non : une réponse obtenue sans
+/// validation ne sert jamais une requête validante.
+type CacheKey = (String, DnsRecordType, bool);
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    /// Réponse (éventuellement vide) ou NXDOMAIN.
+    answer: Option<DnsAnswer>,
+    /// Statut DNSSEC d'un NXDOMAIN (celui d'une réponse est dans `answer`).
+    denial: DnssecStatus,
+    /// Expiration en secondes Unix.
+    expires_at: u64,
+}
+
+#[derive(Debug)]
+struct CacheState {
+    entries: HashMap<CacheKey, CacheEntry>,
+    /// Index des entrées par expiration, pour l'éviction.
+    expiry: BTreeSet<(u64, CacheKey)>,
+    negative_ttl: u32,
+    max_ttl: u32,
+    max_entries: usize,
+}
+
+/// Cache DNS partagé, indexé par nom et type d'enregistrement ; les réponses
+/// d'un résolveur validant sont rangées à part.
+///
+/// Les réponses sont conservées pendant leur TTL (plafonné par
+/// [`with_max_ttl`](Self::with_max_ttl)) ; NODATA et NXDOMAIN sont mis en cache
+/// négatif (RFC 2308) selon le TTL dérivé du SOA, à défaut
+/// [`with_negative_ttl`](Self::with_negative_ttl). Les échecs transitoires
+/// (timeout, SERVFAIL) ne le sont jamais. Au-delà de
+/// [`with_max_entries`](Self::with_max_entries), les entrées expirées puis
+/// les plus proches de l'expiration sont évincées. Les clones
+/// partagent le même contenu ; on l'attache à une requête via
+/// [`DnsConfig::with_cache`](super::DnsConfig::with_cache).
+#[derive(Clone)]
+pub struct DnsCache {
+    state: Arc<Mutex<CacheState>>,
+}
+
+impl Default for DnsCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DnsCache {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CacheState {
+                entries: HashMap::new(),
+                expiry: BTreeSet::new(),
+                negative_ttl: DEFAULT_NEGATIVE_TTL,
+                max_ttl: DEFAULT_MAX_TTL,
+                max_entries: DEFAULT_MAX_ENTRIES,
+            })),
+        }
+    }
+
+    /// Durée de conservation d'un NXDOMAIN ou d'un NODATA dont la réponse ne
+    /// donne pas de TTL négatif (300 s par défaut).
+    pub fn with_negative_ttl(self, ttl: Duration) -> Self {
+        self.state().negative_ttl = duration_secs(ttl);
+        self
+    }
+
+    /// TTL maximal retenu, quelle que soit la valeur publiée (1 jour par défaut).
+    pub fn with_max_ttl(self, ttl: Duration) -> Self {
+        self.state().max_ttl = duration_secs(ttl);
+        self
+    }
+
+    /// Nombre maximal d'entrées conservées (10 000 par défaut).
+    pub fn with_max_entries(self, max_entries: usize) -> Self {
+        let mut state = self.state();
+        state.max_entries = max_entries;
+        state.shrink_to(max_entries, unix_now());
+        drop(state);
+        self
+    }
+
+    /// Limite fixée par [`with_max_entries`](Self::with_max_entries).
+    pub fn max_entries(&self) -> usize {
+        self.state().max_entries
+    }
+
+    /// Nombre d'entrées, expirées comprises.
+    pub fn len(&self) -> usize {
+        self.state().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state();
+        state.entries.clear();
+        state.expiry.clear();
+    }
+
+    /// Retire les entrées expirées.
+    pub fn purge_expired(&self) {
+        self.state().purge_expired(unix_now());
+    }
+
+    /// Enveloppe `backend` : les requêtes passent d'abord par ce cache.
+    pub fn wrap<B: DnsBackend>(&self, backend: B) -> CachingBackend<B> {
+        self.wrap_resolver(backend, false)
+    }
+
+    /// Comme [`wrap`](Self::wrap), en séparant les réponses d'un résolveur
+    /// validant (`validating`) des autres.
+    pub(crate) fn wrap_resolver<B>(&self, backend: B, validating: bool) -> CachingBackend<B> {
+        CachingBackend {
+            inner: backend,
+            cache: self.clone(),
+            validating,
+        }
+    }
+
+    pub(crate) fn lookup_at<B: DnsBackend + ?Sized>(
+        &self,
+        backend: &B,
+        name: &str,
+        rtype: DnsRecordType,
+        validating: bool,
+        now: u64,
+    ) -> Result<DnsAnswer, DnsError> {
+        if let Some(hit) = self.cached(name, rtype, validating, now) {
+            return hit;
+        }
+        // Le verrou n'est pas tenu pendant la requête réseau.
+        let outcome = backend.lookup(name, rtype);
+        self.store(name, rtype, validating, &outcome, now);
+        outcome
+    }
+
+    /// Réponse encore valide pour `name`/`rtype`, si elle est en cache.
+    pub(crate) fn cached(
+        &self,
+        name: &str,
+        rtype: DnsRecordType,
+        validating: bool,
+        now: u64,
+    ) -> Option<Result<DnsAnswer, DnsError>> {
+        let key = (normalize_name(name), rtype, validating);
+        self.state()
+            .entries
+            .get(&key)
+            .filter(|entry| entry.expires_at > now)
+            .map(|entry| entry.outcome(name, now))
+    }
+
+    pub(crate) fn store(
+        &self,
+        name: &str,
+        rtype: DnsRecordType,
+        validating: bool,
+        outcome: &Result<DnsAnswer, DnsError>,
+        now: u64,
+    ) {
+        let key = (normalize_name(name), rtype, validating);
+        let mut state = self.state();
+        let ttl = match outcome {
+            Ok(answer) if answer.records.is_empty() => {
+                Some(answer.ttl.unwrap_or(state.negative_ttl))
+            }
+            Ok(answer) => answer.ttl,
+            Err(DnsError::NxDomain { negative_ttl, .. }) => {
+                Some(negative_ttl.unwrap_or(state.negative_ttl))
+            }
+            Err(_) => None,
+        };
+        match ttl.map(|ttl| ttl.min(state.max_ttl)) {
+            Some(ttl) if ttl > 0 => {
+                let denial = match outcome {
+                    Err(DnsError::NxDomain { dnssec, .. }) => *dnssec,
+                    _ => DnssecStatus::Indeterminate,
+                };
+                let entry = CacheEntry {
+                    answer: outcome.as_ref().ok().cloned(),
+                    denial,
+                    expires_at: now + u64::from(ttl),
+                };
+                state.insert(key, entry, now);
+            }
+            _ => state.remove(&key),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, CacheState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl CacheState {
+    fn insert(&mut self, key: CacheKey, entry: CacheEntry, now: u64) {
+        if self.max_entries == 0 {
+            return;
+        }
+        if self.entries.contains_key(&key) {
+            self.remove(&key);
+        } else {
+            self.shrink_to(self.max_entries - 1, now);
+        }
+        self.expiry.insert((entry.expires_at, key.clone()));
+        self.entries.insert(key, entry);
+    }
+
+    fn remove(&mut self, key: &CacheKey) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.expiry.remove(&(entry.expires_at, key.clone()));
+        }
+    }
+
+    /// Retire l'entrée qui expire le plus tôt, si elle expire avant `before`.
+    fn pop_expiring_before(&mut self, before: u64) -> bool {
+        match self.expiry.first() {
+            Some((expires_at, _)) if *expires_at < before => {
+                if let Some((_, key)) = self.expiry.pop_first() {
+                    self.entries.remove(&key);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn purge_expired(&mut self, now: u64) {
+        while self.pop_expiring_before(now + 1) {}
+    }
+
+    /// Ramène le cache à `limit` entrées : d'abord en retirant celles expirées
+    /// à `now`, puis celles qui expirent le plus tôt.
+    fn shrink_to(&mut self, limit: usize, now: u64) {
+        if self.entries.len() <= limit {
+            return;
+        }
+        self.purge_expired(now);
+        while self.entries.len() > limit && self.pop_expiring_before(u64::MAX) {}
+    }
+}
+
+impl CacheEntry {
+    /// Restitue l'entrée avec le TTL restant.
+    fn outcome(&self, name: &str, now: u64) -> Result<DnsAnswer, DnsError> {
+        let remaining = u32::try_from(self.expires_at - now).unwrap_or(u32::MAX);
+        match &self.answer {
+            Some(answer) => Ok(DnsAnswer {
+                ttl: Some(remaining),
+                ..answer.clone()
+            }),
+            None => Err(DnsError::NxDomain {
+                name: name.to_string(),
+                dnssec: self.denial,
+                negative_ttl: Some(remaining),
+            }),
+        }
+    }
+}
+
+impl fmt::Debug for DnsCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DnsCache")
+            .field("entries", &self.len())
+            .finish()
+    }
+}
+
+/// Deux caches sont égaux s'ils partagent le même contenu.
+impl PartialEq for DnsCache {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+impl Eq for DnsCache {}
+
+/// Backend servi par un [`DnsCache`] ; voir [`DnsCache::wrap`].
+#[derive(Debug)]
+pub struct CachingBackend<B> {
+    pub(crate) inner: B,
+    pub(crate) cache: DnsCache,
+    pub(crate) validating: bool,
+}
+
+impl<B: DnsBackend> DnsBackend for CachingBackend<B> {
+    fn lookup(&self, name: &str, rtype: DnsRecordType) -> Result<DnsAnswer, DnsError> {
+        self.cache
+            .lookup_at(&self.inner, name, rtype, self.validating, unix_now())
+    }
+}
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+fn duration_secs(duration: Duration) -> u32 {
+    u32::try_from(duration.as_secs()).unwrap_or(u32::MAX)
+}
+
+#[cfg(feature = "with-serde")]
+mod persist {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::{CacheEntry, DEFAULT_MAX_ENTRIES, DnsCache, unix_now};
+    use crate::dns::DnssecStatus;
+    use crate::dns::backend::{DnsAnswer, DnsRecord, DnsRecordType};
+
+    /// Le statut DNSSEC n'est pas enregistré : le fichier n'étant pas signé,
+    /// les entrées relues servent les seules requêtes non validantes.
+    #[derive(Serialize, Deserialize)]
+    struct PersistedEntry {
+        name: String,
+        rtype: DnsRecordType,
+        expires_at: u64,
+        /// `None` pour un NXDOMAIN.
+        records: Option<Vec<DnsRecord>>,
+    }
+
+    impl DnsCache {
+        /// Charge un cache enregistré par [`save`](Self::save) ; un fichier
+        /// absent donne un cache vide. Les entrées expirées sont ignorées, et
+        /// au-delà de 10 000 entrées celles qui expirent le plus tôt (voir
+        /// [`load_with_max_entries`](Self::load_with_max_entries)).
+        pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+            Self::load_with_max_entries(path, DEFAULT_MAX_ENTRIES)
+        }
+
+        /// Comme [`load`](Self::load), avec la limite de
+        /// [`with_max_entries`](Self::with_max_entries).
+        pub fn load_with_max_entries(
+            path: impl AsRef<Path>,
+            max_entries: usize,
+        ) -> io::Result<Self> {
+            let cache = Self::new().with_max_entries(max_entries);
+            let raw = match fs::read_to_string(path) {
+                Ok(raw) => raw,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(cache),
+                Err(err) => return Err(err),
+            };
+            let mut persisted: Vec<PersistedEntry> = serde_json::from_str(&raw)?;
+            let now = unix_now();
+            persisted.retain(|entry| entry.expires_at > now);
+            // Les entrées qui expirent le plus tard sont gardées en priorité.
+            persisted.sort_by_key(|entry| std::cmp::Reverse(entry.expires_at));
+            let mut state = cache.state();
+            for entry in persisted.into_iter().take(max_entries) {
+                state.insert(
+                    (entry.name, entry.rtype, false),
+                    CacheEntry {
+                        answer: entry.records.map(|records| DnsAnswer::new(records, None)),
+                        denial: DnssecStatus::Indeterminate,
+                        expires_at: entry.expires_at,
+                    },
+                    now,
+                );
+            }
+            drop(state);
+            Ok(cache)
+        }
+
+        /// Enregistre les entrées encore valides au format JSON, sans leur
+        /// statut DNSSEC.
+        pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+            let now = unix_now();
+            let mut persisted = BTreeMap::new();
+            for ((name, rtype, _), entry) in &self.state().entries {
+                if entry.expires_at <= now {
+                    continue;
+                }
+                persisted
+                    .entry((name.clone(), *rtype))
+                    .or_insert_with(|| PersistedEntry {
+                        name: name.clone(),
+                        rtype: *rtype,
+                        expires_at: entry.expires_at,
+                        records: entry.answer.as_ref().map(|answer| answer.records.clone()),
+                    });
+            }
+            let persisted: Vec<PersistedEntry> = persisted.into_values().collect();
+            fs::write(path, serde_json::to_string(&persisted)?)
+        }
+    }
+}
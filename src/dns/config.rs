@@ -0,0 +1,332 @@
+use std::fmt;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::time::Duration;
+
+use thiserror::Error;
+
+use super::dnssec::ConfiguredResolver;
+use super::{DnsBackend, DnsCache};
+use trust_dns_resolver::{
+    Resolver,
+    config::{NameServerConfig, NameServerConfigGroup, Protocol, ResolverConfig, ResolverOpts},
+    system_conf::read_system_conf,
+};
+
+/// Transport utilisé pour joindre un serveur DNS.
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "with-serde", serde(rename_all = "lowercase"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DnsTransport {
+    /// UDP avec repli TCP sur réponse tronquée.
+    #[default]
+    Udp,
+    Tcp,
+    /// DNS-over-TLS (feature `with-dns-over-tls`).
+    Tls,
+    /// DNS-over-HTTPS (feature `with-dns-over-https`).
+    Https,
+}
+
+impl DnsTransport {
+    fn scheme(self) -> &'static str {
+        match self {
+            Self::Udp => "udp",
+            Self::Tcp => "tcp",
+            Self::Tls => "tls",
+            Self::Https => "https",
+        }
+    }
+
+    fn default_port(self) -> u16 {
+        match self {
+            Self::Udp | Self::Tcp => 53,
+            Self::Tls => 853,
+            Self::Https => 443,
+        }
+    }
+}
+
+/// Serveur DNS amont.
+///
+/// Se construit depuis `ip`, `ip:port`, `[ipv6]:port`, éventuellement préfixé
+/// de `udp://`, `tcp://`, `tls://` ou `https://` ; le nom TLS attendu suit un
+/// `#` (`tls://1.1.1.1#cloudflare-dns.com`).
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnsServer {
+    pub addr: SocketAddr,
+    #[cfg_attr(feature = "with-serde", serde(default))]
+    pub transport: DnsTransport,
+    /// Nom présenté par le certificat (obligatoire pour DoT/DoH).
+    #[cfg_attr(feature = "with-serde", serde(default))]
+    pub tls_name: Option<String>,
+}
+
+impl DnsServer {
+    pub fn new(addr: SocketAddr, transport: DnsTransport) -> Self {
+        Self {
+            addr,
+            transport,
+            tls_name: None,
+        }
+    }
+
+    pub fn with_tls_name(mut self, name: impl Into<String>) -> Self {
+        self.tls_name = Some(name.into());
+        self
+    }
+
+    fn name_server_configs(&self, tcp_only: bool) -> io::Result<Vec<NameServerConfig>> {
+        let protocols = match self.transport {
+            DnsTransport::Udp if tcp_only => vec![Protocol::Tcp],
+            DnsTransport::Udp => vec![Protocol::Udp, Protocol::Tcp],
+            DnsTransport::Tcp => vec![Protocol::Tcp],
+            DnsTransport::Tls | DnsTransport::Https => vec![self.encrypted_protocol()?],
+        };
+        Ok(protocols
+            .into_iter()
+            .map(|protocol| {
+                let mut config = NameServerConfig::new(self.addr, protocol);
+                config.tls_dns_name = self.tls_name.clone();
+                config
+            })
+            .collect())
+    }
+
+    fn encrypted_protocol(&self) -> io::Result<Protocol> {
+        if self.tls_name.is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{self}: a TLS name is required (append '#<name>')"),
+            ));
+        }
+        match self.transport {
+            DnsTransport::Https => https_protocol(self),
+            _ => tls_protocol(self),
+        }
+    }
+}
+
+#[cfg(any(feature = "with-dns-over-tls", feature = "with-dns-over-https"))]
+fn tls_protocol(_server: &DnsServer) -> io::Result<Protocol> {
+    Ok(Protocol::Tls)
+}
+
+#[cfg(not(any(feature = "with-dns-over-tls", feature = "with-dns-over-https")))]
+fn tls_protocol(server: &DnsServer) -> io::Result<Protocol> {
+    Err(unsupported(server, "with-dns-over-tls"))
+}
+
+#[cfg(feature = "with-dns-over-https")]
+fn https_protocol(_server: &DnsServer) -> io::Result<Protocol> {
+    Ok(Protocol::Https)
+}
+
+#[cfg(not(feature = "with-dns-over-https"))]
+fn https_protocol(server: &DnsServer) -> io::Result<Protocol> {
+    Err(unsupported(server, "with-dns-over-https"))
+}
+
+#[cfg(not(feature = "with-dns-over-https"))]
+fn unsupported(server: &DnsServer, feature: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{server}: requires the '{feature}' feature"),
+    )
+}
+
+impl fmt::Display for DnsServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}://{}", self.transport.scheme(), self.addr)?;
+        if let Some(name) = &self.tls_name {
+            write!(f, "#{name}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum DnsConfigError {
+    #[error("invalid DNS server '{input}': {reason}")]
+    InvalidServer { input: String, reason: &'static str },
+}
+
+impl FromStr for DnsServer {
+    type Err = DnsConfigError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason| DnsConfigError::InvalidServer {
+            input: input.to_string(),
+            reason,
+        };
+        let trimmed = input.trim();
+        let (transport, rest) = match trimmed.split_once("://") {
+            Some(("udp", rest)) => (DnsTransport::Udp, rest),
+            Some(("tcp", rest)) => (DnsTransport::Tcp, rest),
+            Some(("tls", rest)) => (DnsTransport::Tls, rest),
+            Some(("https", rest)) => (DnsTransport::Https, rest),
+            Some(_) => return Err(invalid("unknown scheme, use udp|tcp|tls|https")),
+            None => (DnsTransport::Udp, trimmed),
+        };
+        let (host, tls_name) = match rest.split_once('#') {
+            Some((_, "")) => return Err(invalid("empty TLS name")),
+            Some((host, name)) => (host, Some(name.to_string())),
+            None => (rest, None),
+        };
+        let addr = match host.parse::<SocketAddr>() {
+            Ok(addr) => addr,
+            Err(_) => {
+                let bare = host
+                    .strip_prefix('[')
+                    .and_then(|h| h.strip_suffix(']'))
+                    .unwrap_or(host);
+                let ip: IpAddr = bare
+                    .parse()
+                    .map_err(|_| invalid("expected an IP address with optional port"))?;
+                SocketAddr::new(ip, transport.default_port())
+            }
+        };
+        Ok(Self {
+            addr,
+            transport,
+            tls_name,
+        })
+    }
+}
+
+/// Options du résolveur. Les champs à `None` gardent la valeur système
+/// (`resolv.conf`) ou celle par défaut de trust-dns.
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "with-serde", serde(default))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DnsConfig {
+    /// Serveurs amont ; vide = serveurs du système.
+    pub nameservers: Vec<DnsServer>,
+    pub timeout_ms: Option<u64>,
+    /// Nombre de tentatives par requête.
+    pub attempts: Option<usize>,
+    /// Nombre d'entrées du cache du résolveur (0 le désactive).
+    pub cache_size: Option<usize>,
+    pub ndots: Option<usize>,
+    pub edns0: Option<bool>,
+    /// N'interroge qu'en TCP (les serveurs UDP passent en TCP).
+    pub tcp_only: bool,
+    /// Valide les réponses DNSSEC (feature `with-dnssec`) ; les réponses
+    /// `Bogus` deviennent des erreurs.
+    pub dnssec: bool,
+    /// Cache partagé entre requêtes (jamais sérialisé).
+    #[cfg_attr(feature = "with-serde", serde(skip))]
+    pub cache: Option<DnsCache>,
+}
+
+impl DnsConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_nameserver(mut self, server: DnsServer) -> Self {
+        self.nameservers.push(server);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_ms = Some(timeout.as_millis().try_into().unwrap_or(u64::MAX));
+        self
+    }
+
+    pub fn with_cache(mut self, cache: DnsCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn with_dnssec(mut self, dnssec: bool) -> Self {
+        self.dnssec = dnssec;
+        self
+    }
+
+    /// Construit le backend décrit par cette configuration : le résolveur
+    /// (validant si [`dnssec`](Self::dnssec)), derrière [`cache`](Self::cache)
+    /// s'il est défini.
+    pub(crate) fn build_backend(&self) -> io::Result<Box<dyn DnsBackend>> {
+        let resolver = ConfiguredResolver {
+            plain: self.build_resolver()?,
+            validating: match self.validating_parts()? {
+                Some((config, opts)) => Some(Resolver::new(config, opts)?),
+                None => None,
+            },
+        };
+        Ok(match &self.cache {
+            Some(cache) => Box::new(cache.wrap_resolver(resolver, self.dnssec)),
+            None => Box::new(resolver),
+        })
+    }
+
+    /// Construit le résolveur synchrone décrit par cette configuration.
+    pub(crate) fn build_resolver(&self) -> io::Result<Resolver> {
+        let (config, opts) = self.resolver_parts()?;
+        Resolver::new(config, opts)
+    }
+
+    /// Configuration du résolveur validant, si DNSSEC est demandé.
+    pub(crate) fn validating_parts(&self) -> io::Result<Option<(ResolverConfig, ResolverOpts)>> {
+        if !self.dnssec {
+            return Ok(None);
+        }
+        if !cfg!(feature = "with-dnssec") {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "DNSSEC validation requires the 'with-dnssec' feature",
+            ));
+        }
+        let (config, mut opts) = self.resolver_parts()?;
+        opts.validate = true;
+        opts.edns0 = true;
+        Ok(Some((config, opts)))
+    }
+
+    pub(crate) fn resolver_parts(&self) -> io::Result<(ResolverConfig, ResolverOpts)> {
+        let (system, mut opts) = if self.nameservers.is_empty() {
+            read_system_conf().map_err(io::Error::other)?
+        } else {
+            (ResolverConfig::new(), ResolverOpts::default())
+        };
+
+        let mut servers = Vec::new();
+        if self.nameservers.is_empty() {
+            servers.extend(
+                system
+                    .name_servers()
+                    .iter()
+                    .filter(|server| !self.tcp_only || server.protocol != Protocol::Udp)
+                    .cloned(),
+            );
+        }
+        for server in &self.nameservers {
+            servers.extend(server.name_server_configs(self.tcp_only)?);
+        }
+        let config = ResolverConfig::from_parts(
+            system.domain().cloned(),
+            system.search().to_vec(),
+            NameServerConfigGroup::from(servers),
+        );
+
+        if let Some(ms) = self.timeout_ms {
+            opts.timeout = Duration::from_millis(ms);
+        }
+        if let Some(attempts) = self.attempts {
+            opts.attempts = attempts;
+        }
+        if let Some(size) = self.cache_size {
+            opts.cache_size = size;
+        }
+        if let Some(ndots) = self.ndots {
+            opts.ndots = ndots;
+        }
+        if let Some(edns0) = self.edns0 {
+            opts.edns0 = edns0;
+        }
+        Ok((config, opts))
+    }
+}
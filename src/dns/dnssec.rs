@@ -0,0 +1,288 @@
+use trust_dns_resolver::{
+    Name, Resolver,
+    error::{ResolveError, ResolveErrorKind},
+    lookup::Lookup,
+    proto::error::ProtoErrorKind,
+    proto::rr::RecordType,
+};
+
+use super::backend::{DnsAnswer, DnsBackend, DnsError, DnsRecordType, answer_from_lookup};
+
+/// Statut DNSSEC d'une réponse (RFC 4035 §4.3).
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "with-serde", serde(rename_all = "lowercase"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DnssecStatus {
+    /// Chaîne de confiance validée jusqu'à la racine.
+    Secure,
+    /// Zone prouvée non signée : la zone parente, validée, n'a pas de DS
+    /// pour elle. Le résolveur ne le conclut pas tant que trust-dns ne
+    /// vérifie pas les NSEC.
+    Insecure,
+    /// Signatures invalides : la réponse ne doit pas être utilisée.
+    Bogus,
+    /// Validation non effectuée (désactivée) ou impossible à conclure.
+    #[default]
+    Indeterminate,
+}
+
+impl DnssecStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Secure => "secure",
+            Self::Insecure => "insecure",
+            Self::Bogus => "bogus",
+            Self::Indeterminate => "indeterminate",
+        }
+    }
+
+    /// Statut d'une décision fondée sur deux réponses : le moins sûr des deux
+    /// (`Bogus` < `Indeterminate` < `Insecure` < `Secure`).
+    pub fn weakest(self, other: Self) -> Self {
+        if other.trust() < self.trust() {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn trust(self) -> u8 {
+        match self {
+            Self::Bogus => 0,
+            Self::Indeterminate => 1,
+            Self::Insecure => 2,
+            Self::Secure => 3,
+        }
+    }
+}
+
+/// Résolveur de la configuration, doublé d'un résolveur validant si
+/// [`DnsConfig::dnssec`](super::DnsConfig::dnssec) est actif.
+pub(crate) struct ConfiguredResolver<R> {
+    pub plain: R,
+    pub validating: Option<R>,
+}
+
+impl DnsBackend for ConfiguredResolver<Resolver> {
+    fn lookup(&self, name: &str, rtype: DnsRecordType) -> Result<DnsAnswer, DnsError> {
+        let Some(validating) = &self.validating else {
+            return answer_from_lookup(name, self.plain.lookup(name, rtype.into()));
+        };
+        match validated(name, validating.lookup(name, rtype.into())) {
+            Validation::Done(outcome) => outcome,
+            Validation::Retry(ValidationFailure::Unsigned) => {
+                let failure = match UnsignedProof::new(name) {
+                    Ok(mut proof) => loop {
+                        let (zone, zone_rtype, validate) = proof.query();
+                        let resolver = if validate { validating } else { &self.plain };
+                        if let Some(failure) = proof.advance(resolver.lookup(zone, zone_rtype)) {
+                            break failure;
+                        }
+                    },
+                    Err(failure) => failure,
+                };
+                unvalidated(name, failure, self.plain.lookup(name, rtype.into()))
+            }
+            Validation::Retry(failure) => {
+                unvalidated(name, failure, self.plain.lookup(name, rtype.into()))
+            }
+        }
+    }
+}
+
+/// Issue d'une requête validante.
+pub(crate) enum Validation {
+    Done(Result<DnsAnswer, DnsError>),
+    /// La validation a échoué : il faut la réponse non validée pour conclure.
+    Retry(ValidationFailure),
+}
+
+pub(crate) enum ValidationFailure {
+    /// Pas de RRSIG : la zone n'est que supposée non signée, voir
+    /// [`UnsignedProof`].
+    Unsigned,
+    /// Signatures présentes mais rejetées, ou absentes d'une zone signée.
+    Rejected(String),
+    /// Zone ni prouvée signée ni prouvée non signée.
+    Unproven,
+}
+
+/// Les réponses négatives (NXDOMAIN, NODATA) ne passent pas par la
+/// vérification NSEC de trust-dns : elles restent `Indeterminate`.
+pub(crate) fn validated(name: &str, result: Result<Lookup, ResolveError>) -> Validation {
+    match proto_kind(&result) {
+        Some(ProtoErrorKind::RrsigsNotPresent { .. }) => {
+            return Validation::Retry(ValidationFailure::Unsigned);
+        }
+        Some(ProtoErrorKind::Message(_) | ProtoErrorKind::Msg(_)) => {
+            let reason = result.err().map(|err| err.to_string()).unwrap_or_default();
+            return Validation::Retry(ValidationFailure::Rejected(reason));
+        }
+        _ => {}
+    }
+    let outcome = answer_from_lookup(name, result);
+    let status = match &outcome {
+        Ok(answer) if !answer.records.is_empty() => DnssecStatus::Secure,
+        _ => DnssecStatus::Indeterminate,
+    };
+    Validation::Done(with_status(outcome, status))
+}
+
+fn proto_kind<T>(result: &Result<T, ResolveError>) -> Option<&ProtoErrorKind> {
+    match result.as_ref().err()?.kind() {
+        ResolveErrorKind::Proto(proto) => Some(proto.kind()),
+        _ => None,
+    }
+}
+
+/// Nombre maximal de requêtes d'une [`UnsignedProof`].
+const MAX_PROOF_QUERIES: usize = 16;
+
+/// Recherche de la délégation d'une réponse sans RRSIG (RFC 4035 §5.2) :
+/// si la zone parente publie un DS pour l'apex de la réponse, la zone est
+/// signée et la réponse est `Rejected`. Une réponse DS elle-même non signée
+/// renvoie la recherche à la zone parente.
+///
+/// trust-dns ne vérifie pas les NSEC des réponses négatives : l'absence de DS
+/// n'est jamais authentifiée et la recherche conclut alors `Unproven`, jamais
+/// `Unsigned`. Elle ne fait pas de requêtes : le pilote envoie
+/// [`query`](Self::query) et transmet la réponse à
+/// [`advance`](Self::advance), de façon synchrone ou non.
+pub(crate) struct UnsignedProof {
+    step: ProofStep,
+    queries: usize,
+}
+
+enum ProofStep {
+    /// SOA (non validé) pour trouver l'apex de la zone de ce nom.
+    Apex(Name),
+    /// DS (validé) de cet apex, servi par la zone parente.
+    Ds(Name),
+}
+
+impl UnsignedProof {
+    pub(crate) fn new(name: &str) -> Result<Self, ValidationFailure> {
+        let mut name = Name::from_ascii(name).map_err(|_| ValidationFailure::Unproven)?;
+        name.set_fqdn(true);
+        Ok(Self {
+            step: ProofStep::Apex(name),
+            queries: 0,
+        })
+    }
+
+    /// Nom, type et résolveur (validant ou non) de la prochaine requête.
+    pub(crate) fn query(&self) -> (Name, RecordType, bool) {
+        match &self.step {
+            ProofStep::Apex(name) => (name.clone(), RecordType::SOA, false),
+            ProofStep::Ds(apex) => (apex.clone(), RecordType::DS, true),
+        }
+    }
+
+    /// Tient compte de la réponse à [`query`](Self::query) ; renvoie la
+    /// conclusion (`Rejected` ou `Unproven`) une fois la recherche terminée.
+    pub(crate) fn advance(
+        &mut self,
+        result: Result<Lookup, ResolveError>,
+    ) -> Option<ValidationFailure> {
+        self.queries += 1;
+        if self.queries > MAX_PROOF_QUERIES {
+            return Some(ValidationFailure::Unproven);
+        }
+        match self.next_step(result) {
+            Ok(step) => {
+                self.step = step;
+                None
+            }
+            Err(conclusion) => Some(conclusion),
+        }
+    }
+
+    /// Étape suivante, ou conclusion en `Err`.
+    fn next_step(
+        &self,
+        result: Result<Lookup, ResolveError>,
+    ) -> Result<ProofStep, ValidationFailure> {
+        let unsigned = matches!(
+            proto_kind(&result),
+            Some(ProtoErrorKind::RrsigsNotPresent { .. })
+        );
+        match (&self.step, result) {
+            (ProofStep::Apex(name), Ok(lookup)) => {
+                if lookup
+                    .record_iter()
+                    .any(|record| record.record_type() == RecordType::SOA && record.name() == name)
+                {
+                    Ok(ProofStep::Ds(name.clone()))
+                } else {
+                    parent_apex(name)
+                }
+            }
+            (ProofStep::Apex(name), Err(err)) => match err.kind() {
+                // NODATA : le SOA d'autorité désigne l'apex.
+                ResolveErrorKind::NoRecordsFound { soa: Some(soa), .. }
+                    if soa.name().zone_of(name) =>
+                {
+                    Ok(ProofStep::Ds(soa.name().clone()))
+                }
+                ResolveErrorKind::NoRecordsFound { .. } => parent_apex(name),
+                _ => Err(ValidationFailure::Unproven),
+            },
+            // Réponse DS elle-même non signée : la parente doit être examinée.
+            (ProofStep::Ds(apex), _) if unsigned => parent_apex(apex),
+            (ProofStep::Ds(apex), Ok(lookup)) if lookup.record_iter().next().is_some() => Err(
+                ValidationFailure::Rejected(format!("unsigned answer, but {apex} has a DS record")),
+            ),
+            // Absence de DS, sans NSEC vérifié.
+            (ProofStep::Ds(_), _) => Err(ValidationFailure::Unproven),
+        }
+    }
+}
+
+/// `name` n'est pas un apex : chercher celui de son parent.
+fn parent_apex(name: &Name) -> Result<ProofStep, ValidationFailure> {
+    if name.is_root() {
+        Err(ValidationFailure::Unproven)
+    } else {
+        Ok(ProofStep::Apex(name.base_name()))
+    }
+}
+
+/// Conclut d'après la réponse non validée : des enregistrements dont la
+/// validation a été rejetée sont `Bogus` (une réponse négative reste
+/// `Indeterminate`), ceux d'une zone ni signée ni prouvée non signée
+/// `Indeterminate`.
+pub(crate) fn unvalidated(
+    name: &str,
+    failure: ValidationFailure,
+    result: Result<Lookup, ResolveError>,
+) -> Result<DnsAnswer, DnsError> {
+    let outcome = answer_from_lookup(name, result);
+    match failure {
+        ValidationFailure::Unsigned => with_status(outcome, DnssecStatus::Insecure),
+        ValidationFailure::Unproven => with_status(outcome, DnssecStatus::Indeterminate),
+        ValidationFailure::Rejected(reason) => match outcome {
+            Ok(answer) if !answer.records.is_empty() => Err(DnsError::Bogus {
+                name: name.to_string(),
+                reason,
+            }),
+            other => with_status(other, DnssecStatus::Indeterminate),
+        },
+    }
+}
+
+fn with_status(
+    outcome: Result<DnsAnswer, DnsError>,
+    status: DnssecStatus,
+) -> Result<DnsAnswer, DnsError> {
+    match outcome {
+        Ok(answer) => Ok(answer.with_dnssec(status)),
+        Err(DnsError::NxDomain {
+            name, negative_ttl, ..
+        }) => Err(DnsError::NxDomain {
+            name,
+            dnssec: status,
+            negative_ttl,
+        }),
+        Err(err) => Err(err),
+    }
+}
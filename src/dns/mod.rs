@@ -0,0 +1,34 @@
+//! Couche DNS partagée par `mx`, `auth` et `smtp_verify`.
+//!
+//! [`DnsConfig`] décrit les serveurs à interroger (UDP/TCP, DoT, DoH) et les
+//! options du résolveur ; une configuration vide reprend `/etc/resolv.conf`.
+//! [`DnsBackend`] abstrait la source des enregistrements : le résolveur
+//! trust-dns en production, [`StaticZone`] pour les tests hors ligne.
+//! [`DnsCache`] mémorise les réponses selon leur TTL d'un appel à l'autre.
+//! Avec [`DnsConfig::dnssec`], chaque réponse porte son [`DnssecStatus`].
+//! Avec la feature `async`, `AsyncDnsBackend` fournit le pendant tokio.
+
+mod backend;
+mod cache;
+mod config;
+mod dnssec;
+#[cfg(feature = "async")]
+mod nonblocking;
+mod zone;
+
+pub use backend::{DnsAnswer, DnsBackend, DnsError, DnsRecord, DnsRecordType, MxRdata, TlsaRdata};
+pub use cache::{CachingBackend, DnsCache};
+pub use config::{DnsConfig, DnsConfigError, DnsServer, DnsTransport};
+pub use dnssec::DnssecStatus;
+#[cfg(feature = "async")]
+pub use nonblocking::AsyncDnsBackend;
+pub use zone::{StaticZone, ZoneError};
+
+pub(crate) use backend::nx_as_answer;
+#[cfg(all(test, feature = "async"))]
+pub(crate) use nonblocking::block_on;
+#[cfg(feature = "async")]
+pub(crate) use nonblocking::replay;
+
+#[cfg(test)]
+mod tests;
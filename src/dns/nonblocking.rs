@@ -0,0 +1,206 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::{Future, ready};
+use std::io;
+use std::panic::{AssertUnwindSafe, catch_unwind, resume_unwind};
+
+use trust_dns_resolver::TokioAsyncResolver;
+
+use super::backend::answer_from_lookup;
+use super::cache::unix_now;
+use super::dnssec::{
+    ConfiguredResolver, UnsignedProof, Validation, ValidationFailure, unvalidated, validated,
+};
+use super::{
+    CachingBackend, DnsAnswer, DnsBackend, DnsCache, DnsConfig, DnsError, DnsRecordType, StaticZone,
+};
+
+/// Pendant asynchrone de [`DnsBackend`] (feature `async`), mêmes conventions :
+/// NODATA donne une réponse vide, NXDOMAIN [`DnsError::NxDomain`].
+///
+/// Les variantes `*_async` n'ont pas de logique propre : elles rejouent la
+/// logique synchrone sur les réponses déjà obtenues. Chaque passe s'arrête à
+/// la première requête inconnue, récupérée ici avant la passe suivante : une
+/// vérification qui fait N requêtes est donc exécutée N + 1 fois, soit un
+/// coût CPU en O(N²) pour N allers-retours réseau.
+pub trait AsyncDnsBackend: Sync {
+    fn lookup(
+        &self,
+        name: &str,
+        rtype: DnsRecordType,
+    ) -> impl Future<Output = Result<DnsAnswer, DnsError>> + Send;
+}
+
+impl AsyncDnsBackend for TokioAsyncResolver {
+    async fn lookup(&self, name: &str, rtype: DnsRecordType) -> Result<DnsAnswer, DnsError> {
+        answer_from_lookup(
+            name,
+            TokioAsyncResolver::lookup(self, name, rtype.into()).await,
+        )
+    }
+}
+
+impl AsyncDnsBackend for ConfiguredResolver<TokioAsyncResolver> {
+    async fn lookup(&self, name: &str, rtype: DnsRecordType) -> Result<DnsAnswer, DnsError> {
+        let Some(validating) = &self.validating else {
+            return AsyncDnsBackend::lookup(&self.plain, name, rtype).await;
+        };
+        match validated(name, validating.lookup(name, rtype.into()).await) {
+            Validation::Done(outcome) => outcome,
+            Validation::Retry(ValidationFailure::Unsigned) => {
+                let failure = match UnsignedProof::new(name) {
+                    Ok(mut proof) => loop {
+                        let (zone, zone_rtype, validate) = proof.query();
+                        let resolver = if validate { validating } else { &self.plain };
+                        if let Some(failure) =
+                            proof.advance(resolver.lookup(zone, zone_rtype).await)
+                        {
+                            break failure;
+                        }
+                    },
+                    Err(failure) => failure,
+                };
+                unvalidated(name, failure, self.plain.lookup(name, rtype.into()).await)
+            }
+            Validation::Retry(failure) => {
+                unvalidated(name, failure, self.plain.lookup(name, rtype.into()).await)
+            }
+        }
+    }
+}
+
+impl AsyncDnsBackend for StaticZone {
+    fn lookup(
+        &self,
+        name: &str,
+        rtype: DnsRecordType,
+    ) -> impl Future<Output = Result<DnsAnswer, DnsError>> + Send {
+        ready(DnsBackend::lookup(self, name, rtype))
+    }
+}
+
+impl<B: AsyncDnsBackend> AsyncDnsBackend for CachingBackend<B> {
+    fn lookup(
+        &self,
+        name: &str,
+        rtype: DnsRecordType,
+    ) -> impl Future<Output = Result<DnsAnswer, DnsError>> + Send {
+        lookup_cached(Some(&self.cache), &self.inner, name, rtype, self.validating)
+    }
+}
+
+/// Résolveur tokio décrit par une [`DnsConfig`], derrière son cache éventuel.
+pub(crate) struct AsyncResolverBackend {
+    resolver: ConfiguredResolver<TokioAsyncResolver>,
+    cache: Option<DnsCache>,
+}
+
+impl AsyncDnsBackend for AsyncResolverBackend {
+    fn lookup(
+        &self,
+        name: &str,
+        rtype: DnsRecordType,
+    ) -> impl Future<Output = Result<DnsAnswer, DnsError>> + Send {
+        lookup_cached(
+            self.cache.as_ref(),
+            &self.resolver,
+            name,
+            rtype,
+            self.resolver.validating.is_some(),
+        )
+    }
+}
+
+impl DnsConfig {
+    pub(crate) fn build_async_backend(&self) -> io::Result<AsyncResolverBackend> {
+        let (config, opts) = self.resolver_parts()?;
+        Ok(AsyncResolverBackend {
+            resolver: ConfiguredResolver {
+                plain: TokioAsyncResolver::tokio(config, opts),
+                validating: self
+                    .validating_parts()?
+                    .map(|(config, opts)| TokioAsyncResolver::tokio(config, opts)),
+            },
+            cache: self.cache.clone(),
+        })
+    }
+}
+
+async fn lookup_cached<B: AsyncDnsBackend + ?Sized>(
+    cache: Option<&DnsCache>,
+    backend: &B,
+    name: &str,
+    rtype: DnsRecordType,
+    validating: bool,
+) -> Result<DnsAnswer, DnsError> {
+    let Some(cache) = cache else {
+        return backend.lookup(name, rtype).await;
+    };
+    let now = unix_now();
+    if let Some(hit) = cache.cached(name, rtype, validating, now) {
+        return hit;
+    }
+    let outcome = backend.lookup(name, rtype).await;
+    cache.store(name, rtype, validating, &outcome, now);
+    outcome
+}
+
+type Query = (String, DnsRecordType);
+
+/// Réponses obtenues jusqu'ici ; une requête absente est notée puis
+/// interrompt la passe (voir [`replay`]).
+#[derive(Default)]
+pub(crate) struct Prefetched {
+    answers: RefCell<HashMap<Query, Result<DnsAnswer, DnsError>>>,
+    missing: RefCell<Option<Query>>,
+}
+
+/// Charge utile du déroulement qui interrompt une passe de [`replay`].
+struct NotFetched;
+
+impl DnsBackend for Prefetched {
+    fn lookup(&self, name: &str, rtype: DnsRecordType) -> Result<DnsAnswer, DnsError> {
+        let query = (name.to_string(), rtype);
+        if let Some(answer) = self.answers.borrow().get(&query) {
+            return answer.clone();
+        }
+        *self.missing.borrow_mut() = Some(query);
+        // Sans passer par le hook de panique : rien n'est affiché.
+        resume_unwind(Box::new(NotFetched))
+    }
+}
+
+/// Exécute `run` (logique synchrone) jusqu'à ce qu'il aboutisse sans requête
+/// inconnue. Une passe s'arrête à la première réponse manquante, récupérée
+/// sur `backend` avant de relancer `run` : la logique ne voit jamais de
+/// réponse provisoire et le résultat est celui qu'aurait donné `run` sur
+/// `backend` directement.
+pub(crate) async fn replay<B, T>(backend: &B, mut run: impl FnMut(&Prefetched) -> T) -> T
+where
+    B: AsyncDnsBackend + ?Sized,
+{
+    let prefetched = Prefetched::default();
+    loop {
+        match catch_unwind(AssertUnwindSafe(|| run(&prefetched))) {
+            Ok(outcome) => return outcome,
+            Err(payload) if payload.is::<NotFetched>() => {}
+            Err(payload) => resume_unwind(payload),
+        }
+        let query = prefetched
+            .missing
+            .take()
+            .expect("an interrupted pass records its query");
+        let answer = backend.lookup(&query.0, query.1).await;
+        prefetched.answers.borrow_mut().insert(query, answer);
+    }
+}
+
+/// Exécute `future` sur un runtime tokio mono-thread (tests).
+#[cfg(test)]
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("tokio runtime")
+        .block_on(future)
+}
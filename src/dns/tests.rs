@@ -0,0 +1,688 @@
+use std::time::Duration;
+
+use trust_dns_resolver::config::Protocol;
+
+use super::backend::normalize_name;
+use std::cell::Cell;
+
+use super::{
+    DnsAnswer, DnsBackend, DnsCache, DnsConfig, DnsConfigError, DnsError, DnsRecord, DnsRecordType,
+    DnsServer, DnsTransport, DnssecStatus, MxRdata, StaticZone, ZoneError,
+};
+
+#[test]
+fn parses_server_specs() {
+    let plain: DnsServer = "192.0.2.53".parse().expect("plain ip");
+    assert_eq!(plain.addr, "192.0.2.53:53".parse().unwrap());
+    assert_eq!(plain.transport, DnsTransport::Udp);
+
+    let tcp: DnsServer = "tcp://[2001:db8::53]:5353".parse().expect("tcp v6");
+    assert_eq!(tcp.addr, "[2001:db8::53]:5353".parse().unwrap());
+    assert_eq!(tcp.transport, DnsTransport::Tcp);
+
+    let dot: DnsServer = "tls://1.1.1.1#cloudflare-dns.com".parse().expect("dot");
+    assert_eq!(dot.addr.port(), 853);
+    assert_eq!(dot.tls_name.as_deref(), Some("cloudflare-dns.com"));
+    assert_eq!(dot.to_string(), "tls://1.1.1.1:853#cloudflare-dns.com");
+
+    let doh: DnsServer = "https://[2606:4700::1111]#one.one.one.one"
+        .parse()
+        .expect("doh");
+    assert_eq!(doh.addr.port(), 443);
+}
+
+#[test]
+fn rejects_invalid_server_specs() {
+    for input in ["dns.example", "quic://1.1.1.1", "tls://1.1.1.1#"] {
+        let err = input.parse::<DnsServer>().expect_err(input);
+        assert!(matches!(err, DnsConfigError::InvalidServer { .. }));
+    }
+}
+
+#[test]
+fn custom_servers_and_options_override_defaults() {
+    let config = DnsConfig::new()
+        .with_nameserver("192.0.2.53:5353".parse().unwrap())
+        .with_timeout(Duration::from_millis(1_500));
+    let config = DnsConfig {
+        attempts: Some(4),
+        cache_size: Some(0),
+        ndots: Some(0),
+        edns0: Some(true),
+        ..config
+    };
+
+    let (resolver, opts) = config.resolver_parts().expect("parts");
+    let protocols: Vec<_> = resolver
+        .name_servers()
+        .iter()
+        .map(|server| (server.socket_addr.port(), server.protocol))
+        .collect();
+    assert_eq!(
+        protocols,
+        vec![(5353, Protocol::Udp), (5353, Protocol::Tcp)]
+    );
+    assert_eq!(opts.timeout, Duration::from_millis(1_500));
+    assert_eq!(opts.attempts, 4);
+    assert_eq!(opts.cache_size, 0);
+    assert_eq!(opts.ndots, 0);
+    assert!(opts.edns0);
+}
+
+#[test]
+fn tcp_only_drops_udp() {
+    let config = DnsConfig {
+        tcp_only: true,
+        ..DnsConfig::new().with_nameserver("192.0.2.53".parse().unwrap())
+    };
+    let (resolver, _) = config.resolver_parts().expect("parts");
+    assert!(
+        resolver
+            .name_servers()
+            .iter()
+            .all(|server| server.protocol == Protocol::Tcp)
+    );
+}
+
+#[test]
+fn encrypted_servers_require_tls_name() {
+    let server = DnsServer::new("1.1.1.1:853".parse().unwrap(), DnsTransport::Tls);
+    let config = DnsConfig::new().with_nameserver(server);
+    assert!(config.resolver_parts().is_err());
+}
+
+#[test]
+fn normalize_name_trims_dot_and_lowercases() {
+    assert_eq!(normalize_name(" MX1.Example.COM. "), "mx1.example.com");
+    assert_eq!(normalize_name("."), "");
+}
+
+#[test]
+fn parses_zone_files() {
+    let zone = StaticZone::from_zone_str(
+        "$ORIGIN example.com.
+$TTL 600
+@            IN MX 10 mx1 ; commentaire
+             MX 0 .
+mx1      60  IN A 192.0.2.25
+_dmarc       TXT \"v=DMARC1; \" \"p=reject\"
+_25._tcp.mx1 TLSA 3 1 1 AB cd01",
+    )
+    .expect("valid zone");
+
+    let mx = zone.lookup("example.com", DnsRecordType::Mx).unwrap();
+    assert_eq!(mx.ttl, Some(600));
+    assert_eq!(
+        zone.mx("example.com").unwrap(),
+        vec![
+            MxRdata {
+                preference: 10,
+                exchange: "mx1.example.com".to_string()
+            },
+            MxRdata {
+                preference: 0,
+                exchange: String::new()
+            },
+        ]
+    );
+    assert_eq!(
+        zone.lookup("MX1.example.com.", DnsRecordType::A)
+            .unwrap()
+            .ttl,
+        Some(60)
+    );
+    assert_eq!(
+        zone.txt("_dmarc.example.com").unwrap(),
+        vec!["v=DMARC1; p=reject".to_string()]
+    );
+    let tlsa = zone.tlsa("_25._tcp.mx1.example.com").unwrap();
+    assert_eq!(tlsa[0].usage, 3);
+    assert_eq!(tlsa[0].data, vec![0xab, 0xcd, 0x01]);
+}
+
+#[test]
+fn rejects_malformed_zone_lines() {
+    let err = StaticZone::from_zone_str("example.com. MX ten mx1.example.com.")
+        .expect_err("bad preference");
+    assert!(matches!(err, ZoneError::Parse { line: 1, .. }));
+}
+
+#[test]
+fn distinguishes_nxdomain_from_nodata() {
+    let zone = StaticZone::new().with_record(
+        "mail.example.com",
+        DnsRecord::A("192.0.2.1".parse().unwrap()),
+    );
+
+    assert!(zone.aaaa("mail.example.com").unwrap().is_empty());
+    // Un nom intermédiaire existe (empty non-terminal).
+    assert!(zone.a("example.com").unwrap().is_empty());
+    assert_eq!(
+        zone.a("other.example.com"),
+        Err(DnsError::NxDomain {
+            name: "other.example.com".to_string(),
+            dnssec: DnssecStatus::Indeterminate,
+            negative_ttl: None,
+        })
+    );
+}
+
+#[test]
+fn follows_cname_chains() {
+    let zone = StaticZone::from_zone_str(
+        "www.example.com. CNAME web.example.com.
+web.example.com. CNAME host.example.net.
+host.example.net. A 192.0.2.80",
+    )
+    .expect("valid zone");
+
+    assert_eq!(
+        zone.a("www.example.com").unwrap(),
+        vec!["192.0.2.80".parse::<std::net::Ipv4Addr>().unwrap()]
+    );
+    assert_eq!(
+        zone.cname("www.example.com").unwrap().as_deref(),
+        Some("web.example.com")
+    );
+    assert_eq!(zone.cname("host.example.net").unwrap(), None);
+}
+
+#[test]
+fn dnssec_validation_needs_the_feature() {
+    assert!(DnsConfig::new().validating_parts().unwrap().is_none());
+
+    let config = DnsConfig::new()
+        .with_nameserver("192.0.2.53".parse().unwrap())
+        .with_dnssec(true);
+    let parts = config.validating_parts();
+    if cfg!(feature = "with-dnssec") {
+        let (_, opts) = parts.unwrap().expect("validation requested");
+        assert!(opts.validate);
+    } else {
+        assert_eq!(parts.unwrap_err().kind(), std::io::ErrorKind::Unsupported);
+    }
+}
+
+#[test]
+fn weakest_dnssec_status_wins() {
+    use DnssecStatus::*;
+    assert_eq!(Secure.weakest(Insecure), Insecure);
+    assert_eq!(Insecure.weakest(Indeterminate), Indeterminate);
+    assert_eq!(Indeterminate.weakest(Bogus), Bogus);
+    assert_eq!(Secure.weakest(Secure), Secure);
+}
+
+#[test]
+fn classifies_failed_validation() {
+    use trust_dns_resolver::error::{ResolveError, ResolveErrorKind};
+    use trust_dns_resolver::lookup::Lookup;
+    use trust_dns_resolver::proto::error::{ProtoError, ProtoErrorKind};
+    use trust_dns_resolver::proto::op::{Query, ResponseCode};
+    use trust_dns_resolver::proto::rr::{Name, RData, RecordType, rdata::A};
+
+    use super::dnssec::{Validation, ValidationFailure, unvalidated, validated};
+
+    let name = Name::from_ascii("example.com.").unwrap();
+    let plain = || {
+        Ok(Lookup::from_rdata(
+            Query::query(name.clone(), RecordType::A),
+            RData::A(A::new(192, 0, 2, 1)),
+        ))
+    };
+    let failure = |kind: ProtoErrorKind| {
+        validated(
+            "example.com",
+            Err(ResolveError::from(ProtoError::from(kind))),
+        )
+    };
+
+    let Validation::Done(secure) = validated("example.com", plain()) else {
+        panic!("validated answer");
+    };
+    assert_eq!(secure.unwrap().dnssec, DnssecStatus::Secure);
+
+    let Validation::Retry(unsigned) = failure(ProtoErrorKind::RrsigsNotPresent {
+        name: name.clone(),
+        record_type: RecordType::A,
+    }) else {
+        panic!("unsigned answer needs a plain lookup");
+    };
+    let insecure = unvalidated("example.com", unsigned, plain()).unwrap();
+    assert_eq!(insecure.dnssec, DnssecStatus::Insecure);
+    assert_eq!(insecure.addrs().len(), 1);
+
+    let Validation::Retry(rejected) = failure(ProtoErrorKind::Message("validation failed")) else {
+        panic!("rejected answer needs a plain lookup");
+    };
+    assert!(matches!(rejected, ValidationFailure::Rejected(_)));
+    let bogus = unvalidated("example.com", rejected, plain()).unwrap_err();
+    assert!(bogus.is_bogus());
+
+    // NXDOMAIN sans preuve NSEC vérifiée : non concluant.
+    let nx = ResolveError::from(ResolveErrorKind::NoRecordsFound {
+        query: Box::new(Query::query(name.clone(), RecordType::A)),
+        soa: None,
+        negative_ttl: Some(60),
+        response_code: ResponseCode::NXDomain,
+        trusted: true,
+    });
+    let Validation::Done(denied) = validated("example.com", Err(nx)) else {
+        panic!("negative answer is not retried");
+    };
+    assert!(matches!(
+        denied,
+        Err(DnsError::NxDomain {
+            dnssec: DnssecStatus::Indeterminate,
+            negative_ttl: Some(60),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn checks_the_parent_ds_of_unsigned_answers() {
+    use trust_dns_resolver::error::{ResolveError, ResolveErrorKind};
+    use trust_dns_resolver::lookup::Lookup;
+    use trust_dns_resolver::proto::error::{ProtoError, ProtoErrorKind};
+    use trust_dns_resolver::proto::op::{Query, ResponseCode};
+    use trust_dns_resolver::proto::rr::rdata::{A, SOA};
+    use trust_dns_resolver::proto::rr::{Name, RData, Record, RecordType};
+
+    use super::dnssec::{UnsignedProof, ValidationFailure, unvalidated};
+
+    let name = |text: &str| Name::from_ascii(text).unwrap();
+    // NODATA dont le SOA d'autorité appartient à `zone`.
+    let nodata = |zone: &str| {
+        let soa = SOA::new(
+            name("ns.example."),
+            name("hostmaster.example."),
+            1,
+            0,
+            0,
+            0,
+            60,
+        );
+        Err(ResolveError::from(ResolveErrorKind::NoRecordsFound {
+            query: Box::new(Query::query(name(zone), RecordType::SOA)),
+            soa: Some(Box::new(Record::from_rdata(name(zone), 60, soa))),
+            negative_ttl: Some(60),
+            response_code: ResponseCode::NoError,
+            trusted: true,
+        }))
+    };
+    let records = || {
+        Ok(Lookup::from_rdata(
+            Query::query(name("example.com."), RecordType::A),
+            RData::A(A::new(192, 0, 2, 1)),
+        ))
+    };
+    let unsigned = || {
+        Err(ResolveError::from(ProtoError::from(
+            ProtoErrorKind::RrsigsNotPresent {
+                name: name("example.com."),
+                record_type: RecordType::DS,
+            },
+        )))
+    };
+    let step = |proof: &mut UnsignedProof, expected: (&str, RecordType, bool), answer| {
+        let (zone, rtype, validating) = proof.query();
+        assert_eq!(
+            (zone, rtype, validating),
+            (name(expected.0), expected.1, expected.2)
+        );
+        proof.advance(answer)
+    };
+
+    // www.example.com est dans example.com, dont com renvoie un NODATA DS
+    // sans NSEC vérifié : la zone n'est pas prouvée non signée.
+    let mut proof = UnsignedProof::new("www.example.com").ok().unwrap();
+    let apex = ("www.example.com.", RecordType::SOA, false);
+    assert!(step(&mut proof, apex, nodata("example.com.")).is_none());
+    let ds = ("example.com.", RecordType::DS, true);
+    let Some(unproven) = step(&mut proof, ds, nodata("com.")) else {
+        panic!("an unauthenticated DS denial ends the proof");
+    };
+    assert!(matches!(unproven, ValidationFailure::Unproven));
+    let answer = unvalidated("www.example.com", unproven, records()).unwrap();
+    assert_eq!(answer.dnssec, DnssecStatus::Indeterminate);
+    assert_eq!(answer.addrs().len(), 1);
+
+    // Un DS publié : la zone est signée et la réponse sans RRSIG est bogus.
+    let mut proof = UnsignedProof::new("example.com").ok().unwrap();
+    let apex = ("example.com.", RecordType::SOA, false);
+    assert!(step(&mut proof, apex, nodata("example.com.")).is_none());
+    assert!(matches!(
+        step(&mut proof, ds, records()),
+        Some(ValidationFailure::Rejected(_))
+    ));
+
+    // Réponse DS non signée : la recherche remonte à la zone parente, qui
+    // est déléguée par DS et aurait donc dû signer sa réponse.
+    let mut proof = UnsignedProof::new("example.com").ok().unwrap();
+    assert!(step(&mut proof, apex, nodata("example.com.")).is_none());
+    assert!(step(&mut proof, ds, unsigned()).is_none());
+    assert!(step(&mut proof, ("com.", RecordType::SOA, false), nodata("com.")).is_none());
+    assert!(matches!(
+        step(&mut proof, ("com.", RecordType::DS, true), records()),
+        Some(ValidationFailure::Rejected(_))
+    ));
+}
+
+#[test]
+fn static_zone_reports_marked_dnssec_status() {
+    let zone = StaticZone::from_zone_str(
+        "www.example.com. CNAME host.example.net.
+host.example.net. A 192.0.2.80
+mail.example.com. A 192.0.2.25",
+    )
+    .expect("valid zone")
+    .with_dnssec("example.com", DnssecStatus::Secure)
+    .with_dnssec("example.net", DnssecStatus::Insecure);
+
+    let answer = zone.lookup("mail.example.com", DnsRecordType::A).unwrap();
+    assert_eq!(answer.dnssec, DnssecStatus::Secure);
+    // Le CNAME sort de la zone signée : la réponse n'est plus que `Insecure`.
+    let answer = zone.lookup("www.example.com", DnsRecordType::A).unwrap();
+    assert_eq!(answer.dnssec, DnssecStatus::Insecure);
+    assert_eq!(
+        zone.lookup("missing.example.com", DnsRecordType::A),
+        Err(DnsError::NxDomain {
+            name: "missing.example.com".to_string(),
+            dnssec: DnssecStatus::Secure,
+            negative_ttl: None,
+        })
+    );
+
+    let bogus = zone.with_dnssec("mail.example.com", DnssecStatus::Bogus);
+    assert!(bogus.a("mail.example.com").unwrap_err().is_bogus());
+}
+
+#[cfg(feature = "with-serde")]
+#[test]
+fn parses_yaml_fixtures() {
+    let zone = StaticZone::from_yaml_str(
+        "example.com:
+  MX: [\"10 mx1.example.com.\"]
+  TXT: [\"v=spf1 -all\"]
+mx1.example.com:
+  A: [192.0.2.25]
+",
+    )
+    .expect("valid yaml");
+
+    assert_eq!(
+        zone.mx("example.com").unwrap()[0].exchange,
+        "mx1.example.com"
+    );
+    assert_eq!(
+        zone.txt("example.com").unwrap(),
+        vec!["v=spf1 -all".to_string()]
+    );
+    assert_eq!(zone.a("mx1.example.com").unwrap().len(), 1);
+}
+
+/// Zone qui compte les requêtes reçues.
+struct Counting {
+    zone: StaticZone,
+    queries: Cell<usize>,
+}
+
+impl Counting {
+    fn new(zone: &str) -> Self {
+        Self {
+            zone: StaticZone::from_zone_str(zone).expect("valid zone"),
+            queries: Cell::new(0),
+        }
+    }
+}
+
+impl DnsBackend for Counting {
+    fn lookup(&self, name: &str, rtype: DnsRecordType) -> Result<DnsAnswer, DnsError> {
+        self.queries.set(self.queries.get() + 1);
+        if name == "timeout.example" {
+            return Err(DnsError::Timeout {
+                name: name.to_string(),
+            });
+        }
+        // NXDOMAIN dont le SOA donne un TTL négatif de 30 s.
+        if name == "soa.example" {
+            return Err(DnsError::NxDomain {
+                name: name.to_string(),
+                dnssec: DnssecStatus::Indeterminate,
+                negative_ttl: Some(30),
+            });
+        }
+        self.zone.lookup(name, rtype)
+    }
+}
+
+#[test]
+fn cache_serves_answers_until_ttl_expires() {
+    let backend = Counting::new("example.com. 60 MX 10 mx1.example.com.");
+    let cache = DnsCache::new();
+
+    let first = cache.lookup_at(&backend, "example.com", DnsRecordType::Mx, false, 1_000);
+    let second = cache.lookup_at(&backend, "EXAMPLE.com.", DnsRecordType::Mx, false, 1_030);
+    assert_eq!(backend.queries.get(), 1);
+    assert_eq!(first.unwrap().records, second.as_ref().unwrap().records);
+    assert_eq!(second.unwrap().ttl, Some(30));
+
+    cache
+        .lookup_at(&backend, "example.com", DnsRecordType::Mx, false, 1_060)
+        .unwrap();
+    assert_eq!(backend.queries.get(), 2);
+}
+
+#[test]
+fn cache_stores_negative_answers_but_not_failures() {
+    let backend = Counting::new("example.com. 60 A 192.0.2.1");
+    let cache = DnsCache::new().with_negative_ttl(Duration::from_secs(120));
+
+    for now in [0, 100] {
+        assert!(
+            cache
+                .lookup_at(&backend, "missing.example", DnsRecordType::A, false, now)
+                .unwrap_err()
+                .is_nx_domain()
+        );
+        // NODATA : conservé selon le TTL négatif de la réponse.
+        let nodata = cache.lookup_at(&backend, "example.com", DnsRecordType::Aaaa, false, now);
+        assert!(nodata.unwrap().records.is_empty());
+    }
+    assert_eq!(backend.queries.get(), 2);
+
+    cache
+        .lookup_at(&backend, "missing.example", DnsRecordType::A, false, 120)
+        .unwrap_err();
+    assert_eq!(backend.queries.get(), 3);
+
+    for now in [0, 1] {
+        cache
+            .lookup_at(&backend, "timeout.example", DnsRecordType::A, false, now)
+            .unwrap_err();
+    }
+    assert_eq!(backend.queries.get(), 5);
+}
+
+#[test]
+fn cache_keeps_nxdomain_for_soa_negative_ttl() {
+    let backend = Counting::new("example.com. 60 A 192.0.2.1");
+    let cache = DnsCache::new().with_negative_ttl(Duration::from_secs(120));
+
+    let first = cache.lookup_at(&backend, "soa.example", DnsRecordType::A, false, 0);
+    let second = cache.lookup_at(&backend, "soa.example", DnsRecordType::A, false, 20);
+    assert_eq!(backend.queries.get(), 1);
+    assert!(first.unwrap_err().is_nx_domain());
+    assert!(matches!(
+        second,
+        Err(DnsError::NxDomain {
+            negative_ttl: Some(10),
+            ..
+        })
+    ));
+    cache
+        .lookup_at(&backend, "soa.example", DnsRecordType::A, false, 30)
+        .unwrap_err();
+    assert_eq!(backend.queries.get(), 2);
+
+    let capped = DnsCache::new().with_max_ttl(Duration::from_secs(10));
+    for now in [0, 10] {
+        capped
+            .lookup_at(&backend, "soa.example", DnsRecordType::A, false, now)
+            .unwrap_err();
+    }
+    assert_eq!(backend.queries.get(), 4);
+}
+
+#[test]
+fn cache_evicts_entries_beyond_capacity() {
+    let backend = Counting::new(
+        "a.example. 300 A 192.0.2.1
+b.example. 100 A 192.0.2.2
+c.example. 200 A 192.0.2.3",
+    );
+    let cache = DnsCache::new().with_max_entries(2);
+    let now = super::cache::unix_now();
+
+    for name in ["a.example", "b.example", "c.example"] {
+        cache
+            .lookup_at(&backend, name, DnsRecordType::A, false, now)
+            .unwrap();
+    }
+    assert_eq!(cache.len(), 2);
+    // `b.example` expirait le plus tôt : c'est lui qui a été évincé.
+    assert!(
+        cache
+            .cached("b.example", DnsRecordType::A, false, now)
+            .is_none()
+    );
+    assert!(
+        cache
+            .cached("a.example", DnsRecordType::A, false, now)
+            .is_some()
+    );
+
+    let cache = cache.with_max_entries(1);
+    assert_eq!(cache.len(), 1);
+    assert!(
+        cache
+            .cached("a.example", DnsRecordType::A, false, now)
+            .is_some()
+    );
+}
+
+#[test]
+fn cache_keeps_dnssec_status() {
+    let mut backend = Counting::new("example.com. 60 MX 10 mx1.example.com.");
+    backend.zone.set_dnssec("example.com", DnssecStatus::Secure);
+    let cache = DnsCache::new();
+
+    for now in [0, 10] {
+        let answer = cache.lookup_at(&backend, "example.com", DnsRecordType::Mx, false, now);
+        assert_eq!(answer.unwrap().dnssec, DnssecStatus::Secure);
+        let missing = cache.lookup_at(
+            &backend,
+            "missing.example.com",
+            DnsRecordType::Mx,
+            false,
+            now,
+        );
+        assert!(matches!(
+            missing,
+            Err(DnsError::NxDomain {
+                dnssec: DnssecStatus::Secure,
+                ..
+            })
+        ));
+    }
+    assert_eq!(backend.queries.get(), 2);
+
+    // Une requête validante ne réutilise pas une réponse non validée.
+    cache
+        .lookup_at(&backend, "example.com", DnsRecordType::Mx, true, 20)
+        .unwrap();
+    assert_eq!(backend.queries.get(), 3);
+    assert_eq!(cache.len(), 3);
+}
+
+#[test]
+fn cache_caps_ttl() {
+    let backend = Counting::new("example.com. 604800 TXT \"v=spf1 -all\"");
+    let cache = DnsCache::new().with_max_ttl(Duration::from_secs(3_600));
+
+    cache
+        .lookup_at(&backend, "example.com", DnsRecordType::Txt, false, 0)
+        .unwrap();
+    cache
+        .lookup_at(&backend, "example.com", DnsRecordType::Txt, false, 3_600)
+        .unwrap();
+    assert_eq!(backend.queries.get(), 2);
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+fn cache_wraps_backends_for_checks() {
+    let cache = DnsCache::new();
+    let backend = cache.wrap(Counting::new("example.com. MX 10 mx1.example.com."));
+
+    assert_eq!(backend.mx("example.com").unwrap().len(), 1);
+    assert_eq!(backend.mx("example.com").unwrap().len(), 1);
+    assert_eq!(cache.len(), 1);
+    assert_eq!(
+        DnsConfig::new().with_cache(cache.clone()).cache,
+        Some(cache)
+    );
+}
+
+#[cfg(feature = "with-serde")]
+#[test]
+fn cache_persists_to_disk() {
+    let path =
+        std::env::temp_dir().join(format!("mailcheck-dns-cache-{}.json", std::process::id()));
+    let mut backend = Counting::new(
+        "example.com. 3600 MX 10 mx1.example.com.
+example.org. 600 MX 10 mx1.example.org.",
+    );
+    backend.zone.set_dnssec("example.com", DnssecStatus::Secure);
+    let cache = DnsCache::new();
+    let backend = cache.wrap_resolver(backend, true);
+    backend.mx("example.com").unwrap();
+    backend.mx("example.org").unwrap();
+    assert!(backend.a("missing.example").unwrap_err().is_nx_domain());
+    cache.save(&path).expect("save cache");
+
+    let truncated = DnsCache::load_with_max_entries(&path, 2).expect("load cache");
+    assert_eq!(truncated.len(), 2);
+    assert!(
+        truncated
+            .cached("missing.example", DnsRecordType::A, false, 0)
+            .is_none()
+    );
+
+    let reloaded = DnsCache::load(&path).expect("load cache");
+    assert_eq!(reloaded.len(), 3);
+    let offline = reloaded.wrap(StaticZone::new());
+    let answer = offline.lookup("example.com", DnsRecordType::Mx).unwrap();
+    assert_eq!(answer.dnssec, DnssecStatus::Indeterminate);
+    assert_eq!(
+        offline.mx("example.com").unwrap()[0].exchange,
+        "mx1.example.com"
+    );
+    assert!(offline.a("missing.example").unwrap_err().is_nx_domain());
+    // Le fichier n'est pas signé : rien n'en est servi à une requête validante.
+    let validating = reloaded.wrap_resolver(StaticZone::new(), true);
+    assert!(validating.mx("example.com").unwrap_err().is_nx_domain());
+
+    let forged = r#"[{"name":"example.net","rtype":"Mx","expires_at":18446744073709551615,"records":[],"dnssec":"secure"}]"#;
+    std::fs::write(&path, forged).expect("write cache");
+    let reloaded = DnsCache::load(&path).expect("load cache");
+    std::fs::remove_file(&path).ok();
+    let answer = reloaded
+        .cached("example.net", DnsRecordType::Mx, false, 0)
+        .expect("cached")
+        .unwrap();
+    assert_eq!(answer.dnssec, DnssecStatus::Indeterminate);
+
+    let missing = DnsCache::load(path.with_extension("absent")).expect("missing file");
+    assert!(missing.is_empty());
+}
@@ -0,0 +1,414 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use thiserror::Error;
+
+use super::DnssecStatus;
+use super::backend::{
+    DnsAnswer, DnsBackend, DnsError, DnsRecord, DnsRecordType, MxRdata, TlsaRdata, normalize_name,
+};
+
+const DEFAULT_TTL: u32 = 300;
+const MAX_CNAME_CHAIN: usize = 8;
+
+#[derive(Debug, Error)]
+pub enum ZoneError {
+    #[error("line {line}: {message}")]
+    Parse { line: usize, message: String },
+    #[error("{name}: {message}")]
+    InvalidRecord { name: String, message: String },
+    #[error("read zone {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[cfg(feature = "with-serde")]
+    #[error("invalid YAML zone: {source}")]
+    Yaml {
+        #[source]
+        source: serde_yaml::Error,
+    },
+    #[cfg(not(feature = "with-serde"))]
+    #[error("YAML zones require the 'with-serde' feature")]
+    YamlUnsupported,
+}
+
+impl ZoneError {
+    fn parse(line: usize, message: impl Into<String>) -> Self {
+        Self::Parse {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+/// Backend DNS en mémoire, pour les tests et l'exécution hors ligne.
+///
+/// Un nom absent de la zone (et sans descendant) répond NXDOMAIN, un nom
+/// présent sans le type demandé répond NODATA ; les CNAME sont suivis comme
+/// le ferait un résolveur récursif. Les réponses sont `Indeterminate` côté
+/// DNSSEC, sauf statut fixé par [`set_dnssec`](Self::set_dnssec).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StaticZone {
+    records: BTreeMap<String, Vec<(u32, DnsRecord)>>,
+    dnssec: BTreeMap<String, DnssecStatus>,
+}
+
+impl StaticZone {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, name: &str, ttl: u32, record: DnsRecord) {
+        self.records
+            .entry(normalize_name(name))
+            .or_default()
+            .push((ttl, record));
+    }
+
+    pub fn with_record(mut self, name: &str, record: DnsRecord) -> Self {
+        self.insert(name, DEFAULT_TTL, record);
+        self
+    }
+
+    /// Statut DNSSEC des réponses pour `zone` et ses sous-domaines (le
+    /// suffixe le plus long l'emporte) ; `Bogus` fait échouer la requête.
+    pub fn set_dnssec(&mut self, zone: &str, status: DnssecStatus) {
+        self.dnssec.insert(normalize_name(zone), status);
+    }
+
+    pub fn with_dnssec(mut self, zone: &str, status: DnssecStatus) -> Self {
+        self.set_dnssec(zone, status);
+        self
+    }
+
+    /// Charge un fichier de zone, ou un fixture YAML si l'extension est
+    /// `.yaml`/`.yml`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ZoneError> {
+        let path = path.as_ref();
+        let raw = std::fs::read_to_string(path).map_err(|source| ZoneError::Io {
+            path: path.display().to_string(),
+            source,
+        })?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => Self::from_yaml_str(&raw),
+            _ => Self::from_zone_str(&raw),
+        }
+    }
+
+    /// Analyse un sous-ensemble du format RFC 1035 : `$ORIGIN`, `$TTL`, une
+    /// entrée par ligne `nom [ttl] [IN] type rdata`, `@` et noms relatifs,
+    /// propriétaire omis (ligne indentée) = précédent, commentaires `;`.
+    pub fn from_zone_str(input: &str) -> Result<Self, ZoneError> {
+        let mut zone = Self::new();
+        let mut origin: Option<String> = None;
+        let mut default_ttl = DEFAULT_TTL;
+        let mut previous: Option<String> = None;
+
+        for (idx, raw_line) in input.lines().enumerate() {
+            let line_no = idx + 1;
+            let line = strip_comment(raw_line);
+            if line.trim().is_empty() {
+                continue;
+            }
+            let tokens = tokenize(line).map_err(|msg| ZoneError::parse(line_no, msg))?;
+
+            match tokens[0].as_str() {
+                "$ORIGIN" => {
+                    let value = tokens
+                        .get(1)
+                        .ok_or_else(|| ZoneError::parse(line_no, "$ORIGIN without a name"))?;
+                    origin = Some(normalize_name(value));
+                    continue;
+                }
+                "$TTL" => {
+                    default_ttl = tokens
+                        .get(1)
+                        .and_then(|ttl| ttl.parse().ok())
+                        .ok_or_else(|| ZoneError::parse(line_no, "invalid $TTL"))?;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let mut rest = tokens.as_slice();
+            let owner = if line.starts_with(char::is_whitespace) {
+                previous
+                    .clone()
+                    .ok_or_else(|| ZoneError::parse(line_no, "record without owner name"))?
+            } else {
+                let owner = absolute_name(&rest[0], origin.as_deref());
+                rest = &rest[1..];
+                owner
+            };
+
+            let mut ttl = default_ttl;
+            if let Some(value) = rest.first().and_then(|token| token.parse().ok()) {
+                ttl = value;
+                rest = &rest[1..];
+            }
+            if rest
+                .first()
+                .is_some_and(|token| token.eq_ignore_ascii_case("IN"))
+            {
+                rest = &rest[1..];
+            }
+            let (rtype, rdata) = rest
+                .split_first()
+                .ok_or_else(|| ZoneError::parse(line_no, "missing record type"))?;
+            let rtype = DnsRecordType::from_mnemonic(rtype)
+                .ok_or_else(|| ZoneError::parse(line_no, format!("unsupported type {rtype}")))?;
+            let record = parse_rdata(rtype, rdata, origin.as_deref())
+                .map_err(|msg| ZoneError::parse(line_no, msg))?;
+
+            zone.insert(&owner, ttl, record);
+            previous = Some(owner);
+        }
+        Ok(zone)
+    }
+
+    /// Fixture YAML : `nom: { TYPE: [rdata, …] }`, rdata au format zone
+    /// (`MX: ["10 mx1.example.com."]`). Les noms sont absolus.
+    #[cfg(feature = "with-serde")]
+    pub fn from_yaml_str(input: &str) -> Result<Self, ZoneError> {
+        let document: BTreeMap<String, BTreeMap<String, Vec<serde_yaml::Value>>> =
+            serde_yaml::from_str(input).map_err(|source| ZoneError::Yaml { source })?;
+        let mut zone = Self::new();
+        for (name, types) in document {
+            for (rtype, values) in types {
+                let invalid = |message: String| ZoneError::InvalidRecord {
+                    name: name.clone(),
+                    message,
+                };
+                let parsed = DnsRecordType::from_mnemonic(&rtype)
+                    .ok_or_else(|| invalid(format!("unsupported type {rtype}")))?;
+                for value in values {
+                    let text = match value {
+                        serde_yaml::Value::String(text) => text,
+                        other => serde_yaml::to_string(&other)
+                            .map_err(|source| ZoneError::Yaml { source })?
+                            .trim()
+                            .to_string(),
+                    };
+                    let tokens = if parsed == DnsRecordType::Txt {
+                        vec![text]
+                    } else {
+                        tokenize(&text).map_err(invalid)?
+                    };
+                    let record = parse_rdata(parsed, &tokens, None)
+                        .map_err(|msg| invalid(format!("{rtype}: {msg}")))?;
+                    zone.insert(&name, DEFAULT_TTL, record);
+                }
+            }
+        }
+        Ok(zone)
+    }
+
+    #[cfg(not(feature = "with-serde"))]
+    pub fn from_yaml_str(_input: &str) -> Result<Self, ZoneError> {
+        Err(ZoneError::YamlUnsupported)
+    }
+
+    fn dnssec_status(&self, name: &str) -> DnssecStatus {
+        let mut candidate = name;
+        loop {
+            if let Some(status) = self.dnssec.get(candidate) {
+                return *status;
+            }
+            match candidate.split_once('.') {
+                Some((_, parent)) => candidate = parent,
+                None => return DnssecStatus::Indeterminate,
+            }
+        }
+    }
+
+    fn exists(&self, name: &str) -> bool {
+        let suffix = format!(".{name}");
+        self.records
+            .keys()
+            .any(|key| key == name || key.ends_with(&suffix))
+    }
+
+    fn matching(&self, name: &str, rtype: DnsRecordType) -> Vec<(u32, DnsRecord)> {
+        self.records
+            .get(name)
+            .map(|records| {
+                records
+                    .iter()
+                    .filter(|(_, record)| record.record_type() == rtype)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl DnsBackend for StaticZone {
+    fn lookup(&self, name: &str, rtype: DnsRecordType) -> Result<DnsAnswer, DnsError> {
+        let mut current = normalize_name(name);
+        let mut chain = Vec::new();
+        let mut dnssec = DnssecStatus::Secure;
+        for _ in 0..MAX_CNAME_CHAIN {
+            dnssec = dnssec.weakest(self.dnssec_status(&current));
+            if dnssec == DnssecStatus::Bogus {
+                return Err(DnsError::Bogus {
+                    name: name.to_string(),
+                    reason: format!("{current} is marked bogus"),
+                });
+            }
+            if !self.exists(&current) {
+                return Err(DnsError::NxDomain {
+                    name: name.to_string(),
+                    dnssec,
+                    negative_ttl: None,
+                });
+            }
+            let found = self.matching(&current, rtype);
+            if !found.is_empty() || rtype == DnsRecordType::Cname {
+                chain.extend(found);
+                break;
+            }
+            match self.matching(&current, DnsRecordType::Cname).pop() {
+                Some((ttl, DnsRecord::Cname(target))) => {
+                    chain.push((ttl, DnsRecord::Cname(target.clone())));
+                    current = target;
+                }
+                _ => break,
+            }
+        }
+
+        let ttl = chain.iter().map(|(ttl, _)| *ttl).min();
+        let records = chain.into_iter().map(|(_, record)| record).collect();
+        Ok(DnsAnswer::new(records, ttl.or(Some(DEFAULT_TTL))).with_dnssec(dnssec))
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (idx, ch) in line.char_indices() {
+        match ch {
+            '"' => quoted = !quoted,
+            ';' if !quoted => return &line[..idx],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Découpe une ligne en jetons ; les chaînes entre guillemets restent entières.
+fn tokenize(line: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(&ch) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+        } else if ch == '"' {
+            chars.next();
+            let mut token = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => token.extend(chars.next()),
+                    Some(other) => token.push(other),
+                    None => return Err("unterminated quoted string".to_string()),
+                }
+            }
+            tokens.push(token);
+        } else {
+            let mut token = String::new();
+            while let Some(&next) = chars.peek() {
+                if next.is_whitespace() {
+                    break;
+                }
+                token.push(next);
+                chars.next();
+            }
+            tokens.push(token);
+        }
+    }
+    if tokens.is_empty() {
+        return Err("empty record".to_string());
+    }
+    Ok(tokens)
+}
+
+fn absolute_name(name: &str, origin: Option<&str>) -> String {
+    match (name, origin) {
+        ("@", Some(origin)) => origin.to_string(),
+        (name, _) if name.ends_with('.') => normalize_name(name),
+        (name, Some(origin)) => normalize_name(&format!("{name}.{origin}")),
+        (name, None) => normalize_name(name),
+    }
+}
+
+fn parse_rdata(
+    rtype: DnsRecordType,
+    tokens: &[String],
+    origin: Option<&str>,
+) -> Result<DnsRecord, String> {
+    let field = |idx: usize| {
+        tokens
+            .get(idx)
+            .map(String::as_str)
+            .ok_or_else(|| format!("{} record is missing fields", rtype.as_str()))
+    };
+    let number = |idx: usize| -> Result<u16, String> {
+        field(idx)?
+            .parse()
+            .map_err(|_| format!("{}: invalid number '{}'", rtype.as_str(), tokens[idx]))
+    };
+
+    let record = match rtype {
+        DnsRecordType::Mx => {
+            let exchange = field(1)?;
+            let exchange = if exchange == "." {
+                String::new()
+            } else {
+                absolute_name(exchange, origin)
+            };
+            DnsRecord::Mx(MxRdata {
+                preference: number(0)?,
+                exchange,
+            })
+        }
+        DnsRecordType::Txt => {
+            field(0)?;
+            DnsRecord::Txt(tokens.concat())
+        }
+        DnsRecordType::A => DnsRecord::A(field(0)?.parse().map_err(|_| "invalid IPv4 address")?),
+        DnsRecordType::Aaaa => {
+            DnsRecord::Aaaa(field(0)?.parse().map_err(|_| "invalid IPv6 address")?)
+        }
+        DnsRecordType::Cname => DnsRecord::Cname(absolute_name(field(0)?, origin)),
+        DnsRecordType::Ptr => DnsRecord::Ptr(absolute_name(field(0)?, origin)),
+        DnsRecordType::Tlsa => {
+            let byte = |idx| {
+                number(idx)
+                    .and_then(|n| u8::try_from(n).map_err(|_| "TLSA field out of range".into()))
+            };
+            DnsRecord::Tlsa(TlsaRdata {
+                usage: byte(0)?,
+                selector: byte(1)?,
+                matching_type: byte(2)?,
+                data: decode_hex(&tokens.get(3..).unwrap_or_default().concat())?,
+            })
+        }
+    };
+    Ok(record)
+}
+
+fn decode_hex(input: &str) -> Result<Vec<u8>, String> {
+    if input.is_empty() || input.len() % 2 != 0 {
+        return Err("TLSA data must be non-empty hex".to_string());
+    }
+    (0..input.len())
+        .step_by(2)
+        .map(|idx| {
+            input
+                .get(idx..idx + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| "TLSA data must be hex".to_string())
+        })
+        .collect()
+}
@@ -8,8 +8,15 @@ pub use validator::{
     SpecCharacters,
     SpecClass,
     SpecFinding,
+    SpecHintRule,
+    SpecHintSubstitution,
+    SpecLocale,
     SpecOptions,
+    SpecRiskContribution,
+    SpecRiskFactor,
+    SpecRiskWeights,
     SpecSegment,
+    SpecTransliteration,
     ValidationMode,
     ValidationReport,
     normalize_email, // << nouveau
@@ -18,23 +25,100 @@ pub use validator::{
     validate_email_with_spec,
 };
 
+#[cfg(any(
+    feature = "with-mx",
+    feature = "with-smtp-verify",
+    feature = "with-auth-records",
+    feature = "with-dane"
+))]
+pub mod dns;
+#[cfg(any(
+    feature = "with-mx",
+    feature = "with-smtp-verify",
+    feature = "with-auth-records",
+    feature = "with-dane"
+))]
+#[cfg(feature = "async")]
+pub use dns::AsyncDnsBackend;
+#[cfg(any(
+    feature = "with-mx",
+    feature = "with-smtp-verify",
+    feature = "with-auth-records",
+    feature = "with-dane"
+))]
+pub use dns::{
+    CachingBackend, DnsAnswer, DnsBackend, DnsCache, DnsConfig, DnsConfigError, DnsError,
+    DnsRecord, DnsRecordType, DnsServer, DnsTransport, DnssecStatus, MxRdata, StaticZone,
+    TlsaRdata, ZoneError,
+};
+
 #[cfg(feature = "with-mx")]
 pub mod mx;
+#[cfg(all(feature = "with-mx", feature = "with-dane"))]
+pub use mx::MxDaneStatus;
 #[cfg(feature = "with-mx")]
-pub use mx::{Error as MxError, MxRecord, MxStatus, check_mx};
+pub use mx::{
+    Error as MxError, MxLint, MxLookupOptions, MxRecord, MxStatus, check_mx, check_mx_with_backend,
+    check_mx_with_options,
+};
+#[cfg(all(feature = "with-mx", feature = "async"))]
+pub use mx::{check_mx_async, check_mx_async_with_backend};
 
 #[cfg(feature = "with-smtp-verify")]
 pub mod smtp_verify;
 #[cfg(feature = "with-smtp-verify")]
 pub use smtp_verify::{
     Existence, SmtpProbeOptions, SmtpProbeReport, SmtpVerifyError, check_mailaddress_exists,
+    check_mailaddress_exists_with_backend,
+};
+#[cfg(all(feature = "with-smtp-verify", feature = "async"))]
+pub use smtp_verify::{
+    check_mailaddress_exists_async, check_mailaddress_exists_async_with_backend,
 };
 
 #[cfg(feature = "with-auth-records")]
 pub mod auth;
+#[cfg(all(feature = "with-mta-sts", feature = "async"))]
+pub use auth::{AsyncPolicyFetcher, check_auth_records_async_with_fetcher};
 #[cfg(feature = "with-auth-records")]
 pub use auth::{
-    AuthError, AuthLookupOptions, AuthStatus, DkimIssue, DkimPolicyStatus, DkimSelectorStatus,
-    DkimStatus, DkimWeakness, DmarcIssue, DmarcPolicy, DmarcStatus, DmarcWeakness, SpfIssue,
-    SpfQualifier, SpfStatus, check_auth_records, check_auth_records_with_options,
+    AuthError, AuthLookupOptions, AuthStatus, BimiDmarcIssue, BimiIssue, BimiSelectorStatus,
+    BimiStatus, DkimIssue, DkimKey, DkimKeyType, DkimPolicyStatus, DkimSelectorStatus, DkimStatus,
+    DkimWeakness, DmarcAlignment, DmarcFailureOption, DmarcIssue, DmarcOrigin, DmarcPolicy,
+    DmarcRecord, DmarcReportAuthorization, DmarcScope, DmarcStatus, DmarcUri, DmarcWeakness,
+    DnssecLookup, PublicSuffixList, SpfCheck, SpfFlatRecord, SpfFlattenError, SpfFlattenOptions,
+    SpfFlattened, SpfIssue, SpfLink, SpfLint, SpfQualifier, SpfResult, SpfStatus, SpfTraceEvent,
+    SpfTraceStep, SpfTree, SpfTreeNode, TlsRptIssue, TlsRptStatus, TlsRptUri, check_auth_records,
+    check_auth_records_with_backend, check_auth_records_with_options, check_spf_host,
+    check_spf_host_with_backend, flatten_spf, flatten_spf_with_backend, lint_spf,
+    lint_spf_with_backend,
+};
+#[cfg(feature = "with-mta-sts")]
+pub use auth::{
+    HttpsPolicyFetcher, MtaStsIssue, MtaStsMode, MtaStsPolicy, MtaStsStatus, PolicyFetchError,
+    PolicyFetcher, check_auth_records_with_fetcher,
+};
+#[cfg(all(feature = "with-auth-records", feature = "async"))]
+pub use auth::{
+    check_auth_records_async, check_auth_records_async_with_backend, check_spf_host_async,
+    check_spf_host_async_with_backend, flatten_spf_async, flatten_spf_async_with_backend,
+    lint_spf_async, lint_spf_async_with_backend,
+};
+
+#[cfg(feature = "with-dmarc-report")]
+pub mod dmarc_report;
+#[cfg(feature = "with-dmarc-report")]
+pub use dmarc_report::{
+    AggregateReport, ReportAuthResult, ReportDisposition, ReportDkimResult, ReportError,
+    ReportMetadata, ReportPolicyOverride, ReportPublishedPolicy, ReportRecord, ReportSourceSummary,
+    ReportSpfResult, ReportVerdict, load_aggregate_reports, parse_aggregate_report,
+    read_aggregate_reports, summarize_aggregate_reports,
+};
+
+#[cfg(feature = "with-dane")]
+pub mod dane;
+#[cfg(feature = "with-dane")]
+pub use dane::{
+    CertificateMatch, DaneError, DaneState, DaneStatus, TlsaIssue, TlsaRecord, check_dane,
+    check_dane_with_backend, check_dane_with_options,
 };
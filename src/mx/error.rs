@@ -17,7 +17,7 @@ pub enum MxError {
     #[error("MX lookup failed: {source}")]
     Lookup {
         #[source]
-        source: trust_dns_resolver::error::ResolveError,
+        source: crate::dns::DnsError,
     },
 }
 
@@ -30,7 +30,7 @@ impl MxError {
         Self::ResolverInit { source }
     }
 
-    pub(crate) fn lookup(source: trust_dns_resolver::error::ResolveError) -> Self {
+    pub(crate) fn lookup(source: crate::dns::DnsError) -> Self {
         Self::Lookup { source }
     }
 }
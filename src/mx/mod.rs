@@ -2,14 +2,21 @@
 //!
 //! The public entry point is [`check_mx`], which performs a synchronous lookup
 //! using the system resolver and returns a [`MxStatus`] describing the outcome.
+//! [`check_mx_with_options`] can additionally resolve every exchange and lint
+//! its addresses. With the `async` feature, [`check_mx_async`] does the same
+//! on the tokio resolver.
 
 mod error;
 mod resolver;
 mod types;
 
 pub use error::MxError as Error;
-pub use resolver::check_mx;
-pub use types::{MxRecord, MxStatus};
+pub use resolver::{check_mx, check_mx_with_backend, check_mx_with_options};
+#[cfg(feature = "async")]
+pub use resolver::{check_mx_async, check_mx_async_with_backend};
+#[cfg(feature = "with-dane")]
+pub use types::MxDaneStatus;
+pub use types::{MxLint, MxLookupOptions, MxRecord, MxStatus};
 
 #[cfg(test)]
 mod tests;
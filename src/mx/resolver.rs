@@ -1,65 +1,247 @@
-use trust_dns_resolver::{Resolver, error::ResolveError};
+use std::net::{IpAddr, Ipv4Addr};
 
-use super::{Error, MxRecord, MxStatus};
+#[cfg(feature = "with-dane")]
+use crate::dane::lookup_dane;
+#[cfg(feature = "async")]
+use crate::dns::{AsyncDnsBackend, replay};
+use crate::dns::{DnsBackend, DnsError, DnsRecordType, DnssecStatus, nx_as_answer};
 
-/// Lookup MX records for `domain` using the system resolver.
+#[cfg(feature = "with-dane")]
+use super::MxDaneStatus;
+use super::{Error, MxLint, MxLookupOptions, MxRecord, MxStatus};
+
+/// Lookup MX records for `domain` using the system resolver (see
+/// [`MxLookupOptions::with_dns_config`] to pick other nameservers).
 ///
 /// The domain is normalized via IDNA before querying DNS. The resulting
-/// [`MxStatus`] contains the sorted list of records (ascending preference).
+/// [`MxStatus`] contains the sorted list of records (ascending preference),
+/// [`MxStatus::NullMx`] when the domain only publishes a null MX (RFC 7505),
+/// or distinguishes a missing domain (NXDOMAIN) from a domain without MX whose
+/// A/AAAA records act as implicit MX (RFC 5321 §5.1). Each status carries
+/// the [`DnssecStatus`] of the answers it is based on; with
+/// [`DnsConfig::dnssec`](crate::dns::DnsConfig::dnssec), bogus answers are
+/// returned as [`Error::Lookup`].
 pub fn check_mx(domain: &str) -> Result<MxStatus, Error> {
+    check_mx_with_options(domain, &MxLookupOptions::default())
+}
+
+/// Like [`check_mx`], but with [`MxLookupOptions::resolve_exchanges`] every
+/// exchange is also resolved to A/AAAA and linted (unresolvable, CNAME,
+/// private or loopback addresses).
+pub fn check_mx_with_options(domain: &str, options: &MxLookupOptions) -> Result<MxStatus, Error> {
     let ascii = normalize_domain(domain)?;
-    let resolver = Resolver::from_system_conf().map_err(Error::resolver_init)?;
-    resolve_with(&resolver, &ascii)
+    let backend = options
+        .dns_config()
+        .build_backend()
+        .map_err(Error::resolver_init)?;
+    resolve_with(&*backend, &ascii, options)
 }
 
-pub(crate) fn resolve_with<R>(resolver: &R, ascii_domain: &str) -> Result<MxStatus, Error>
+/// Like [`check_mx_with_options`], but queries `backend` instead of building
+/// a resolver (e.g. a [`StaticZone`](crate::dns::StaticZone) in tests).
+pub fn check_mx_with_backend<B>(
+    backend: &B,
+    domain: &str,
+    options: &MxLookupOptions,
+) -> Result<MxStatus, Error>
 where
-    R: LookupMx,
+    B: DnsBackend + ?Sized,
 {
-    let mut records = resolver.lookup_mx(ascii_domain).map_err(Error::lookup)?;
+    let ascii = normalize_domain(domain)?;
+    resolve_with(backend, &ascii, options)
+}
+
+/// Async variant of [`check_mx_with_options`] (`async` feature), querying the
+/// tokio resolver described by [`MxLookupOptions::dns_config`].
+/// Replays the sync logic per lookup, see [`AsyncDnsBackend`](crate::AsyncDnsBackend).
+#[cfg(feature = "async")]
+pub async fn check_mx_async(domain: &str, options: &MxLookupOptions) -> Result<MxStatus, Error> {
+    let backend = options
+        .dns_config()
+        .build_async_backend()
+        .map_err(Error::resolver_init)?;
+    check_mx_async_with_backend(&backend, domain, options).await
+}
+
+/// Async variant of [`check_mx_with_backend`]; runs the same resolution logic.
+/// Replays the sync logic per lookup, see [`AsyncDnsBackend`](crate::AsyncDnsBackend).
+#[cfg(feature = "async")]
+pub async fn check_mx_async_with_backend<B>(
+    backend: &B,
+    domain: &str,
+    options: &MxLookupOptions,
+) -> Result<MxStatus, Error>
+where
+    B: AsyncDnsBackend + ?Sized,
+{
+    let ascii = normalize_domain(domain)?;
+    replay(backend, |prefetched| {
+        resolve_with(prefetched, &ascii, options)
+    })
+    .await
+}
+
+pub(crate) fn resolve_with<B>(
+    backend: &B,
+    ascii_domain: &str,
+    options: &MxLookupOptions,
+) -> Result<MxStatus, Error>
+where
+    B: DnsBackend + ?Sized,
+{
+    let answer = match backend.lookup(ascii_domain, DnsRecordType::Mx) {
+        Ok(answer) => answer,
+        Err(DnsError::NxDomain { dnssec, .. }) => return Ok(MxStatus::DomainNotFound { dnssec }),
+        Err(err) => return Err(Error::lookup(err)),
+    };
+    let dnssec = answer.dnssec;
+    let mut records: Vec<MxRecord> = answer
+        .mx()
+        .into_iter()
+        .map(|mx| MxRecord::new(mx.preference, mx.exchange))
+        .collect();
 
     records.sort();
     records.dedup();
 
     if records.is_empty() {
-        Ok(MxStatus::NoRecords)
-    } else {
-        Ok(MxStatus::Records(records))
+        return implicit_mx(backend, ascii_domain, dnssec);
+    }
+    if records.iter().all(MxRecord::is_null) {
+        return Ok(MxStatus::NullMx { dnssec });
+    }
+    for record in records.iter_mut() {
+        if record.is_null() {
+            record.lints.push(MxLint::NullMxWithOtherRecords);
+        } else {
+            lint_exchange(backend, record, options);
+            #[cfg(feature = "with-dane")]
+            if options.checks_dane() && !is_ip_literal(&record.exchange) {
+                record.dane = Some(match lookup_dane(backend, &record.exchange, 25) {
+                    Ok(status) => MxDaneStatus::Checked(status),
+                    Err(err) => MxDaneStatus::LookupFailed {
+                        reason: err.to_string(),
+                    },
+                });
+            }
+        }
     }
+    Ok(MxStatus::Records { records, dnssec })
 }
 
-pub(crate) fn normalize_domain(domain: &str) -> Result<String, Error> {
-    let trimmed = domain.trim();
-    if trimmed.is_empty() {
-        return Err(Error::EmptyDomain);
+/// Les échecs de requête (SERVFAIL, réponse bogus…) deviennent un
+/// [`MxLint::LookupFailed`] propre à l'exchange plutôt qu'une erreur globale.
+fn lint_exchange<B>(backend: &B, record: &mut MxRecord, options: &MxLookupOptions)
+where
+    B: DnsBackend + ?Sized,
+{
+    if is_ip_literal(&record.exchange) {
+        record.lints.push(MxLint::IpLiteral);
+        return;
     }
-    idna::domain_to_ascii(trimmed).map_err(Error::idna)
+    if !options.resolves_exchanges() {
+        return;
+    }
+
+    let cname_dnssec = match nx_as_answer(backend.lookup(&record.exchange, DnsRecordType::Cname)) {
+        Ok(cname) => {
+            if let Some(target) = cname.cname() {
+                record.lints.push(MxLint::CnameTarget { target });
+            }
+            cname.dnssec
+        }
+        Err(err) => {
+            record.lints.push(MxLint::lookup_failed(&err));
+            return;
+        }
+    };
+
+    let (mut addrs, dnssec) = match lookup_addrs(backend, &record.exchange) {
+        Ok(found) => found,
+        Err(err) => {
+            record.lints.push(MxLint::lookup_failed(&err));
+            return;
+        }
+    };
+    record.dnssec = cname_dnssec.weakest(dnssec);
+    addrs.sort();
+    addrs.dedup();
+    if addrs.is_empty() {
+        record.lints.push(MxLint::Unresolvable);
+    }
+    record
+        .lints
+        .extend(addrs.iter().copied().filter_map(address_lint));
+    record.addresses = addrs;
+}
+
+/// Exchange écrit comme une adresse (`192.0.2.1`, `[2001:db8::1]`).
+fn is_ip_literal(exchange: &str) -> bool {
+    let bare = exchange
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .unwrap_or(exchange);
+    bare.parse::<IpAddr>().is_ok()
 }
 
-pub(crate) fn normalize_exchange(exchange: String) -> String {
-    let trimmed = exchange.trim_end_matches('.');
-    trimmed.to_ascii_lowercase()
+fn address_lint(addr: IpAddr) -> Option<MxLint> {
+    let addr = match addr {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(addr, IpAddr::V4),
+        IpAddr::V4(_) => addr,
+    };
+    if addr.is_loopback() || addr.is_unspecified() {
+        return Some(MxLint::LoopbackAddress { addr });
+    }
+    let private = match addr {
+        IpAddr::V4(v4) => v4.is_private() || v4.is_link_local() || is_shared_v4(v4),
+        IpAddr::V6(v6) => v6.is_unique_local() || v6.is_unicast_link_local(),
+    };
+    private.then_some(MxLint::PrivateAddress { addr })
 }
 
-pub(crate) trait LookupMx {
-    fn lookup_mx(&self, domain: &str) -> Result<Vec<MxRecord>, ResolveError>;
+/// Espace partagé CGNAT 100.64.0.0/10 (RFC 6598).
+fn is_shared_v4(addr: Ipv4Addr) -> bool {
+    let [a, b, ..] = addr.octets();
+    a == 100 && (b & 0xc0) == 64
 }
 
-impl LookupMx for Resolver {
-    fn lookup_mx(&self, domain: &str) -> Result<Vec<MxRecord>, ResolveError> {
-        let lookup = Resolver::mx_lookup(self, domain)?;
-        let mut records = Vec::new();
-        for mx in lookup.iter() {
-            let exchange = normalize_exchange(mx.exchange().to_utf8());
-            records.push(MxRecord::new(mx.preference(), exchange));
-        }
-        Ok(records)
+/// Repli RFC 5321 §5.1 : sans MX, les A/AAAA du domaine servent de MX implicite.
+fn implicit_mx<B>(
+    backend: &B,
+    ascii_domain: &str,
+    mx_dnssec: DnssecStatus,
+) -> Result<MxStatus, Error>
+where
+    B: DnsBackend + ?Sized,
+{
+    let (mut addrs, dnssec) = lookup_addrs(backend, ascii_domain)?;
+    let dnssec = mx_dnssec.weakest(dnssec);
+    addrs.sort();
+    addrs.dedup();
+    if addrs.is_empty() {
+        Ok(MxStatus::NoMailHost { dnssec })
+    } else {
+        Ok(MxStatus::NoMxButAddress { addrs, dnssec })
     }
 }
 
-#[cfg(test)]
-impl LookupMx for crate::mx::tests::StubResolver {
-    fn lookup_mx(&self, domain: &str) -> Result<Vec<MxRecord>, ResolveError> {
-        (self.on_lookup)(domain)
+/// Adresses A puis AAAA de `name` et le statut DNSSEC le plus faible des deux
+/// réponses.
+fn lookup_addrs<B>(backend: &B, name: &str) -> Result<(Vec<IpAddr>, DnssecStatus), Error>
+where
+    B: DnsBackend + ?Sized,
+{
+    let v4 = nx_as_answer(backend.lookup(name, DnsRecordType::A)).map_err(Error::lookup)?;
+    let v6 = nx_as_answer(backend.lookup(name, DnsRecordType::Aaaa)).map_err(Error::lookup)?;
+    let mut addrs = v4.addrs();
+    addrs.extend(v6.addrs());
+    Ok((addrs, v4.dnssec.weakest(v6.dnssec)))
+}
+
+pub(crate) fn normalize_domain(domain: &str) -> Result<String, Error> {
+    let trimmed = domain.trim();
+    if trimmed.is_empty() {
+        return Err(Error::EmptyDomain);
     }
+    idna::domain_to_ascii(trimmed).map_err(Error::idna)
 }
@@ -1,22 +1,20 @@
-use super::{MxRecord, MxStatus, resolver};
-use trust_dns_resolver::error::ResolveError;
+use super::{MxLint, MxLookupOptions, MxStatus, check_mx_with_backend, resolver};
+use crate::dns::{DnssecStatus, StaticZone};
 
-type LookupResult = Result<Vec<MxRecord>, ResolveError>;
-type LookupFn = dyn Fn(&str) -> LookupResult;
+/// Zone écrite en ligne dans le test ; l'indentation est retirée.
+fn zone(text: &str) -> StaticZone {
+    let text: Vec<&str> = text.lines().map(str::trim_start).collect();
+    StaticZone::from_zone_str(&text.join("\n")).expect("valid zone")
+}
 
-pub(crate) struct StubResolver {
-    pub on_lookup: Box<LookupFn>,
+fn resolve(zone: &StaticZone) -> MxStatus {
+    check_mx_with_backend(zone, "example.com", &MxLookupOptions::default())
+        .expect("lookup succeeds")
 }
 
-impl StubResolver {
-    fn new<F>(f: F) -> Self
-    where
-        F: Fn(&str) -> LookupResult + 'static,
-    {
-        Self {
-            on_lookup: Box::new(f),
-        }
-    }
+fn resolve_exchanges(zone: &StaticZone) -> MxStatus {
+    let options = MxLookupOptions::new().resolve_exchanges(true);
+    check_mx_with_backend(zone, "example.com", &options).expect("lookup succeeds")
 }
 
 #[test]
@@ -27,20 +25,18 @@ fn normalize_domain_rejects_empty() {
 
 #[test]
 fn resolve_with_sorts_and_dedups_records() {
-    let stub = StubResolver::new(|domain| {
-        assert_eq!(domain, "example.com");
-        Ok(vec![
-            MxRecord::new(20, "mx2.example.com"),
-            MxRecord::new(10, "mx1.example.com"),
-            MxRecord::new(10, "mx1.example.com"),
-            MxRecord::new(30, "mx3.example.com"),
-        ])
-    });
-
-    let status = resolver::resolve_with(&stub, "example.com").expect("lookup succeeds");
+    let zone = zone(
+        "$ORIGIN example.com.
+         @ MX 20 mx2
+         @ MX 10 MX1.example.com.
+         @ MX 10 mx1
+         @ MX 30 mx3",
+    );
+
+    let status = resolve(&zone);
     let records = match status {
-        MxStatus::Records(records) => records,
-        MxStatus::NoRecords => panic!("expected records"),
+        MxStatus::Records { records, .. } => records,
+        other => panic!("expected records, got {other:?}"),
     };
     assert_eq!(records.len(), 3);
     assert_eq!(records[0].preference, 10);
@@ -49,18 +45,281 @@ fn resolve_with_sorts_and_dedups_records() {
 }
 
 #[test]
-fn resolve_with_handles_no_records() {
-    let stub = StubResolver::new(|domain| {
-        assert_eq!(domain, "example.com");
-        Ok(Vec::new())
-    });
+fn resolve_with_reports_no_mail_host() {
+    let zone = zone("example.com. TXT \"v=spf1 -all\"");
+
+    let status = resolve(&zone);
+    assert_eq!(
+        status,
+        MxStatus::NoMailHost {
+            dnssec: DnssecStatus::Indeterminate
+        }
+    );
+    assert!(!status.accepts_mail());
+}
+
+#[test]
+fn resolve_with_falls_back_to_implicit_mx() {
+    let zone = zone(
+        "example.com. AAAA 2001:db8::1
+         example.com. A 192.0.2.1",
+    );
+
+    let status = resolve(&zone);
+    let MxStatus::NoMxButAddress { addrs, .. } = &status else {
+        panic!("expected implicit MX, got {status:?}");
+    };
+    assert_eq!(addrs.len(), 2);
+    assert!(addrs[0].is_ipv4());
+    assert!(status.accepts_mail());
+}
+
+#[test]
+fn resolve_with_distinguishes_nxdomain() {
+    let zone = zone("other.example. A 192.0.2.1");
+
+    let status = resolve(&zone);
+    assert_eq!(
+        status,
+        MxStatus::DomainNotFound {
+            dnssec: DnssecStatus::Indeterminate
+        }
+    );
+}
+
+#[test]
+fn resolve_with_detects_null_mx() {
+    let zone = zone("example.com. MX 0 .");
+
+    let status = resolve(&zone);
+    assert_eq!(
+        status,
+        MxStatus::NullMx {
+            dnssec: DnssecStatus::Indeterminate
+        }
+    );
+    assert!(status.records().is_empty());
+}
+
+#[test]
+fn resolve_with_flags_null_mx_mixed_with_records() {
+    let zone = zone(
+        "example.com. MX 10 mx1.example.com.
+         example.com. MX 0 .",
+    );
+
+    let status = resolve(&zone);
+    let records = status.records();
+    assert_eq!(records.len(), 2);
+    assert!(records[0].is_null());
+    assert_eq!(records[0].lints, vec![MxLint::NullMxWithOtherRecords]);
+    assert!(records[1].lints.is_empty());
+    assert_eq!(status.lints().count(), 1);
+}
+
+#[test]
+fn resolve_with_leaves_exchanges_unresolved_by_default() {
+    let zone = zone(
+        "example.com. MX 10 mx1.example.com.
+         mx1.example.com. A 192.0.2.10",
+    );
+
+    let status = resolve(&zone);
+    assert!(status.records()[0].addresses.is_empty());
+    assert_eq!(status.lints().count(), 0);
+}
+
+#[test]
+fn resolve_with_resolves_and_lints_exchanges() {
+    let zone = zone(
+        "$ORIGIN example.com.
+         @     MX 10 mx1
+         @     MX 20 mx2
+         @     MX 30 alias
+         @     MX 40 gone
+         mx1   AAAA 2001:db8::25
+         mx1   A 192.0.2.25
+         mx2   A 10.0.0.5
+         mx2   A 127.0.0.1
+         alias CNAME mail.provider.example.
+         mail.provider.example. A 192.0.2.26",
+    );
+
+    let status = resolve_exchanges(&zone);
+    let records = status.records();
+    assert_eq!(records[0].addresses.len(), 2);
+    assert!(records[0].addresses[0].is_ipv4());
+    assert!(records[0].lints.is_empty());
+    assert_eq!(
+        records[1].lints,
+        vec![
+            MxLint::PrivateAddress {
+                addr: "10.0.0.5".parse().unwrap()
+            },
+            MxLint::LoopbackAddress {
+                addr: "127.0.0.1".parse().unwrap()
+            },
+        ]
+    );
+    assert_eq!(
+        records[2].lints,
+        vec![MxLint::CnameTarget {
+            target: "mail.provider.example".to_string()
+        }]
+    );
+    assert_eq!(
+        records[2].addresses,
+        vec!["192.0.2.26".parse::<std::net::IpAddr>().unwrap()]
+    );
+    assert_eq!(records[3].lints, vec![MxLint::Unresolvable]);
+}
+
+#[test]
+fn exchange_lookup_failures_are_per_record_lints() {
+    let zone = zone(
+        "example.com. MX 10 mx.example.com.
+         example.com. MX 20 mx.broken.example.
+         mx.example.com. A 192.0.2.25
+         mx.broken.example. A 192.0.2.26",
+    )
+    .with_dnssec("broken.example", DnssecStatus::Bogus);
+
+    let status = resolve_exchanges(&zone);
+    let records = status.records();
+    assert!(records[0].lints.is_empty());
+    assert_eq!(records[0].addresses.len(), 1);
+    assert!(matches!(
+        records[1].lints.as_slice(),
+        [MxLint::LookupFailed { reason }] if reason.contains("bogus")
+    ));
+    assert!(records[1].addresses.is_empty());
+}
+
+#[test]
+fn resolve_with_flags_ip_literal_exchanges() {
+    let zone = zone(
+        "example.com. MX 10 192.0.2.1.
+         example.com. MX 20 [2001:db8::1].",
+    );
+
+    let status = resolve_exchanges(&zone);
+    assert!(
+        status
+            .records()
+            .iter()
+            .all(|record| record.lints == vec![MxLint::IpLiteral])
+    );
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_resolution_matches_sync() {
+    use super::check_mx_async_with_backend;
+    use crate::dns::block_on;
+
+    let zone = zone(
+        "$ORIGIN example.com.
+         @   MX 10 mx1
+         @   MX 20 alias
+         mx1 A 10.0.0.5
+         alias CNAME mx1",
+    );
+    let options = MxLookupOptions::new().resolve_exchanges(true);
 
-    let status = resolver::resolve_with(&stub, "example.com").expect("lookup succeeds");
-    assert!(matches!(status, MxStatus::NoRecords));
+    let future = check_mx_async_with_backend(&zone, "example.com", &options);
+    fn assert_send<T: Send>(value: T) -> T {
+        value
+    }
+    let status = block_on(assert_send(future)).expect("lookup succeeds");
+    assert_eq!(status, resolve_exchanges(&zone));
+    assert_eq!(status.records()[1].addresses.len(), 1);
 }
 
 #[test]
-fn normalize_exchange_trims_dot_and_lowercases() {
-    let out = resolver::normalize_exchange("Mail.EXAMPLE.com.".to_string());
-    assert_eq!(out, "mail.example.com");
+fn reports_weakest_dnssec_status_of_the_decision() {
+    let signed = zone(
+        "example.com. MX 10 mx1.example.com.
+         mx1.example.com. A 192.0.2.10
+         mx1.example.com. AAAA 2001:db8::10",
+    )
+    .with_dnssec("example.com", DnssecStatus::Secure);
+    let status = resolve_exchanges(&signed);
+    assert_eq!(status.dnssec(), DnssecStatus::Secure);
+    assert_eq!(status.records()[0].dnssec, DnssecStatus::Secure);
+
+    // MX signé, mais l'exchange vit dans une zone non signée.
+    let mixed = signed.with_dnssec("mx1.example.com", DnssecStatus::Insecure);
+    let status = resolve_exchanges(&mixed);
+    assert_eq!(status.dnssec(), DnssecStatus::Secure);
+    assert_eq!(status.records()[0].dnssec, DnssecStatus::Insecure);
+
+    let implicit = zone("example.com. A 192.0.2.1")
+        .with_dnssec("example.com", DnssecStatus::Secure)
+        .with_dnssec("com", DnssecStatus::Insecure);
+    assert_eq!(resolve(&implicit).dnssec(), DnssecStatus::Secure);
+
+    let missing = zone("other.example. A 192.0.2.1").with_dnssec("com", DnssecStatus::Secure);
+    assert_eq!(
+        resolve(&missing),
+        MxStatus::DomainNotFound {
+            dnssec: DnssecStatus::Secure
+        }
+    );
+}
+
+#[test]
+fn bogus_answers_are_errors() {
+    let zone =
+        zone("example.com. MX 10 mx1.example.com.").with_dnssec("example.com", DnssecStatus::Bogus);
+
+    let err = check_mx_with_backend(&zone, "example.com", &MxLookupOptions::default())
+        .expect_err("bogus MX must not be used");
+    assert!(matches!(
+        err,
+        super::Error::Lookup { ref source } if source.is_bogus()
+    ));
+}
+
+#[cfg(feature = "with-dane")]
+#[test]
+fn checks_dane_of_each_exchange() {
+    use super::MxDaneStatus;
+    use crate::dane::DaneState;
+
+    let zone = zone(
+        "$ORIGIN example.com.
+         @ MX 10 mx1
+         @ MX 20 mx2
+         @ MX 30 mx.broken.example.
+         mx1 A 192.0.2.1
+         mx2 A 192.0.2.2
+         mx.broken.example. A 192.0.2.3
+         _25._tcp.mx1 TLSA 3 1 1 0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+    )
+    .with_dnssec("example.com", DnssecStatus::Secure)
+    .with_dnssec("broken.example", DnssecStatus::Bogus);
+
+    let records = match resolve(&zone) {
+        MxStatus::Records { records, .. } => records,
+        other => panic!("expected records, got {other:?}"),
+    };
+    assert!(records.iter().all(|record| record.dane.is_none()));
+
+    let options = MxLookupOptions::new().check_dane(true);
+    let records = match check_mx_with_backend(&zone, "example.com", &options) {
+        Ok(MxStatus::Records { records, .. }) => records,
+        other => panic!("expected records, got {other:?}"),
+    };
+    let states: Vec<_> = records[..2]
+        .iter()
+        .map(|record| match &record.dane {
+            Some(MxDaneStatus::Checked(dane)) => dane.state(),
+            other => panic!("expected a DANE status, got {other:?}"),
+        })
+        .collect();
+    assert_eq!(states, vec![DaneState::Usable, DaneState::NotPublished]);
+    assert!(matches!(
+        &records[2].dane,
+        Some(MxDaneStatus::LookupFailed { reason }) if reason.contains("bogus")
+    ));
 }
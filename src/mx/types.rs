@@ -1,8 +1,39 @@
+use std::net::IpAddr;
+
+#[cfg(feature = "with-dane")]
+use crate::dane::DaneStatus;
+use crate::dns::{DnsConfig, DnssecStatus};
+
 #[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct MxRecord {
     pub preference: u16,
+    /// Exchange normalisé (minuscules, sans point final). Vide pour un null MX.
     pub exchange: String,
+    #[cfg_attr(
+        feature = "with-serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub lints: Vec<MxLint>,
+    /// Adresses A/AAAA de l'exchange, renseignées avec
+    /// [`MxLookupOptions::resolve_exchanges`].
+    #[cfg_attr(
+        feature = "with-serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub addresses: Vec<IpAddr>,
+    /// Statut DNSSEC des réponses CNAME/A/AAAA de l'exchange, renseigné avec
+    /// [`MxLookupOptions::resolve_exchanges`].
+    #[cfg_attr(feature = "with-serde", serde(default))]
+    pub dnssec: DnssecStatus,
+    /// TLSA de l'exchange, renseignés avec [`MxLookupOptions::check_dane`]
+    /// (feature `with-dane`).
+    #[cfg(feature = "with-dane")]
+    #[cfg_attr(
+        feature = "with-serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub dane: Option<MxDaneStatus>,
 }
 
 impl MxRecord {
@@ -10,22 +41,164 @@ impl MxRecord {
         Self {
             preference,
             exchange: exchange.into(),
+            lints: Vec::new(),
+            addresses: Vec::new(),
+            dnssec: DnssecStatus::Indeterminate,
+            #[cfg(feature = "with-dane")]
+            dane: None,
         }
     }
+
+    /// `true` pour un null MX (RFC 7505) : exchange racine `.`.
+    pub fn is_null(&self) -> bool {
+        self.exchange.is_empty()
+    }
 }
 
+/// Résultat DANE d'un exchange (feature `with-dane`).
+#[cfg(feature = "with-dane")]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MxDaneStatus {
+    /// TLSA récupérés (éventuellement absents) et analysés.
+    Checked(DaneStatus),
+    /// La requête TLSA a échoué (SERVFAIL, délai, réponse bogus…).
+    LookupFailed { reason: String },
+}
+
+/// Anomalies de configuration rattachées à un enregistrement MX.
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MxLint {
+    /// Null MX publié à côté d'autres MX (interdit par RFC 7505 §3).
+    NullMxWithOtherRecords,
+    /// L'exchange ne se résout en aucune adresse A/AAAA.
+    Unresolvable,
+    /// L'exchange est un alias CNAME (interdit par RFC 2181 §10.3).
+    CnameTarget { target: String },
+    /// L'exchange est une adresse IP littérale et non un nom d'hôte.
+    IpLiteral,
+    /// L'exchange pointe vers une adresse privée (RFC 1918, ULA, lien local…).
+    PrivateAddress { addr: IpAddr },
+    /// L'exchange pointe vers une adresse de bouclage ou non spécifiée.
+    LoopbackAddress { addr: IpAddr },
+    /// La résolution CNAME/A/AAAA de l'exchange a échoué (SERVFAIL, délai,
+    /// réponse DNSSEC bogus…) ; ses adresses restent inconnues.
+    LookupFailed { reason: String },
+}
+
+impl MxLint {
+    pub(crate) fn lookup_failed(err: &impl std::fmt::Display) -> Self {
+        MxLint::LookupFailed {
+            reason: err.to_string(),
+        }
+    }
+}
+
+/// Options de [`check_mx_with_options`](super::check_mx_with_options).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MxLookupOptions {
+    resolve_exchanges: bool,
+    #[cfg(feature = "with-dane")]
+    check_dane: bool,
+    dns: DnsConfig,
+}
+
+impl MxLookupOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Résout chaque exchange en A/AAAA et vérifie les CNAME (requêtes DNS
+    /// supplémentaires par MX).
+    pub fn resolve_exchanges(mut self, value: bool) -> Self {
+        self.resolve_exchanges = value;
+        self
+    }
+
+    pub fn resolves_exchanges(&self) -> bool {
+        self.resolve_exchanges
+    }
+
+    /// Récupère et vérifie les TLSA DANE de chaque exchange (RFC 7672) ; ils
+    /// ne sont exploitables qu'avec [`DnsConfig::dnssec`].
+    #[cfg(feature = "with-dane")]
+    pub fn check_dane(mut self, value: bool) -> Self {
+        self.check_dane = value;
+        self
+    }
+
+    #[cfg(feature = "with-dane")]
+    pub fn checks_dane(&self) -> bool {
+        self.check_dane
+    }
+
+    pub fn with_dns_config(mut self, dns: DnsConfig) -> Self {
+        self.dns = dns;
+        self
+    }
+
+    pub fn dns_config(&self) -> &DnsConfig {
+        &self.dns
+    }
+}
+
+/// Résultat de la recherche MX. `dnssec` est le statut le plus faible des
+/// réponses sur lesquelles repose la conclusion (MX, et A/AAAA pour le repli).
 #[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MxStatus {
-    Records(Vec<MxRecord>),
-    NoRecords,
+    Records {
+        records: Vec<MxRecord>,
+        dnssec: DnssecStatus,
+    },
+    /// Le domaine publie uniquement un null MX (`MX 0 .`) : il n'accepte aucun
+    /// courrier.
+    NullMx { dnssec: DnssecStatus },
+    /// Le domaine n'existe pas (NXDOMAIN).
+    DomainNotFound { dnssec: DnssecStatus },
+    /// Aucun MX, mais des A/AAAA utilisables comme MX implicite (RFC 5321 §5.1).
+    NoMxButAddress {
+        addrs: Vec<IpAddr>,
+        dnssec: DnssecStatus,
+    },
+    /// Le domaine existe mais n'a ni MX, ni A, ni AAAA.
+    NoMailHost { dnssec: DnssecStatus },
 }
 
 impl MxStatus {
     pub fn records(&self) -> &[MxRecord] {
         match self {
-            Self::Records(records) => records.as_slice(),
-            Self::NoRecords => &[],
+            Self::Records { records, .. } => records.as_slice(),
+            Self::NullMx { .. }
+            | Self::DomainNotFound { .. }
+            | Self::NoMxButAddress { .. }
+            | Self::NoMailHost { .. } => &[],
         }
     }
+
+    pub fn dnssec(&self) -> DnssecStatus {
+        match self {
+            Self::Records { dnssec, .. }
+            | Self::NullMx { dnssec }
+            | Self::DomainNotFound { dnssec }
+            | Self::NoMxButAddress { dnssec, .. }
+            | Self::NoMailHost { dnssec } => *dnssec,
+        }
+    }
+
+    /// `true` si un serveur peut recevoir du courrier pour le domaine (MX
+    /// explicite non nul ou MX implicite).
+    pub fn accepts_mail(&self) -> bool {
+        match self {
+            Self::Records { records, .. } => records.iter().any(|record| !record.is_null()),
+            Self::NoMxButAddress { .. } => true,
+            Self::NullMx { .. } | Self::DomainNotFound { .. } | Self::NoMailHost { .. } => false,
+        }
+    }
+
+    /// Toutes les anomalies relevées sur les enregistrements.
+    pub fn lints(&self) -> impl Iterator<Item = &MxLint> {
+        self.records().iter().flat_map(|record| record.lints.iter())
+    }
 }
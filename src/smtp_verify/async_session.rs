@@ -0,0 +1,185 @@
+//! Pendant tokio de [`SmtpSession`](crate::smtp_verify::session::SmtpSession).
+
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_native_tls::{TlsConnector, TlsStream};
+
+use crate::smtp_verify::error::SmtpVerifyError;
+use crate::smtp_verify::session::{
+    ReplyBuilder, SmtpReply, Transcript, connection_closed, take_line,
+};
+
+enum StreamState {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+    Invalid,
+}
+
+pub struct AsyncSmtpSession {
+    state: StreamState,
+    buffer: Vec<u8>,
+    timeout: Option<Duration>,
+    pub transcript: Transcript,
+}
+
+impl AsyncSmtpSession {
+    pub async fn connect(
+        host: &str,
+        addresses: &[SocketAddr],
+        timeout: Option<Duration>,
+    ) -> Result<Self, SmtpVerifyError> {
+        let mut last_err = None;
+        for addr in addresses {
+            match deadline(timeout, TcpStream::connect(addr)).await {
+                Ok(stream) => {
+                    return Ok(Self {
+                        state: StreamState::Plain(stream),
+                        buffer: Vec::new(),
+                        timeout,
+                        transcript: Transcript::new(host),
+                    });
+                }
+                Err(err) => {
+                    last_err = Some(SmtpVerifyError::Connect {
+                        host: addr.to_string(),
+                        source: err,
+                    })
+                }
+            }
+        }
+        Err(last_err.unwrap_or(SmtpVerifyError::NoSmtpServers))
+    }
+
+    pub async fn read_banner(&mut self) -> Result<SmtpReply, SmtpVerifyError> {
+        let reply = self.read_reply().await?;
+        self.transcript.record_reply(&reply);
+        Ok(reply)
+    }
+
+    pub async fn send_command(&mut self, command: &str) -> Result<SmtpReply, SmtpVerifyError> {
+        self.transcript.record("C", command);
+        self.write_line(command).await?;
+        let reply = self.read_reply().await?;
+        self.transcript.record_reply(&reply);
+        Ok(reply)
+    }
+
+    pub async fn upgrade_tls(
+        &mut self,
+        domain: &str,
+        connector: &TlsConnector,
+    ) -> Result<(), SmtpVerifyError> {
+        let plain = match std::mem::replace(&mut self.state, StreamState::Invalid) {
+            StreamState::Plain(stream) => stream,
+            tls @ StreamState::Tls(_) => {
+                self.state = tls;
+                return Ok(());
+            }
+            StreamState::Invalid => {
+                return Err(SmtpVerifyError::Protocol("invalid stream state".into()));
+            }
+        };
+        let tls = match deadline(self.timeout, async {
+            Ok(connector.connect(domain, plain).await)
+        })
+        .await
+        {
+            Ok(Ok(tls)) => tls,
+            Ok(Err(err)) => return Err(SmtpVerifyError::Tls { source: err }),
+            Err(err) => return Err(SmtpVerifyError::Io { source: err }),
+        };
+        self.state = StreamState::Tls(Box::new(tls));
+        Ok(())
+    }
+
+    /// Certificat DER du serveur, une fois TLS négocié.
+    #[cfg(feature = "with-dane")]
+    pub fn peer_certificate(&self) -> Option<Vec<u8>> {
+        match &self.state {
+            StreamState::Tls(tls) => tls.get_ref().peer_certificate().ok()??.to_der().ok(),
+            _ => None,
+        }
+    }
+
+    pub async fn quit(&mut self) -> Result<(), SmtpVerifyError> {
+        self.transcript.record("C", "QUIT");
+        self.write_line("QUIT").await?;
+        if let Ok(reply) = self.read_reply().await {
+            self.transcript.record_reply(&reply);
+        }
+        Ok(())
+    }
+
+    async fn write_line(&mut self, command: &str) -> Result<(), SmtpVerifyError> {
+        let mut data = command.as_bytes().to_vec();
+        data.extend_from_slice(b"\r\n");
+        let timeout = self.timeout;
+        let written = match &mut self.state {
+            StreamState::Plain(stream) => deadline(timeout, write_flush(stream, &data)).await,
+            StreamState::Tls(stream) => deadline(timeout, write_flush(stream, &data)).await,
+            StreamState::Invalid => {
+                return Err(SmtpVerifyError::Protocol("invalid stream state".into()));
+            }
+        };
+        written.map_err(|err| SmtpVerifyError::Io { source: err })
+    }
+
+    async fn read_reply(&mut self) -> Result<SmtpReply, SmtpVerifyError> {
+        let mut reply = ReplyBuilder::default();
+        loop {
+            let line = self.read_line().await?;
+            if let Some(done) = reply.push(&line)? {
+                return Ok(done);
+            }
+        }
+    }
+
+    async fn read_line(&mut self) -> Result<String, SmtpVerifyError> {
+        loop {
+            if let Some(line) = take_line(&mut self.buffer) {
+                return line;
+            }
+
+            let mut buf = [0u8; 512];
+            let timeout = self.timeout;
+            let read = match &mut self.state {
+                StreamState::Plain(stream) => deadline(timeout, stream.read(&mut buf)).await,
+                StreamState::Tls(stream) => deadline(timeout, stream.read(&mut buf)).await,
+                StreamState::Invalid => {
+                    return Err(SmtpVerifyError::Protocol("invalid stream state".into()));
+                }
+            };
+            let read = read.map_err(|err| SmtpVerifyError::Io { source: err })?;
+            if read == 0 {
+                return Err(connection_closed());
+            }
+            self.buffer.extend_from_slice(&buf[..read]);
+        }
+    }
+}
+
+async fn write_flush<S>(stream: &mut S, data: &[u8]) -> io::Result<()>
+where
+    S: AsyncWriteExt + Unpin,
+{
+    stream.write_all(data).await?;
+    stream.flush().await
+}
+
+/// Applique le timeout de la sonde à une opération (aucun si `None`).
+async fn deadline<T>(
+    timeout: Option<Duration>,
+    operation: impl Future<Output = io::Result<T>>,
+) -> io::Result<T> {
+    match timeout {
+        Some(limit) => tokio::time::timeout(limit, operation)
+            .await
+            .unwrap_or_else(|_| Err(io::Error::new(io::ErrorKind::TimedOut, "timed out"))),
+        None => operation.await,
+    }
+}
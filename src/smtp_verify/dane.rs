@@ -0,0 +1,85 @@
+//! DANE des MX sondés (RFC 7672, feature `with-dane`) : TLSA récupérés avec
+//! les adresses, certificat comparé après STARTTLS.
+
+use native_tls::TlsConnector;
+
+use crate::dane::{CertificateMatch, DaneState, DaneStatus, lookup_dane};
+use crate::dns::DnsBackend;
+use crate::smtp_verify::dns::{HostCandidate, lookup_error};
+use crate::smtp_verify::error::SmtpVerifyError;
+use crate::smtp_verify::options::SmtpProbeOptions;
+
+/// Récupère les TLSA de chaque hôte si [`SmtpProbeOptions::dane`] est actif.
+pub(crate) fn attach<B>(backend: &B, hosts: &mut [HostCandidate], options: &SmtpProbeOptions)
+where
+    B: DnsBackend + ?Sized,
+{
+    for candidate in hosts {
+        candidate.dane = options
+            .dane
+            .then(|| lookup_dane(backend, &candidate.host, options.smtp_port));
+    }
+}
+
+/// Connecteur des hôtes DANE : le certificat est vérifié par les TLSA et non
+/// par la PKI, un certificat auto-signé pouvant être légitime (DANE-EE).
+pub(crate) fn connector(
+    options: &SmtpProbeOptions,
+) -> Result<Option<TlsConnector>, SmtpVerifyError> {
+    if !options.dane {
+        return Ok(None);
+    }
+    TlsConnector::builder()
+        .danger_accept_invalid_certs(true)
+        .danger_accept_invalid_hostnames(true)
+        .build()
+        .map(Some)
+        .map_err(|err| SmtpVerifyError::Tls { source: err })
+}
+
+/// Connecteur à utiliser pour `candidate` : celui de DANE si ses TLSA sont
+/// utilisables, sinon la vérification PKI habituelle.
+pub(crate) fn connector_for<'a, C>(
+    candidate: &HostCandidate,
+    pkix: &'a C,
+    dane: Option<&'a C>,
+) -> &'a C {
+    match (&candidate.dane, dane) {
+        (Some(Ok(status)), Some(dane)) if status.state() == DaneState::Usable => dane,
+        _ => pkix,
+    }
+}
+
+/// Statut DANE de `candidate` ; l'échec de sa requête TLSA exclut l'hôte
+/// (RFC 7672 §2.2).
+pub(crate) fn host_status(
+    candidate: &HostCandidate,
+) -> Result<Option<DaneStatus>, SmtpVerifyError> {
+    candidate.dane.clone().transpose().map_err(lookup_error)
+}
+
+/// Compare le certificat présenté (`None` : illisible) aux TLSA de l'hôte.
+pub(crate) fn record_certificate(status: &mut Option<DaneStatus>, leaf_der: Option<Vec<u8>>) {
+    if let Some(status) = status
+        .as_mut()
+        .filter(|status| status.state() == DaneState::Usable)
+    {
+        status.certificate = Some(match leaf_der {
+            Some(der) => status.match_certificate(&der),
+            None => CertificateMatch::InvalidCertificate,
+        });
+    }
+}
+
+/// Fin de session : un hôte DANE qui n'a pas négocié TLS est signalé.
+pub(crate) fn finish(mut status: Option<DaneStatus>) -> Option<DaneStatus> {
+    if let Some(status) = status
+        .as_mut()
+        .filter(|status| status.state() == DaneState::Usable)
+    {
+        status
+            .certificate
+            .get_or_insert(CertificateMatch::NoStartTls);
+    }
+    status
+}
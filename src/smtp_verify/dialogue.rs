@@ -0,0 +1,343 @@
+//! Déroulé du dialogue SMTP avec un MX, indépendant des entrées/sorties :
+//! la version bloquante et la version tokio n'en sont que des pilotes.
+
+#[cfg(feature = "with-dane")]
+use crate::dane::DaneStatus;
+use crate::smtp_verify::error::SmtpVerifyError;
+use crate::smtp_verify::options::SmtpProbeOptions;
+use crate::smtp_verify::session::SmtpReply;
+use crate::smtp_verify::types::{Existence, SmtpProbeReport};
+use crate::smtp_verify::util::{confidence_for, random_local_part};
+use crate::validator::{ValidationMode, normalize_email};
+
+/// Adresse sondée et paramètres d'enveloppe, communs à tous les MX.
+pub(crate) struct ProbePlan {
+    pub local: String,
+    pub domain: String,
+    pub mail_from: String,
+    pub helo: String,
+    pub catchall_locals: Vec<String>,
+    pub starttls_required: bool,
+}
+
+impl ProbePlan {
+    pub fn new(addr: &str, options: &SmtpProbeOptions) -> Result<Self, SmtpVerifyError> {
+        let normalized = normalize_email(addr, ValidationMode::Strict)
+            .map_err(|err| SmtpVerifyError::Protocol(err.to_string()))?;
+
+        if !normalized.valid {
+            return Err(SmtpVerifyError::invalid_email(normalized.reasons.clone()));
+        }
+
+        let local = normalized.local.clone();
+        let domain = if !normalized.ascii_domain.is_empty() {
+            normalized.ascii_domain.clone()
+        } else if !normalized.domain.is_empty() {
+            idna::domain_to_ascii(&normalized.domain).map_err(SmtpVerifyError::idna)?
+        } else {
+            return Err(SmtpVerifyError::InvalidEmail {
+                reasons: vec!["domain missing".to_string()],
+            });
+        };
+
+        let fallback_mail_from = format!("postmaster@{domain}");
+        let mail_from = options.mail_from(&fallback_mail_from).into_owned();
+        let helo = options.helo_name(&domain).into_owned();
+
+        let catchall_count = options.catchall_probes.min(5);
+        let catchall_locals = (0..catchall_count)
+            .map(|_| random_local_part(local.len()))
+            .collect();
+
+        Ok(Self {
+            local,
+            domain,
+            mail_from,
+            helo,
+            catchall_locals,
+            starttls_required: options.starttls_required,
+        })
+    }
+}
+
+/// Action demandée au pilote de la session.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Step {
+    /// Envoyer la commande et transmettre la réponse à [`HostDialogue::on_reply`].
+    Send(String),
+    /// Négocier TLS puis appeler [`HostDialogue::tls_established`].
+    StartTls,
+    /// Terminer (`RSET` d'abord si `reset`, puis `QUIT`).
+    Done { existence: Existence, reset: bool },
+}
+
+#[derive(Debug)]
+enum State {
+    Banner,
+    Ehlo,
+    StartTls,
+    EhloOverTls,
+    MailFrom,
+    Target,
+    CatchAll {
+        next: usize,
+        accepted: usize,
+        rejected: usize,
+        tempfail: usize,
+    },
+}
+
+pub(crate) struct HostDialogue<'a> {
+    host: &'a str,
+    plan: &'a ProbePlan,
+    state: State,
+}
+
+impl<'a> HostDialogue<'a> {
+    /// Dialogue avec `host` ; la première réponse attendue est la bannière.
+    pub fn new(host: &'a str, plan: &'a ProbePlan) -> Self {
+        Self {
+            host,
+            plan,
+            state: State::Banner,
+        }
+    }
+
+    pub fn on_reply(&mut self, reply: &SmtpReply) -> Step {
+        match self.state {
+            State::Banner => {
+                if reply.code == 521 {
+                    return done("server does not receive mail");
+                }
+                self.send(State::Ehlo, self.ehlo())
+            }
+            State::Ehlo => {
+                let starttls_advertised = reply.has_capability("STARTTLS");
+                if self.plan.starttls_required && !starttls_advertised {
+                    return done(format!(
+                        "STARTTLS required but not offered by {}",
+                        self.host
+                    ));
+                }
+                if starttls_advertised {
+                    self.send(State::StartTls, "STARTTLS".to_string())
+                } else {
+                    self.mail_from()
+                }
+            }
+            State::StartTls => {
+                if !reply.is_positive_completion() {
+                    return done(format!(
+                        "STARTTLS rejected by {} (code {})",
+                        self.host, reply.code
+                    ));
+                }
+                Step::StartTls
+            }
+            State::EhloOverTls => self.mail_from(),
+            State::MailFrom => {
+                if reply.is_permanent_failure() {
+                    return done(format!("MAIL FROM rejected with {}", reply.code));
+                }
+                let target = self.rcpt(&self.plan.local);
+                self.send(State::Target, target)
+            }
+            State::Target => match classify_target(reply) {
+                TargetExistence::DoesNotExist => Step::Done {
+                    existence: Existence::DoesNotExist,
+                    reset: false,
+                },
+                TargetExistence::Indeterminate(reason) => done(reason),
+                TargetExistence::Accepted if self.plan.catchall_locals.is_empty() => {
+                    done("catch-all probes disabled")
+                }
+                TargetExistence::Accepted => self.next_catchall(0, 0, 0, 0),
+            },
+            State::CatchAll {
+                next,
+                mut accepted,
+                mut rejected,
+                mut tempfail,
+            } => {
+                if reply.is_positive_completion() {
+                    accepted += 1;
+                } else if is_permanent_no_mailbox(reply) {
+                    rejected += 1;
+                } else if reply.is_transient_failure() {
+                    tempfail += 1;
+                }
+                self.next_catchall(next, accepted, rejected, tempfail)
+            }
+        }
+    }
+
+    pub fn tls_established(&mut self) -> Step {
+        // EHLO again over TLS
+        self.send(State::EhloOverTls, self.ehlo())
+    }
+
+    fn ehlo(&self) -> String {
+        format!("EHLO {}", self.plan.helo)
+    }
+
+    fn rcpt(&self, local: &str) -> String {
+        format!("RCPT TO:<{}@{}>", local, self.plan.domain)
+    }
+
+    fn mail_from(&mut self) -> Step {
+        let envelope = if self.plan.mail_from.is_empty() {
+            "MAIL FROM:<>".to_string()
+        } else {
+            format!("MAIL FROM:<{}>", self.plan.mail_from)
+        };
+        self.send(State::MailFrom, envelope)
+    }
+
+    fn next_catchall(
+        &mut self,
+        from: usize,
+        accepted: usize,
+        rejected: usize,
+        tempfail: usize,
+    ) -> Step {
+        let pending = self.plan.catchall_locals[from..]
+            .iter()
+            .position(|alias| alias != &self.plan.local);
+        if let Some(offset) = pending {
+            let next = from + offset;
+            let command = self.rcpt(&self.plan.catchall_locals[next]);
+            let state = State::CatchAll {
+                next: next + 1,
+                accepted,
+                rejected,
+                tempfail,
+            };
+            return self.send(state, command);
+        }
+
+        let existence = if accepted > 0 {
+            Existence::CatchAll
+        } else if rejected > 0 && tempfail == 0 {
+            Existence::Exists
+        } else if tempfail > 0 {
+            Existence::Indeterminate("temporary failure on catch-all probes".to_string())
+        } else {
+            Existence::Indeterminate("ambiguous catch-all probes".to_string())
+        };
+        Step::Done {
+            existence,
+            reset: true,
+        }
+    }
+
+    fn send(&mut self, state: State, command: String) -> Step {
+        self.state = state;
+        Step::Send(command)
+    }
+}
+
+fn done(reason: impl Into<String>) -> Step {
+    Step::Done {
+        existence: Existence::Indeterminate(reason.into()),
+        reset: false,
+    }
+}
+
+enum TargetExistence {
+    Accepted,
+    DoesNotExist,
+    Indeterminate(String),
+}
+
+fn classify_target(reply: &SmtpReply) -> TargetExistence {
+    if reply.is_positive_completion() {
+        return TargetExistence::Accepted;
+    }
+    if is_permanent_no_mailbox(reply) {
+        return TargetExistence::DoesNotExist;
+    }
+    if reply.code == 521 {
+        return TargetExistence::Indeterminate("521 host does not accept mail".to_string());
+    }
+    if reply.is_transient_failure() {
+        return TargetExistence::Indeterminate(format!("temporary failure {}", reply.code));
+    }
+    TargetExistence::Indeterminate(format!("unexpected response {}", reply.code))
+}
+
+fn is_permanent_no_mailbox(reply: &SmtpReply) -> bool {
+    matches!(reply.code, 550 | 551 | 553)
+}
+
+/// Résultat d'un MX : verdict et journal de la session.
+pub(crate) struct HostReport {
+    pub existence: Existence,
+    pub transcript: Vec<String>,
+    #[cfg(feature = "with-dane")]
+    pub dane: Option<DaneStatus>,
+}
+
+/// Agrège les MX essayés jusqu'au premier verdict tranché.
+pub(crate) struct ProbeOutcome {
+    mx_tried: Vec<String>,
+    transcripts: Vec<String>,
+    last_result: Existence,
+    #[cfg(feature = "with-dane")]
+    dane: Vec<DaneStatus>,
+}
+
+impl ProbeOutcome {
+    pub fn new() -> Self {
+        Self {
+            mx_tried: Vec::new(),
+            transcripts: Vec::new(),
+            last_result: Existence::Indeterminate("no server responded".to_string()),
+            #[cfg(feature = "with-dane")]
+            dane: Vec::new(),
+        }
+    }
+
+    /// Rapport d'un domaine en null MX : aucune adresse ne peut exister.
+    pub fn null_mx(err: &SmtpVerifyError) -> SmtpProbeReport {
+        let existence = Existence::DoesNotExist;
+        let confidence = confidence_for(&existence);
+        SmtpProbeReport::new(existence, Vec::new(), vec![err.to_string()], confidence)
+    }
+
+    /// Ajoute le résultat de `host` ; `true` si le verdict est tranché.
+    pub fn record(&mut self, host: &str, result: Result<HostReport, SmtpVerifyError>) -> bool {
+        self.mx_tried.push(host.to_string());
+        match result {
+            Ok(host_report) => {
+                self.transcripts.extend(host_report.transcript);
+                #[cfg(feature = "with-dane")]
+                self.dane.extend(host_report.dane);
+                let decisive = matches!(
+                    host_report.existence,
+                    Existence::Exists | Existence::DoesNotExist
+                );
+                self.last_result = host_report.existence;
+                decisive
+            }
+            Err(err) => {
+                self.transcripts.push(format!("[{host}] ! error: {err}"));
+                self.last_result = Existence::Indeterminate(err.to_string());
+                false
+            }
+        }
+    }
+
+    pub fn finish(self) -> SmtpProbeReport {
+        let confidence = confidence_for(&self.last_result);
+        SmtpProbeReport {
+            #[cfg(feature = "with-dane")]
+            dane: self.dane,
+            ..SmtpProbeReport::new(
+                self.last_result,
+                self.mx_tried,
+                self.transcripts,
+                confidence,
+            )
+        }
+    }
+}
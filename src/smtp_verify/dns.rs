@@ -1,6 +1,8 @@
-use std::net::{SocketAddr, ToSocketAddrs};
+use std::net::{IpAddr, SocketAddr};
 
-use trust_dns_resolver::Resolver;
+#[cfg(feature = "with-dane")]
+use crate::dane::DaneStatus;
+use crate::dns::{DnsAnswer, DnsBackend, DnsConfig, DnsError, DnsRecordType, nx_as_answer};
 
 use crate::smtp_verify::error::SmtpVerifyError;
 
@@ -9,22 +11,35 @@ pub struct HostCandidate {
     pub host: String,
     pub preference: u16,
     pub addresses: Vec<SocketAddr>,
+    /// TLSA de l'hôte, `None` si la sonde DANE n'est pas demandée.
+    #[cfg(feature = "with-dane")]
+    pub dane: Option<Result<DaneStatus, DnsError>>,
 }
 
-pub fn build_resolver() -> Result<Resolver, SmtpVerifyError> {
-    Resolver::from_system_conf().map_err(|err| SmtpVerifyError::ResolverInit { source: err })
+pub fn build_backend(dns: &DnsConfig) -> Result<Box<dyn DnsBackend>, SmtpVerifyError> {
+    dns.build_backend()
+        .map_err(|err| SmtpVerifyError::ResolverInit { source: err })
 }
 
-pub fn resolve_hosts(
-    resolver: &Resolver,
+/// Resolve the SMTP hosts for `domain`, ordered by preference.
+///
+/// Returns [`SmtpVerifyError::NullMx`] when the domain only publishes a null
+/// MX (RFC 7505); null entries mixed with real exchanges are skipped.
+pub fn resolve_hosts<B>(
+    backend: &B,
     domain: &str,
     max_hosts: usize,
     allow_ipv6: bool,
-) -> Result<Vec<HostCandidate>, SmtpVerifyError> {
-    let mut hosts = lookup_mx(resolver, domain, allow_ipv6)?;
+    port: u16,
+) -> Result<Vec<HostCandidate>, SmtpVerifyError>
+where
+    B: DnsBackend + ?Sized,
+{
+    let addrs = |host: &str| resolve_addrs(backend, host, allow_ipv6, port);
+    let mut hosts = lookup_mx(backend, domain, addrs)?;
     if hosts.is_empty() {
         // RFC: fall back to implicit MX (A/AAAA records for domain itself)
-        let addresses = resolve_addrs(domain, allow_ipv6)?;
+        let addresses = addrs(domain)?;
         if addresses.is_empty() {
             return Err(SmtpVerifyError::NoSmtpServers);
         }
@@ -32,6 +47,8 @@ pub fn resolve_hosts(
             host: domain.to_string(),
             preference: 0,
             addresses,
+            #[cfg(feature = "with-dane")]
+            dane: None,
         });
     }
 
@@ -40,47 +57,69 @@ pub fn resolve_hosts(
     Ok(hosts)
 }
 
-fn lookup_mx(
-    resolver: &Resolver,
+fn lookup_mx<B, F>(
+    backend: &B,
     domain: &str,
-    allow_ipv6: bool,
-) -> Result<Vec<HostCandidate>, SmtpVerifyError> {
+    resolve: F,
+) -> Result<Vec<HostCandidate>, SmtpVerifyError>
+where
+    B: DnsBackend + ?Sized,
+    F: Fn(&str) -> Result<Vec<SocketAddr>, SmtpVerifyError>,
+{
+    let records = lookup(backend, domain, DnsRecordType::Mx)?.mx();
+    if !records.is_empty() && records.iter().all(|record| record.exchange.is_empty()) {
+        return Err(SmtpVerifyError::NullMx {
+            domain: domain.to_string(),
+        });
+    }
+
     let mut out = Vec::new();
-    match resolver.mx_lookup(domain) {
-        Ok(lookup) => {
-            for record in lookup.iter() {
-                let host = record.exchange().to_utf8();
-                let host_trimmed = host.trim_end_matches('.').to_string();
-                let addrs = resolve_addrs(&host_trimmed, allow_ipv6)?;
-                if addrs.is_empty() {
-                    continue;
-                }
-                out.push(HostCandidate {
-                    host: host_trimmed,
-                    preference: record.preference(),
-                    addresses: addrs,
-                });
-            }
+    for record in records {
+        if record.exchange.is_empty() {
+            continue;
+        }
+        let addrs = resolve(&record.exchange)?;
+        if addrs.is_empty() {
+            continue;
         }
-        Err(err) => match err.kind() {
-            trust_dns_resolver::error::ResolveErrorKind::NoRecordsFound { .. } => {}
-            _ => return Err(SmtpVerifyError::Lookup { source: err }),
-        },
+        out.push(HostCandidate {
+            host: record.exchange,
+            preference: record.preference,
+            addresses: addrs,
+            #[cfg(feature = "with-dane")]
+            dane: None,
+        });
     }
     Ok(out)
 }
 
-fn resolve_addrs(domain: &str, allow_ipv6: bool) -> Result<Vec<SocketAddr>, SmtpVerifyError> {
-    let mut addrs = Vec::new();
-    let query = format!("{domain}:25");
-    let iter = query
-        .to_socket_addrs()
-        .map_err(|err| SmtpVerifyError::Io { source: err })?;
-    for addr in iter {
-        if !allow_ipv6 && addr.is_ipv6() {
-            continue;
-        }
-        addrs.push(addr);
+fn resolve_addrs<B>(
+    backend: &B,
+    domain: &str,
+    allow_ipv6: bool,
+    port: u16,
+) -> Result<Vec<SocketAddr>, SmtpVerifyError>
+where
+    B: DnsBackend + ?Sized,
+{
+    let mut ips: Vec<IpAddr> = lookup(backend, domain, DnsRecordType::A)?.addrs();
+    if allow_ipv6 {
+        ips.extend(lookup(backend, domain, DnsRecordType::Aaaa)?.addrs());
     }
-    Ok(addrs)
+    Ok(ips
+        .into_iter()
+        .map(|ip| SocketAddr::new(ip, port))
+        .collect())
+}
+
+/// Réponse à `name`/`rtype` ; NXDOMAIN donne une réponse vide.
+fn lookup<B>(backend: &B, name: &str, rtype: DnsRecordType) -> Result<DnsAnswer, SmtpVerifyError>
+where
+    B: DnsBackend + ?Sized,
+{
+    nx_as_answer(backend.lookup(name, rtype)).map_err(lookup_error)
+}
+
+pub(crate) fn lookup_error(source: DnsError) -> SmtpVerifyError {
+    SmtpVerifyError::Lookup { source }
 }
@@ -14,10 +14,12 @@ pub enum SmtpVerifyError {
     #[error("DNS lookup failed: {source}")]
     Lookup {
         #[source]
-        source: trust_dns_resolver::error::ResolveError,
+        source: crate::dns::DnsError,
     },
     #[error("no SMTP servers available for the domain")]
     NoSmtpServers,
+    #[error("{domain} publishes a null MX (RFC 7505) and does not accept mail")]
+    NullMx { domain: String },
     #[error("connection to {host} failed: {source}")]
     Connect {
         host: String,
@@ -2,8 +2,15 @@
 //!
 //! The public entry point is [`check_mailaddress_exists`], which executes a
 //! minimal SMTP dialogue against the MX hosts (with A/AAAA fallback) and
-//! classifies the observed behaviour into [`Existence`] variants.
+//! classifies the observed behaviour into [`Existence`] variants. The `async`
+//! feature adds `check_mailaddress_exists_async`, which drives the same
+//! dialogue over tokio sockets.
 
+#[cfg(feature = "async")]
+mod async_session;
+#[cfg(feature = "with-dane")]
+mod dane;
+mod dialogue;
 mod dns;
 mod error;
 mod options;
@@ -12,7 +19,12 @@ mod session;
 mod types;
 mod util;
 
+#[cfg(test)]
+mod tests;
+
 pub use error::SmtpVerifyError;
 pub use options::SmtpProbeOptions;
-pub use probe::check_mailaddress_exists;
+pub use probe::{check_mailaddress_exists, check_mailaddress_exists_with_backend};
+#[cfg(feature = "async")]
+pub use probe::{check_mailaddress_exists_async, check_mailaddress_exists_async_with_backend};
 pub use types::{Existence, SmtpProbeReport};
@@ -1,6 +1,8 @@
 use std::borrow::Cow;
 use std::time::Duration;
 
+use crate::dns::DnsConfig;
+
 #[cfg(feature = "with-serde")]
 use serde::{Deserialize, Serialize};
 
@@ -15,6 +17,17 @@ pub struct SmtpProbeOptions {
     pub max_mx: usize,
     pub catchall_probes: u8,
     pub ipv6: bool,
+    /// Port SMTP des serveurs sondés (25 ; autre valeur pour les tests).
+    #[cfg_attr(feature = "with-serde", serde(default = "default_smtp_port"))]
+    pub smtp_port: u16,
+    /// Résolveur utilisé pour les MX et leurs adresses.
+    #[cfg_attr(feature = "with-serde", serde(default))]
+    pub dns: DnsConfig,
+    /// Récupère les TLSA de chaque MX et compare le certificat présenté après
+    /// STARTTLS (RFC 7672) ; voir [`SmtpProbeReport::dane`](crate::SmtpProbeReport).
+    #[cfg(feature = "with-dane")]
+    #[cfg_attr(feature = "with-serde", serde(default))]
+    pub dane: bool,
 }
 
 impl Default for SmtpProbeOptions {
@@ -27,10 +40,18 @@ impl Default for SmtpProbeOptions {
             max_mx: 3,
             catchall_probes: 1,
             ipv6: false,
+            smtp_port: default_smtp_port(),
+            dns: DnsConfig::default(),
+            #[cfg(feature = "with-dane")]
+            dane: false,
         }
     }
 }
 
+fn default_smtp_port() -> u16 {
+    25
+}
+
 impl SmtpProbeOptions {
     /// Return the timeout as a [`Duration`]. A zero timeout disables the
     /// connection/read deadline.
@@ -1,280 +1,223 @@
 use native_tls::TlsConnector;
 
-use crate::smtp_verify::dns::{HostCandidate, build_resolver, resolve_hosts};
+use crate::dns::DnsBackend;
+#[cfg(feature = "async")]
+use crate::dns::{AsyncDnsBackend, replay};
+#[cfg(feature = "async")]
+use crate::smtp_verify::async_session::AsyncSmtpSession;
+#[cfg(feature = "with-dane")]
+use crate::smtp_verify::dane;
+use crate::smtp_verify::dialogue::{HostDialogue, HostReport, ProbeOutcome, ProbePlan, Step};
+use crate::smtp_verify::dns::{HostCandidate, build_backend, resolve_hosts};
 use crate::smtp_verify::error::SmtpVerifyError;
 use crate::smtp_verify::options::SmtpProbeOptions;
-use crate::smtp_verify::session::{SmtpReply, SmtpSession};
-use crate::smtp_verify::types::{Existence, SmtpProbeReport};
-use crate::smtp_verify::util::{confidence_for, random_local_part};
-use crate::validator::{ValidationMode, normalize_email};
+use crate::smtp_verify::session::SmtpSession;
+use crate::smtp_verify::types::SmtpProbeReport;
 
 pub fn check_mailaddress_exists(
     addr: &str,
     options: &SmtpProbeOptions,
 ) -> Result<SmtpProbeReport, SmtpVerifyError> {
-    let normalized = normalize_email(addr, ValidationMode::Strict)
-        .map_err(|err| SmtpVerifyError::Protocol(err.to_string()))?;
-
-    if !normalized.valid {
-        return Err(SmtpVerifyError::invalid_email(normalized.reasons.clone()));
-    }
+    let backend = build_backend(&options.dns)?;
+    check_mailaddress_exists_with_backend(&*backend, addr, options)
+}
 
-    let local = normalized.local.clone();
-    let domain = if !normalized.ascii_domain.is_empty() {
-        normalized.ascii_domain.clone()
-    } else if !normalized.domain.is_empty() {
-        idna::domain_to_ascii(&normalized.domain).map_err(SmtpVerifyError::idna)?
-    } else {
-        return Err(SmtpVerifyError::InvalidEmail {
-            reasons: vec!["domain missing".to_string()],
-        });
+/// Like [`check_mailaddress_exists`], but resolves MX hosts and their
+/// addresses through `backend` instead of [`SmtpProbeOptions::dns`].
+pub fn check_mailaddress_exists_with_backend<B>(
+    backend: &B,
+    addr: &str,
+    options: &SmtpProbeOptions,
+) -> Result<SmtpProbeReport, SmtpVerifyError>
+where
+    B: DnsBackend + ?Sized,
+{
+    let plan = ProbePlan::new(addr, options)?;
+    #[allow(unused_mut)]
+    let mut hosts = match resolve_hosts(
+        backend,
+        &plan.domain,
+        options.max_mx,
+        options.ipv6,
+        options.smtp_port,
+    ) {
+        Ok(hosts) => hosts,
+        Err(err @ SmtpVerifyError::NullMx { .. }) => return Ok(ProbeOutcome::null_mx(&err)),
+        Err(err) => return Err(err),
     };
 
-    let resolver = build_resolver()?;
-    let hosts = resolve_hosts(&resolver, &domain, options.max_mx, options.ipv6)?;
-
     if hosts.is_empty() {
         return Err(SmtpVerifyError::NoSmtpServers);
     }
 
-    let connector = TlsConnector::new().map_err(|err| SmtpVerifyError::Tls { source: err })?;
-
-    let fallback_mail_from = format!("postmaster@{domain}");
-    let mail_from = options.mail_from(&fallback_mail_from).into_owned();
-    let helo = options.helo_name(&domain);
+    #[cfg(feature = "with-dane")]
+    dane::attach(backend, &mut hosts, options);
 
-    let catchall_count = options.catchall_probes.min(5);
-    let catchall_locals: Vec<String> = (0..catchall_count)
-        .map(|_| random_local_part(local.len()))
-        .collect();
-
-    let mut mx_tried = Vec::new();
-    let mut transcripts = Vec::new();
-    let mut last_result = Existence::Indeterminate("no server responded".to_string());
+    let connector = TlsConnector::new().map_err(|err| SmtpVerifyError::Tls { source: err })?;
+    #[cfg(feature = "with-dane")]
+    let dane_connector = dane::connector(options)?;
 
+    let mut outcome = ProbeOutcome::new();
     for candidate in hosts {
-        mx_tried.push(candidate.host.clone());
-        match probe_host(
-            &candidate,
-            &TargetAddress {
-                local: &local,
-                domain: &domain,
-            },
-            options,
-            &connector,
-            &mail_from,
-            helo.as_ref(),
-            &catchall_locals,
-        ) {
-            Ok(host_report) => {
-                transcripts.extend(host_report.transcript);
-                match host_report.existence {
-                    Existence::Exists | Existence::DoesNotExist => {
-                        let confidence = confidence_for(&host_report.existence);
-                        return Ok(SmtpProbeReport::new(
-                            host_report.existence,
-                            mx_tried,
-                            transcripts,
-                            confidence,
-                        ));
-                    }
-                    other => {
-                        last_result = other;
-                    }
-                }
-            }
-            Err(err) => {
-                transcripts.push(format!("[{}] ! error: {err}", candidate.host));
-                last_result = Existence::Indeterminate(err.to_string());
-            }
+        #[cfg(feature = "with-dane")]
+        let connector = dane::connector_for(&candidate, &connector, dane_connector.as_ref());
+        #[cfg(not(feature = "with-dane"))]
+        let connector = &connector;
+        let result = probe_host(&candidate, &plan, options, connector);
+        if outcome.record(&candidate.host, result) {
+            break;
         }
     }
-
-    let confidence = confidence_for(&last_result);
-    Ok(SmtpProbeReport::new(
-        last_result,
-        mx_tried,
-        transcripts,
-        confidence,
-    ))
-}
-
-struct TargetAddress<'a> {
-    local: &'a str,
-    domain: &'a str,
-}
-
-struct HostReport {
-    existence: Existence,
-    transcript: Vec<String>,
+    Ok(outcome.finish())
 }
 
 fn probe_host(
     candidate: &HostCandidate,
-    target: &TargetAddress<'_>,
+    plan: &ProbePlan,
     options: &SmtpProbeOptions,
     connector: &TlsConnector,
-    mail_from: &str,
-    helo: &str,
-    catchall_locals: &[String],
 ) -> Result<HostReport, SmtpVerifyError> {
+    #[cfg(feature = "with-dane")]
+    let mut dane = dane::host_status(candidate)?;
     let mut session =
         SmtpSession::connect(&candidate.host, &candidate.addresses, options.timeout())?;
-    let banner = session.read_banner()?;
-    if banner.code == 521 {
-        session.quit().ok();
-        let transcript = std::mem::take(&mut session.transcript);
-        return Ok(HostReport {
-            existence: Existence::Indeterminate("server does not receive mail".to_string()),
-            transcript,
-        });
-    }
-
-    let helo_cmd = format!("EHLO {helo}");
-    let ehlo = session.send_command(&helo_cmd)?;
-
-    let starttls_advertised = ehlo.has_capability("STARTTLS");
-    if options.starttls_required && !starttls_advertised {
-        session.quit().ok();
-        let transcript = std::mem::take(&mut session.transcript);
-        return Ok(HostReport {
-            existence: Existence::Indeterminate(format!(
-                "STARTTLS required but not offered by {}",
-                candidate.host
-            )),
-            transcript,
-        });
+    let mut dialogue = HostDialogue::new(&candidate.host, plan);
+    let mut step = dialogue.on_reply(&session.read_banner()?);
+    loop {
+        step = match step {
+            Step::Send(command) => dialogue.on_reply(&session.send_command(&command)?),
+            Step::StartTls => {
+                session
+                    .stream
+                    .upgrade_tls(&candidate.host, connector, options.timeout())?;
+                #[cfg(feature = "with-dane")]
+                dane::record_certificate(&mut dane, session.stream.peer_certificate());
+                dialogue.tls_established()
+            }
+            Step::Done { existence, reset } => {
+                if reset {
+                    session.send_command("RSET").ok();
+                }
+                session.quit().ok();
+                return Ok(HostReport {
+                    existence,
+                    transcript: session.transcript.lines,
+                    #[cfg(feature = "with-dane")]
+                    dane: dane::finish(dane),
+                });
+            }
+        };
     }
+}
 
-    if starttls_advertised {
-        let tls_reply = session.starttls(&candidate.host, connector, options.timeout())?;
-        if !tls_reply.is_positive_completion() {
-            session.quit().ok();
-            let transcript = std::mem::take(&mut session.transcript);
-            return Ok(HostReport {
-                existence: Existence::Indeterminate(format!(
-                    "STARTTLS rejected by {} (code {})",
-                    candidate.host, tls_reply.code
-                )),
-                transcript,
-            });
-        }
-        // EHLO again over TLS
-        let _ = session.send_command(&helo_cmd)?;
-    }
+/// Async variant of [`check_mailaddress_exists`] (`async` feature): tokio
+/// resolver, `tokio::net::TcpStream` and tokio-native-tls, same dialogue.
+/// Replays the sync logic per lookup, see [`AsyncDnsBackend`](crate::AsyncDnsBackend).
+#[cfg(feature = "async")]
+pub async fn check_mailaddress_exists_async(
+    addr: &str,
+    options: &SmtpProbeOptions,
+) -> Result<SmtpProbeReport, SmtpVerifyError> {
+    let backend = options
+        .dns
+        .build_async_backend()
+        .map_err(|err| SmtpVerifyError::ResolverInit { source: err })?;
+    check_mailaddress_exists_async_with_backend(&backend, addr, options).await
+}
 
-    let envelope = if mail_from.is_empty() {
-        "MAIL FROM:<>".to_string()
-    } else {
-        format!("MAIL FROM:<{}>", mail_from)
+/// Async variant of [`check_mailaddress_exists_with_backend`].
+/// Replays the sync logic per lookup, see [`AsyncDnsBackend`](crate::AsyncDnsBackend).
+#[cfg(feature = "async")]
+pub async fn check_mailaddress_exists_async_with_backend<B>(
+    backend: &B,
+    addr: &str,
+    options: &SmtpProbeOptions,
+) -> Result<SmtpProbeReport, SmtpVerifyError>
+where
+    B: AsyncDnsBackend + ?Sized,
+{
+    let plan = ProbePlan::new(addr, options)?;
+    let resolved = replay(backend, |prefetched| {
+        resolve_hosts(
+            prefetched,
+            &plan.domain,
+            options.max_mx,
+            options.ipv6,
+            options.smtp_port,
+        )
+    })
+    .await;
+    #[allow(unused_mut)]
+    let mut hosts = match resolved {
+        Ok(hosts) => hosts,
+        Err(err @ SmtpVerifyError::NullMx { .. }) => return Ok(ProbeOutcome::null_mx(&err)),
+        Err(err) => return Err(err),
     };
-    let mail_reply = session.send_command(&envelope)?;
-    if mail_reply.is_permanent_failure() {
-        session.quit().ok();
-        let transcript = std::mem::take(&mut session.transcript);
-        return Ok(HostReport {
-            existence: Existence::Indeterminate(format!(
-                "MAIL FROM rejected with {}",
-                mail_reply.code
-            )),
-            transcript,
-        });
-    }
-
-    let target_cmd = format!("RCPT TO:<{}@{}>", target.local, target.domain);
-    let target_reply = session.send_command(&target_cmd)?;
-    let existence = classify_target(&target_reply);
 
-    match existence {
-        TargetExistence::DoesNotExist => {
-            session.quit().ok();
-            let transcript = std::mem::take(&mut session.transcript);
-            return Ok(HostReport {
-                existence: Existence::DoesNotExist,
-                transcript,
-            });
-        }
-        TargetExistence::Indeterminate(reason) => {
-            session.quit().ok();
-            let transcript = std::mem::take(&mut session.transcript);
-            return Ok(HostReport {
-                existence: Existence::Indeterminate(reason),
-                transcript,
-            });
-        }
-        TargetExistence::Accepted => {}
+    if hosts.is_empty() {
+        return Err(SmtpVerifyError::NoSmtpServers);
     }
 
-    if catchall_locals.is_empty() {
-        session.quit().ok();
-        let transcript = std::mem::take(&mut session.transcript);
-        return Ok(HostReport {
-            existence: Existence::Indeterminate("catch-all probes disabled".to_string()),
-            transcript,
-        });
-    }
+    #[cfg(feature = "with-dane")]
+    replay(backend, |prefetched| {
+        dane::attach(prefetched, &mut hosts, options)
+    })
+    .await;
 
-    let mut accepted_random = 0usize;
-    let mut rejected_random = 0usize;
-    let mut tempfail_random = 0usize;
+    let connector = TlsConnector::new()
+        .map(tokio_native_tls::TlsConnector::from)
+        .map_err(|err| SmtpVerifyError::Tls { source: err })?;
+    #[cfg(feature = "with-dane")]
+    let dane_connector = dane::connector(options)?.map(tokio_native_tls::TlsConnector::from);
 
-    for alias in catchall_locals {
-        if alias.eq(target.local) {
-            continue;
-        }
-        let cmd = format!("RCPT TO:<{}@{}>", alias, target.domain);
-        let reply = session.send_command(&cmd)?;
-        if reply.is_positive_completion() {
-            accepted_random += 1;
-        } else if is_permanent_no_mailbox(&reply) {
-            rejected_random += 1;
-        } else if reply.is_transient_failure() {
-            tempfail_random += 1;
+    let mut outcome = ProbeOutcome::new();
+    for candidate in hosts {
+        #[cfg(feature = "with-dane")]
+        let connector = dane::connector_for(&candidate, &connector, dane_connector.as_ref());
+        #[cfg(not(feature = "with-dane"))]
+        let connector = &connector;
+        let result = probe_host_async(&candidate, &plan, options, connector).await;
+        if outcome.record(&candidate.host, result) {
+            break;
         }
     }
-
-    session.send_command("RSET").ok();
-    session.quit().ok();
-
-    let existence = if accepted_random > 0 {
-        Existence::CatchAll
-    } else if rejected_random > 0 && tempfail_random == 0 {
-        Existence::Exists
-    } else if tempfail_random > 0 {
-        Existence::Indeterminate("temporary failure on catch-all probes".to_string())
-    } else {
-        Existence::Indeterminate("ambiguous catch-all probes".to_string())
-    };
-
-    let transcript = std::mem::take(&mut session.transcript);
-    Ok(HostReport {
-        existence,
-        transcript,
-    })
-}
-
-enum TargetExistence {
-    Accepted,
-    DoesNotExist,
-    Indeterminate(String),
+    Ok(outcome.finish())
 }
 
-fn classify_target(reply: &SmtpReply) -> TargetExistence {
-    if reply.is_positive_completion() {
-        return TargetExistence::Accepted;
-    }
-    if is_permanent_no_mailbox(reply) {
-        return TargetExistence::DoesNotExist;
-    }
-    if reply.code == 521 {
-        return TargetExistence::Indeterminate("521 host does not accept mail".to_string());
-    }
-    if reply.is_transient_failure() {
-        return TargetExistence::Indeterminate(format!("temporary failure {}", reply.code));
+#[cfg(feature = "async")]
+async fn probe_host_async(
+    candidate: &HostCandidate,
+    plan: &ProbePlan,
+    options: &SmtpProbeOptions,
+    connector: &tokio_native_tls::TlsConnector,
+) -> Result<HostReport, SmtpVerifyError> {
+    #[cfg(feature = "with-dane")]
+    let mut dane = dane::host_status(candidate)?;
+    let mut session =
+        AsyncSmtpSession::connect(&candidate.host, &candidate.addresses, options.timeout()).await?;
+    let mut dialogue = HostDialogue::new(&candidate.host, plan);
+    let mut step = dialogue.on_reply(&session.read_banner().await?);
+    loop {
+        step = match step {
+            Step::Send(command) => dialogue.on_reply(&session.send_command(&command).await?),
+            Step::StartTls => {
+                session.upgrade_tls(&candidate.host, connector).await?;
+                #[cfg(feature = "with-dane")]
+                dane::record_certificate(&mut dane, session.peer_certificate());
+                dialogue.tls_established()
+            }
+            Step::Done { existence, reset } => {
+                if reset {
+                    session.send_command("RSET").await.ok();
+                }
+                session.quit().await.ok();
+                return Ok(HostReport {
+                    existence,
+                    transcript: session.transcript.lines,
+                    #[cfg(feature = "with-dane")]
+                    dane: dane::finish(dane),
+                });
+            }
+        };
     }
-    TargetExistence::Indeterminate(format!("unexpected response {}", reply.code))
-}
-
-fn is_permanent_no_mailbox(reply: &SmtpReply) -> bool {
-    matches!(reply.code, 550 | 551 | 553)
 }
@@ -104,6 +104,15 @@ impl SmtpStream {
         Ok(())
     }
 
+    /// Certificat DER du serveur, une fois TLS négocié.
+    #[cfg(feature = "with-dane")]
+    pub fn peer_certificate(&self) -> Option<Vec<u8>> {
+        match &self.state {
+            StreamState::Tls(tls) => tls.peer_certificate().ok()??.to_der().ok(),
+            _ => None,
+        }
+    }
+
     pub fn send_command(&mut self, command: &str) -> Result<(), SmtpVerifyError> {
         let mut data = command.as_bytes().to_vec();
         data.extend_from_slice(b"\r\n");
@@ -136,53 +145,19 @@ impl SmtpStream {
     }
 
     pub fn read_reply(&mut self) -> Result<SmtpReply, SmtpVerifyError> {
-        let mut lines = Vec::new();
-        let mut code: Option<u16> = None;
+        let mut reply = ReplyBuilder::default();
         loop {
             let line = self.read_line()?;
-            if line.len() < 3 {
-                return Err(SmtpVerifyError::Protocol(format!("invalid reply: {line}")));
-            }
-            let parsed_code = line[..3]
-                .parse::<u16>()
-                .map_err(|_| SmtpVerifyError::Protocol(format!("invalid code in line: {line}")))?;
-            if let Some(existing) = code {
-                if existing != parsed_code {
-                    return Err(SmtpVerifyError::Protocol(format!(
-                        "inconsistent reply codes: {existing} vs {parsed_code}"
-                    )));
-                }
-            } else {
-                code = Some(parsed_code);
-            }
-            let is_last = !line.as_bytes().get(3).map(|b| *b == b'-').unwrap_or(false);
-            let text = if line.len() > 4 {
-                line[4..].to_string()
-            } else {
-                String::new()
-            };
-            lines.push(text);
-            if is_last {
-                break;
+            if let Some(done) = reply.push(&line)? {
+                return Ok(done);
             }
         }
-        Ok(SmtpReply {
-            code: code.unwrap_or(0),
-            lines,
-        })
     }
 
     fn read_line(&mut self) -> Result<String, SmtpVerifyError> {
         loop {
-            if let Some(pos) = self.buffer.iter().position(|byte| *byte == b'\n') {
-                let mut line = self.buffer.drain(..=pos).collect::<Vec<_>>();
-                if line.ends_with(b"\r\n") {
-                    line.truncate(line.len() - 2);
-                } else if line.ends_with(b"\n") {
-                    line.truncate(line.len() - 1);
-                }
-                return String::from_utf8(line)
-                    .map_err(|err| SmtpVerifyError::Protocol(format!("utf8 error: {err}")));
+            if let Some(line) = take_line(&mut self.buffer) {
+                return line;
             }
 
             let mut buf = [0u8; 512];
@@ -195,15 +170,76 @@ impl SmtpStream {
             };
             let read = read.map_err(|err| SmtpVerifyError::Io { source: err })?;
             if read == 0 {
-                return Err(SmtpVerifyError::Io {
-                    source: io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed"),
-                });
+                return Err(connection_closed());
             }
             self.buffer.extend_from_slice(&buf[..read]);
         }
     }
 }
 
+/// Retire du tampon la première ligne complète, sans son CRLF.
+pub(crate) fn take_line(buffer: &mut Vec<u8>) -> Option<Result<String, SmtpVerifyError>> {
+    let pos = buffer.iter().position(|byte| *byte == b'\n')?;
+    let mut line = buffer.drain(..=pos).collect::<Vec<_>>();
+    if line.ends_with(b"\r\n") {
+        line.truncate(line.len() - 2);
+    } else if line.ends_with(b"\n") {
+        line.truncate(line.len() - 1);
+    }
+    Some(
+        String::from_utf8(line)
+            .map_err(|err| SmtpVerifyError::Protocol(format!("utf8 error: {err}"))),
+    )
+}
+
+pub(crate) fn connection_closed() -> SmtpVerifyError {
+    SmtpVerifyError::Io {
+        source: io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed"),
+    }
+}
+
+/// Assemble les lignes d'une réponse, éventuellement multi-ligne (`250-…`).
+#[derive(Debug, Default)]
+pub(crate) struct ReplyBuilder {
+    code: Option<u16>,
+    lines: Vec<String>,
+}
+
+impl ReplyBuilder {
+    /// Ajoute une ligne ; renvoie la réponse complète après la dernière.
+    pub(crate) fn push(&mut self, line: &str) -> Result<Option<SmtpReply>, SmtpVerifyError> {
+        if line.len() < 3 {
+            return Err(SmtpVerifyError::Protocol(format!("invalid reply: {line}")));
+        }
+        let parsed_code = line[..3]
+            .parse::<u16>()
+            .map_err(|_| SmtpVerifyError::Protocol(format!("invalid code in line: {line}")))?;
+        if let Some(existing) = self.code {
+            if existing != parsed_code {
+                return Err(SmtpVerifyError::Protocol(format!(
+                    "inconsistent reply codes: {existing} vs {parsed_code}"
+                )));
+            }
+        } else {
+            self.code = Some(parsed_code);
+        }
+        let is_last = !line.as_bytes().get(3).map(|b| *b == b'-').unwrap_or(false);
+        let text = if line.len() > 4 {
+            line[4..].to_string()
+        } else {
+            String::new()
+        };
+        self.lines.push(text);
+        if !is_last {
+            return Ok(None);
+        }
+        Ok(Some(SmtpReply {
+            code: parsed_code,
+            lines: std::mem::take(&mut self.lines),
+        }))
+    }
+}
+
 fn complete_handshake(
     connector: &TlsConnector,
     domain: &str,
@@ -224,10 +260,40 @@ fn complete_handshake(
     }
 }
 
-pub struct SmtpSession {
+/// Journal d'une session, préfixé par l'hôte (`[mx] C: …` / `[mx] S: …`).
+#[derive(Debug)]
+pub(crate) struct Transcript {
     host: String,
+    pub lines: Vec<String>,
+}
+
+impl Transcript {
+    pub fn new(host: &str) -> Self {
+        Self {
+            host: host.to_string(),
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, direction: &str, message: &str) {
+        self.lines
+            .push(format!("[{}] {direction}: {message}", self.host));
+    }
+
+    pub fn record_reply(&mut self, reply: &SmtpReply) {
+        if reply.lines.is_empty() {
+            self.record("S", &format!("{}", reply.code));
+        } else {
+            for line in &reply.lines {
+                self.record("S", &format!("{} {}", reply.code, line));
+            }
+        }
+    }
+}
+
+pub struct SmtpSession {
     pub stream: SmtpStream,
-    pub transcript: Vec<String>,
+    pub transcript: Transcript,
 }
 
 impl SmtpSession {
@@ -241,9 +307,8 @@ impl SmtpSession {
             match SmtpStream::connect(addr, timeout) {
                 Ok(stream) => {
                     return Ok(Self {
-                        host: host.to_string(),
                         stream,
-                        transcript: Vec::new(),
+                        transcript: Transcript::new(host),
                     });
                 }
                 Err(err) => last_err = Some(err),
@@ -252,55 +317,26 @@ impl SmtpSession {
         Err(last_err.unwrap_or(SmtpVerifyError::NoSmtpServers))
     }
 
-    pub fn record(&mut self, direction: &str, message: &str) {
-        self.transcript
-            .push(format!("[{}] {direction}: {message}", self.host));
-    }
-
     pub fn read_banner(&mut self) -> Result<SmtpReply, SmtpVerifyError> {
         let reply = self.stream.read_reply()?;
-        self.record_reply(&reply);
+        self.transcript.record_reply(&reply);
         Ok(reply)
     }
 
     pub fn send_command(&mut self, command: &str) -> Result<SmtpReply, SmtpVerifyError> {
-        self.record("C", command);
+        self.transcript.record("C", command);
         self.stream.send_command(command)?;
         let reply = self.stream.read_reply()?;
-        self.record_reply(&reply);
-        Ok(reply)
-    }
-
-    pub fn starttls(
-        &mut self,
-        domain: &str,
-        connector: &TlsConnector,
-        timeout: Option<Duration>,
-    ) -> Result<SmtpReply, SmtpVerifyError> {
-        let reply = self.send_command("STARTTLS")?;
-        if !reply.is_positive_completion() {
-            return Ok(reply);
-        }
-        self.stream.upgrade_tls(domain, connector, timeout)?;
+        self.transcript.record_reply(&reply);
         Ok(reply)
     }
 
     pub fn quit(&mut self) -> Result<(), SmtpVerifyError> {
-        self.record("C", "QUIT");
+        self.transcript.record("C", "QUIT");
         self.stream.send_command("QUIT")?;
         if let Ok(reply) = self.stream.read_reply() {
-            self.record_reply(&reply);
+            self.transcript.record_reply(&reply);
         }
         Ok(())
     }
-
-    fn record_reply(&mut self, reply: &SmtpReply) {
-        if reply.lines.is_empty() {
-            self.record("S", &format!("{}", reply.code));
-        } else {
-            for line in &reply.lines {
-                self.record("S", &format!("{} {}", reply.code, line));
-            }
-        }
-    }
 }
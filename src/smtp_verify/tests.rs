@@ -0,0 +1,135 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+
+use super::{Existence, SmtpProbeOptions, SmtpVerifyError, check_mailaddress_exists_with_backend};
+use crate::dns::StaticZone;
+
+/// Serveur SMTP minimal : accepte `alice`, refuse toute autre boîte.
+fn fake_smtp_server() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind listener");
+    let port = listener.local_addr().expect("local addr").port();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().expect("accept");
+        let mut reader = BufReader::new(stream.try_clone().expect("clone stream"));
+        let mut writer = stream;
+        writer.write_all(b"220 mx.example.test ESMTP\r\n").unwrap();
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap_or(0) > 0 {
+            let command = line.trim_end().to_ascii_uppercase();
+            let reply: &[u8] = if command.starts_with("EHLO") {
+                b"250-mx.example.test\r\n250 SIZE 1000000\r\n"
+            } else if command.starts_with("RCPT TO:<ALICE@") {
+                b"250 OK\r\n"
+            } else if command.starts_with("RCPT") {
+                b"550 5.1.1 no such user\r\n"
+            } else if command.starts_with("QUIT") {
+                writer.write_all(b"221 bye\r\n").ok();
+                break;
+            } else {
+                b"250 OK\r\n"
+            };
+            writer.write_all(reply).unwrap();
+            line.clear();
+        }
+    });
+    port
+}
+
+fn options(port: u16) -> SmtpProbeOptions {
+    SmtpProbeOptions {
+        smtp_port: port,
+        ..SmtpProbeOptions::default()
+    }
+}
+
+#[test]
+fn probes_offline_through_static_zone() {
+    let zone = StaticZone::from_zone_str(
+        "example.test. MX 10 mx.example.test.\nmx.example.test. A 127.0.0.1",
+    )
+    .expect("valid zone");
+    let port = fake_smtp_server();
+
+    let report = check_mailaddress_exists_with_backend(&zone, "alice@example.test", &options(port))
+        .expect("probe succeeds");
+    assert_eq!(report.result, Existence::Exists);
+    assert_eq!(report.mx_tried, vec!["mx.example.test".to_string()]);
+}
+
+#[cfg(feature = "with-dane")]
+#[test]
+fn dane_host_without_starttls_is_reported() {
+    use crate::dane::{CertificateMatch, DaneState};
+    use crate::dns::DnssecStatus;
+
+    let port = fake_smtp_server();
+    let zone = StaticZone::from_zone_str(&format!(
+        "example.test. MX 10 mx.example.test.\n\
+         mx.example.test. A 127.0.0.1\n\
+         _{port}._tcp.mx.example.test. TLSA 3 1 1 {}",
+        "ab".repeat(32)
+    ))
+    .expect("valid zone")
+    .with_dnssec("example.test", DnssecStatus::Secure);
+    let options = SmtpProbeOptions {
+        dane: true,
+        ..options(port)
+    };
+
+    let report = check_mailaddress_exists_with_backend(&zone, "alice@example.test", &options)
+        .expect("probe succeeds");
+    assert_eq!(report.result, Existence::Exists);
+    assert_eq!(report.dane.len(), 1);
+    assert_eq!(report.dane[0].state(), DaneState::Usable);
+    assert_eq!(
+        report.dane[0].certificate,
+        Some(CertificateMatch::NoStartTls)
+    );
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_probe_matches_sync_dialogue() {
+    use super::check_mailaddress_exists_async_with_backend;
+    use crate::dns::block_on;
+
+    let zone = StaticZone::from_zone_str(
+        "example.test. MX 10 mx.example.test.\nmx.example.test. A 127.0.0.1",
+    )
+    .expect("valid zone");
+    let port = fake_smtp_server();
+
+    let report = block_on(check_mailaddress_exists_async_with_backend(
+        &zone,
+        "bob@example.test",
+        &options(port),
+    ))
+    .expect("probe succeeds");
+    assert_eq!(report.result, Existence::DoesNotExist);
+    assert!(
+        report
+            .transcript
+            .iter()
+            .any(|line| line == "[mx.example.test] C: RCPT TO:<bob@example.test>")
+    );
+}
+
+#[test]
+fn null_mx_reports_does_not_exist() {
+    let zone = StaticZone::from_zone_str("example.test. MX 0 .").expect("valid zone");
+
+    let report = check_mailaddress_exists_with_backend(&zone, "alice@example.test", &options(25))
+        .expect("probe succeeds");
+    assert_eq!(report.result, Existence::DoesNotExist);
+    assert!(report.mx_tried.is_empty());
+}
+
+#[test]
+fn unknown_domain_has_no_smtp_servers() {
+    let zone = StaticZone::new();
+
+    let err = check_mailaddress_exists_with_backend(&zone, "alice@example.test", &options(25))
+        .expect_err("no servers");
+    assert!(matches!(err, SmtpVerifyError::NoSmtpServers));
+}
@@ -3,6 +3,9 @@ use std::fmt;
 #[cfg(feature = "with-serde")]
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "with-dane")]
+use crate::dane::DaneStatus;
+
 /// Classification of the observed SMTP behaviour for a mailbox.
 #[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -44,6 +47,13 @@ pub struct SmtpProbeReport {
     pub mx_tried: Vec<String>,
     pub transcript: Vec<String>,
     pub confidence: f32,
+    /// Statut DANE des MX essayés, si [`SmtpProbeOptions::dane`](crate::SmtpProbeOptions).
+    #[cfg(feature = "with-dane")]
+    #[cfg_attr(
+        feature = "with-serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub dane: Vec<DaneStatus>,
 }
 
 impl SmtpProbeReport {
@@ -58,6 +68,8 @@ impl SmtpProbeReport {
             mx_tried,
             transcript,
             confidence,
+            #[cfg(feature = "with-dane")]
+            dane: Vec::new(),
         }
     }
 }
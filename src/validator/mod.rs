@@ -4,10 +4,13 @@ mod spec;
 mod types;
 
 pub use types::{
-    EmailError, NormalizedEmail, SpecCharacters, SpecClass, SpecFinding, SpecOptions, SpecSegment,
-    ValidationMode, ValidationReport,
+    EmailError, NormalizedEmail, SpecCharacters, SpecClass, SpecFinding, SpecHintRule,
+    SpecHintSubstitution, SpecLocale, SpecOptions, SpecRiskContribution, SpecRiskFactor,
+    SpecRiskWeights, SpecSegment, SpecTransliteration, ValidationMode, ValidationReport,
 };
 
+use unicode_normalization::UnicodeNormalization;
+
 use domain::{check_domain, normalize_domain};
 use local::{is_local_relaxed, is_local_strict};
 use spec::{analyze_spec_characters, join_spec_notes};
@@ -138,6 +141,8 @@ pub fn normalize_email_with_spec(
         ascii_domain,
         mode,
         valid: ok,
+        nfc: input.nfc().collect(),
+        nfkc: input.nfkc().collect(),
         reasons,
         spec_chars,
         has_confusables,
@@ -181,6 +186,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn normalized_exposes_nfc_and_nfkc_forms() {
+        let n = normalize_email("ｊａｎｅ@exa\u{0301}mple.com", ValidationMode::Strict).unwrap();
+        assert_eq!(n.nfc, "ｊａｎｅ@exámple.com");
+        assert_eq!(n.nfkc, "jane@exámple.com");
+    }
+
     #[test]
     fn strict_profile_flags_confusable_domain() {
         let report = validate_email_with_spec(
@@ -205,4 +217,26 @@ mod tests {
         assert!(!report.ok);
         assert!(report.reasons.iter().any(|r| r.contains(".fr domain")));
     }
+
+    #[test]
+    fn locale_profiles_warn_on_their_sensitive_suffixes() {
+        let cases = [
+            ("de-fraud", "user@exаmple.bund.de", ".bund.de domain"),
+            ("es-fraud", "user@exаmple.gob.es", ".gob.es domain"),
+            ("it-fraud", "user@exаmple.gov.it", ".gov.it domain"),
+            ("pt-br-fraud", "user@exаmple.gov.br", ".gov.br domain"),
+            ("generic-bank", "user@exаmple.bank", ".bank domain"),
+        ];
+        for (profile, email, expected) in cases {
+            let options = SpecOptions::profile(profile).expect("builtin profile");
+            let report =
+                validate_email_with_spec(email, ValidationMode::Strict, Some(options)).unwrap();
+            assert!(!report.ok, "{profile}");
+            assert!(
+                report.reasons.iter().any(|r| r.contains(expected)),
+                "{profile}: {:?}",
+                report.reasons
+            );
+        }
+    }
 }
@@ -2,11 +2,14 @@ use std::borrow::Cow;
 use std::collections::HashSet;
 
 use phf::phf_map;
-use unicode_normalization::UnicodeNormalization;
-use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::char::{compose, is_combining_mark};
+use unicode_normalization::{UnicodeNormalization, is_nfc};
 use unicode_script::{Script, UnicodeScript};
 
-use super::types::{SpecCharacters, SpecClass, SpecFinding, SpecOptions, SpecSegment};
+use super::types::{
+    SpecCharacters, SpecClass, SpecFinding, SpecHintRule, SpecHintSubstitution, SpecLocale,
+    SpecOptions, SpecRiskContribution, SpecRiskFactor, SpecSegment, SpecTransliteration,
+};
 
 const DIACRITIC_MAP: phf::Map<char, &'static str> = phf_map! {
     'à' => "a", 'á' => "a", 'â' => "a", 'ä' => "a", 'ã' => "a", 'å' => "a",
@@ -26,7 +29,8 @@ const DIACRITIC_MAP: phf::Map<char, &'static str> = phf_map! {
     'æ' => "ae", 'Æ' => "AE",
 };
 
-const FR_HINT_EXTRA_MAP: phf::Map<char, &'static str> = phf_map! {
+// Ponctuation typographique reconnue dès qu'une locale est active.
+const TYPOGRAPHIC_PUNCT_MAP: phf::Map<char, &'static str> = phf_map! {
     '«' => "\"",
     '»' => "\"",
     '“' => "\"",
@@ -38,6 +42,66 @@ const FR_HINT_EXTRA_MAP: phf::Map<char, &'static str> = phf_map! {
     '‑' => "-",
 };
 
+const DE_PUNCT_MAP: phf::Map<char, &'static str> = phf_map! {
+    '„' => "\"",
+    '‚' => "'",
+    '‹' => "'",
+    '›' => "'",
+};
+
+const ES_PUNCT_MAP: phf::Map<char, &'static str> = phf_map! {
+    '¡' => "",
+    '¿' => "",
+};
+
+// Translittérations conventionnelles propres à une locale.
+const DE_TRANSLIT_MAP: phf::Map<char, &'static str> = phf_map! {
+    'ä' => "ae", 'ö' => "oe", 'ü' => "ue",
+    'Ä' => "Ae", 'Ö' => "Oe", 'Ü' => "Ue",
+    'ß' => "ss", 'ẞ' => "SS",
+};
+
+// Lettres latines sans décomposition NFKD (profil `Full`).
+const LATIN_TRANSLIT_MAP: phf::Map<char, &'static str> = phf_map! {
+    'ß' => "ss", 'ẞ' => "SS",
+    'ø' => "o", 'Ø' => "O",
+    'ł' => "l", 'Ł' => "L",
+    'đ' => "d", 'Đ' => "D",
+    'ð' => "d", 'Ð' => "D",
+    'þ' => "th", 'Þ' => "TH",
+    'ħ' => "h", 'Ħ' => "H",
+    'ı' => "i",
+    'ŀ' => "l", 'Ŀ' => "L",
+    'ŋ' => "ng", 'Ŋ' => "NG",
+    'ĸ' => "k",
+    'ſ' => "s",
+};
+
+// Romanisation (minuscules ; la casse est reportée à l'usage).
+const CYRILLIC_ROMAN_MAP: phf::Map<char, &'static str> = phf_map! {
+    'а' => "a", 'б' => "b", 'в' => "v", 'г' => "g", 'д' => "d",
+    'е' => "e", 'ё' => "yo", 'ж' => "zh", 'з' => "z", 'и' => "i",
+    'й' => "y", 'к' => "k", 'л' => "l", 'м' => "m", 'н' => "n",
+    'о' => "o", 'п' => "p", 'р' => "r", 'с' => "s", 'т' => "t",
+    'у' => "u", 'ф' => "f", 'х' => "kh", 'ц' => "ts", 'ч' => "ch",
+    'ш' => "sh", 'щ' => "shch", 'ъ' => "", 'ы' => "y", 'ь' => "",
+    'э' => "e", 'ю' => "yu", 'я' => "ya",
+    'і' => "i", 'ї' => "yi", 'є' => "ye", 'ґ' => "g", 'ў' => "u",
+    'ђ' => "dj", 'ј' => "j", 'љ' => "lj", 'њ' => "nj", 'ћ' => "c",
+    'џ' => "dz", 'ѓ' => "gj", 'ќ' => "kj", 'ѕ' => "dz",
+};
+
+const GREEK_ROMAN_MAP: phf::Map<char, &'static str> = phf_map! {
+    'α' => "a", 'β' => "v", 'γ' => "g", 'δ' => "d", 'ε' => "e",
+    'ζ' => "z", 'η' => "i", 'θ' => "th", 'ι' => "i", 'κ' => "k",
+    'λ' => "l", 'μ' => "m", 'ν' => "n", 'ξ' => "x", 'ο' => "o",
+    'π' => "p", 'ρ' => "r", 'σ' => "s", 'ς' => "s", 'τ' => "t",
+    'υ' => "y", 'φ' => "f", 'χ' => "ch", 'ψ' => "ps", 'ω' => "o",
+    'ά' => "a", 'έ' => "e", 'ή' => "i", 'ί' => "i", 'ό' => "o",
+    'ύ' => "y", 'ώ' => "o", 'ϊ' => "i", 'ϋ' => "y", 'ΐ' => "i",
+    'ΰ' => "y",
+};
+
 const CONFUSABLE_MAP: phf::Map<char, &'static str> = phf_map! {
     // Cyrillic
     'а' => "a",
@@ -208,6 +272,7 @@ pub(crate) fn analyze_spec_characters(
         computation.characters.normalized_ascii_hint = hint;
     }
 
+    computation.score_risk(domain, options);
     computation
 }
 
@@ -241,6 +306,118 @@ impl SpecComputation {
             }
         }
     }
+
+    /// Agrège les findings en `risk_score` pondéré (borné à 1) et son détail.
+    fn score_risk(&mut self, domain: &str, options: &SpecOptions) {
+        let weights = &options.risk_weights;
+        let details = &self.characters.details;
+        let count = |class: SpecClass, in_local: bool| {
+            details
+                .iter()
+                .filter(|f| f.class == class && (f.segment == SpecSegment::Local) == in_local)
+                .count()
+        };
+        let mut breakdown = Vec::new();
+
+        let local_confusables = count(SpecClass::Confusable, true);
+        if local_confusables > 0 {
+            breakdown.push(SpecRiskContribution {
+                factor: SpecRiskFactor::LocalConfusable,
+                weight: permille(weights.local_confusable),
+                note: format!("{local_confusables} confusable(s) in local part"),
+            });
+        }
+        if !self.confusable_labels_for_policy.is_empty() {
+            breakdown.push(SpecRiskContribution {
+                factor: SpecRiskFactor::DomainConfusable,
+                weight: permille(weights.domain_confusable),
+                note: format!(
+                    "confusable(s) in label(s) {}",
+                    self.confusable_labels_for_policy.join(", ")
+                ),
+            });
+        }
+        if count(SpecClass::MixedScript, true) > 0 || !self.mixed_labels_for_policy.is_empty() {
+            breakdown.push(SpecRiskContribution {
+                factor: SpecRiskFactor::MixedScripts,
+                weight: permille(weights.mixed_scripts),
+                note: "mixed Unicode scripts".to_string(),
+            });
+        }
+        let invisible = count(SpecClass::Invisible, true) + count(SpecClass::Invisible, false);
+        if invisible > 0 {
+            breakdown.push(SpecRiskContribution {
+                factor: SpecRiskFactor::Invisible,
+                weight: permille(weights.invisible),
+                note: format!("{invisible} invisible character(s)"),
+            });
+        }
+        if let Some(target) = lookalike_target(domain, options) {
+            breakdown.push(SpecRiskContribution {
+                factor: SpecRiskFactor::Lookalike,
+                weight: permille(weights.lookalike),
+                note: format!("domain looks like protected '{target}'"),
+            });
+        }
+        if !self.confusable_labels_for_policy.is_empty() {
+            let domain_lower = domain.to_ascii_lowercase();
+            if let Some((tld, _)) = options
+                .confusable_tld_warnings
+                .iter()
+                .find(|(tld, _)| domain_matches_tld(&domain_lower, tld))
+            {
+                breakdown.push(SpecRiskContribution {
+                    factor: SpecRiskFactor::SensitiveTld,
+                    weight: permille(weights.sensitive_tld),
+                    note: format!("confusables under sensitive suffix .{tld}"),
+                });
+            }
+        }
+
+        let total: u32 = breakdown.iter().map(|c| u32::from(c.weight)).sum();
+        self.characters.risk_score = total.min(1000) as u16;
+        self.characters.risk_breakdown = breakdown;
+    }
+}
+
+/// Poids `[0, 1]` de [`SpecRiskWeights`] en millièmes.
+fn permille(weight: f32) -> u16 {
+    (weight.clamp(0.0, 1.0) * 1000.0).round() as u16
+}
+
+/// Premier domaine protégé dont `domain` est un sosie visuel.
+fn lookalike_target<'a>(domain: &str, options: &'a SpecOptions) -> Option<&'a str> {
+    if domain.is_empty() || options.lookalike_targets.is_empty() {
+        return None;
+    }
+    let domain_lower = domain.to_lowercase();
+    let skeleton = visual_skeleton(&domain_lower, options);
+    options
+        .lookalike_targets
+        .iter()
+        .map(String::as_str)
+        .find(|target| {
+            let target_lower = target.to_lowercase();
+            target_lower != domain_lower && visual_skeleton(&target_lower, options) == skeleton
+        })
+}
+
+/// Squelette visuel : homoglyphes ramenés au latin, invisibles supprimés,
+/// puis repli des séquences ASCII trompeuses (`rn` → `m`, `0` → `o`…).
+fn visual_skeleton(text: &str, options: &SpecOptions) -> String {
+    let mut skeleton = String::new();
+    for ch in text.chars().filter(|ch| !is_invisible(*ch)) {
+        match ascii_hint_for_char(ch, options, true) {
+            Some((hint, _)) => skeleton.push_str(&hint),
+            None => skeleton.push(ch),
+        }
+    }
+    skeleton
+        .to_ascii_lowercase()
+        .replace("rn", "m")
+        .replace("vv", "w")
+        .replace('0', "o")
+        .replace('1', "l")
 }
 
 fn domain_matches_tld(domain: &str, tld: &str) -> bool {
@@ -264,20 +441,33 @@ fn process_segment(
     let mut result = SegmentResult::default();
     let mut primary_script: Option<Script> = None;
     let mut mixed_reported = false;
+    let latin_context = text.chars().any(|c| c.script() == Script::Latin);
 
-    for ch in text.chars() {
-        let ascii_hint = ascii_hint_for_char(ch, options);
+    if options.detect_normalization {
+        normalization_findings(&segment, text, characters);
+    }
 
+    // Le détail des substitutions n'est tenu qu'au-delà de la table de base.
+    let record_rules = options.transliteration != SpecTransliteration::Basic;
+    for ch in text.chars() {
         if let Some(ref mut buf) = ascii_buf {
-            if let Some(ref hint) = ascii_hint {
+            if let Some((hint, rule)) = ascii_hint_for_char(ch, options, latin_context) {
                 buf.push_str(hint.as_ref());
+                if record_rules {
+                    characters.hint_substitutions.push(SpecHintSubstitution {
+                        segment: segment.clone(),
+                        codepoint: ch,
+                        replacement: hint.into_owned(),
+                        rule,
+                    });
+                }
             } else {
                 buf.push(ch);
             }
         }
 
         if options.detect_confusables {
-            if let Some(repl) = CONFUSABLE_MAP.get(&ch) {
+            if let Some(repl) = confusable_for(ch, options) {
                 result.confusable = true;
                 characters.has_confusables = true;
                 let note = format!("{}({}) → {}(lat)", ch, script_abbrev(ch), repl);
@@ -291,7 +481,7 @@ fn process_segment(
         }
 
         if options.detect_diacritics {
-            if let Some(repl) = DIACRITIC_MAP.get(&ch) {
+            if let Some(repl) = diacritic_for(ch, options) {
                 characters.has_diacritics = true;
                 let note = format!("{ch} → {repl} (diacritic)");
                 characters.details.push(SpecFinding {
@@ -312,6 +502,16 @@ fn process_segment(
             }
         }
 
+        if options.detect_invisible && is_invisible(ch) {
+            let note = format!("U+{:04X} invisible character", ch as u32);
+            characters.details.push(SpecFinding {
+                segment: segment.clone(),
+                codepoint: ch,
+                class: SpecClass::Invisible,
+                note,
+            });
+        }
+
         if options.detect_mixed_scripts {
             if let Some(script) = major_script(ch) {
                 if let Some(primary) = primary_script {
@@ -343,24 +543,55 @@ fn process_segment(
     result
 }
 
-fn ascii_hint_for_char<'a>(ch: char, options: &SpecOptions) -> Option<Cow<'a, str>> {
+/// Calcule la substitution ASCII d'un caractère et la règle appliquée.
+///
+/// `latin_context` indique si le segment contient des lettres latines : en
+/// translittération `Full`, un segment sans latin (nom cyrillique, grec…) est
+/// romanisé plutôt que ramené à ses homoglyphes.
+fn ascii_hint_for_char<'a>(
+    ch: char,
+    options: &'a SpecOptions,
+    latin_context: bool,
+) -> Option<(Cow<'a, str>, SpecHintRule)> {
+    let full = options.transliteration == SpecTransliteration::Full;
+
     if is_combining_mark(ch) {
-        return Some(Cow::Borrowed(""));
+        return Some((Cow::Borrowed(""), SpecHintRule::CombiningMark));
     }
-    if let Some(repl) = CONFUSABLE_MAP.get(&ch) {
-        return Some(Cow::Borrowed(repl));
+    if options.strip_invisible && is_invisible(ch) {
+        return Some((Cow::Borrowed(""), SpecHintRule::Invisible));
+    }
+    if full && !latin_context {
+        if let Some(repl) = romanize(ch) {
+            return Some((repl, SpecHintRule::Romanization));
+        }
+    }
+    if let Some(repl) = confusable_for(ch, options) {
+        return Some((Cow::Borrowed(repl), SpecHintRule::Confusable));
+    }
+    if let Some(repl) = options.extra_diacritics.get(&ch) {
+        return Some((Cow::Borrowed(repl.as_str()), SpecHintRule::Diacritic));
+    }
+    if let Some(repl) = locale_transliteration(ch, options.hint_locale) {
+        return Some((Cow::Borrowed(repl), SpecHintRule::Locale));
     }
     if let Some(repl) = DIACRITIC_MAP.get(&ch) {
-        return Some(Cow::Borrowed(repl));
+        return Some((Cow::Borrowed(repl), SpecHintRule::Diacritic));
     }
-    if options.use_fr_hint_extensions {
-        if let Some(repl) = FR_HINT_EXTRA_MAP.get(&ch) {
-            return Some(Cow::Borrowed(repl));
-        }
+    if let Some(repl) = locale_punctuation(ch, options.hint_locale) {
+        return Some((Cow::Borrowed(repl), SpecHintRule::Punctuation));
     }
     if ch.is_ascii() {
         return None;
     }
+    if full {
+        if let Some(repl) = LATIN_TRANSLIT_MAP.get(&ch) {
+            return Some((Cow::Borrowed(repl), SpecHintRule::Transliteration));
+        }
+        if let Some(repl) = romanize(ch) {
+            return Some((repl, SpecHintRule::Romanization));
+        }
+    }
 
     let mut decomposed = String::new();
     for d in ch.to_string().nfkd() {
@@ -368,13 +599,174 @@ fn ascii_hint_for_char<'a>(ch: char, options: &SpecOptions) -> Option<Cow<'a, st
             decomposed.push(d);
         }
     }
-    if decomposed.is_empty() {
-        None
-    } else {
-        Some(Cow::Owned(decomposed))
+    if !decomposed.is_empty() {
+        return Some((Cow::Owned(decomposed), SpecHintRule::Nfkd));
+    }
+
+    if full && ch.is_alphabetic() {
+        let fallback: String = deunicode::deunicode_char(ch)
+            .unwrap_or("")
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect();
+        if !fallback.is_empty() {
+            return Some((Cow::Owned(fallback), SpecHintRule::Fallback));
+        }
+    }
+    None
+}
+
+/// Table de confusables du profil, puis table intégrée.
+fn confusable_for(ch: char, options: &SpecOptions) -> Option<&str> {
+    options
+        .extra_confusables
+        .get(&ch)
+        .map(String::as_str)
+        .or_else(|| CONFUSABLE_MAP.get(&ch).copied())
+}
+
+fn diacritic_for(ch: char, options: &SpecOptions) -> Option<&str> {
+    options
+        .extra_diacritics
+        .get(&ch)
+        .map(String::as_str)
+        .or_else(|| locale_transliteration(ch, options.hint_locale))
+        .or_else(|| DIACRITIC_MAP.get(&ch).copied())
+}
+
+fn locale_transliteration(ch: char, locale: Option<SpecLocale>) -> Option<&'static str> {
+    match locale? {
+        SpecLocale::De => DE_TRANSLIT_MAP.get(&ch).copied(),
+        SpecLocale::Fr | SpecLocale::Es | SpecLocale::It | SpecLocale::PtBr => None,
+    }
+}
+
+fn locale_punctuation(ch: char, locale: Option<SpecLocale>) -> Option<&'static str> {
+    let specific = match locale? {
+        SpecLocale::De => DE_PUNCT_MAP.get(&ch),
+        SpecLocale::Es => ES_PUNCT_MAP.get(&ch),
+        SpecLocale::Fr | SpecLocale::It | SpecLocale::PtBr => None,
+    };
+    specific.or_else(|| TYPOGRAPHIC_PUNCT_MAP.get(&ch)).copied()
+}
+
+/// Romanisation cyrillique/grecque ; la majuscule initiale est conservée
+/// (`Ж` → `Zh`).
+fn romanize(ch: char) -> Option<Cow<'static, str>> {
+    let lower = ch.to_lowercase().next().unwrap_or(ch);
+    let repl = CYRILLIC_ROMAN_MAP
+        .get(&lower)
+        .or_else(|| GREEK_ROMAN_MAP.get(&lower))?;
+    if lower == ch {
+        return Some(Cow::Borrowed(repl));
+    }
+    let mut chars = repl.chars();
+    Some(match chars.next() {
+        Some(first) => Cow::Owned(first.to_ascii_uppercase().to_string() + chars.as_str()),
+        None => Cow::Borrowed(repl),
+    })
+}
+
+/// Nombre de marques combinantes tolérées sur une même base (le vietnamien
+/// en empile deux : `ệ`).
+const MAX_STACKED_MARKS: usize = 2;
+
+/// Findings de normalisation : segment non NFC, caractères de compatibilité
+/// (modifiés par NFKC) et séquences combinantes anormales.
+fn normalization_findings(segment: &SpecSegment, text: &str, characters: &mut SpecCharacters) {
+    let mut push = |codepoint: char, class: SpecClass, note: String| {
+        characters.details.push(SpecFinding {
+            segment: segment.clone(),
+            codepoint,
+            class,
+            note,
+        });
+    };
+
+    if !is_nfc(text) {
+        let mut prev: Option<char> = None;
+        let culprit = text
+            .chars()
+            .find(|&ch| {
+                let composes = prev.is_some_and(|base| compose(base, ch).is_some());
+                prev = Some(ch);
+                composes || !is_nfc(ch.encode_utf8(&mut [0; 4]))
+            })
+            .or_else(|| text.chars().next());
+        if let Some(ch) = culprit {
+            let nfc: String = text.nfc().collect();
+            push(
+                ch,
+                SpecClass::NotNfc,
+                format!("not NFC-normalized (NFC: {nfc})"),
+            );
+        }
+    }
+
+    for ch in text.chars() {
+        let nfc: String = ch.to_string().nfc().collect();
+        let nfkc: String = ch.to_string().nfkc().collect();
+        if nfkc != nfc {
+            push(
+                ch,
+                SpecClass::Compatibility,
+                format!("U+{:04X} → {nfkc} (NFKC compatibility)", ch as u32),
+            );
+        }
+    }
+
+    let mut marks = 0usize;
+    let mut previous_mark: Option<char> = None;
+    let mut has_base = false;
+    for ch in text.chars() {
+        if !is_combining_mark(ch) {
+            marks = 0;
+            previous_mark = None;
+            has_base = true;
+            continue;
+        }
+        marks += 1;
+        let note = if !has_base {
+            Some(format!("U+{:04X} combining mark without base", ch as u32))
+        } else if previous_mark == Some(ch) {
+            Some(format!("U+{:04X} repeated combining mark", ch as u32))
+        } else if marks == MAX_STACKED_MARKS + 1 {
+            Some(format!(
+                "U+{:04X} stacked combining marks (> {MAX_STACKED_MARKS} on one base)",
+                ch as u32
+            ))
+        } else {
+            None
+        };
+        if let Some(note) = note {
+            push(ch, SpecClass::CombiningSequence, note);
+        }
+        previous_mark = Some(ch);
     }
 }
 
+/// Caractères sans rendu visible : espaces de largeur nulle, jointures,
+/// contrôles bidirectionnels, tiret conditionnel, remplissages hangul.
+fn is_invisible(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{00AD}'
+            | '\u{061C}'
+            | '\u{115F}'
+            | '\u{1160}'
+            | '\u{17B4}'
+            | '\u{17B5}'
+            | '\u{180E}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{202A}'..='\u{202E}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{2066}'..='\u{206F}'
+            | '\u{3164}'
+            | '\u{FEFF}'
+            | '\u{FFA0}'
+    )
+}
+
 fn major_script(ch: char) -> Option<Script> {
     match ch.script() {
         Script::Common | Script::Inherited | Script::Unknown => None,
@@ -473,6 +865,254 @@ mod tests {
         assert!(notes.contains("mixed scripts"));
     }
 
+    #[test]
+    fn full_transliteration_handles_special_latin_letters() {
+        let mut opts = SpecOptions::standard();
+        opts.transliteration = SpecTransliteration::Full;
+        let spec = analyze_spec_characters("müller", "straße.de", &opts).characters;
+        assert_eq!(
+            spec.normalized_ascii_hint.as_deref(),
+            Some("muller@strasse.de")
+        );
+        let sharp_s = spec
+            .hint_substitutions
+            .iter()
+            .find(|s| s.codepoint == 'ß')
+            .expect("ß substitution");
+        assert_eq!(sharp_s.replacement, "ss");
+        assert_eq!(sharp_s.rule, SpecHintRule::Transliteration);
+    }
+
+    #[test]
+    fn basic_transliteration_keeps_unmapped_letters() {
+        let opts = SpecOptions::standard();
+        let spec = analyze_spec_characters("user", "straße.de", &opts).characters;
+        assert_eq!(
+            spec.normalized_ascii_hint.as_deref(),
+            Some("user@straße.de")
+        );
+    }
+
+    #[test]
+    fn full_transliteration_romanizes_non_latin_segments() {
+        let mut opts = SpecOptions::standard();
+        opts.transliteration = SpecTransliteration::Full;
+        let spec = analyze_spec_characters("Пётр", "example.com", &opts).characters;
+        assert_eq!(
+            spec.normalized_ascii_hint.as_deref(),
+            Some("Pyotr@example.com")
+        );
+        assert!(
+            spec.hint_substitutions
+                .iter()
+                .all(|s| s.rule == SpecHintRule::Romanization)
+        );
+
+        let spec = analyze_spec_characters("σοφία", "example.com", &opts).characters;
+        assert_eq!(
+            spec.normalized_ascii_hint.as_deref(),
+            Some("sofia@example.com")
+        );
+    }
+
+    #[test]
+    fn full_transliteration_keeps_confusables_in_latin_context() {
+        let mut opts = SpecOptions::standard();
+        opts.transliteration = SpecTransliteration::Full;
+        let spec = analyze_spec_characters("user", "exаmple.com", &opts).characters;
+        assert_eq!(
+            spec.normalized_ascii_hint.as_deref(),
+            Some("user@example.com")
+        );
+        assert!(
+            spec.hint_substitutions
+                .iter()
+                .any(|s| s.rule == SpecHintRule::Confusable)
+        );
+    }
+
+    #[test]
+    fn full_transliteration_falls_back_for_cjk() {
+        let mut opts = SpecOptions::standard();
+        opts.transliteration = SpecTransliteration::Full;
+        let spec = analyze_spec_characters("张伟", "example.com", &opts).characters;
+        let hint = spec.normalized_ascii_hint.expect("hint");
+        assert!(hint.is_ascii(), "{hint}");
+        assert!(
+            spec.hint_substitutions
+                .iter()
+                .all(|s| s.rule == SpecHintRule::Fallback)
+        );
+    }
+
+    #[test]
+    fn basic_transliteration_does_not_report_rules() {
+        for opts in [SpecOptions::standard(), SpecOptions::strict()] {
+            let spec = analyze_spec_characters("josé", "exemple.fr", &opts).characters;
+            assert_eq!(
+                spec.normalized_ascii_hint.as_deref(),
+                Some("jose@exemple.fr")
+            );
+            assert!(spec.hint_substitutions.is_empty());
+        }
+    }
+
+    #[test]
+    fn extra_maps_extend_builtin_tables() {
+        let mut opts = SpecOptions::standard();
+        opts.extra_confusables.insert('ı', "i".to_string());
+        opts.extra_diacritics.insert('ș', "s".to_string());
+        let spec = analyze_spec_characters("ștefan", "paypal-ıd.com", &opts).characters;
+        assert!(spec.has_confusables);
+        assert!(spec.has_diacritics);
+        assert_eq!(
+            spec.normalized_ascii_hint.as_deref(),
+            Some("stefan@paypal-id.com")
+        );
+    }
+
+    #[test]
+    fn german_locale_uses_umlaut_transliteration() {
+        let opts = SpecOptions::de_fraud();
+        let spec = analyze_spec_characters("jürgen„x“", "müller.de", &opts).characters;
+        assert_eq!(
+            spec.normalized_ascii_hint.as_deref(),
+            Some("juergen\"x\"@mueller.de")
+        );
+        assert!(
+            spec.hint_substitutions
+                .iter()
+                .any(|s| s.codepoint == 'ü' && s.rule == SpecHintRule::Locale)
+        );
+        let notes = join_spec_notes(&spec.details).unwrap();
+        assert!(notes.contains("ü → ue"));
+    }
+
+    #[test]
+    fn spanish_locale_drops_inverted_marks() {
+        let opts = SpecOptions::es_fraud();
+        let spec = analyze_spec_characters("¡hola!", "peña.es", &opts).characters;
+        assert_eq!(spec.normalized_ascii_hint.as_deref(), Some("hola!@pena.es"));
+    }
+
+    #[test]
+    fn risk_score_weights_domain_confusables_above_local() {
+        let opts = SpecOptions::standard();
+        let local = analyze_spec_characters("usеr", "example.com", &opts).characters;
+        let domain = analyze_spec_characters("user", "exаmple.com", &opts).characters;
+        assert!(local.risk_score > 0);
+        assert!(domain.risk_score > local.risk_score);
+        assert!(
+            domain
+                .risk_breakdown
+                .iter()
+                .any(|c| c.factor == SpecRiskFactor::DomainConfusable)
+        );
+        let clean = analyze_spec_characters("user", "example.com", &opts).characters;
+        assert_eq!(clean.risk_score, 0);
+        assert!(clean.risk_breakdown.is_empty());
+    }
+
+    #[test]
+    fn risk_score_counts_invisible_lookalike_and_sensitive_tld() {
+        let mut opts = SpecOptions::fr_fraud();
+        opts.lookalike_targets = vec!["impots.gouv.fr".to_string()];
+        let spec = analyze_spec_characters("us\u{200B}er", "irnpоts.gouv.fr", &opts).characters;
+        let factors: Vec<_> = spec.risk_breakdown.iter().map(|c| c.factor).collect();
+        assert!(factors.contains(&SpecRiskFactor::Invisible));
+        assert!(factors.contains(&SpecRiskFactor::Lookalike));
+        assert!(factors.contains(&SpecRiskFactor::SensitiveTld));
+        assert_eq!(spec.risk_score, 1000);
+        assert_eq!(
+            spec.normalized_ascii_hint.as_deref(),
+            Some("us\u{200B}er@irnpots.gouv.fr")
+        );
+
+        opts.strip_invisible = true;
+        let spec = analyze_spec_characters("us\u{200B}er", "irnpоts.gouv.fr", &opts).characters;
+        assert_eq!(
+            spec.normalized_ascii_hint.as_deref(),
+            Some("user@irnpots.gouv.fr")
+        );
+    }
+
+    #[test]
+    fn risk_weights_are_configurable() {
+        let mut opts = SpecOptions::standard();
+        opts.risk_weights.domain_confusable = 0.0;
+        opts.risk_weights.mixed_scripts = 0.05;
+        let spec = analyze_spec_characters("user", "exаmple.com", &opts).characters;
+        assert_eq!(spec.risk_score, 50);
+    }
+
+    #[test]
+    fn reports_non_nfc_segments() {
+        let opts = SpecOptions::standard();
+        let spec = analyze_spec_characters("jose\u{0301}", "example.com", &opts).characters;
+        let finding = spec
+            .details
+            .iter()
+            .find(|f| f.class == SpecClass::NotNfc)
+            .expect("NFC finding");
+        assert_eq!(finding.segment, SpecSegment::Local);
+        assert_eq!(finding.codepoint, '\u{0301}');
+        assert!(finding.note.contains("josé"));
+
+        let composed = analyze_spec_characters("josé", "example.com", &opts).characters;
+        assert!(
+            !composed
+                .details
+                .iter()
+                .any(|f| f.class == SpecClass::NotNfc)
+        );
+    }
+
+    #[test]
+    fn reports_compatibility_characters() {
+        let opts = SpecOptions::standard();
+        let spec = analyze_spec_characters("ﬁle", "ｅxample.com", &opts).characters;
+        let compat: Vec<_> = spec
+            .details
+            .iter()
+            .filter(|f| f.class == SpecClass::Compatibility)
+            .map(|f| f.codepoint)
+            .collect();
+        assert_eq!(compat, vec!['ﬁ', 'ｅ']);
+        assert_eq!(
+            spec.normalized_ascii_hint.as_deref(),
+            Some("file@example.com")
+        );
+    }
+
+    #[test]
+    fn reports_combining_sequence_anomalies() {
+        let opts = SpecOptions::standard();
+        let stacked = "e\u{0301}\u{0302}\u{0303}";
+        let spec = analyze_spec_characters(stacked, "example.com", &opts).characters;
+        assert!(
+            spec.details
+                .iter()
+                .any(|f| f.class == SpecClass::CombiningSequence && f.note.contains("stacked"))
+        );
+
+        let orphan = analyze_spec_characters("\u{0301}a", "example.com", &opts).characters;
+        assert!(
+            orphan
+                .details
+                .iter()
+                .any(|f| f.note.contains("without base"))
+        );
+
+        let vietnamese = analyze_spec_characters("e\u{0302}\u{0323}", "example.com", &opts);
+        assert!(
+            !vietnamese
+                .characters
+                .details
+                .iter()
+                .any(|f| f.class == SpecClass::CombiningSequence)
+        );
+    }
+
     #[test]
     fn punycode_domain_is_neutral() {
         let opts = SpecOptions::standard();
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use thiserror::Error;
 
 // AJOUTE ces derives sur ValidationMode
@@ -22,6 +24,13 @@ pub enum SpecClass {
     Diacritic,
     Confusable,
     MixedScript,
+    Invisible,
+    /// Segment non normalisé NFC (ex. `e` + U+0301 au lieu de `é`).
+    NotNfc,
+    /// Caractère de compatibilité modifié par NFKC (pleine chasse, ligatures…).
+    Compatibility,
+    /// Séquence combinante anormale (marques empilées, répétées ou orphelines).
+    CombiningSequence,
 }
 
 #[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
@@ -33,6 +42,103 @@ pub struct SpecFinding {
     pub note: String,
 }
 
+/// Règle ayant produit une substitution dans le hint ASCII.
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecHintRule {
+    /// Marque combinante supprimée.
+    CombiningMark,
+    /// Caractère invisible (espace de largeur nulle, contrôle bidi…) supprimé.
+    Invisible,
+    /// Homoglyphe remplacé par son équivalent latin visuel.
+    Confusable,
+    /// Lettre accentuée de la table de base.
+    Diacritic,
+    /// Translittération propre à la locale du profil (`ä` → `ae` en allemand).
+    Locale,
+    /// Ponctuation typographique (locale du profil).
+    Punctuation,
+    /// Lettre latine spéciale (`ß` → `ss`, `ø` → `o`, `ł` → `l`…).
+    Transliteration,
+    /// Romanisation cyrillique/grecque.
+    Romanization,
+    /// Décomposition de compatibilité NFKD.
+    Nfkd,
+    /// Translittération générique (CJK et autres écritures).
+    Fallback,
+}
+
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecHintSubstitution {
+    pub segment: SpecSegment,
+    pub codepoint: char,
+    pub replacement: String,
+    pub rule: SpecHintRule,
+}
+
+/// Table de translittération utilisée pour le hint ASCII.
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpecTransliteration {
+    /// Diacritiques latins + confusables + NFKD (comportement historique).
+    #[default]
+    Basic,
+    /// Ajoute les lettres latines spéciales, la romanisation cyrillique/grecque
+    /// et un repli générique pour les autres écritures.
+    Full,
+}
+
+/// Facteur contribuant au score de risque d'homographe.
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecRiskFactor {
+    LocalConfusable,
+    DomainConfusable,
+    MixedScripts,
+    Invisible,
+    Lookalike,
+    SensitiveTld,
+}
+
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecRiskContribution {
+    pub factor: SpecRiskFactor,
+    /// Poids appliqué, en millièmes.
+    pub weight: u16,
+    pub note: String,
+}
+
+/// Poids de chaque facteur dans [`SpecCharacters::risk_score`] (somme bornée à 1).
+#[cfg_attr(
+    feature = "with-serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpecRiskWeights {
+    pub local_confusable: f32,
+    pub domain_confusable: f32,
+    pub mixed_scripts: f32,
+    pub invisible: f32,
+    pub lookalike: f32,
+    pub sensitive_tld: f32,
+}
+
+impl Default for SpecRiskWeights {
+    fn default() -> Self {
+        Self {
+            local_confusable: 0.15,
+            domain_confusable: 0.35,
+            mixed_scripts: 0.2,
+            invisible: 0.25,
+            lookalike: 0.5,
+            sensitive_tld: 0.2,
+        }
+    }
+}
+
 #[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct SpecCharacters {
@@ -43,6 +149,19 @@ pub struct SpecCharacters {
     pub details: Vec<SpecFinding>,
     #[cfg_attr(feature = "with-serde", serde(skip_serializing_if = "Option::is_none"))]
     pub normalized_ascii_hint: Option<String>,
+    #[cfg_attr(
+        feature = "with-serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub hint_substitutions: Vec<SpecHintSubstitution>,
+    /// Score de risque d'homographe en millièmes (0 à 1000).
+    #[cfg_attr(feature = "with-serde", serde(default))]
+    pub risk_score: u16,
+    #[cfg_attr(
+        feature = "with-serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub risk_breakdown: Vec<SpecRiskContribution>,
 }
 
 #[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
@@ -63,6 +182,12 @@ pub struct NormalizedEmail {
     pub ascii_domain: String,
     pub mode: ValidationMode, // -> a maintenant PartialEq/Eq + (de)serde
     pub valid: bool,
+    /// Adresse (trimée) en forme NFC.
+    #[cfg_attr(feature = "with-serde", serde(default))]
+    pub nfc: String,
+    /// Adresse (trimée) en forme NFKC.
+    #[cfg_attr(feature = "with-serde", serde(default))]
+    pub nfkc: String,
     pub reasons: Vec<String>,
     #[cfg_attr(feature = "with-serde", serde(skip_serializing_if = "Option::is_none"))]
     pub spec_chars: Option<SpecCharacters>,
@@ -78,6 +203,11 @@ pub struct NormalizedEmail {
     pub ascii_hint: Option<String>,
 }
 
+#[cfg_attr(
+    feature = "with-serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
 #[derive(Debug, Clone)]
 pub struct SpecOptions {
     pub detect_diacritics: bool,
@@ -88,7 +218,23 @@ pub struct SpecOptions {
     pub domain_confusable_reason: Option<String>,
     pub domain_mixed_scripts_reason: Option<String>,
     pub confusable_tld_warnings: Vec<(String, String)>,
-    pub use_fr_hint_extensions: bool,
+    /// Conventions locales du hint ASCII (translittération, ponctuation).
+    pub hint_locale: Option<SpecLocale>,
+    pub transliteration: SpecTransliteration,
+    /// Confusables supplémentaires (caractère → équivalent latin), prioritaires
+    /// sur la table intégrée.
+    pub extra_confusables: BTreeMap<char, String>,
+    /// Diacritiques supplémentaires (caractère → translittération).
+    pub extra_diacritics: BTreeMap<char, String>,
+    pub detect_invisible: bool,
+    /// Retire les caractères invisibles du hint ASCII (désactivé par
+    /// défaut : ils y sont conservés tels quels).
+    pub strip_invisible: bool,
+    pub detect_normalization: bool,
+    /// Domaines protégés : un domaine dont le squelette visuel coïncide avec
+    /// l'un d'eux (sans lui être identique) est un sosie.
+    pub lookalike_targets: Vec<String>,
+    pub risk_weights: SpecRiskWeights,
 }
 
 impl Default for SpecOptions {
@@ -102,12 +248,106 @@ impl Default for SpecOptions {
             domain_confusable_reason: None,
             domain_mixed_scripts_reason: None,
             confusable_tld_warnings: Vec::new(),
-            use_fr_hint_extensions: false,
+            hint_locale: None,
+            transliteration: SpecTransliteration::Basic,
+            extra_confusables: BTreeMap::new(),
+            extra_diacritics: BTreeMap::new(),
+            detect_invisible: true,
+            strip_invisible: false,
+            detect_normalization: true,
+            lookalike_targets: Vec::new(),
+            risk_weights: SpecRiskWeights::default(),
         }
     }
 }
 
+/// Locale d'un profil anti-fraude : sélectionne la translittération
+/// (`ä` → `ae` en allemand) et la ponctuation typographique reconnues.
+#[cfg_attr(
+    feature = "with-serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecLocale {
+    Fr,
+    De,
+    Es,
+    It,
+    PtBr,
+}
+
+/// Description d'un profil anti-fraude : nom, locale et suffixes sensibles.
+struct FraudProfile {
+    name: &'static str,
+    locale: Option<SpecLocale>,
+    sensitive_suffixes: &'static [&'static str],
+}
+
+const FR_FRAUD: FraudProfile = FraudProfile {
+    name: "fr-fraud",
+    locale: Some(SpecLocale::Fr),
+    sensitive_suffixes: &["fr", "gouv.fr"],
+};
+
+const DE_FRAUD: FraudProfile = FraudProfile {
+    name: "de-fraud",
+    locale: Some(SpecLocale::De),
+    sensitive_suffixes: &["de", "bund.de"],
+};
+
+const ES_FRAUD: FraudProfile = FraudProfile {
+    name: "es-fraud",
+    locale: Some(SpecLocale::Es),
+    sensitive_suffixes: &["es", "gob.es"],
+};
+
+const IT_FRAUD: FraudProfile = FraudProfile {
+    name: "it-fraud",
+    locale: Some(SpecLocale::It),
+    sensitive_suffixes: &["it", "gov.it"],
+};
+
+const PT_BR_FRAUD: FraudProfile = FraudProfile {
+    name: "pt-br-fraud",
+    locale: Some(SpecLocale::PtBr),
+    sensitive_suffixes: &["br", "gov.br"],
+};
+
+const GENERIC_BANK: FraudProfile = FraudProfile {
+    name: "generic-bank",
+    locale: None,
+    sensitive_suffixes: &["bank", "insurance", "banque"],
+};
+
 impl SpecOptions {
+    /// Noms acceptés par [`SpecOptions::profile`].
+    pub const PROFILE_NAMES: &'static [&'static str] = &[
+        "standard",
+        "strict",
+        FR_FRAUD.name,
+        DE_FRAUD.name,
+        ES_FRAUD.name,
+        IT_FRAUD.name,
+        PT_BR_FRAUD.name,
+        GENERIC_BANK.name,
+    ];
+
+    /// Profils intégrés, par nom (voir [`SpecOptions::PROFILE_NAMES`]).
+    pub fn profile(name: &str) -> Option<Self> {
+        match name {
+            "standard" => Some(Self::standard()),
+            "strict" => Some(Self::strict()),
+            "fr-fraud" => Some(Self::fr_fraud()),
+            "de-fraud" => Some(Self::de_fraud()),
+            "es-fraud" => Some(Self::es_fraud()),
+            "it-fraud" => Some(Self::it_fraud()),
+            "pt-br-fraud" => Some(Self::pt_br_fraud()),
+            "generic-bank" => Some(Self::generic_bank()),
+            _ => None,
+        }
+    }
+
     pub fn standard() -> Self {
         Self::default()
     }
@@ -119,22 +359,49 @@ impl SpecOptions {
     }
 
     pub fn fr_fraud() -> Self {
+        Self::fraud(&FR_FRAUD)
+    }
+
+    pub fn de_fraud() -> Self {
+        Self::fraud(&DE_FRAUD)
+    }
+
+    pub fn es_fraud() -> Self {
+        Self::fraud(&ES_FRAUD)
+    }
+
+    pub fn it_fraud() -> Self {
+        Self::fraud(&IT_FRAUD)
+    }
+
+    pub fn pt_br_fraud() -> Self {
+        Self::fraud(&PT_BR_FRAUD)
+    }
+
+    pub fn generic_bank() -> Self {
+        Self::fraud(&GENERIC_BANK)
+    }
+
+    fn fraud(profile: &FraudProfile) -> Self {
+        let name = profile.name;
         let mut opts = Self::standard();
-        opts.use_fr_hint_extensions = true;
-        opts.domain_confusable_reason =
-            Some("fr-fraud profile: domain label has confusable non-latin characters".to_string());
+        opts.hint_locale = profile.locale;
+        opts.transliteration = SpecTransliteration::Full;
+        opts.domain_confusable_reason = Some(format!(
+            "{name} profile: domain label has confusable non-latin characters"
+        ));
         opts.domain_mixed_scripts_reason =
-            Some("fr-fraud profile: domain uses mixed Unicode scripts".to_string());
-        opts.confusable_tld_warnings = vec![
-            (
-                "fr".to_string(),
-                "fr-fraud profile: .fr domain with confusable characters detected".to_string(),
-            ),
-            (
-                "gouv.fr".to_string(),
-                "fr-fraud profile: .gouv.fr domain with confusable characters detected".to_string(),
-            ),
-        ];
+            Some(format!("{name} profile: domain uses mixed Unicode scripts"));
+        opts.confusable_tld_warnings = profile
+            .sensitive_suffixes
+            .iter()
+            .map(|suffix| {
+                (
+                    suffix.to_string(),
+                    format!("{name} profile: .{suffix} domain with confusable characters detected"),
+                )
+            })
+            .collect();
         opts
     }
 }